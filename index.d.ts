@@ -2,20 +2,367 @@
 /* eslint-disable */
 export declare class MetaPicture {
   coverType: "Cover Art (Other)" | "Cover Art (Png Icon)" | "Cover Art (Icon)" | "Cover Art (Front)" | "Cover Art (Back)" | "Cover Art (Leaflet)" | "Cover Art (Media)" | "Cover Art (Lead Artist)" | "Cover Art (Artist)" | "Cover Art (Conductor)" | "Cover Art (Band)" | "Cover Art (Composer)" | "Cover Art (Lyricist)" | "Cover Art (Recording Location)" | "Cover Art (During Recording)" | "Cover Art (During Performance)" | "Cover Art (Video Capture)" | "Cover Art (Fish)" | "Cover Art (Illustration)" | "Cover Art (Band Logotype)" | "Cover Art (Publisher Logotype)" | "Unknown"
+  /**
+   * MIME type written to the file when the data is not a recognised image format,
+   * {@link detectedMimeType} is written otherwise
+   */
   mimeType?: string
   description?: string
-  data: Uint8Array
-  constructor(mime: string, data: Uint8Array, desc?: string | undefined | null)
+  /** Image data, create a new picture to change it */
+  readonly data: Uint8Array
+  /**
+   * @param mime MIME type, kept as given, see {@link detectedMimeType} for the type of the data
+   * @param coverType Picture type, defaults to `PictureType.Front`
+   */
+  constructor(mime: string, data: Uint8Array, desc?: string | undefined | null, coverType?: PictureType | undefined | null)
+  /** SHA-256 of the image data as lowercase hex, identical images have the same hash */
+  get hash(): string
+  /**
+   * MIME type detected from the image data, or `null` if it is not a JPEG, PNG, GIF, BMP, WebP
+   * or TIFF image
+   */
+  get detectedMimeType(): string | null
+  /** Image width in pixels, detected from the image data */
+  get width(): number | null
+  /** Image height in pixels, detected from the image data */
+  get height(): number | null
+  /** Color depth in bits per pixel, detected from the image data */
+  get colorDepth(): number | null
+  /** Number of colors for indexed-color images, `0` otherwise */
+  get numColors(): number | null
+  /**
+   * Downscale and re-encode the picture as JPEG, on a worker thread
+   *
+   * The EXIF orientation is applied to the pixels, as the JPEG is written without EXIF data.
+   *
+   * @param options Maximum dimension and JPEG quality, the size is kept by default
+   *
+   * @returns A new JPEG picture with the same type and description
+   *
+   * @throws If the image cannot be decoded
+   */
+  optimize(options?: OptimizePictureOptions | undefined | null): Promise<MetaPicture>
+  /**
+   * Downscale and re-encode the picture as JPEG
+   *
+   * This is the synchronous version of {@link optimize}
+   *
+   * @param options Maximum dimension and JPEG quality, the size is kept by default
+   *
+   * @returns A new JPEG picture with the same type and description
+   *
+   * @throws If the image cannot be decoded
+   */
+  optimizeSync(options?: OptimizePictureOptions | undefined | null): MetaPicture
 }
 
 export declare class MusicFile {
+  /**
+   * Whether the metadata was modified since the file was loaded or saved to its path
+   *
+   * @note Setting a field back to its loaded value makes the file clean again.
+   * A pending {@link convertLegacyCoverArt}, FLAC block, Opus output gain and MP4 iTunes atom changes always make the file dirty.
+   */
+  get isDirty(): boolean
+  /** List the metadata fields that differ from the loaded file */
+  changes(): Array<FieldChange>
+  /** Discard all metadata modifications, restoring the tags as they were when loaded */
+  revert(): void
+  /**
+   * Codec details beyond the generic audio properties, or `null` if they could not be read
+   *
+   * Holds MPEG frame and Xing/LAME header details, the Opus and Vorbis identification headers,
+   * the MP4 codec and profile, the WAV format tag and channel mask, and the Monkey's Audio
+   * and WavPack compression.
+   */
+  get codecInfo(): CodecInfo | null
+  /**
+   * Copy metadata from another file, converting between tag formats
+   *
+   * Items are mapped through their generic key, so e.g. Vorbis comments can be copied to ID3v2
+   * or MP4 atoms. Keys present in the source replace the same keys in this file, other keys are kept.
+   * ReplayGain gains are copied like {@link trackReplayGain} and {@link albumReplayGain} set them,
+   * so they become R128 gains in Opus files.
+   *
+   * @param source File to copy the metadata from
+   * @param options Keys to include or exclude, and whether to copy pictures
+   *
+   * @returns Copied keys, and keys the tag format of this file cannot represent
+   *
+   * @throws If pictures are copied from a source that was loaded without its pictures
+   */
+  copyTagsFrom(source: MusicFile, options?: CopyTagsOptions | undefined | null): CopyTagsReport
+  /**
+   * Cue sheet embedded in the file, or `null` if there is none
+   *
+   * Read from the `CUESHEET` item of APE tags (Monkey's Audio, WavPack), Vorbis comments, and the
+   * ID3v2 `TXXX` frame described `CUESHEET`. In FLAC files, a `CUESHEET` Vorbis comment is preferred
+   * over the CUESHEET metadata block, which only has track positions, ISRCs and the catalog number.
+   *
+   * @note The `cue ` chunk of WAV files is not read. Cue sheets added after loading are not read
+   * until the file is saved in place.
+   */
+  get embeddedCueSheet(): CueSheet | null
+  /**
+   * Read the sidecar cue sheet of the file, `<name>.cue` or `<name>.<extension>.cue` in the same directory
+   *
+   * @returns The cue sheet, or `null` if there is no sidecar cue sheet
+   *
+   * @throws If the file was loaded from a buffer, or the cue sheet is invalid
+   * @throws In WebAssembly environments
+   */
+  readSidecarCueSheet(): CueSheet | null
+  /**
+   * Split the audio of the file into the tracks of a cue sheet
+   *
+   * @param cue Cue sheet of the file, defaults to {@link embeddedCueSheet}
+   *
+   * @returns Audio tracks stored in this file, in cue sheet order
+   *
+   * @throws If no cue sheet is given or embedded, or a track has no index
+   */
+  cueTracks(cue?: CueSheet | undefined | null): Array<CueTrackEntry>
+  /**
+   * Set the metadata of one cue sheet track, e.g. on a file split from the cue sheet image
+   *
+   * Sets the title, artist, album, album artist, composer, genre, year, track number
+   * and track total from the cue sheet. Fields the cue sheet doesn't have are left untouched.
+   *
+   * @param cue Cue sheet to take the metadata from
+   * @param number Track number in the cue sheet
+   *
+   * @returns Keys of the fields whose value actually changed, see {@link update}
+   *
+   * @throws If the cue sheet has no audio track with that number
+   */
+  applyCueTrack(cue: CueSheet, number: number): Array<string>
+  /**
+   * Remove embedded pictures whose image data is identical to another embedded picture
+   *
+   * Of identical pictures the front cover is kept, otherwise the first one.
+   *
+   * @returns Number of removed pictures
+   */
+  dedupePictures(): number
+  /**
+   * Apply multiple metadata fields at once
+   *
+   * Every field is validated with the same rules as its setter before anything is
+   * applied, so either all fields are written or none of them. A patch that changes nothing
+   * is not recorded in the undo history.
+   *
+   * @param patch Fields to update, set a field to `null` to remove it
+   *
+   * @returns Keys of the fields whose value actually changed
+   *
+   * @throws If any field is invalid, listing every invalid field
+   */
+  update(patch: MetadataPatch): Array<string>
+  /**
+   * File type detected from the content when loading
+   *
+   * Unlike {@link tagType}, this is the audio container, e.g. an MP3 and a WAV file can both have ID3v2 tags.
+   */
+  get fileType(): "Aac" | "Aiff" | "Ape" | "Flac" | "Mpeg" | "Mp4" | "Mpc" | "Opus" | "Vorbis" | "Speex" | "Wav" | "WavPack"
+  /** Canonical file extension of the {@link fileType}, without the leading dot, e.g. `"m4a"` */
+  get extension(): string
+  /** MIME type of the {@link fileType}, e.g. `"audio/flac"` */
+  get mimeType(): string
+  /**
+   * Compare the extension of the file name with the {@link fileType} detected from the content
+   *
+   * Files are often named after the wrong format, e.g. an M4A file named `.mp3`.
+   * The comparison ignores case and accepts common alternatives, like `.ogg` for Opus.
+   *
+   * @param name File name to check instead of the path, required for files loaded from a buffer
+   *
+   * @throws If no name is given and the file was loaded from a buffer
+   */
+  checkExtension(name?: string | undefined | null): ExtensionCheck
+  /**
+   * Rename the file to the canonical extension of its {@link fileType} if the current one doesn't match
+   *
+   * The rest of the file name is kept, e.g. `song.mp3` containing FLAC audio becomes `song.flac`.
+   * An existing file is never overwritten. Afterwards {@link path} and saving use the new name.
+   *
+   * @returns The new path, or `null` if the extension already matches
+   *
+   * @throws If the file was loaded from a buffer
+   * @throws If a file with the new name already exists
+   * @throws If the file can't be renamed
+   * @throws In WebAssembly environments
+   */
+  fixExtension(): string | null
+  /**
+   * FLAC metadata blocks besides Vorbis comments and pictures, or `null` for other formats
+   *
+   * STREAMINFO, SEEKTABLE and CUESHEET are read from the loaded file,
+   * APPLICATION blocks and padding include pending changes.
+   */
+  get flacMetadata(): FlacMetadata | null
+  /**
+   * Set the total size of the PADDING block written on the next save
+   *
+   * Padding lets later tag edits fit without rewriting the audio data. By default, the existing
+   * padding grows or shrinks to keep the audio data in place when possible.
+   *
+   * @param bytes Padding size in bytes, `0` removes all padding
+   *
+   * @throws If the file is not a FLAC file, or the size exceeds 16 MiB
+   */
+  setFlacPadding(bytes: number): void
+  /**
+   * Add an APPLICATION block, written on the next save
+   *
+   * @param id Registered application ID, four ASCII characters
+   * @param data Application data
+   *
+   * @throws If the file is not a FLAC file, the ID is invalid, or the data is too large
+   */
+  addFlacApplication(id: string, data: Uint8Array): void
+  /**
+   * Remove APPLICATION blocks on the next save
+   *
+   * @param id Only remove blocks with this application ID, all blocks are removed by default
+   *
+   * @returns Number of removed blocks
+   *
+   * @throws If the file is not a FLAC file, or the ID is invalid
+   */
+  removeFlacApplications(id?: string | undefined | null): number
+  /**
+   * Encoder delay and padding for gapless playback, or `null` if the file has none
+   *
+   * Read from the `iTunSMPB` comment of MP4 files and ID3v2 tags, then from the LAME header
+   * of MP3 files, then from the pre-skip of the Opus identification header.
+   * The values are reported as stored, MP3 decoders add their own delay of 529 samples.
+   */
+  get gapless(): GaplessInfo | null
+  /**
+   * Write the gapless values as an `iTunSMPB` comment
+   *
+   * @param encoderDelay Samples to discard from the start
+   * @param encoderPadding Samples to discard from the end
+   * @param originalSamples Samples of the original audio
+   *
+   * @throws If the file is not an MP4 file and has no ID3v2 tag, or the sample count is invalid
+   */
+  setGapless(encoderDelay: number, encoderPadding: number, originalSamples: number): void
+  /**
+   * Remove the `iTunSMPB` comment
+   *
+   * @returns Whether a comment was removed
+   */
+  removeGapless(): boolean
+  /** Whether there is a modification that can be undone */
+  get canUndo(): boolean
+  /** Whether there is an undone modification that can be redone */
+  get canRedo(): boolean
+  /**
+   * Undo the last metadata modification
+   *
+   * Every setter call, {@link update} and {@link revert} is one step. Only the last 100 steps are kept.
+   * Pending FLAC block, Opus output gain, MP4 iTunes atom and {@link convertLegacyCoverArt} changes are undone too.
+   *
+   * @returns `false` if there is nothing to undo
+   */
+  undo(): boolean
+  /**
+   * Redo the last undone metadata modification
+   *
+   * @returns `false` if there is nothing to redo
+   */
+  redo(): boolean
+  /** Mark the current metadata state, to return to it later with {@link restoreCheckpoint} */
+  checkpoint(): number
+  /**
+   * Undo or redo modifications until the metadata is back at a checkpoint
+   *
+   * @param checkpoint Value returned by {@link checkpoint}
+   *
+   * @throws If the checkpoint is no longer in the history, e.g. after new modifications were made on top of an undone state
+   */
+  restoreCheckpoint(checkpoint: number): void
+  /** Drop all undo and redo steps, keeping the current metadata */
+  clearHistory(): void
+  /**
+   * Position of the audio stream and the metadata blocks in the loaded file, or `null` if the file
+   * structure could not be read
+   *
+   * Describes the file as it was loaded or last saved to its path, saving to a buffer or another
+   * path doesn't update it.
+   * For Ogg files, the comment block covers the header pages after the identification header.
+   */
+  get audioLayout(): AudioLayout | null
+  /**
+   * Measure the loudness of the audio as in ReplayGain 2.0 and EBU R128
+   *
+   * The audio is decoded and measured with the gating of ITU-R BS.1770, on a worker thread.
+   * Opus, APE, WavPack and Musepack audio can't be decoded.
+   *
+   * @param options Reference loudness, true peak and whether to write the track ReplayGain tags
+   * @param source The file content, required for files loaded from a buffer
+   *
+   * @throws If the file was loaded from a buffer and no source is given
+   * @throws If the audio can't be decoded
+   */
+  analyzeLoudness(options?: LoudnessOptions | undefined | null, source?: Uint8Array | undefined | null): Promise<LoudnessResult>
+  /**
+   * Measure the loudness of the audio as in ReplayGain 2.0 and EBU R128
+   *
+   * This is the synchronous version of {@link analyzeLoudness}
+   *
+   * @param options Reference loudness, true peak and whether to write the track ReplayGain tags
+   * @param source The file content, required for files loaded from a buffer
+   *
+   * @throws If the file was loaded from a buffer and no source is given
+   * @throws If the audio can't be decoded
+   */
+  analyzeLoudnessSync(options?: LoudnessOptions | undefined | null, source?: Uint8Array | undefined | null): LoudnessResult
+  /**
+   * How pictures are stored in the comment header of the loaded Ogg file, or `null` for other formats
+   *
+   * Pictures in both `METADATA_BLOCK_PICTURE` and legacy `COVERART` fields are available in {@link pictures}.
+   * Saving always writes `METADATA_BLOCK_PICTURE` fields.
+   */
+  get oggPictureFields(): OggPictureFields | null
+  /**
+   * Convert pictures from legacy `COVERART` fields to standard `METADATA_BLOCK_PICTURE` fields
+   *
+   * The pictures are written in the standard form on the next save, even if nothing else was modified.
+   *
+   * @param coverType Picture type of the converted pictures, defaults to `PictureType.Front`
+   *
+   * @returns Number of converted pictures
+   */
+  convertLegacyCoverArt(coverType?: PictureType | undefined | null): number
+  /**
+   * Gain in dB applied by every Opus decoder, from the identification header, or `null` for other formats
+   *
+   * Includes a change made with {@link setOpusOutputGain} that is not saved yet.
+   */
+  get opusOutputGain(): number | null
+  /**
+   * Set the output gain of the Opus identification header, written on the next save
+   *
+   * Unlike `R128_TRACK_GAIN`, the output gain is applied by every player.
+   * It is stored in steps of 1/256 dB.
+   *
+   * @param gain Gain in dB, from `-128` to `127.99`
+   *
+   * @throws If the file is not an Opus file, or the gain is out of range
+   */
+  setOpusOutputGain(gain: number): void
   /**
    * Audio quality classification ("HQ", "SQ", or "HiRes")
    *
-   * Quality is determined based on file format, sample rate, and bit depth:
+   * Quality is determined based on codec, sample rate, and bit depth:
    * - HQ: Lossy formats (MP3, AAC, etc.)
    * - SQ: Lossless formats at CD quality (44.1kHz, 16-bit)
    * - HiRes: Lossless formats exceeding CD quality (>44.1kHz, >=16-bit)
+   *
+   * ALAC in MP4 is lossless, WavPack hybrid files are lossy. Use {@link classifyQuality} for other
+   * thresholds, more tiers and the reasons of the classification.
    */
   get quality(): "HQ" | "SQ" | "HiRes"
   /**
@@ -27,8 +374,11 @@ export declare class MusicFile {
   /**
    * Audio bit rate in kbps, or `null` if not available
    *
+   * For MPEG files loaded with `accurateDuration`, this is the average bitrate of the audio frames.
+   *
    * @note If the audio properties don't provide a bitrate, this method calculates
-   * an approximate bitrate based on file size and duration, including metadata.
+   * an approximate bitrate based on the size of the audio stream and duration. If the
+   * audio stream could not be located, the file size is used, including metadata.
    * The calculated bitrate is constrained between 8 and 10,000 kbps.
    */
   get bitRate(): number | null
@@ -44,8 +394,107 @@ export declare class MusicFile {
    * Common values: 1 (mono), 2 (stereo), 6 (5.1 surround), 8 (7.1 surround)
    */
   get channels(): number | null
-  /** Audio duration in milliseconds, 0 if not available */
+  /**
+   * Audio duration in milliseconds, 0 if not available
+   *
+   * See {@link durationMethod} for how it was determined.
+   */
   get duration(): number
+  /**
+   * How {@link duration} and {@link bitRate} were determined
+   *
+   * - FrameScan: Every MPEG frame was read, when loaded with `accurateDuration`
+   * - VbrHeader: Frame count of the Xing, Info or VBRI header of an MPEG stream
+   * - Estimate: MPEG stream without frame count, estimated from the first frame and the file size
+   * - Header: Stream headers of other formats
+   */
+  get durationMethod(): "FrameScan" | "VbrHeader" | "Estimate" | "Header"
+  /**
+   * Classify the audio quality with configurable thresholds and labels
+   *
+   * Lossless detection is codec-aware: ALAC in MP4 is lossless, WavPack hybrid and A-law or µ-law WAV are lossy.
+   * Tiers are:
+   * - LQ: Lossy below `minHqBitRate`
+   * - HQ: Lossy
+   * - SQ: Lossless at CD quality
+   * - HiRes: Lossless with at least `minHiResSampleRate` or `minHiResBitDepth`, including 32-bit float
+   * - DSD: DSD audio in WavPack
+   * - MQA?: FLAC tagged by an MQA encoder, the audio itself is not checked
+   *
+   * @param options Thresholds and custom labels
+   */
+  classifyQuality(options?: QualityOptions | undefined | null): QualityClassification
+  /**
+   * Write embedded pictures to image files
+   *
+   * @param dir Directory to write to, created if missing
+   * @param template File name without extension, `{type}` is replaced by the picture type
+   * (`cover`, `back`, `artist`, ...) and `{index}` by the 1-based picture index. Defaults to `"{type}"`.
+   * The extension is detected from the image data.
+   *
+   * @returns Paths of the written files
+   *
+   * @throws If the template contains a path separator, or writing fails
+   * @throws In WebAssembly environments
+   */
+  exportPictures(dir: string, template?: string | undefined | null): Array<string>
+  /**
+   * Embed images next to the audio file, such as `cover.jpg` or `folder.png`
+   *
+   * `cover`, `folder`, `front` and `albumart` are embedded as front cover, `back` as back cover,
+   * `leaflet` and `booklet` as leaflet, `disc`, `cd` and `media` as media, and `artist` as artist picture.
+   * Names are matched case-insensitively, files that are not valid images are ignored.
+   *
+   * @param options Whether to replace embedded pictures of the same type
+   *
+   * @returns Paths of the embedded image files
+   *
+   * @throws If the file was loaded from a buffer
+   * @throws In WebAssembly environments
+   */
+  importSidecarArt(options?: SidecarArtOptions | undefined | null): Array<string>
+  /**
+   * Read all common metadata fields, audio properties and picture descriptors at once
+   *
+   * @param options Snapshot options, image data of pictures is omitted by default
+   */
+  snapshot(options?: SnapshotOptions | undefined | null): MusicFileSnapshot
+  /** Plain object representation used by `JSON.stringify`, same as {@link snapshot} without picture data */
+  toJSON(): MusicFileSnapshot
+  /**
+   * iTunes Sound Check from the `iTunNORM` comment, or `null` if not set or malformed
+   *
+   * Apple players ignore ReplayGain and normalize with Sound Check. It is stored in a freeform atom
+   * in MP4 files and in a `COMM` frame in ID3v2 tags.
+   */
+  get soundCheck(): SoundCheck | null
+  /**
+   * Set or remove the iTunes Sound Check
+   *
+   * Attenuation is limited to about 18 dB, like iTunes does.
+   *
+   * @param gain Gain in dB, or `null` to remove the `iTunNORM` comment
+   * @param peak Peak where `1.0` is full scale, defaults to {@link trackReplayPeak} or `1.0`
+   *
+   * @throws If the file is not an MP4 file and has no ID3v2 tag, or a value is invalid
+   */
+  setSoundCheck(gain: number | null, peak?: number | undefined | null): void
+  /**
+   * Set the iTunes Sound Check from the track ReplayGain gain and peak
+   *
+   * The ReplayGain gain is used as is, so Apple players reach the same loudness as ReplayGain players.
+   *
+   * @returns `false` if no track ReplayGain gain is set
+   *
+   * @throws If the file is not an MP4 file and has no ID3v2 tag
+   */
+  soundCheckFromReplayGain(): boolean
+  /**
+   * Set the track ReplayGain gain and peak from the iTunes Sound Check
+   *
+   * @returns `false` if no valid `iTunNORM` comment is set
+   */
+  replayGainFromSoundCheck(): boolean
   /** File's metadata tag type, or `null` if not recognized or no available tag */
   get tagType(): "AIFF" | "APE" | "ID3V1" | "ID3V2" | "ILST" | "RIFF" | "VORBIS" | null
   /** Title, or `null` if not set or no available tag */
@@ -75,7 +524,11 @@ export declare class MusicFile {
   /** Total number of discs in the album, or `null` if not set or no available tag */
   get discsTotal(): number | null
   set discsTotal(discsTotal: number | null)
-  /** Comment, or `null` if not set or no available tag */
+  /**
+   * Comment, or `null` if not set or no available tag
+   *
+   * iTunes comments of ID3v2 tags, like `iTunNORM`, are not included.
+   */
   get comment(): string | null
   set comment(comment: string | null)
   /**
@@ -110,6 +563,8 @@ export declare class MusicFile {
    * Track replay gain in dB, or `null` if not set or no available tag
    *
    * @note Replay gain is used to normalize playback volume across different tracks.
+   * For Opus files it is read from and written to `R128_TRACK_GAIN`, converted from -23 LUFS to the
+   * ReplayGain 2.0 reference of -18 LUFS. The header output gain is applied on top, see {@link opusOutputGain}.
    */
   get trackReplayGain(): number | null
   set trackReplayGain(trackReplayGain: number | null)
@@ -124,6 +579,7 @@ export declare class MusicFile {
    * Album replay gain in dB, or `null` if not set or no available tag
    *
    * @note Album replay gain normalizes playback volume across different albums.
+   * For Opus files it is read from and written to `R128_ALBUM_GAIN`, like {@link trackReplayGain}.
    */
   get albumReplayGain(): number | null
   set albumReplayGain(albumReplayGain: number | null)
@@ -141,30 +597,93 @@ export declare class MusicFile {
    */
   get pictures(): Array<MetaPicture> | null
   set pictures(pictures: Array<MetaPicture> | null)
+  /**
+   * Descriptors of the embedded pictures without copying the image data
+   *
+   * The list is in the same order as {@link pictures}, use {@link getPictureData} to read the data of one picture
+   * and {@link getPictureHash} to hash it.
+   */
+  get pictureInfo(): Array<PictureInfo>
+  /**
+   * Image data of the embedded picture at `index`, or `null` if there is no such picture
+   *
+   * @param index Index in {@link pictureInfo}
+   */
+  getPictureData(index: number): Uint8Array | null
+  /**
+   * SHA-256 of the image data of the embedded picture at `index` as lowercase hex, or `null` if
+   * there is no such picture
+   *
+   * @param index Index in {@link pictureInfo}
+   */
+  getPictureHash(index: number): string | null
+  /** Front cover picture, or `null` if there is none */
+  get frontCover(): MetaPicture | null
+  /** Embedded pictures of the given type */
+  getPicturesByType(coverType: PictureType): Array<MetaPicture>
+  /**
+   * Replace all embedded pictures of the given type with one picture
+   *
+   * The picture is stored with the given type, regardless of its own `coverType`.
+   * Pictures of other types are kept.
+   */
+  setPicture(coverType: PictureType, picture: MetaPicture): void
+  /**
+   * Remove all embedded pictures of the given type
+   *
+   * @returns Number of removed pictures
+   */
+  removePicture(coverType: PictureType): number
+  /**
+   * Downscale and re-encode all embedded pictures as JPEG, on a worker thread
+   *
+   * Downscaled pictures always replace the original. Pictures that already fit are only
+   * replaced when the re-encoded picture is smaller, and pictures that cannot be decoded are
+   * kept as they are. The EXIF orientation is applied to the pixels, as the JPEG is written
+   * without EXIF data. Pictures changed while optimizing are kept. Changes are written on the
+   * next save.
+   *
+   * @param options Maximum dimension and JPEG quality, the size is kept by default
+   *
+   * @returns Number of replaced pictures and the picture sizes before and after
+   */
+  optimizePictures(options?: OptimizePictureOptions | undefined | null): Promise<OptimizePicturesReport>
+  /**
+   * Downscale and re-encode all embedded pictures as JPEG
+   *
+   * This is the synchronous version of {@link optimizePictures}
+   *
+   * @param options Maximum dimension and JPEG quality, the size is kept by default
+   *
+   * @returns Number of replaced pictures and the picture sizes before and after
+   */
+  optimizePicturesSync(options?: OptimizePictureOptions | undefined | null): OptimizePicturesReport
   /**
    * Load music file from a file path or byte buffer
    *
    * @param source The file system path or a Uint8Array containing the audio file data
+   * @param options Load options, e.g. to skip reading embedded pictures
    *
    * @throws If the path doesn't exist or isn't accessible
    * @throws If the file doesn't contain a valid audio format
    * @throws If runs in WebAssembly environments (due to file system restrictions).
    */
-  static load(path: string): Promise<MusicFile>
-  static load(buffer: Uint8Array): Promise<MusicFile>
+  static load(path: string, options?: LoadOptions | undefined | null): Promise<MusicFile>
+  static load(buffer: Uint8Array, options?: LoadOptions | undefined | null): Promise<MusicFile>
   /**
    * Load music file from a file path or byte buffer
    *
    * This is the synchronous version of {@link load}
    *
    * @param source The file system path or a Uint8Array containing the audio file data
+   * @param options Load options, e.g. to skip reading embedded pictures
    *
    * @throws If the path doesn't exist or isn't accessible
    * @throws If the file doesn't contain a valid audio format
    * @throws If runs in WebAssembly environments (due to file system restrictions).
    */
-  static loadSync(path: string): MusicFile
-  static loadSync(buffer: Uint8Array): MusicFile
+  static loadSync(path: string, options?: LoadOptions | undefined | null): MusicFile
+  static loadSync(buffer: Uint8Array, options?: LoadOptions | undefined | null): MusicFile
   /**
    * Current audio file path
    *
@@ -178,10 +697,15 @@ export declare class MusicFile {
    * @param bufferOrPath Optional output file path (Node.js only) or source buffer. If provided,
    * saves to this path (or a new buffer that creates from the source buffer with new tags) for this call.
    *
+   * @note Saving to the existing path is skipped when the metadata is unchanged, see {@link isDirty}.
+   * After saving to the existing path, the saved metadata becomes the loaded state.
+   *
    * @throws If the file was loaded from a buffer and no buffer is provided.
    * @throws If the file was loaded from a buffer and wants to save to a custom path.
    * @throws If custom path is provided in WebAssembly environments
+   * @throws If the file was loaded with `skipPictures` and has pictures
    * @throws If saving fails due to file format constraints
+   * @throws If the file saved to the existing path cannot be read back
    */
   save(path?: string | null): Promise<void>
   save(buffer: Uint8Array): Promise<Uint8Array>
@@ -193,11 +717,835 @@ export declare class MusicFile {
    * @param bufferOrPath Optional output file path (Node.js only) or source buffer. If provided,
    * saves to this path (or a new buffer that creates from the source buffer with new tags) for this call.
    *
+   * @note Saving to the existing path is skipped when the metadata is unchanged, see {@link isDirty}.
+   * After saving to the existing path, the saved metadata becomes the loaded state.
+   *
    * @throws If the file was loaded from a buffer and no buffer is provided.
    * @throws If the file was loaded from a buffer and wants to save to a custom path.
    * @throws If custom path is provided in WebAssembly environments
+   * @throws If the file was loaded with `skipPictures` and has pictures
    * @throws If saving fails due to file format constraints
+   * @throws If the file saved to the existing path cannot be read back
    */
   saveSync(path?: string | null): void
   saveSync(buffer: Uint8Array): Uint8Array
 }
+
+/** Result of {@link analyzeAlbumLoudness} */
+export interface AlbumLoudnessResult {
+  /** Loudness of each file, in the order given */
+  tracks: Array<LoudnessResult>
+  /** Loudness of all files together */
+  album: LoudnessResult
+}
+
+/** Details of the Monkey's Audio header */
+export interface ApeInfo {
+  /** Encoder version, e.g. `3990` for 3.99 */
+  version: number
+  /** Compression level, `1000` (Fast) to `5000` (Insane) */
+  compressionLevel: number
+  compression: "Fast" | "Normal" | "High" | "Extra High" | "Insane" | "Unknown"
+}
+
+/** Position of the audio stream and the metadata in the loaded file */
+export interface AudioLayout {
+  fileSize: number
+  /** Offset of the audio stream from the start of the file in bytes */
+  audioOffset: number
+  /** Size of the audio stream in bytes */
+  audioSize: number
+  /** Tags, pictures and padding, in file order */
+  blocks: Array<MetadataBlock>
+  /** Total size of the metadata blocks in bytes */
+  metadataSize: number
+  /** Size of the embedded picture data in bytes, `0` when loaded with `skipPictures` */
+  pictureSize: number
+  /** Bitrate of the audio stream alone in kbps, `null` if the duration is unknown */
+  audioBitRate?: number
+}
+
+/**
+ * Codec details beyond the generic audio properties
+ *
+ * Only the field of the file's own format is set.
+ */
+export interface CodecInfo {
+  /** Codec of the audio stream, e.g. `"MP3"`, `"AAC"`, `"ALAC"` or `"PCM"` */
+  codec: string
+  mpeg?: MpegInfo
+  opus?: OpusInfo
+  vorbis?: VorbisInfo
+  mp4?: Mp4Info
+  wav?: WavInfo
+  ape?: ApeInfo
+  wavpack?: WavPackInfo
+}
+
+export interface CopyTagsOptions {
+  /** Only copy these keys, e.g. `["TrackTitle", "ReplayGainTrackGain"]`, defaults to all keys */
+  include?: Array<string>
+  /** Keys that are not copied */
+  exclude?: Array<string>
+  /** Whether to copy embedded pictures, defaults to `true` */
+  pictures?: boolean
+}
+
+/** Result of {@link MusicFile.copyTagsFrom} */
+export interface CopyTagsReport {
+  /** Keys that were copied */
+  copied: Array<string>
+  /**
+   * Keys that cannot be represented in the destination tag type, and were skipped
+   *
+   * Includes the custom fields of the source, like unmapped Vorbis comments and iTunes
+   * freeform atoms, and `"pictures"` if the destination tag type cannot hold pictures.
+   */
+  unsupported: Array<string>
+  /** Number of pictures copied */
+  pictures: number
+}
+
+export interface CueIndex {
+  number: number
+  /** Position in milliseconds from the beginning of the file */
+  time: number
+}
+
+/** Contents of a CUE sheet */
+export interface CueSheet {
+  /** Album title */
+  title?: string
+  /** Album performer */
+  performer?: string
+  songwriter?: string
+  /** Media catalog number */
+  catalog?: string
+  /** From `REM GENRE` */
+  genre?: string
+  /** From `REM DATE` */
+  date?: string
+  /** From `REM COMMENT` */
+  comment?: string
+  tracks: Array<CueTrack>
+}
+
+export interface CueTrack {
+  number: number
+  /** Track data type, `"AUDIO"` for audio tracks */
+  dataType: string
+  /** Name of the file the track is stored in, from the preceding `FILE` command */
+  file?: string
+  /** Type of that file, e.g. `"WAVE"` */
+  fileType?: string
+  title?: string
+  performer?: string
+  songwriter?: string
+  isrc?: string
+  /** Track flags, e.g. `"DCP"` or `"PRE"` */
+  flags: Array<string>
+  /** Silence before the track that is not stored in the file, in milliseconds */
+  pregap?: number
+  /** Silence after the track that is not stored in the file, in milliseconds */
+  postgap?: number
+  indices: Array<CueIndex>
+}
+
+/** A track of a cue sheet, located in the audio of a single file */
+export interface CueTrackEntry {
+  number: number
+  title?: string
+  /** Track performer, or the album performer if the track has none */
+  performer?: string
+  /** Album title */
+  album?: string
+  isrc?: string
+  /** Start of the track (`INDEX 01`) in milliseconds */
+  start: number
+  /** End of the track in milliseconds, the start of the next track or the end of the file */
+  end: number
+  /** Track duration in milliseconds */
+  duration: number
+}
+
+/** Result of comparing a file name extension with the detected file type */
+export interface ExtensionCheck {
+  /** Extension of the file name, lowercased and without the leading dot, `null` if it has none */
+  extension?: string
+  /** Canonical extension of the detected file type */
+  expected: string
+  /** All extensions accepted for the detected file type, e.g. `"ogg"` for Opus */
+  accepted: Array<string>
+  /** Whether the extension is one of the accepted ones */
+  matches: boolean
+}
+
+/** A metadata field that differs from the loaded file */
+export interface FieldChange {
+  /** Field key as used by {@link MusicFile.update}, or `"pictures"` */
+  field: string
+  /** Value when the file was loaded, `null` if it was not set */
+  oldValue: string | number | Array<PictureInfo> | null
+  /** Current value, `null` if it was removed */
+  newValue: string | number | Array<PictureInfo> | null
+  /** Tag type the change is written to */
+  tagType: "AIFF" | "APE" | "ID3V1" | "ID3V2" | "ILST" | "RIFF" | "VORBIS" | null
+}
+
+/** File type detected from the content of a file */
+export interface FileTypeInfo {
+  fileType: "Aac" | "Aiff" | "Ape" | "Flac" | "Mpeg" | "Mp4" | "Mpc" | "Opus" | "Vorbis" | "Speex" | "Wav" | "WavPack"
+  /** Canonical file extension, without the leading dot */
+  extension: string
+  mimeType: string
+}
+
+export interface FlacApplication {
+  /** Registered application ID, four ASCII characters */
+  id: string
+  data: Uint8Array
+}
+
+export interface FlacCueIndex {
+  /** Offset in samples, relative to the track offset */
+  offset: number
+  number: number
+}
+
+/** Contents of the CUESHEET block */
+export interface FlacCueSheet {
+  mediaCatalogNumber: string
+  /** Number of lead-in samples */
+  leadInSamples: number
+  /** Whether the cue sheet corresponds to a Compact Disc */
+  isCd: boolean
+  tracks: Array<FlacCueTrack>
+}
+
+export interface FlacCueTrack {
+  /** Offset in samples, relative to the beginning of the audio */
+  offset: number
+  /** Track number, `170` (CD-DA) or `255` for the lead-out track */
+  number: number
+  isrc: string
+  isAudio: boolean
+  preEmphasis: boolean
+  indices: Array<FlacCueIndex>
+}
+
+/** Metadata blocks of a FLAC file besides Vorbis comments and pictures */
+export interface FlacMetadata {
+  streamInfo: FlacStreamInfo
+  seekPoints: Array<FlacSeekPoint>
+  cueSheet?: FlacCueSheet
+  /** APPLICATION blocks, including changes that are written on the next save */
+  applications: Array<FlacApplication>
+  /** Total size of the PADDING blocks in bytes, including changes that are written on the next save */
+  padding: number
+}
+
+/** Point of the SEEKTABLE block, placeholder points are omitted */
+export interface FlacSeekPoint {
+  /** Number of the first sample in the target frame */
+  sampleNumber: number
+  /** Offset in bytes from the first frame to the target frame */
+  offset: number
+  /** Number of samples in the target frame */
+  samples: number
+}
+
+/** Details of the STREAMINFO block */
+export interface FlacStreamInfo {
+  /** Minimum block size in samples */
+  minBlockSize: number
+  /** Maximum block size in samples */
+  maxBlockSize: number
+  /** Minimum frame size in bytes, `0` if unknown */
+  minFrameSize: number
+  /** Maximum frame size in bytes, `0` if unknown */
+  maxFrameSize: number
+  /** Sample rate in Hz */
+  sampleRate: number
+  channels: number
+  bitsPerSample: number
+  /** Total samples per channel, `0` if unknown */
+  totalSamples: number
+  /** MD5 of the decoded audio as lowercase hex, all zeros if not computed by the encoder */
+  md5: string
+}
+
+/** Encoder delay and padding to trim for gapless playback */
+export interface GaplessInfo {
+  /** Where the values were read from, in this order of precedence */
+  source: "iTunSMPB" | "LAME" | "Opus"
+  /**
+   * Samples to discard from the start of the decoded stream
+   *
+   * Opus counts samples at 48 kHz, other formats at the sample rate of the file.
+   */
+  encoderDelay: number
+  /** Samples to discard from the end of the decoded stream, or `null` if unknown */
+  encoderPadding?: number
+  /** Samples of the original audio, without delay and padding, or `null` if unknown */
+  originalSamples?: number
+}
+
+/** LAME extension of a Xing or Info header, also written by FFmpeg */
+export interface LameInfo {
+  /** Encoder name and version, e.g. `"LAME3.100"` */
+  encoder: string
+  vbrMethod: "CBR" | "ABR" | "VBR" | "Unknown"
+  /** Lowpass filter frequency in Hz */
+  lowpass?: number
+  /** Samples of encoder delay at the start of the stream */
+  encoderDelay: number
+  /** Samples of padding at the end of the stream */
+  encoderPadding: number
+}
+
+export interface LoadOptions {
+  /**
+   * Skip reading embedded pictures, defaults to `false`
+   *
+   * Saves memory when only text metadata is needed. If the file has pictures it cannot be saved,
+   * because writing the tags would remove the skipped pictures.
+   */
+  skipPictures?: boolean
+  /**
+   * Read every MPEG frame for the exact duration and bitrate, defaults to `false`
+   *
+   * Without it, MP3 files lacking a Xing or VBRI header have an estimated duration.
+   * Slower, because the whole file is read. Ignored for other formats.
+   */
+  accurateDuration?: boolean
+}
+
+/** Options of {@link MusicFile.analyzeLoudness} and {@link analyzeAlbumLoudness} */
+export interface LoudnessOptions {
+  /** Target loudness in LUFS the gain is computed against, defaults to `-18` (ReplayGain 2.0) */
+  referenceLoudness?: number
+  /** Measure the true peak by 4x oversampling, defaults to `true` */
+  truePeak?: boolean
+  /**
+   * Write the ReplayGain gain and peak tags, defaults to `false`
+   *
+   * The sample peak is written, as ReplayGain players expect.
+   */
+  write?: boolean
+  /**
+   * With `write`, also write the iTunes Sound Check from the track gain, defaults to `false`
+   *
+   * Skipped for files whose tag is neither MP4 nor ID3v2, see {@link MusicFile.soundCheck}.
+   */
+  soundCheck?: boolean
+}
+
+/** Loudness of a track or an album */
+export interface LoudnessResult {
+  /** Integrated loudness in LUFS, or `null` if the audio is silent */
+  loudness?: number
+  /** Gain in dB to reach the reference loudness, or `null` if the audio is silent */
+  gain?: number
+  /** Sample peak, where `1.0` is full scale */
+  peak: number
+  /** True peak, where `1.0` is full scale, or `null` if not measured */
+  truePeak?: number
+}
+
+/** A metadata block of the loaded file */
+export interface MetadataBlock {
+  kind: "ID3v2" | "ID3v1" | "APE" | "Lyrics3" | "VorbisComment" | "Picture" | "Padding" | "Application" | "CueSheet" | "Ilst" | "RiffInfo" | "AiffText"
+  /** Offset from the start of the file in bytes */
+  offset: number
+  /** Size in bytes, including the block header */
+  size: number
+}
+
+/** Partial metadata update, fields set to `null` are removed and missing fields are left untouched */
+export interface MetadataPatch {
+  title?: string | null
+  artist?: string | null
+  album?: string | null
+  albumArtist?: string | null
+  year?: number | null
+  genre?: string | null
+  trackNumber?: number | null
+  trackTotal?: number | null
+  discNumber?: number | null
+  discsTotal?: number | null
+  comment?: string | null
+  composer?: string | null
+  conductor?: string | null
+  lyricist?: string | null
+  publisher?: string | null
+  lyrics?: string | null
+  copyright?: string | null
+  rating?: number | null
+  trackReplayGain?: number | null
+  trackReplayPeak?: number | null
+  albumReplayGain?: number | null
+  albumReplayPeak?: number | null
+}
+
+/** Details of the audio track of an MP4 file */
+export interface Mp4Info {
+  codec: "AAC" | "ALAC" | "MP3" | "FLAC" | "Unknown"
+  /** MPEG-4 audio object type, e.g. `2` for AAC LC */
+  audioObjectType?: number
+  /** Name of common AAC profiles, e.g. `"LC"` or `"HE-AAC"` */
+  profile?: string
+  /** Major brand of the `ftyp` atom, e.g. `"M4A "` */
+  brand: string
+  drmProtected: boolean
+}
+
+/** Totals of every frame of an MPEG stream */
+export interface MpegFrameScan {
+  /** Number of audio frames, without a Xing, Info or VBRI header frame */
+  frames: number
+  /** Exact duration in milliseconds */
+  duration: number
+  /** Average bitrate of the audio frames in kbps */
+  bitRate: number
+  /** Size of the audio frames in bytes, without tags and pictures */
+  bytes: number
+}
+
+/** Details of the first MPEG audio frame */
+export interface MpegInfo {
+  version: "1" | "2" | "2.5"
+  layer: number
+  channelMode: "Stereo" | "Joint Stereo" | "Dual Channel" | "Mono"
+  /** Joint stereo mode extension bits */
+  modeExtension?: number
+  emphasis?: "50/15 ms" | "CCIT J.17"
+  copyright: boolean
+  original: boolean
+  /** Xing, Info or VBRI header of the first frame */
+  vbrHeader?: MpegVbrHeader
+  /** Totals of every frame, only set when loaded with `accurateDuration` */
+  frameScan?: MpegFrameScan
+}
+
+/** Summary header in the first frame of an MPEG stream */
+export interface MpegVbrHeader {
+  /** `"Info"` is the Xing header of a CBR stream */
+  kind: "Xing" | "Info" | "VBRI"
+  /** Number of audio frames */
+  frames?: number
+  /** Size of the audio stream in bytes */
+  bytes?: number
+  /** Encoder quality indicator */
+  quality?: number
+  /** LAME extension of a Xing or Info header */
+  lame?: LameInfo
+}
+
+/** Plain object with all common metadata and audio properties of a {@link MusicFile} */
+export interface MusicFileSnapshot {
+  tagType?: "AIFF" | "APE" | "ID3V1" | "ID3V2" | "ILST" | "RIFF" | "VORBIS"
+  title?: string
+  artist?: string
+  album?: string
+  albumArtist?: string
+  year?: number
+  genre?: string
+  trackNumber?: number
+  trackTotal?: number
+  discNumber?: number
+  discsTotal?: number
+  comment?: string
+  composer?: string
+  conductor?: string
+  lyricist?: string
+  publisher?: string
+  lyrics?: string
+  copyright?: string
+  rating?: 1 | 2 | 3 | 4 | 5
+  trackReplayGain?: number
+  trackReplayPeak?: number
+  albumReplayGain?: number
+  albumReplayPeak?: number
+  /** Audio duration in milliseconds */
+  duration: number
+  /** Audio bit rate in kbps */
+  bitRate?: number
+  /** Audio sample rate in Hz */
+  sampleRate?: number
+  /** Audio bit depth in bits */
+  bitDepth?: number
+  /** Number of audio channels */
+  channels?: number
+  quality: "HQ" | "SQ" | "HiRes"
+  /** Embedded pictures, image data and hashes are only included when requested */
+  pictures: Array<PictureInfo>
+}
+
+/** How the pictures of an Ogg file are stored in its comment header */
+export interface OggPictureFields {
+  /** Number of `METADATA_BLOCK_PICTURE` fields */
+  blockPictures: number
+  /** Number of legacy `COVERART` fields */
+  legacyCoverArt: number
+  /** Number of picture fields that could not be decoded, these are dropped on save */
+  invalid: number
+}
+
+export interface OptimizePictureOptions {
+  /**
+   * Maximum width and height in pixels, at least `1`, larger images are downscaled keeping the
+   * aspect ratio
+   */
+  maxDimension?: number
+  /** JPEG quality in [1, 100], defaults to `85` */
+  quality?: number
+}
+
+/** Result of {@link MusicFile.optimizePictures} */
+export interface OptimizePicturesReport {
+  /** Number of pictures that were replaced by a downscaled or smaller version */
+  optimized: number
+  /** Total size of all pictures before optimizing, in bytes */
+  bytesBefore: number
+  /** Total size of all pictures after optimizing, in bytes */
+  bytesAfter: number
+  /** Number of bytes saved, negative if downscaled pictures are larger than the originals */
+  bytesSaved: number
+}
+
+/** Details of the Opus identification header */
+export interface OpusInfo {
+  version: number
+  /** Samples to discard from the start of the decoded stream, at 48 kHz */
+  preSkip: number
+  /** Sample rate of the original input in Hz, `0` if unknown */
+  inputSampleRate: number
+  /** Gain applied when decoding, in dB */
+  outputGain: number
+  channelMappingFamily: number
+}
+
+/** Identical pictures embedded in one or more files */
+export interface PictureGroup {
+  /** SHA-256 of the image data as lowercase hex, same as {@link MetaPicture.hash} */
+  hash: string
+  mimeType?: string
+  /** Image data size in bytes */
+  size: number
+  /** Every place the picture is embedded, in file and picture order */
+  occurrences: Array<PictureOccurrence>
+}
+
+/** Picture descriptor without the image data */
+export interface PictureInfo {
+  coverType: "Cover Art (Other)" | "Cover Art (Png Icon)" | "Cover Art (Icon)" | "Cover Art (Front)" | "Cover Art (Back)" | "Cover Art (Leaflet)" | "Cover Art (Media)" | "Cover Art (Lead Artist)" | "Cover Art (Artist)" | "Cover Art (Conductor)" | "Cover Art (Band)" | "Cover Art (Composer)" | "Cover Art (Lyricist)" | "Cover Art (Recording Location)" | "Cover Art (During Recording)" | "Cover Art (During Performance)" | "Cover Art (Video Capture)" | "Cover Art (Fish)" | "Cover Art (Illustration)" | "Cover Art (Band Logotype)" | "Cover Art (Publisher Logotype)" | "Unknown"
+  mimeType?: string
+  description?: string
+  /** Image data size in bytes */
+  size: number
+  /** Image width in pixels, detected from the image data */
+  width?: number
+  /** Image height in pixels, detected from the image data */
+  height?: number
+  /** SHA-256 of the image data as lowercase hex, only present when requested */
+  hash?: string
+  /** Image data, only present when requested */
+  data?: Uint8Array
+}
+
+/** Where a picture of a {@link PictureGroup} is embedded */
+export interface PictureOccurrence {
+  /** Index of the file in the list passed to {@link groupPictures} */
+  fileIndex: number
+  /** Index of the picture in {@link MusicFile.pictureInfo} of that file */
+  pictureIndex: number
+  coverType: "Cover Art (Other)" | "Cover Art (Png Icon)" | "Cover Art (Icon)" | "Cover Art (Front)" | "Cover Art (Back)" | "Cover Art (Leaflet)" | "Cover Art (Media)" | "Cover Art (Lead Artist)" | "Cover Art (Artist)" | "Cover Art (Conductor)" | "Cover Art (Band)" | "Cover Art (Composer)" | "Cover Art (Lyricist)" | "Cover Art (Recording Location)" | "Cover Art (During Recording)" | "Cover Art (During Performance)" | "Cover Art (Video Capture)" | "Cover Art (Fish)" | "Cover Art (Illustration)" | "Cover Art (Band Logotype)" | "Cover Art (Publisher Logotype)" | "Unknown"
+}
+
+/** Type of an embedded picture, the values match {@link MetaPicture.coverType} */
+export declare enum PictureType {
+  Other = 'Cover Art (Other)',
+  PngIcon = 'Cover Art (Png Icon)',
+  Icon = 'Cover Art (Icon)',
+  Front = 'Cover Art (Front)',
+  Back = 'Cover Art (Back)',
+  Leaflet = 'Cover Art (Leaflet)',
+  Media = 'Cover Art (Media)',
+  LeadArtist = 'Cover Art (Lead Artist)',
+  Artist = 'Cover Art (Artist)',
+  Conductor = 'Cover Art (Conductor)',
+  Band = 'Cover Art (Band)',
+  Composer = 'Cover Art (Composer)',
+  Lyricist = 'Cover Art (Lyricist)',
+  RecordingLocation = 'Cover Art (Recording Location)',
+  DuringRecording = 'Cover Art (During Recording)',
+  DuringPerformance = 'Cover Art (During Performance)',
+  VideoCapture = 'Cover Art (Video Capture)',
+  Fish = 'Cover Art (Fish)',
+  Illustration = 'Cover Art (Illustration)',
+  BandLogotype = 'Cover Art (Band Logotype)',
+  PublisherLogotype = 'Cover Art (Publisher Logotype)',
+  Unknown = 'Unknown'
+}
+
+/** Result of {@link MusicFile.classifyQuality} */
+export interface QualityClassification {
+  /** Label of the tier, custom labels applied */
+  label: string
+  tier: "LQ" | "HQ" | "SQ" | "HiRes" | "DSD" | "MQA?"
+  lossless: boolean
+  /** Why the file was classified this way, e.g. `"ALAC is lossless"` */
+  reasons: Array<string>
+}
+
+/** Labels of the quality tiers, e.g. `{ sq: "Lossless" }` */
+export interface QualityLabels {
+  /** Lossy below `minHqBitRate`, defaults to `"LQ"` */
+  lq?: string
+  /** Lossy, defaults to `"HQ"` */
+  hq?: string
+  /** Lossless at CD quality, defaults to `"SQ"` */
+  sq?: string
+  /** Lossless beyond CD quality, defaults to `"HiRes"` */
+  hiRes?: string
+  /** DSD audio, defaults to `"DSD"` */
+  dsd?: string
+  /** Possibly MQA encoded audio, defaults to `"MQA?"` */
+  mqa?: string
+}
+
+/** Thresholds and labels of {@link MusicFile.classifyQuality} */
+export interface QualityOptions {
+  /** Lossy files below this bit rate in kbps are "LQ", defaults to `0` so every lossy file is "HQ" */
+  minHqBitRate?: number
+  /** Lossless files with at least this sample rate in Hz are "HiRes", defaults to `48000` */
+  minHiResSampleRate?: number
+  /** Lossless files with at least this bit depth are "HiRes", defaults to `24` */
+  minHiResBitDepth?: number
+  /** Report DSD audio as "DSD" instead of "HiRes", defaults to `true` */
+  dsd?: boolean
+  /** Report FLAC files tagged by an MQA encoder as "MQA?", defaults to `true` */
+  mqa?: boolean
+  /** Custom labels of the tiers */
+  labels?: QualityLabels
+}
+
+export interface ScanEntry {
+  /** Full path of the audio file */
+  path: string
+  /** File type detected from the file content */
+  fileType: "Aac" | "Aiff" | "Ape" | "Flac" | "Mpeg" | "Mp4" | "Mpc" | "Opus" | "Vorbis" | "Speex" | "Wav" | "WavPack"
+  /** File size in bytes */
+  size: number
+  /** Audio duration in milliseconds */
+  duration: number
+  /** Audio bit rate in kbps */
+  bitRate?: number
+  /** Audio sample rate in Hz */
+  sampleRate?: number
+  /** Number of audio channels */
+  channels?: number
+  title?: string
+  artist?: string
+  album?: string
+}
+
+export interface ScanOptions {
+  /**
+   * Glob patterns a file must match to be reported, relative to the root.
+   * Patterns without `/` are matched against the file name only.
+   */
+  include?: Array<string>
+  /**
+   * Glob patterns of files and directories to skip, relative to the root.
+   * Patterns without `/` are matched against the entry name only.
+   */
+  exclude?: Array<string>
+  /**
+   * File extensions (without the leading dot) to consider, case-insensitive.
+   * All files are probed by content if omitted.
+   */
+  extensions?: Array<string>
+  /** Content-detected file types to report, e.g. `["Flac", "Mpeg"]` */
+  fileTypes?: Array<string>
+  /**
+   * Maximum directory depth below the root, `0` only scans the root itself.
+   * Unlimited if omitted.
+   */
+  maxDepth?: number
+  /** Whether to follow symbolic links, defaults to `false` */
+  followSymlinks?: boolean
+  /** Whether to read title, artist and album, defaults to `true` */
+  readTags?: boolean
+}
+
+export interface SidecarArtOptions {
+  /** Replace embedded pictures of the same type, defaults to `false` which keeps them */
+  replace?: boolean
+}
+
+export interface SnapshotOptions {
+  /** Whether to include image data of embedded pictures, defaults to `false` */
+  includePictureData?: boolean
+  /** Whether to include the SHA-256 hash of embedded pictures, defaults to `false` */
+  includePictureHashes?: boolean
+}
+
+/** iTunes Sound Check values of an `iTunNORM` comment */
+export interface SoundCheck {
+  /** Gain in dB applied by Apple players, from the louder channel */
+  gain: number
+  /** Peak of the louder channel, where `1.0` is full scale */
+  peak: number
+  /** The ten values of the comment */
+  values: Array<number>
+}
+
+/** Bitrates of the Vorbis identification header, in bits per second */
+export interface VorbisInfo {
+  version: number
+  nominalBitrate?: number
+  minBitrate?: number
+  maxBitrate?: number
+}
+
+/** Details of the `fmt ` chunk of a WAV file */
+export interface WavInfo {
+  /** Format tag, `1` for PCM and `3` for IEEE float */
+  formatTag: number
+  /** Speaker positions of the channels, as `WAVE_FORMAT_EXTENSIBLE` channel mask bits */
+  channelMask?: number
+}
+
+/** Details of the first WavPack block */
+export interface WavPackInfo {
+  version: number
+  /** Whether the audio is lossless, `false` for hybrid lossy files */
+  lossless: boolean
+  /** Whether the samples are 32-bit floating point */
+  float: boolean
+  /** Whether the audio is DSD, supported since WavPack 5 */
+  dsd: boolean
+  /** Compression mode, `null` if the encoder didn't store it */
+  compression?: "Fast" | "Normal" | "High" | "Very High"
+}
+
+/**
+ * Measure the loudness of each file and of all files together as an album
+ *
+ * The audio is decoded on a worker thread. The album loudness is gated over the audio of all files,
+ * and the album peak is the highest track peak. With `write`, both the track and the album
+ * ReplayGain tags of every file are written, after checking that all of them can be written.
+ *
+ * @param files Files of the album
+ * @param options Reference loudness, true peak and whether to write the ReplayGain tags
+ * @param sources The content of each file, required for files loaded from a buffer
+ *
+ * @throws If a file was loaded from a buffer and no source is given
+ * @throws If the audio of a file can't be decoded
+ */
+export declare function analyzeAlbumLoudness(files: Array<MusicFile>, options?: LoudnessOptions | undefined | null, sources?: Array<Uint8Array | undefined | null> | undefined | null): Promise<AlbumLoudnessResult>
+
+/**
+ * Measure the loudness of each file and of all files together as an album
+ *
+ * This is the synchronous version of {@link analyzeAlbumLoudness}
+ *
+ * @param files Files of the album
+ * @param options Reference loudness, true peak and whether to write the ReplayGain tags
+ * @param sources The content of each file, required for files loaded from a buffer
+ *
+ * @throws If a file was loaded from a buffer and no source is given
+ * @throws If the audio of a file can't be decoded
+ */
+export declare function analyzeAlbumLoudnessSync(files: Array<MusicFile>, options?: LoudnessOptions | undefined | null, sources?: Array<Uint8Array | undefined | null> | undefined | null): AlbumLoudnessResult
+
+/**
+ * Detect the type of an audio file from its header, without reading tags or audio properties
+ *
+ * The file name is ignored, only the content is used.
+ *
+ * @param source The file system path or a Uint8Array containing the start of the file
+ *
+ * @returns The detected type, or `null` if the content is not a supported audio format
+ *
+ * @throws If the path doesn't exist or isn't accessible
+ * @throws If a path is given in WebAssembly environments
+ */
+export declare function detectFileType(source: Uint8Array | string): FileTypeInfo | null
+
+/**
+ * Write a cue sheet as CUE text
+ *
+ * Times are rounded to whole CD frames (1/75 second). Double quotes in text values are
+ * replaced by single quotes, as CUE has no way to escape them.
+ */
+export declare function formatCueSheet(cue: CueSheet): string
+
+/**
+ * Generate an iTunes `iTunNORM` comment
+ *
+ * @param gain Gain in dB
+ * @param peak Peak where `1.0` is full scale
+ *
+ * @throws If a value is not finite, or the peak is negative
+ */
+export declare function formatSoundCheck(gain: number, peak: number): string
+
+/**
+ * Group the embedded pictures of several files by their image data
+ *
+ * Each distinct image is reported once, with every file and position it is embedded at,
+ * so shared cover art can be stored once. Groups are in the order the images first appear.
+ *
+ * @param files Files to compare
+ */
+export declare function groupPictures(files: Array<MusicFile>): Array<PictureGroup>
+
+/**
+ * Parse a CUE sheet
+ *
+ * @param text Cue sheet text, or its bytes in UTF-8 or Latin-1
+ *
+ * @throws If a command has an invalid number or time
+ */
+export declare function parseCueSheet(text: Uint8Array | string): CueSheet
+
+/**
+ * Parse an iTunes `iTunNORM` comment
+ *
+ * @param text The ten hexadecimal values of the comment
+ *
+ * @returns The Sound Check, or `null` if the text is malformed
+ */
+export declare function parseSoundCheck(text: string): SoundCheck | null
+
+/**
+ * Recursively scan a directory for audio files
+ *
+ * Files are detected by their content rather than their extension. Each audio file
+ * is streamed to `onEntry` with lightweight metadata as soon as it is found.
+ *
+ * @param root The directory to scan
+ * @param onEntry Called for every audio file found
+ * @param options Filtering and traversal options
+ *
+ * @returns The number of audio files found
+ *
+ * @throws If the root doesn't exist or isn't a directory
+ * @throws If runs in WebAssembly environments (due to file system restrictions).
+ */
+export declare function scanDirectory(root: string, onEntry: ((arg: ScanEntry) => void), options?: ScanOptions | undefined | null): Promise<number>
+
+/**
+ * Recursively scan a directory for audio files
+ *
+ * This is the synchronous version of {@link scanDirectory}, returning all entries at once.
+ *
+ * @param root The directory to scan
+ * @param options Filtering and traversal options
+ *
+ * @throws If the root doesn't exist or isn't a directory
+ * @throws If runs in WebAssembly environments (due to file system restrictions).
+ */
+export declare function scanDirectorySync(root: string, options?: ScanOptions | undefined | null): Array<ScanEntry>
@@ -702,3 +702,14 @@ if (!nativeBinding) {
 module.exports = nativeBinding
 module.exports.MetaPicture = nativeBinding.MetaPicture
 module.exports.MusicFile = nativeBinding.MusicFile
+module.exports.PictureType = nativeBinding.PictureType
+module.exports.analyzeAlbumLoudness = nativeBinding.analyzeAlbumLoudness
+module.exports.analyzeAlbumLoudnessSync = nativeBinding.analyzeAlbumLoudnessSync
+module.exports.detectFileType = nativeBinding.detectFileType
+module.exports.formatCueSheet = nativeBinding.formatCueSheet
+module.exports.formatSoundCheck = nativeBinding.formatSoundCheck
+module.exports.groupPictures = nativeBinding.groupPictures
+module.exports.parseCueSheet = nativeBinding.parseCueSheet
+module.exports.parseSoundCheck = nativeBinding.parseSoundCheck
+module.exports.scanDirectory = nativeBinding.scanDirectory
+module.exports.scanDirectorySync = nativeBinding.scanDirectorySync
@@ -1,12 +1,14 @@
-import type { MetaPicture as MetaPictureInstance, MusicFile as MusicFileInstance } from './index'
+import type { LoadOptions, MetaPicture as MetaPictureInstance, MusicFile as MusicFileInstance } from './index'
 
 export type { MetaPicture as MetaPictureInstance, MusicFile as MusicFileInstance } from './index'
 
+export { PictureType, analyzeAlbumLoudness, analyzeAlbumLoudnessSync, detectFileType, formatCueSheet, formatSoundCheck, groupPictures, parseCueSheet, parseSoundCheck } from './index'
+
 export declare const MetaPicture: typeof MetaPictureInstance
 
 export declare const MusicFile: Omit<typeof MusicFileInstance, 'load' | 'loadSync'> & {
-  load: (buffer: Uint8Array) => Promise<MusicFileInstance>
-  loadSync: (buffer: Uint8Array) => MusicFileInstance
+  load: (buffer: Uint8Array, options?: LoadOptions | undefined | null) => Promise<MusicFileInstance>
+  loadSync: (buffer: Uint8Array, options?: LoadOptions | undefined | null) => MusicFileInstance
 }
 
 declare const binding: {
@@ -603,3 +603,14 @@ try {
 export default __napiModule.exports
 export const MetaPicture = __napiModule.exports.MetaPicture
 export const MusicFile = __napiModule.exports.MusicFile
+export const PictureType = __napiModule.exports.PictureType
+export const analyzeAlbumLoudness = __napiModule.exports.analyzeAlbumLoudness
+export const analyzeAlbumLoudnessSync = __napiModule.exports.analyzeAlbumLoudnessSync
+export const detectFileType = __napiModule.exports.detectFileType
+export const formatCueSheet = __napiModule.exports.formatCueSheet
+export const formatSoundCheck = __napiModule.exports.formatSoundCheck
+export const groupPictures = __napiModule.exports.groupPictures
+export const parseCueSheet = __napiModule.exports.parseCueSheet
+export const parseSoundCheck = __napiModule.exports.parseSoundCheck
+export const scanDirectory = __napiModule.exports.scanDirectory
+export const scanDirectorySync = __napiModule.exports.scanDirectorySync
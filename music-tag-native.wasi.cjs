@@ -1,4 +1,4 @@
-// napi-rs-artifact-metadata:{"version":2,"rootEntry":"index.js","exports":["MetaPicture","MusicFile"],"managedRootEntries":["browser.js","index.js","music-tag-native.wasm","music-tag-native.debug.wasm"]}
+// napi-rs-artifact-metadata:{"version":2,"rootEntry":"index.js","exports":["MetaPicture","MusicFile","PictureType","analyzeAlbumLoudness","analyzeAlbumLoudnessSync","detectFileType","formatCueSheet","formatSoundCheck","groupPictures","parseCueSheet","parseSoundCheck","scanDirectory","scanDirectorySync"],"managedRootEntries":["browser.js","index.js","music-tag-native.wasm","music-tag-native.debug.wasm"]}
 /* eslint-disable */
 /* prettier-ignore */
 
@@ -923,3 +923,14 @@ try {
 module.exports = __napiModule.exports
 module.exports.MetaPicture = __napiModule.exports.MetaPicture
 module.exports.MusicFile = __napiModule.exports.MusicFile
+module.exports.PictureType = __napiModule.exports.PictureType
+module.exports.analyzeAlbumLoudness = __napiModule.exports.analyzeAlbumLoudness
+module.exports.analyzeAlbumLoudnessSync = __napiModule.exports.analyzeAlbumLoudnessSync
+module.exports.detectFileType = __napiModule.exports.detectFileType
+module.exports.formatCueSheet = __napiModule.exports.formatCueSheet
+module.exports.formatSoundCheck = __napiModule.exports.formatSoundCheck
+module.exports.groupPictures = __napiModule.exports.groupPictures
+module.exports.parseCueSheet = __napiModule.exports.parseCueSheet
+module.exports.parseSoundCheck = __napiModule.exports.parseSoundCheck
+module.exports.scanDirectory = __napiModule.exports.scanDirectory
+module.exports.scanDirectorySync = __napiModule.exports.scanDirectorySync
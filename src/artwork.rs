@@ -0,0 +1,319 @@
+use std::io::Cursor;
+
+use image::{
+    codecs::jpeg::JpegEncoder, imageops::FilterType, metadata::Orientation, DynamicImage,
+    ImageDecoder, ImageReader, ImageResult,
+};
+use lofty::picture::PictureInformation;
+use sha2::{Digest, Sha256};
+
+/// Image format and header information detected from image bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageInfo {
+    pub mime_type: &'static str,
+    /// Width in pixels, `0` if the header could not be parsed
+    pub width: u32,
+    /// Height in pixels, `0` if the header could not be parsed
+    pub height: u32,
+    /// Color depth in bits per pixel
+    pub color_depth: u32,
+    /// Number of colors of indexed-color images, `0` otherwise
+    pub num_colors: u32,
+}
+
+impl ImageInfo {
+    pub fn picture_information(&self) -> PictureInformation {
+        PictureInformation {
+            width: self.width,
+            height: self.height,
+            color_depth: self.color_depth,
+            num_colors: self.num_colors,
+        }
+    }
+}
+
+/// Detect the image MIME type from the magic bytes
+pub fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+    match data {
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some("image/png"),
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some("image/gif"),
+        [b'B', b'M', ..] if is_bmp(data) => Some("image/bmp"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        [b'I', b'I', 0x2A, 0x00, ..] | [b'M', b'M', 0x00, 0x2A, ..] => Some("image/tiff"),
+        _ => None,
+    }
+}
+
+/// Stable content hash of image bytes, as lowercase hex SHA-256
+pub fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Detect the image format and parse its header
+///
+/// Returns `None` if the data is not a supported image format.
+pub fn image_info(data: &[u8]) -> Option<ImageInfo> {
+    let mime_type = sniff_mime_type(data)?;
+
+    let info = match mime_type {
+        "image/jpeg" => PictureInformation::from_jpeg(data).ok(),
+        "image/png" => PictureInformation::from_png(data).ok(),
+        "image/gif" => gif_info(data),
+        "image/bmp" => bmp_info(data),
+        "image/webp" => webp_info(data),
+        "image/tiff" => tiff_info(data),
+        _ => None,
+    }
+    .unwrap_or_default();
+
+    Some(ImageInfo {
+        mime_type,
+        width: info.width,
+        height: info.height,
+        color_depth: info.color_depth,
+        num_colors: info.num_colors,
+    })
+}
+
+fn u16_le(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn u24_le(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 3)?;
+    Some(u32::from(bytes[0]) | u32::from(bytes[1]) << 8 | u32::from(bytes[2]) << 16)
+}
+
+fn gif_info(data: &[u8]) -> Option<PictureInformation> {
+    let width = u32::from(u16_le(data, 6)?);
+    let height = u32::from(u16_le(data, 8)?);
+    let packed = *data.get(10)?;
+
+    // Size of the global color table is 2^(N + 1), stored in the lowest 3 bits
+    let table_bits = u32::from(packed & 0x07) + 1;
+    let has_table = packed & 0x80 != 0;
+
+    Some(PictureInformation {
+        width,
+        height,
+        color_depth: table_bits,
+        num_colors: if has_table { 1 << table_bits } else { 0 },
+    })
+}
+
+/// Whether `BM` data has a consistent BMP file header and a known DIB header
+///
+/// `BM` alone is too common at the start of other data, e.g. text.
+fn is_bmp(data: &[u8]) -> bool {
+    const FILE_HEADER_SIZE: u32 = 14;
+    let (Some(file_size), Some(pixel_offset), Some(dib_size)) =
+        (u32_le(data, 2), u32_le(data, 10), u32_le(data, 14))
+    else {
+        return false;
+    };
+
+    // BITMAPCOREHEADER, OS/2 and BITMAPINFOHEADER up to BITMAPV5HEADER
+    matches!(dib_size, 12 | 16 | 40 | 52 | 56 | 64 | 108 | 124)
+        && pixel_offset >= FILE_HEADER_SIZE + dib_size
+        && file_size >= pixel_offset
+        && file_size as usize <= data.len()
+}
+
+fn bmp_info(data: &[u8]) -> Option<PictureInformation> {
+    let header_size = u32_le(data, 14)?;
+
+    let (width, height, color_depth, colors_used) = if header_size == 12 {
+        // BITMAPCOREHEADER
+        (
+            u32::from(u16_le(data, 18)?),
+            u32::from(u16_le(data, 20)?),
+            u32::from(u16_le(data, 24)?),
+            0,
+        )
+    } else {
+        // BITMAPINFOHEADER and later, height is negative for top-down bitmaps
+        (
+            (u32_le(data, 18)? as i32).unsigned_abs(),
+            (u32_le(data, 22)? as i32).unsigned_abs(),
+            u32::from(u16_le(data, 28)?),
+            u32_le(data, 46).unwrap_or(0),
+        )
+    };
+
+    let num_colors = match color_depth {
+        1..=8 if colors_used > 0 => colors_used,
+        1..=8 => 1 << color_depth,
+        _ => 0,
+    };
+
+    Some(PictureInformation {
+        width,
+        height,
+        color_depth,
+        num_colors,
+    })
+}
+
+fn webp_info(data: &[u8]) -> Option<PictureInformation> {
+    let (width, height, has_alpha) = match data.get(12..16)? {
+        b"VP8 " => {
+            // Lossy bitstream, the key frame start code precedes the dimensions
+            if data.get(23..26)? != [0x9D, 0x01, 0x2A] {
+                return None;
+            }
+            (
+                u32::from(u16_le(data, 26)? & 0x3FFF),
+                u32::from(u16_le(data, 28)? & 0x3FFF),
+                false,
+            )
+        }
+        b"VP8L" => {
+            // Lossless bitstream, 14 bits each for width and height minus one
+            if *data.get(20)? != 0x2F {
+                return None;
+            }
+            let bits = u32_le(data, 21)?;
+            (
+                (bits & 0x3FFF) + 1,
+                ((bits >> 14) & 0x3FFF) + 1,
+                (bits >> 28) & 1 != 0,
+            )
+        }
+        b"VP8X" => {
+            // Extended format, 24 bits each for canvas width and height minus one
+            let flags = *data.get(20)?;
+            (
+                u24_le(data, 24)? + 1,
+                u24_le(data, 27)? + 1,
+                flags & 0x10 != 0,
+            )
+        }
+        _ => return None,
+    };
+
+    Some(PictureInformation {
+        width,
+        height,
+        color_depth: if has_alpha { 32 } else { 24 },
+        num_colors: 0,
+    })
+}
+
+fn tiff_info(data: &[u8]) -> Option<PictureInformation> {
+    let big_endian = data.starts_with(b"MM");
+    let read_u16 = |offset: usize| -> Option<u16> {
+        let bytes: [u8; 2] = data.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    };
+
+    let ifd = read_u32(4)? as usize;
+    let entry_count = read_u16(ifd)? as usize;
+
+    let mut width = 0;
+    let mut height = 0;
+    let mut bits_per_sample = 1;
+    let mut samples_per_pixel = 1;
+    let mut is_palette = false;
+    for index in 0..entry_count {
+        let entry = ifd + 2 + index * 12;
+        let tag = read_u16(entry)?;
+        let field_type = read_u16(entry + 2)?;
+        let count = read_u32(entry + 4)?;
+        // SHORT values are stored in the first 2 bytes of the value field, LONG values use all 4
+        let value = match field_type {
+            3 => u32::from(read_u16(entry + 8)?),
+            4 => read_u32(entry + 8)?,
+            _ => continue,
+        };
+
+        match tag {
+            256 => width = value,
+            257 => height = value,
+            258 => {
+                // With more than 2 samples the values are stored at an offset
+                bits_per_sample = if field_type == 3 && count > 2 {
+                    u32::from(read_u16(read_u32(entry + 8)? as usize)?)
+                } else {
+                    value
+                };
+            }
+            262 => is_palette = value == 3,
+            277 => samples_per_pixel = value,
+            _ => {}
+        }
+    }
+
+    Some(PictureInformation {
+        width,
+        height,
+        color_depth: bits_per_sample.saturating_mul(samples_per_pixel),
+        num_colors: if is_palette && bits_per_sample < 32 {
+            1 << bits_per_sample
+        } else {
+            0
+        },
+    })
+}
+
+/// JPEG data of a re-encoded image
+pub struct Recompressed {
+    pub data: Vec<u8>,
+    /// Whether the image was downscaled to fit the maximum dimension
+    pub resized: bool,
+}
+
+/// Decode an image, downscale it to fit in `max_dimension` and re-encode it as JPEG
+///
+/// The aspect ratio is kept, images that already fit are only re-encoded. The JPEG is written
+/// without EXIF data, so the EXIF orientation is applied to the pixels instead.
+pub fn recompress_as_jpeg(
+    data: &[u8],
+    max_dimension: Option<u32>,
+    quality: u8,
+) -> ImageResult<Recompressed> {
+    let mut decoder = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut decoded = DynamicImage::from_decoder(decoder)?;
+    decoded.apply_orientation(orientation);
+
+    let max_dimension =
+        max_dimension.filter(|&max| decoded.width() > max || decoded.height() > max);
+    if let Some(max) = max_dimension {
+        decoded = decoded.resize(max, max, FilterType::Lanczos3);
+    }
+
+    // JPEG has no alpha channel
+    let rgb = decoded.into_rgb8();
+    let mut output = Vec::new();
+    JpegEncoder::new_with_quality(&mut output, quality).encode_image(&rgb)?;
+    Ok(Recompressed {
+        data: output,
+        resized: max_dimension.is_some(),
+    })
+}
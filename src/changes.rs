@@ -0,0 +1,213 @@
+use lofty::{
+    file::{AudioFile, TaggedFile as LoftyTaggedFile, TaggedFileExt},
+    picture::Picture,
+    tag::Tag,
+};
+use napi::bindgen_prelude::Either3;
+use napi_derive::napi;
+
+use crate::{
+    meta_picture::{picture_info, PictureInfo},
+    music_file::{
+        fields::{FieldValue, FIELD_KEYS},
+        history::History,
+        MusicFile,
+    },
+};
+
+/// A metadata field that differs from the loaded file
+#[napi(object, use_nullable = true)]
+pub struct FieldChange {
+    /// Field key as used by {@link MusicFile.update}, or `"pictures"`
+    pub field: String,
+    /// Value when the file was loaded, `null` if it was not set
+    #[napi(ts_type = "string | number | Array<PictureInfo> | null")]
+    pub old_value: Option<Either3<String, f64, Vec<PictureInfo>>>,
+    /// Current value, `null` if it was removed
+    #[napi(ts_type = "string | number | Array<PictureInfo> | null")]
+    pub new_value: Option<Either3<String, f64, Vec<PictureInfo>>>,
+    /// Tag type the change is written to
+    #[napi(ts_type = r#""AIFF" | "APE" | "ID3V1" | "ID3V2" | "ILST" | "RIFF" | "VORBIS" | null"#)]
+    pub tag_type: Option<String>,
+}
+
+fn to_change_value(value: FieldValue) -> Either3<String, f64, Vec<PictureInfo>> {
+    match value {
+        FieldValue::Text(text) => Either3::A(text),
+        FieldValue::Number(number) => Either3::B(number),
+    }
+}
+
+fn is_empty_tag(tag: &Tag) -> bool {
+    tag.item_count() == 0 && tag.picture_count() == 0
+}
+
+fn tag_equal(a: &Tag, b: &Tag) -> bool {
+    a.tag_type() == b.tag_type()
+        && a.item_count() == b.item_count()
+        && a.items().all(|item| b.items().any(|other| item == other))
+        && a.pictures() == b.pictures()
+}
+
+/// Compare two tag lists, ignoring item order and empty tags
+pub(crate) fn tags_equal(a: &[Tag], b: &[Tag]) -> bool {
+    let a: Vec<&Tag> = a.iter().filter(|tag| !is_empty_tag(tag)).collect();
+    let b: Vec<&Tag> = b.iter().filter(|tag| !is_empty_tag(tag)).collect();
+
+    a.len() == b.len()
+        && a.iter()
+            .all(|tag| b.iter().any(|other| tag_equal(tag, other)))
+}
+
+impl MusicFile {
+    /// Keep a copy of the loaded tags before they are modified for the first time
+    pub(crate) fn track_original(&mut self) {
+        if self.original.is_none() {
+            self.original = Some(self.file.tags().to_vec());
+        }
+    }
+
+    /// Pictures of the primary or first available tag
+    fn picture_slice(&self) -> &[Picture] {
+        self.file
+            .primary_tag()
+            .or_else(|| self.file.first_tag())
+            .map_or(&[], |tag| tag.pictures())
+    }
+
+    /// Detached file with the tags as they were when loaded, `None` if never modified
+    fn original_file(&self) -> Option<MusicFile> {
+        let tags = self.original.as_ref()?;
+
+        Some(MusicFile {
+            file: LoftyTaggedFile::new(
+                self.file.file_type(),
+                self.file.properties().clone(),
+                tags.clone(),
+            ),
+            inner: self.inner.clone(),
+            original: None,
+            history: History::disabled(),
+            pictures_skipped: self.pictures_skipped,
+            ogg_pictures: self.ogg_pictures.clone(),
+            flac_blocks: self.flac_blocks.clone(),
+            opus_header: self.opus_header.clone(),
+            itunes_atoms: self.itunes_atoms.clone(),
+            codec_info: self.codec_info.clone(),
+            layout: self.layout.clone(),
+            cue_sheet_text: self.cue_sheet_text.clone(),
+            load_options: self.load_options.clone(),
+        })
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// Whether the metadata was modified since the file was loaded or saved to its path
+    ///
+    /// @note Setting a field back to its loaded value makes the file clean again.
+    /// A pending {@link convertLegacyCoverArt}, FLAC block, Opus output gain and MP4 iTunes atom changes always make the file dirty.
+    #[napi(getter)]
+    pub fn is_dirty(&self) -> bool {
+        let convert_pending = self
+            .ogg_pictures
+            .as_ref()
+            .is_some_and(|state| state.convert_pending);
+        let flac_blocks_modified = self
+            .flac_blocks
+            .as_ref()
+            .is_some_and(|flac_blocks| flac_blocks.is_modified());
+        let opus_header_modified = self
+            .opus_header
+            .as_ref()
+            .is_some_and(|opus_header| opus_header.is_modified());
+        let itunes_atoms_modified = self
+            .itunes_atoms
+            .as_ref()
+            .is_some_and(|itunes_atoms| itunes_atoms.is_modified());
+
+        convert_pending
+            || flac_blocks_modified
+            || opus_header_modified
+            || itunes_atoms_modified
+            || self
+                .original
+                .as_ref()
+                .is_some_and(|original| !tags_equal(original, self.file.tags()))
+    }
+
+    /// List the metadata fields that differ from the loaded file
+    #[napi]
+    pub fn changes(&self) -> Vec<FieldChange> {
+        let Some(original) = self.original_file() else {
+            return Vec::new();
+        };
+        let tag_type = self.tag_type();
+
+        let mut changes: Vec<FieldChange> = FIELD_KEYS
+            .iter()
+            .filter_map(|key| {
+                let old_value = original.get_field(key);
+                let new_value = self.get_field(key);
+
+                (old_value != new_value).then(|| FieldChange {
+                    field: key.to_string(),
+                    old_value: old_value.map(to_change_value),
+                    new_value: new_value.map(to_change_value),
+                    tag_type: tag_type.clone(),
+                })
+            })
+            .collect();
+
+        let old_pictures = original.picture_slice();
+        let new_pictures = self.picture_slice();
+        if old_pictures != new_pictures {
+            let to_value = |pictures: &[Picture]| {
+                (!pictures.is_empty()).then(|| {
+                    Either3::C(
+                        pictures
+                            .iter()
+                            .map(|pic| picture_info(pic, false, false))
+                            .collect(),
+                    )
+                })
+            };
+            changes.push(FieldChange {
+                field: "pictures".to_string(),
+                old_value: to_value(old_pictures),
+                new_value: to_value(new_pictures),
+                tag_type,
+            });
+        }
+
+        changes
+    }
+
+    /// Discard all metadata modifications, restoring the tags as they were when loaded
+    #[napi]
+    pub fn revert(&mut self) {
+        if !self.is_dirty() {
+            return;
+        }
+
+        self.record_history();
+        if let Some(state) = self.ogg_pictures.as_mut() {
+            state.convert_pending = false;
+        }
+        if let Some(flac_blocks) = self.flac_blocks.as_mut() {
+            flac_blocks.revert();
+        }
+        if let Some(opus_header) = self.opus_header.as_mut() {
+            opus_header.revert();
+        }
+        if let Some(itunes_atoms) = self.itunes_atoms.as_mut() {
+            itunes_atoms.revert();
+        }
+        if let Some(original) = self.original.take() {
+            self.file.clear();
+            for tag in original {
+                self.file.insert_tag(tag);
+            }
+        }
+    }
+}
@@ -0,0 +1,820 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use lofty::{
+    file::{AudioFile, FileType},
+    iff::wav::{WavFile, WavFormat},
+    mp4::{AudioObjectType, Mp4Codec, Mp4File},
+};
+use napi_derive::napi;
+
+use crate::{
+    music_file::{ogg_pictures::OggHeaders, MusicFile},
+    utils::skip_id3v2,
+};
+
+/// How far into the stream to look for the first MPEG frame
+const MPEG_SEARCH_LIMIT: usize = 64 * 1024;
+/// Start of the tags that can follow the last MPEG frame: ID3v1, APE and Lyrics3
+const MPEG_TRAILING_TAGS: [&[u8]; 3] = [b"TAG", b"APET", b"LYRI"];
+
+/// Details of the first MPEG audio frame
+#[napi(object)]
+#[derive(Clone)]
+pub struct MpegInfo {
+    #[napi(ts_type = r#""1" | "2" | "2.5""#)]
+    pub version: String,
+    pub layer: u32,
+    #[napi(ts_type = r#""Stereo" | "Joint Stereo" | "Dual Channel" | "Mono""#)]
+    pub channel_mode: String,
+    /// Joint stereo mode extension bits
+    pub mode_extension: Option<u32>,
+    #[napi(ts_type = r#""50/15 ms" | "CCIT J.17""#)]
+    pub emphasis: Option<String>,
+    pub copyright: bool,
+    pub original: bool,
+    /// Xing, Info or VBRI header of the first frame
+    pub vbr_header: Option<MpegVbrHeader>,
+    /// Totals of every frame, only set when loaded with `accurateDuration`
+    pub frame_scan: Option<MpegFrameScan>,
+}
+
+/// Totals of every frame of an MPEG stream
+#[napi(object)]
+#[derive(Clone)]
+pub struct MpegFrameScan {
+    /// Number of audio frames, without a Xing, Info or VBRI header frame
+    pub frames: u32,
+    /// Exact duration in milliseconds
+    pub duration: f64,
+    /// Average bitrate of the audio frames in kbps
+    pub bit_rate: u32,
+    /// Size of the audio frames in bytes, without tags and pictures
+    pub bytes: f64,
+}
+
+/// Summary header in the first frame of an MPEG stream
+#[napi(object)]
+#[derive(Clone)]
+pub struct MpegVbrHeader {
+    /// `"Info"` is the Xing header of a CBR stream
+    #[napi(ts_type = r#""Xing" | "Info" | "VBRI""#)]
+    pub kind: String,
+    /// Number of audio frames
+    pub frames: Option<u32>,
+    /// Size of the audio stream in bytes
+    pub bytes: Option<u32>,
+    /// Encoder quality indicator
+    pub quality: Option<u32>,
+    /// LAME extension of a Xing or Info header
+    pub lame: Option<LameInfo>,
+}
+
+/// LAME extension of a Xing or Info header, also written by FFmpeg
+#[napi(object)]
+#[derive(Clone)]
+pub struct LameInfo {
+    /// Encoder name and version, e.g. `"LAME3.100"`
+    pub encoder: String,
+    #[napi(ts_type = r#""CBR" | "ABR" | "VBR" | "Unknown""#)]
+    pub vbr_method: String,
+    /// Lowpass filter frequency in Hz
+    pub lowpass: Option<u32>,
+    /// Samples of encoder delay at the start of the stream
+    pub encoder_delay: u32,
+    /// Samples of padding at the end of the stream
+    pub encoder_padding: u32,
+}
+
+/// Details of the Opus identification header
+#[napi(object)]
+#[derive(Clone)]
+pub struct OpusInfo {
+    pub version: u32,
+    /// Samples to discard from the start of the decoded stream, at 48 kHz
+    pub pre_skip: u32,
+    /// Sample rate of the original input in Hz, `0` if unknown
+    pub input_sample_rate: u32,
+    /// Gain applied when decoding, in dB
+    pub output_gain: f64,
+    pub channel_mapping_family: u32,
+}
+
+/// Bitrates of the Vorbis identification header, in bits per second
+#[napi(object)]
+#[derive(Clone)]
+pub struct VorbisInfo {
+    pub version: u32,
+    pub nominal_bitrate: Option<u32>,
+    pub min_bitrate: Option<u32>,
+    pub max_bitrate: Option<u32>,
+}
+
+/// Details of the audio track of an MP4 file
+#[napi(object)]
+#[derive(Clone)]
+pub struct Mp4Info {
+    #[napi(ts_type = r#""AAC" | "ALAC" | "MP3" | "FLAC" | "Unknown""#)]
+    pub codec: String,
+    /// MPEG-4 audio object type, e.g. `2` for AAC LC
+    pub audio_object_type: Option<u32>,
+    /// Name of common AAC profiles, e.g. `"LC"` or `"HE-AAC"`
+    pub profile: Option<String>,
+    /// Major brand of the `ftyp` atom, e.g. `"M4A "`
+    pub brand: String,
+    pub drm_protected: bool,
+}
+
+/// Details of the `fmt ` chunk of a WAV file
+#[napi(object)]
+#[derive(Clone)]
+pub struct WavInfo {
+    /// Format tag, `1` for PCM and `3` for IEEE float
+    pub format_tag: u32,
+    /// Speaker positions of the channels, as `WAVE_FORMAT_EXTENSIBLE` channel mask bits
+    pub channel_mask: Option<u32>,
+}
+
+/// Details of the Monkey's Audio header
+#[napi(object)]
+#[derive(Clone)]
+pub struct ApeInfo {
+    /// Encoder version, e.g. `3990` for 3.99
+    pub version: u32,
+    /// Compression level, `1000` (Fast) to `5000` (Insane)
+    pub compression_level: u32,
+    #[napi(ts_type = r#""Fast" | "Normal" | "High" | "Extra High" | "Insane" | "Unknown""#)]
+    pub compression: String,
+}
+
+/// Details of the first WavPack block
+#[napi(object)]
+#[derive(Clone)]
+pub struct WavPackInfo {
+    pub version: u32,
+    /// Whether the audio is lossless, `false` for hybrid lossy files
+    pub lossless: bool,
+    /// Whether the samples are 32-bit floating point
+    pub float: bool,
+    /// Whether the audio is DSD, supported since WavPack 5
+    pub dsd: bool,
+    /// Compression mode, `null` if the encoder didn't store it
+    #[napi(ts_type = r#""Fast" | "Normal" | "High" | "Very High""#)]
+    pub compression: Option<String>,
+}
+
+/// Codec details beyond the generic audio properties
+///
+/// Only the field of the file's own format is set.
+#[napi(object)]
+#[derive(Clone)]
+pub struct CodecInfo {
+    /// Codec of the audio stream, e.g. `"MP3"`, `"AAC"`, `"ALAC"` or `"PCM"`
+    pub codec: String,
+    pub mpeg: Option<MpegInfo>,
+    pub opus: Option<OpusInfo>,
+    pub vorbis: Option<VorbisInfo>,
+    pub mp4: Option<Mp4Info>,
+    pub wav: Option<WavInfo>,
+    pub ape: Option<ApeInfo>,
+    pub wavpack: Option<WavPackInfo>,
+}
+
+impl CodecInfo {
+    fn new(codec: &str) -> Self {
+        Self {
+            codec: codec.to_owned(),
+            mpeg: None,
+            opus: None,
+            vorbis: None,
+            mp4: None,
+            wav: None,
+            ape: None,
+            wavpack: None,
+        }
+    }
+}
+
+fn u16_le(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+/// Header of an MPEG audio frame
+#[derive(Clone, Copy, PartialEq)]
+struct FrameHeader {
+    /// `1`, `2`, or `0` for MPEG 2.5
+    version: u8,
+    layer: u8,
+    /// Bitrate in kbps
+    bitrate: u32,
+    sample_rate: u32,
+    padding: bool,
+    channel_mode: u8,
+    mode_extension: u8,
+    copyright: bool,
+    original: bool,
+    emphasis: u8,
+}
+
+const BITRATES_V1: [[u32; 15]; 3] = [
+    [
+        0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+    ],
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+    ],
+    [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ],
+];
+const BITRATES_V2: [[u32; 15]; 2] = [
+    [
+        0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+    ],
+    [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+];
+
+impl FrameHeader {
+    fn parse(header: &[u8]) -> Option<Self> {
+        let header = u32_be(header, 0)?;
+        if header >> 21 != 0x7FF {
+            return None;
+        }
+
+        let version = match (header >> 19) & 0b11 {
+            0 => 0,
+            2 => 2,
+            3 => 1,
+            _ => return None,
+        };
+        let layer = match (header >> 17) & 0b11 {
+            1 => 3,
+            2 => 2,
+            3 => 1,
+            _ => return None,
+        };
+        let bitrate_index = ((header >> 12) & 0xF) as usize;
+        // Free format streams have no fixed frame length
+        if bitrate_index == 0 || bitrate_index == 15 {
+            return None;
+        }
+        let bitrate = match version {
+            1 => BITRATES_V1[layer as usize - 1][bitrate_index],
+            _ => BITRATES_V2[usize::from(layer != 1)][bitrate_index],
+        };
+        let sample_rate = match (header >> 10) & 0b11 {
+            0 => 44100,
+            1 => 48000,
+            2 => 32000,
+            _ => return None,
+        } >> match version {
+            1 => 0,
+            2 => 1,
+            _ => 2,
+        };
+
+        Some(Self {
+            version,
+            layer,
+            bitrate,
+            sample_rate,
+            padding: (header >> 9) & 1 != 0,
+            channel_mode: ((header >> 6) & 0b11) as u8,
+            mode_extension: ((header >> 4) & 0b11) as u8,
+            copyright: (header >> 3) & 1 != 0,
+            original: (header >> 2) & 1 != 0,
+            emphasis: (header & 0b11) as u8,
+        })
+    }
+
+    /// Samples per channel in one frame
+    fn samples(&self) -> u32 {
+        match (self.layer, self.version) {
+            (1, _) => 384,
+            (2, _) | (3, 1) => 1152,
+            _ => 576,
+        }
+    }
+
+    /// Frame size in bytes, including the header
+    fn len(&self) -> usize {
+        let padding = u32::from(self.padding);
+        let len = match self.layer {
+            1 => (12 * self.bitrate * 1000 / self.sample_rate + padding) * 4,
+            _ => self.samples() / 8 * self.bitrate * 1000 / self.sample_rate + padding,
+        };
+        len as usize
+    }
+
+    /// Offset of a Xing header from the frame start, after the side information
+    fn xing_offset(&self) -> usize {
+        let mono = self.channel_mode == 3;
+        4 + match (self.version, mono) {
+            (1, false) => 32,
+            (1, true) | (_, false) => 17,
+            (_, true) => 9,
+        }
+    }
+
+    /// Whether another frame header belongs to the same stream
+    fn matches(&self, other: &Self) -> bool {
+        self.version == other.version
+            && self.layer == other.layer
+            && self.sample_rate == other.sample_rate
+    }
+}
+
+/// Find the first MPEG frame in a buffer, checking that the next frame follows it
+fn find_first_frame(data: &[u8]) -> Option<(usize, FrameHeader)> {
+    (0..data.len().saturating_sub(4)).find_map(|offset| {
+        if data[offset] != 0xFF {
+            return None;
+        }
+        let header = FrameHeader::parse(&data[offset..])?;
+        let next = offset + header.len();
+        match data.get(next..next + 4) {
+            Some(next) => FrameHeader::parse(next)
+                .filter(|next| header.matches(next))
+                .map(|_| (offset, header)),
+            // The next frame is beyond the searched range, trust the header
+            None => Some((offset, header)),
+        }
+    })
+}
+
+fn parse_lame(data: &[u8]) -> Option<LameInfo> {
+    let encoder = data.get(..9)?;
+    if !encoder[..4].iter().all(u8::is_ascii_alphanumeric) {
+        return None;
+    }
+    let encoder = String::from_utf8_lossy(encoder)
+        .trim_end_matches(['\0', ' '])
+        .to_owned();
+
+    let vbr_method = match data.get(9)? & 0x0F {
+        1 | 8 => "CBR",
+        2 | 9 => "ABR",
+        3..=6 => "VBR",
+        _ => "Unknown",
+    };
+    let lowpass = u32::from(*data.get(10)?) * 100;
+    let delay = data.get(21..24)?;
+
+    Some(LameInfo {
+        encoder,
+        vbr_method: vbr_method.to_owned(),
+        lowpass: Some(lowpass).filter(|lowpass| *lowpass > 0),
+        encoder_delay: (u32::from(delay[0]) << 4) | (u32::from(delay[1]) >> 4),
+        encoder_padding: (u32::from(delay[1] & 0x0F) << 8) | u32::from(delay[2]),
+    })
+}
+
+/// Parse the Xing, Info or VBRI header of the first frame
+fn parse_vbr_header(frame: &[u8], header: &FrameHeader) -> Option<MpegVbrHeader> {
+    let xing = frame.get(header.xing_offset()..).unwrap_or_default();
+    if xing.starts_with(b"Xing") || xing.starts_with(b"Info") {
+        let flags = u32_be(xing, 4)?;
+        let mut offset = 8;
+        let mut field = |flag: u32, len: usize| {
+            if flags & flag == 0 {
+                return None;
+            }
+            let value = u32_be(xing, offset);
+            offset += len;
+            value
+        };
+        let frames = field(0x1, 4);
+        let bytes = field(0x2, 4);
+        field(0x4, 100);
+        let quality = field(0x8, 4);
+
+        return Some(MpegVbrHeader {
+            kind: String::from_utf8_lossy(&xing[..4]).into_owned(),
+            frames,
+            bytes,
+            quality,
+            lame: xing.get(offset..).and_then(parse_lame),
+        });
+    }
+
+    let vbri = frame.get(36..).unwrap_or_default();
+    if vbri.starts_with(b"VBRI") {
+        return Some(MpegVbrHeader {
+            kind: "VBRI".to_owned(),
+            frames: u32_be(vbri, 14),
+            bytes: u32_be(vbri, 10),
+            quality: vbri
+                .get(8..10)
+                .map(|quality| u32::from(u16::from_be_bytes([quality[0], quality[1]]))),
+            lame: None,
+        });
+    }
+
+    None
+}
+
+/// Read every frame from the current position, stopping at trailing tags or the end of the stream
+///
+/// Bytes between frames are skipped, resynchronizing on the next frame of the same stream.
+fn scan_mpeg_frames(
+    reader: &mut impl Read,
+    first: &FrameHeader,
+    skip_first: bool,
+) -> Option<MpegFrameScan> {
+    let mut frames = 0u32;
+    let mut samples = 0u64;
+    let mut bytes = 0u64;
+    let mut skipped = 0;
+    let mut is_first = true;
+
+    let mut window = [0; 4];
+    reader.read_exact(&mut window).ok()?;
+    loop {
+        match FrameHeader::parse(&window).filter(|header| header.matches(first)) {
+            Some(header) => {
+                let body = header.len() as u64 - 4;
+                let read = io::copy(&mut reader.by_ref().take(body), &mut io::sink()).ok()?;
+                if read < body {
+                    // Truncated last frame
+                    break;
+                }
+                if !(is_first && skip_first) {
+                    frames += 1;
+                    samples += u64::from(header.samples());
+                    bytes += header.len() as u64;
+                }
+                is_first = false;
+                skipped = 0;
+                if reader.read_exact(&mut window).is_err() {
+                    break;
+                }
+            }
+            None => {
+                if skipped >= MPEG_SEARCH_LIMIT
+                    || MPEG_TRAILING_TAGS.iter().any(|id| window.starts_with(id))
+                {
+                    break;
+                }
+                window.copy_within(1.., 0);
+                if reader.read_exact(&mut window[3..]).is_err() {
+                    break;
+                }
+                skipped += 1;
+            }
+        }
+    }
+
+    let duration = samples as f64 * 1000.0 / f64::from(first.sample_rate);
+    (frames > 0).then(|| MpegFrameScan {
+        frames,
+        duration,
+        bit_rate: (bytes as f64 * 8.0 / duration).round() as u32,
+        bytes: bytes as f64,
+    })
+}
+
+fn read_mpeg_info(reader: &mut (impl Read + Seek), accurate: bool) -> Option<CodecInfo> {
+    let start = skip_id3v2(reader).ok()?;
+    let mut data = Vec::new();
+    reader
+        .by_ref()
+        .take(MPEG_SEARCH_LIMIT as u64)
+        .read_to_end(&mut data)
+        .ok()?;
+
+    let (offset, header) = find_first_frame(&data)?;
+    let frame = &data[offset..(offset + header.len()).min(data.len())];
+    let vbr_header = if header.layer == 3 {
+        parse_vbr_header(frame, &header)
+    } else {
+        None
+    };
+    let frame_scan = if accurate {
+        reader.seek(SeekFrom::Start(start + offset as u64)).ok()?;
+        scan_mpeg_frames(reader, &header, vbr_header.is_some())
+    } else {
+        None
+    };
+
+    let mut info = CodecInfo::new(match header.layer {
+        1 => "MP1",
+        2 => "MP2",
+        _ => "MP3",
+    });
+    info.mpeg = Some(MpegInfo {
+        version: match header.version {
+            1 => "1",
+            2 => "2",
+            _ => "2.5",
+        }
+        .to_owned(),
+        layer: u32::from(header.layer),
+        channel_mode: match header.channel_mode {
+            0 => "Stereo",
+            1 => "Joint Stereo",
+            2 => "Dual Channel",
+            _ => "Mono",
+        }
+        .to_owned(),
+        mode_extension: (header.channel_mode == 1).then_some(u32::from(header.mode_extension)),
+        emphasis: match header.emphasis {
+            1 => Some("50/15 ms".to_owned()),
+            3 => Some("CCIT J.17".to_owned()),
+            _ => None,
+        },
+        copyright: header.copyright,
+        original: header.original,
+        vbr_header,
+        frame_scan,
+    });
+    Some(info)
+}
+
+/// First packet of an Ogg stream, its identification header
+fn read_opus_info(header: &[u8]) -> Option<CodecInfo> {
+    let header = header.strip_prefix(b"OpusHead")?;
+
+    let mut info = CodecInfo::new("Opus");
+    info.opus = Some(OpusInfo {
+        version: u32::from(*header.first()?),
+        pre_skip: u32::from(u16_le(header, 2)?),
+        input_sample_rate: u32_le(header, 4)?,
+        output_gain: f64::from(i16::from_le_bytes(header.get(8..10)?.try_into().ok()?)) / 256.0,
+        channel_mapping_family: u32::from(*header.get(10)?),
+    });
+    Some(info)
+}
+
+fn read_vorbis_info(header: &[u8]) -> Option<CodecInfo> {
+    let header = header.strip_prefix(b"\x01vorbis")?;
+    let bitrate = |offset| {
+        let value = i32::from_le_bytes(header.get(offset..offset + 4)?.try_into().ok()?);
+        u32::try_from(value).ok().filter(|value| *value > 0)
+    };
+
+    let mut info = CodecInfo::new("Vorbis");
+    info.vorbis = Some(VorbisInfo {
+        version: u32_le(header, 0)?,
+        max_bitrate: bitrate(9),
+        nominal_bitrate: bitrate(13),
+        min_bitrate: bitrate(17),
+    });
+    Some(info)
+}
+
+/// Codec details of an MP4 file, before it is converted to a generic file
+pub(crate) fn mp4_codec_info(file: &Mp4File) -> CodecInfo {
+    let properties = file.properties();
+
+    let codec = match properties.codec() {
+        Some(Mp4Codec::AAC) => "AAC",
+        Some(Mp4Codec::ALAC) => "ALAC",
+        Some(Mp4Codec::MP3) => "MP3",
+        Some(Mp4Codec::FLAC) => "FLAC",
+        _ => "Unknown",
+    };
+    let object_type = properties.audio_object_type();
+    let profile = object_type.and_then(|object_type| match object_type {
+        AudioObjectType::AacMain => Some("Main"),
+        AudioObjectType::AacLowComplexity => Some("LC"),
+        AudioObjectType::AacScalableSampleRate => Some("SSR"),
+        AudioObjectType::AacLongTermPrediction => Some("LTP"),
+        AudioObjectType::SpectralBandReplication => Some("HE-AAC"),
+        AudioObjectType::ParametricStereo => Some("HE-AAC v2"),
+        AudioObjectType::ErrorResilientAacLowDelay => Some("LD"),
+        AudioObjectType::ErrorResilientAacEnhancedLowDelay => Some("ELD"),
+        AudioObjectType::UnifiedSpeechAudioCoding => Some("USAC"),
+        _ => None,
+    });
+
+    let mut info = CodecInfo::new(codec);
+    info.mp4 = Some(Mp4Info {
+        codec: codec.to_owned(),
+        audio_object_type: object_type
+            .filter(|object_type| *object_type != AudioObjectType::NULL)
+            .map(|object_type| object_type as u32),
+        profile: profile.map(str::to_owned),
+        brand: properties.ftyp().to_owned(),
+        drm_protected: properties.is_drm_protected(),
+    });
+    info
+}
+
+/// Codec details of a WAV file, before it is converted to a generic file
+pub(crate) fn wav_codec_info(file: &WavFile) -> CodecInfo {
+    let properties = file.properties();
+
+    let (codec, format_tag) = match properties.format() {
+        WavFormat::PCM => ("PCM", 1),
+        WavFormat::IEEE_FLOAT => ("PCM", 3),
+        WavFormat::Other(6) => ("A-law", 6),
+        WavFormat::Other(7) => ("µ-law", 7),
+        WavFormat::Other(0x55) => ("MP3", 0x55),
+        WavFormat::Other(tag) => ("Unknown", u32::from(*tag)),
+    };
+
+    let mut info = CodecInfo::new(codec);
+    info.wav = Some(WavInfo {
+        format_tag,
+        channel_mask: properties.channel_mask().map(|mask| mask.bits()),
+    });
+    info
+}
+
+fn read_ape_info(reader: &mut (impl Read + Seek)) -> Option<CodecInfo> {
+    let start = skip_id3v2(reader).ok()?;
+    let mut header = [0; 64];
+    reader.read_exact(&mut header).ok()?;
+    if !header.starts_with(b"MAC ") {
+        return None;
+    }
+
+    let version = u16_le(&header, 4)?;
+    let compression_level = if version >= 3980 {
+        // The header follows the descriptor
+        let descriptor_len = u32_le(&header, 8)?;
+        reader
+            .seek(SeekFrom::Start(start + u64::from(descriptor_len)))
+            .ok()?;
+        let mut level = [0; 2];
+        reader.read_exact(&mut level).ok()?;
+        u16::from_le_bytes(level)
+    } else {
+        u16_le(&header, 6)?
+    };
+
+    let mut info = CodecInfo::new("Monkey's Audio");
+    info.ape = Some(ApeInfo {
+        version: u32::from(version),
+        compression_level: u32::from(compression_level),
+        compression: match compression_level {
+            1000 => "Fast",
+            2000 => "Normal",
+            3000 => "High",
+            4000 => "Extra High",
+            5000 => "Insane",
+            _ => "Unknown",
+        }
+        .to_owned(),
+    });
+    Some(info)
+}
+
+/// Flags of the `ID_CONFIG_BLOCK` metadata of a WavPack block
+fn wavpack_config(block: &[u8]) -> Option<u32> {
+    const ID_CONFIG_BLOCK: u8 = 0x25;
+    const ID_LARGE: u8 = 0x80;
+    const ID_ODD_SIZE: u8 = 0x40;
+
+    let mut offset = 32;
+    while offset + 2 <= block.len() {
+        let id = block[offset];
+        let (words, header_len) = if id & ID_LARGE != 0 {
+            let size = block.get(offset + 1..offset + 4)?;
+            (
+                usize::from(size[0]) | usize::from(size[1]) << 8 | usize::from(size[2]) << 16,
+                4,
+            )
+        } else {
+            (usize::from(block[offset + 1]), 2)
+        };
+        let data = offset + header_len;
+        let len = words * 2 - usize::from(id & ID_ODD_SIZE != 0 && words > 0);
+
+        if id & !(ID_LARGE | ID_ODD_SIZE) == ID_CONFIG_BLOCK {
+            let config = block.get(data..data + len.min(4))?;
+            return Some(
+                config
+                    .iter()
+                    .rev()
+                    .fold(0, |flags, byte| (flags << 8) | u32::from(*byte)),
+            );
+        }
+        offset = data + words * 2;
+    }
+    None
+}
+
+fn read_wavpack_info(reader: &mut (impl Read + Seek)) -> Option<CodecInfo> {
+    const HYBRID_FLAG: u32 = 0x8;
+    const FLOAT_DATA_FLAG: u32 = 0x80;
+    const DSD_FLAG: u32 = 0x8000_0000;
+    const CONFIG_FAST_FLAG: u32 = 0x200;
+    const CONFIG_HIGH_FLAG: u32 = 0x800;
+    const CONFIG_VERY_HIGH_FLAG: u32 = 0x1000;
+
+    reader.rewind().ok()?;
+    let mut header = [0; 32];
+    reader.read_exact(&mut header).ok()?;
+    if !header.starts_with(b"wvpk") {
+        return None;
+    }
+    let block_len = (u32_le(&header, 4)? as usize + 8).min(MPEG_SEARCH_LIMIT);
+    let mut block = header.to_vec();
+    block.resize(block_len.max(32), 0);
+    reader.read_exact(&mut block[32..]).ok()?;
+
+    let flags = u32_le(&header, 24)?;
+    let compression = wavpack_config(&block).map(|config| {
+        if config & CONFIG_VERY_HIGH_FLAG != 0 {
+            "Very High"
+        } else if config & CONFIG_HIGH_FLAG != 0 {
+            "High"
+        } else if config & CONFIG_FAST_FLAG != 0 {
+            "Fast"
+        } else {
+            "Normal"
+        }
+    });
+
+    let mut info = CodecInfo::new("WavPack");
+    info.wavpack = Some(WavPackInfo {
+        version: u32::from(u16_le(&header, 8)?),
+        lossless: flags & HYBRID_FLAG == 0,
+        float: flags & FLOAT_DATA_FLAG != 0,
+        dsd: flags & DSD_FLAG != 0,
+        compression: compression.map(str::to_owned),
+    });
+    Some(info)
+}
+
+/// Read the codec details of a file, `None` if the stream headers cannot be read
+///
+/// With `accurate`, every frame of an MPEG stream is read for the exact duration and bitrate.
+pub(crate) fn load_codec_info(
+    file_type: FileType,
+    reader: &mut (impl Read + Seek),
+    accurate: bool,
+    ogg_headers: Option<&OggHeaders>,
+) -> Option<CodecInfo> {
+    match file_type {
+        FileType::Mpeg => read_mpeg_info(reader, accurate),
+        FileType::Opus => read_opus_info(ogg_headers?.packets.get(0)?),
+        FileType::Vorbis => read_vorbis_info(ogg_headers?.packets.get(0)?),
+        // Read from the parsed file by `mp4_codec_info` and `wav_codec_info`
+        FileType::Mp4 | FileType::Wav => None,
+        FileType::Ape => read_ape_info(reader),
+        FileType::WavPack => read_wavpack_info(reader),
+        FileType::Aac => Some(CodecInfo::new("AAC")),
+        FileType::Aiff => Some(CodecInfo::new("PCM")),
+        FileType::Flac => Some(CodecInfo::new("FLAC")),
+        FileType::Mpc => Some(CodecInfo::new("Musepack")),
+        FileType::Speex => Some(CodecInfo::new("Speex")),
+        _ => None,
+    }
+}
+
+impl MpegInfo {
+    /// Samples of each audio frame
+    pub(crate) fn samples_per_frame(&self) -> u32 {
+        match (self.layer, self.version.as_str()) {
+            (1, _) => 384,
+            (2, _) | (3, "1") => 1152,
+            _ => 576,
+        }
+    }
+}
+
+impl MusicFile {
+    pub(crate) fn mpeg_info(&self) -> Option<&MpegInfo> {
+        self.codec_info.as_ref()?.mpeg.as_ref()
+    }
+
+    /// Frame totals of an MPEG stream loaded with `accurateDuration`
+    pub(crate) fn mpeg_frame_scan(&self) -> Option<&MpegFrameScan> {
+        self.mpeg_info()?.frame_scan.as_ref()
+    }
+
+    /// Whether the MPEG stream has a VBR header with the number of frames
+    pub(crate) fn has_mpeg_frame_count(&self) -> bool {
+        self.mpeg_info()
+            .and_then(|mpeg| mpeg.vbr_header.as_ref())
+            .is_some_and(|vbr_header| vbr_header.frames.is_some())
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// Codec details beyond the generic audio properties, or `null` if they could not be read
+    ///
+    /// Holds MPEG frame and Xing/LAME header details, the Opus and Vorbis identification headers,
+    /// the MP4 codec and profile, the WAV format tag and channel mask, and the Monkey's Audio
+    /// and WavPack compression.
+    #[napi(getter)]
+    pub fn codec_info(&self) -> Option<CodecInfo> {
+        self.codec_info.clone()
+    }
+}
@@ -0,0 +1,249 @@
+use lofty::{
+    file::{FileType, TaggedFileExt},
+    tag::{ItemKey, TagItem, TagType as LoftyTagType},
+};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::music_file::{helper::write_replay_gain, MusicFile};
+
+const ERR_SOURCE_PICTURES_SKIPPED: &str =
+    "The pictures of the source were skipped when loading, copy with `pictures: false`";
+
+/// ReplayGain gains with their R128 key, copied through the ReplayGain setters
+const REPLAY_GAIN_KEYS: [(ItemKey, ItemKey); 2] = [
+    (ItemKey::ReplayGainTrackGain, ItemKey::R128TrackGain),
+    (ItemKey::ReplayGainAlbumGain, ItemKey::R128AlbumGain),
+];
+
+#[napi(object)]
+#[derive(Default)]
+pub struct CopyTagsOptions {
+    /// Only copy these keys, e.g. `["TrackTitle", "ReplayGainTrackGain"]`, defaults to all keys
+    pub include: Option<Vec<String>>,
+    /// Keys that are not copied
+    pub exclude: Option<Vec<String>>,
+    /// Whether to copy embedded pictures, defaults to `true`
+    pub pictures: Option<bool>,
+}
+
+/// Result of {@link MusicFile.copyTagsFrom}
+#[napi(object)]
+pub struct CopyTagsReport {
+    /// Keys that were copied
+    pub copied: Vec<String>,
+    /// Keys that cannot be represented in the destination tag type, and were skipped
+    ///
+    /// Includes the custom fields of the source, like unmapped Vorbis comments and iTunes
+    /// freeform atoms, and `"pictures"` if the destination tag type cannot hold pictures.
+    pub unsupported: Vec<String>,
+    /// Number of pictures copied
+    pub pictures: u32,
+}
+
+/// Name of an item key as used in {@link CopyTagsOptions}
+fn item_key_name(key: ItemKey) -> String {
+    format!("{key:?}")
+}
+
+/// Key with the same meaning, used when the original key has no mapping in a tag type
+fn equivalent_key(key: ItemKey) -> Option<ItemKey> {
+    match key {
+        ItemKey::Lyrics => Some(ItemKey::UnsyncLyrics),
+        ItemKey::UnsyncLyrics => Some(ItemKey::Lyrics),
+        _ => None,
+    }
+}
+
+/// Find the key to store an item under in the destination tag type
+fn target_key(key: ItemKey, tag_type: LoftyTagType) -> Option<ItemKey> {
+    if key.map_key(tag_type).is_some() {
+        return Some(key);
+    }
+
+    equivalent_key(key).filter(|key| key.map_key(tag_type).is_some())
+}
+
+fn is_replay_gain_key(key: ItemKey) -> bool {
+    REPLAY_GAIN_KEYS
+        .iter()
+        .any(|&(gain_key, r128_key)| key == gain_key || key == r128_key)
+}
+
+fn supports_pictures(tag_type: LoftyTagType) -> bool {
+    matches!(
+        tag_type,
+        LoftyTagType::Ape
+            | LoftyTagType::Id3v2
+            | LoftyTagType::Mp4Ilst
+            | LoftyTagType::VorbisComments
+    )
+}
+
+impl MusicFile {
+    /// Custom fields that lofty drops from the generic tag, by their format-specific key
+    fn unmapped_fields(&self) -> Vec<String> {
+        let comments = self
+            .flac_blocks
+            .as_ref()
+            .map(|flac| flac.unmapped_comments())
+            .or_else(|| {
+                self.ogg_pictures
+                    .as_ref()
+                    .map(|state| state.unmapped_comments.as_slice())
+            })
+            .unwrap_or_default();
+        let atoms = self
+            .itunes_atoms
+            .iter()
+            .flat_map(|atoms| atoms.unmapped_names());
+
+        comments
+            .iter()
+            .map(String::as_str)
+            .chain(atoms)
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// Copy metadata from another file, converting between tag formats
+    ///
+    /// Items are mapped through their generic key, so e.g. Vorbis comments can be copied to ID3v2
+    /// or MP4 atoms. Keys present in the source replace the same keys in this file, other keys are kept.
+    /// ReplayGain gains are copied like {@link trackReplayGain} and {@link albumReplayGain} set them,
+    /// so they become R128 gains in Opus files.
+    ///
+    /// @param source File to copy the metadata from
+    /// @param options Keys to include or exclude, and whether to copy pictures
+    ///
+    /// @returns Copied keys, and keys the tag format of this file cannot represent
+    ///
+    /// @throws If pictures are copied from a source that was loaded without its pictures
+    #[napi]
+    pub fn copy_tags_from(
+        &mut self,
+        source: &MusicFile,
+        options: Option<CopyTagsOptions>,
+    ) -> Result<CopyTagsReport> {
+        let options = options.unwrap_or_default();
+        let copy_pictures = options.pictures.unwrap_or(true);
+        if copy_pictures && source.pictures_skipped {
+            return Err(Error::new(Status::InvalidArg, ERR_SOURCE_PICTURES_SKIPPED));
+        }
+        let is_selected = |name: &str| {
+            options
+                .include
+                .as_ref()
+                .is_none_or(|include| include.iter().any(|key| key == name))
+                && !options
+                    .exclude
+                    .as_ref()
+                    .is_some_and(|exclude| exclude.iter().any(|key| key == name))
+        };
+
+        let mut unsupported: Vec<String> = source
+            .unmapped_fields()
+            .into_iter()
+            .filter(|name| is_selected(name))
+            .collect();
+        let Some(source_tag) = source
+            .file
+            .primary_tag()
+            .or_else(|| source.file.first_tag())
+        else {
+            return Ok(CopyTagsReport {
+                copied: Vec::new(),
+                unsupported,
+                pictures: 0,
+            });
+        };
+        let items: Vec<TagItem> = source_tag
+            .items()
+            .filter(|item| !is_replay_gain_key(item.key()))
+            .filter(|item| is_selected(&item_key_name(item.key())))
+            .cloned()
+            .collect();
+        let pictures = if copy_pictures {
+            source_tag.pictures().to_vec()
+        } else {
+            Vec::new()
+        };
+
+        let mut copied: Vec<String> = Vec::new();
+        let mut gains = Vec::new();
+        for (gain_key, r128_key) in REPLAY_GAIN_KEYS {
+            let name = item_key_name(gain_key);
+            if !is_selected(&name) {
+                continue;
+            }
+            let Some(gain) = source.replay_gain(gain_key, r128_key) else {
+                continue;
+            };
+            match self.replay_gain_text(gain) {
+                Ok(text) => gains.push((gain_key, r128_key, text, name)),
+                Err(_) => unsupported.push(name),
+            }
+        }
+
+        let opus = self.file.file_type() == FileType::Opus;
+        let mut picture_count = 0;
+        self.tag_mut(|tag| {
+            let tag_type = tag.tag_type();
+
+            let mut cleared: Vec<ItemKey> = Vec::new();
+            for item in items {
+                let name = item_key_name(item.key());
+                let Some(key) = target_key(item.key(), tag_type) else {
+                    if !unsupported.contains(&name) {
+                        unsupported.push(name);
+                    }
+                    continue;
+                };
+
+                // Replace existing values of the key, but keep every value of multi-value items
+                if !cleared.contains(&key) {
+                    tag.remove_key(key);
+                    cleared.push(key);
+                }
+                let mut target_item = TagItem::new(key, item.value().clone());
+                target_item.set_lang(*item.lang());
+                target_item.set_description(item.description().to_string());
+                if tag.push(target_item) {
+                    if !copied.contains(&name) {
+                        copied.push(name);
+                    }
+                } else if !unsupported.contains(&name) {
+                    unsupported.push(name);
+                }
+            }
+
+            for (gain_key, r128_key, text, name) in gains {
+                write_replay_gain(tag, opus, gain_key, r128_key, Some(text));
+                copied.push(name);
+            }
+
+            if !pictures.is_empty() {
+                if supports_pictures(tag_type) {
+                    while tag.picture_count() > 0 {
+                        tag.remove_picture(0);
+                    }
+                    for picture in pictures {
+                        tag.push_picture(picture);
+                        picture_count += 1;
+                    }
+                } else {
+                    unsupported.push("pictures".to_string());
+                }
+            }
+        })?;
+
+        Ok(CopyTagsReport {
+            copied,
+            unsupported,
+            pictures: picture_count,
+        })
+    }
+}
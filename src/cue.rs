@@ -0,0 +1,774 @@
+#![cfg_attr(target_arch = "wasm32", allow(dead_code, unused_imports))]
+
+use std::io::{Read, Seek, SeekFrom};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, path::Path};
+
+use lofty::file::FileType;
+
+use napi::{bindgen_prelude::Uint8Array, Either, Error, Result, Status};
+use napi_derive::napi;
+
+#[cfg(target_arch = "wasm32")]
+use crate::music_file::ERR_INVALID_IN_WASM;
+use crate::music_file::{
+    fields::MetadataPatch,
+    flac_blocks::FlacCueSheet,
+    layout::{ape_items, id3v2_frames, id3v2_tag_offset, AudioLayout},
+    ogg_pictures::{comment_fields, OggHeaders},
+    MusicFile,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::music_file::{path_error, ERR_FILE_LOADED_FROM_BUFFER};
+
+/// CD frames per second, the unit of cue sheet times
+const FRAMES_PER_SECOND: f64 = 75.0;
+
+/// Track numbers of the lead-out track in a FLAC CUESHEET block
+const LEAD_OUT_TRACKS: &[u32] = &[170, 255];
+
+/// Key of the cue sheet item of APE tags and Vorbis comments, and description of the ID3v2 `TXXX` frame
+const CUE_SHEET_KEY: &[u8] = b"CUESHEET";
+
+/// Type bits of APE item flags, `0` for UTF-8 text
+const APE_ITEM_TYPE_MASK: u32 = 0b110;
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct CueIndex {
+    pub number: u32,
+    /// Position in milliseconds from the beginning of the file
+    pub time: f64,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct CueTrack {
+    pub number: u32,
+    /// Track data type, `"AUDIO"` for audio tracks
+    pub data_type: String,
+    /// Name of the file the track is stored in, from the preceding `FILE` command
+    pub file: Option<String>,
+    /// Type of that file, e.g. `"WAVE"`
+    pub file_type: Option<String>,
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    pub songwriter: Option<String>,
+    pub isrc: Option<String>,
+    /// Track flags, e.g. `"DCP"` or `"PRE"`
+    pub flags: Vec<String>,
+    /// Silence before the track that is not stored in the file, in milliseconds
+    pub pregap: Option<f64>,
+    /// Silence after the track that is not stored in the file, in milliseconds
+    pub postgap: Option<f64>,
+    pub indices: Vec<CueIndex>,
+}
+
+/// Contents of a CUE sheet
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct CueSheet {
+    /// Album title
+    pub title: Option<String>,
+    /// Album performer
+    pub performer: Option<String>,
+    pub songwriter: Option<String>,
+    /// Media catalog number
+    pub catalog: Option<String>,
+    /// From `REM GENRE`
+    pub genre: Option<String>,
+    /// From `REM DATE`
+    pub date: Option<String>,
+    /// From `REM COMMENT`
+    pub comment: Option<String>,
+    pub tracks: Vec<CueTrack>,
+}
+
+/// A track of a cue sheet, located in the audio of a single file
+#[napi(object)]
+pub struct CueTrackEntry {
+    pub number: u32,
+    pub title: Option<String>,
+    /// Track performer, or the album performer if the track has none
+    pub performer: Option<String>,
+    /// Album title
+    pub album: Option<String>,
+    pub isrc: Option<String>,
+    /// Start of the track (`INDEX 01`) in milliseconds
+    pub start: f64,
+    /// End of the track in milliseconds, the start of the next track or the end of the file
+    pub end: f64,
+    /// Track duration in milliseconds
+    pub duration: f64,
+}
+
+fn cue_error(line: usize, message: impl std::fmt::Display) -> Error {
+    Error::new(
+        Status::InvalidArg,
+        format!("Invalid cue sheet at line {line}: {message}"),
+    )
+}
+
+/// Decode cue sheet bytes, which are UTF-8 or, in older rips, Latin-1
+fn decode_text(data: &[u8]) -> String {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    match std::str::from_utf8(data) {
+        Ok(text) => text.to_owned(),
+        Err(_) => data.iter().map(|&byte| byte as char).collect(),
+    }
+}
+
+/// Read `size` bytes at `offset`
+fn read_range(reader: &mut (impl Read + Seek), offset: u64, size: u64) -> Option<Vec<u8>> {
+    reader.seek(SeekFrom::Start(offset)).ok()?;
+    let mut data = Vec::new();
+    reader.take(size).read_to_end(&mut data).ok()?;
+    (data.len() as u64 == size).then_some(data)
+}
+
+/// Decode ID3v2 text in the given encoding, stopping at the first NUL character
+///
+/// Returns the text and the data after its terminator.
+fn decode_id3v2_text(encoding: u8, data: &[u8]) -> Option<(String, &[u8])> {
+    if matches!(encoding, 0 | 3) {
+        let end = data
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(data.len());
+        let text = match encoding {
+            0 => data[..end].iter().map(|&byte| byte as char).collect(),
+            _ => String::from_utf8_lossy(&data[..end]).into_owned(),
+        };
+        return Some((text, data.get(end + 1..).unwrap_or_default()));
+    }
+
+    let mut units = data
+        .chunks_exact(2)
+        .map(|pair| [pair[0], pair[1]])
+        .take_while(|pair| *pair != [0, 0]);
+    let (first, big_endian) = match encoding {
+        1 => match units.next()? {
+            [0xFF, 0xFE] => (None, false),
+            [0xFE, 0xFF] => (None, true),
+            // UTF-16 without a byte order mark, assume little endian
+            pair => (Some(pair), false),
+        },
+        2 => (None, true),
+        _ => return None,
+    };
+    let units: Vec<u16> = first
+        .into_iter()
+        .chain(units)
+        .map(|pair| match big_endian {
+            true => u16::from_be_bytes(pair),
+            false => u16::from_le_bytes(pair),
+        })
+        .collect();
+    let bom = usize::from(encoding == 1 && first.is_none());
+    let end = (bom + units.len() + 1) * 2;
+    Some((
+        String::from_utf16_lossy(&units),
+        data.get(end..).unwrap_or_default(),
+    ))
+}
+
+/// Value of the `CUESHEET` text item of the APE tag ending at `end`
+fn ape_cue_sheet(reader: &mut (impl Read + Seek), end: u64) -> Option<String> {
+    let item = ape_items(reader, end)?.into_iter().find(|item| {
+        item.key.eq_ignore_ascii_case(CUE_SHEET_KEY) && item.flags & APE_ITEM_TYPE_MASK == 0
+    })?;
+    read_range(reader, item.offset, item.size).map(|value| decode_text(&value))
+}
+
+/// Value of the `TXXX` frame described `CUESHEET` of the ID3v2 tag at `offset`
+fn id3v2_cue_sheet(reader: &mut (impl Read + Seek), offset: u64, end: u64) -> Option<String> {
+    let (version, frames) = id3v2_frames(reader, offset, end)?;
+    let frame_id: &[u8] = if version == 2 { b"TXX" } else { b"TXXX" };
+    frames
+        .iter()
+        // Compressed and encrypted frames are skipped
+        .filter(|frame| frame.id == frame_id && frame.format_flags == 0 && frame.size > 1)
+        .find_map(|frame| {
+            let content = read_range(reader, frame.offset, frame.size.min(end - frame.offset))?;
+            let (description, value) = decode_id3v2_text(content[0], &content[1..])?;
+            if !description.as_bytes().eq_ignore_ascii_case(CUE_SHEET_KEY) {
+                return None;
+            }
+            // ID3v2.4 separates multiple values with NUL characters, keep the first
+            decode_id3v2_text(content[0], value).map(|(value, _)| value)
+        })
+}
+
+/// Read the `CUESHEET` text item of the APE, ID3v2 or Ogg tags of a file
+///
+/// FLAC files keep theirs with the other metadata blocks.
+pub(crate) fn load_cue_sheet_text(
+    reader: &mut (impl Read + Seek),
+    file_type: FileType,
+    layout: Option<&AudioLayout>,
+    ogg: Option<&OggHeaders>,
+) -> Option<String> {
+    if file_type == FileType::Flac {
+        return None;
+    }
+    if let Some(ogg) = ogg {
+        return comment_fields(file_type, ogg.packets.get(1)?)?
+            .into_iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(CUE_SHEET_KEY))
+            .map(|(_, value)| decode_text(value));
+    }
+
+    layout?.blocks.iter().find_map(|block| {
+        let offset = block.offset as u64;
+        let end = offset + block.size as u64;
+        match block.kind.as_str() {
+            "APE" => ape_cue_sheet(reader, end),
+            "ID3v2" => id3v2_cue_sheet(reader, id3v2_tag_offset(file_type, offset), end),
+            _ => None,
+        }
+    })
+}
+
+/// Split a line into its arguments, quoted arguments may contain whitespace
+fn split_args(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let (arg, next) = match rest.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            },
+            None => match rest.find(char::is_whitespace) {
+                Some(end) => (&rest[..end], &rest[end..]),
+                None => (rest, ""),
+            },
+        };
+        args.push(arg.to_owned());
+        rest = next.trim_start();
+    }
+    args
+}
+
+/// Value of a command that takes a single text argument, e.g. `TITLE`
+fn text_arg(line: usize, args: &[String]) -> Result<String> {
+    match args {
+        [] => Err(cue_error(line, "missing value")),
+        // Unquoted values with spaces are common in hand-written sheets
+        args => Ok(args.join(" ")),
+    }
+}
+
+/// Parse a `mm:ss:ff` time into milliseconds
+fn parse_time(line: usize, value: &str) -> Result<f64> {
+    let parts: Vec<&str> = value.split(':').collect();
+    // u64 so that any u32 number of minutes fits in frames
+    let numbers: Option<Vec<u64>> = parts
+        .iter()
+        .map(|part| part.parse::<u32>().ok().map(u64::from))
+        .collect();
+    match numbers.as_deref() {
+        Some(&[minutes, seconds, frames]) if seconds < 60 && frames < 75 => {
+            let frames = (minutes * 60 + seconds) * 75 + frames;
+            Ok(frames as f64 * 1000.0 / FRAMES_PER_SECOND)
+        }
+        _ => Err(cue_error(line, format!("invalid time '{value}'"))),
+    }
+}
+
+/// Format milliseconds as a `mm:ss:ff` time, rounded to whole frames
+fn format_time(time: f64) -> String {
+    let frames = (time.max(0.0) * FRAMES_PER_SECOND / 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}",
+        frames / 75 / 60,
+        frames / 75 % 60,
+        frames % 75
+    )
+}
+
+fn parse_number(line: usize, value: Option<&String>, what: &str) -> Result<u32> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| cue_error(line, format!("invalid {what}")))
+}
+
+fn parse_text(text: &str) -> Result<CueSheet> {
+    let mut sheet = CueSheet::default();
+    let mut file: Option<(String, Option<String>)> = None;
+
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = index + 1;
+        let mut args = split_args(raw_line);
+        if args.is_empty() {
+            continue;
+        }
+        let command = args.remove(0).to_ascii_uppercase();
+        let track = sheet.tracks.last_mut();
+
+        match (command.as_str(), track) {
+            // Track level comments, e.g. ReplayGain values, are not kept
+            ("REM", Some(_)) => {}
+            ("REM", None) => {
+                if args.len() < 2 {
+                    continue;
+                }
+                let key = args.remove(0).to_ascii_uppercase();
+                let value = Some(args.join(" "));
+                match key.as_str() {
+                    "GENRE" => sheet.genre = value,
+                    "DATE" => sheet.date = value,
+                    "COMMENT" => sheet.comment = value,
+                    _ => {}
+                }
+            }
+            ("FILE", _) => {
+                let mut args = args.into_iter();
+                let name = args
+                    .next()
+                    .ok_or_else(|| cue_error(line, "missing file name"))?;
+                file = Some((name, args.next()));
+            }
+            ("TRACK", _) => {
+                let number = parse_number(line, args.first(), "track number")?;
+                let (file, file_type) = file.clone().unzip();
+                sheet.tracks.push(CueTrack {
+                    number,
+                    data_type: args.get(1).cloned().unwrap_or_else(|| "AUDIO".to_owned()),
+                    file,
+                    file_type: file_type.flatten(),
+                    title: None,
+                    performer: None,
+                    songwriter: None,
+                    isrc: None,
+                    flags: Vec::new(),
+                    pregap: None,
+                    postgap: None,
+                    indices: Vec::new(),
+                });
+            }
+            ("TITLE", Some(track)) => track.title = Some(text_arg(line, &args)?),
+            ("TITLE", None) => sheet.title = Some(text_arg(line, &args)?),
+            ("PERFORMER", Some(track)) => track.performer = Some(text_arg(line, &args)?),
+            ("PERFORMER", None) => sheet.performer = Some(text_arg(line, &args)?),
+            ("SONGWRITER", Some(track)) => track.songwriter = Some(text_arg(line, &args)?),
+            ("SONGWRITER", None) => sheet.songwriter = Some(text_arg(line, &args)?),
+            ("CATALOG", _) => sheet.catalog = Some(text_arg(line, &args)?),
+            ("ISRC", Some(track)) => track.isrc = Some(text_arg(line, &args)?),
+            ("FLAGS", Some(track)) => track.flags = args,
+            ("PREGAP", Some(track)) => {
+                track.pregap = Some(parse_time(line, &text_arg(line, &args)?)?)
+            }
+            ("POSTGAP", Some(track)) => {
+                track.postgap = Some(parse_time(line, &text_arg(line, &args)?)?)
+            }
+            ("INDEX", Some(track)) => {
+                let number = parse_number(line, args.first(), "index number")?;
+                let time = args.get(1).ok_or_else(|| cue_error(line, "missing time"))?;
+                track.indices.push(CueIndex {
+                    number,
+                    time: parse_time(line, time)?,
+                });
+            }
+            ("ISRC" | "FLAGS" | "PREGAP" | "POSTGAP" | "INDEX", None) => {
+                return Err(cue_error(line, format!("{command} outside of a track")));
+            }
+            // CDTEXTFILE and unknown commands carry nothing we expose
+            _ => {}
+        }
+    }
+
+    Ok(sheet)
+}
+
+/// Quote a text value, cue sheets cannot escape double quotes so they are replaced by single quotes
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "'"))
+}
+
+/// Quote a `REM` value only if it contains whitespace, as most rippers write them
+fn rem_value(value: &str) -> String {
+    if value.is_empty() || value.contains(char::is_whitespace) {
+        quote(value)
+    } else {
+        value.to_owned()
+    }
+}
+
+fn format_sheet(sheet: &CueSheet) -> String {
+    let mut lines = Vec::new();
+    if let Some(genre) = &sheet.genre {
+        lines.push(format!("REM GENRE {}", rem_value(genre)));
+    }
+    if let Some(date) = &sheet.date {
+        lines.push(format!("REM DATE {}", rem_value(date)));
+    }
+    if let Some(comment) = &sheet.comment {
+        lines.push(format!("REM COMMENT {}", rem_value(comment)));
+    }
+    if let Some(catalog) = &sheet.catalog {
+        lines.push(format!("CATALOG {catalog}"));
+    }
+    if let Some(performer) = &sheet.performer {
+        lines.push(format!("PERFORMER {}", quote(performer)));
+    }
+    if let Some(title) = &sheet.title {
+        lines.push(format!("TITLE {}", quote(title)));
+    }
+    if let Some(songwriter) = &sheet.songwriter {
+        lines.push(format!("SONGWRITER {}", quote(songwriter)));
+    }
+
+    let mut file = None;
+    for track in &sheet.tracks {
+        if let Some(name) = track.file.as_deref().filter(|name| file != Some(*name)) {
+            let file_type = track.file_type.as_deref().unwrap_or("WAVE");
+            lines.push(format!("FILE {} {file_type}", quote(name)));
+            file = Some(name);
+        }
+
+        lines.push(format!("  TRACK {:02} {}", track.number, track.data_type));
+        if let Some(title) = &track.title {
+            lines.push(format!("    TITLE {}", quote(title)));
+        }
+        if let Some(performer) = &track.performer {
+            lines.push(format!("    PERFORMER {}", quote(performer)));
+        }
+        if let Some(songwriter) = &track.songwriter {
+            lines.push(format!("    SONGWRITER {}", quote(songwriter)));
+        }
+        if !track.flags.is_empty() {
+            lines.push(format!("    FLAGS {}", track.flags.join(" ")));
+        }
+        if let Some(isrc) = &track.isrc {
+            lines.push(format!("    ISRC {isrc}"));
+        }
+        if let Some(pregap) = track.pregap {
+            lines.push(format!("    PREGAP {}", format_time(pregap)));
+        }
+        for index in &track.indices {
+            lines.push(format!(
+                "    INDEX {:02} {}",
+                index.number,
+                format_time(index.time)
+            ));
+        }
+        if let Some(postgap) = track.postgap {
+            lines.push(format!("    POSTGAP {}", format_time(postgap)));
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+impl CueTrack {
+    /// Start of the track, its `INDEX 01` or else its first index
+    fn start(&self) -> Option<f64> {
+        self.indices
+            .iter()
+            .find(|index| index.number == 1)
+            .or(self.indices.first())
+            .map(|index| index.time)
+    }
+}
+
+/// Convert a FLAC CUESHEET block, which has no text fields, into a cue sheet
+fn from_flac_cue_sheet(
+    cue_sheet: &FlacCueSheet,
+    sample_rate: u32,
+    file: Option<String>,
+) -> CueSheet {
+    let to_millis = |samples: f64| samples * 1000.0 / sample_rate.max(1) as f64;
+
+    CueSheet {
+        catalog: Some(cue_sheet.media_catalog_number.clone()).filter(|mcn| !mcn.is_empty()),
+        tracks: cue_sheet
+            .tracks
+            .iter()
+            .filter(|track| !LEAD_OUT_TRACKS.contains(&track.number))
+            .map(|track| CueTrack {
+                number: track.number,
+                data_type: if track.is_audio {
+                    "AUDIO"
+                } else {
+                    "MODE1/2352"
+                }
+                .to_owned(),
+                file: file.clone(),
+                file_type: file.as_ref().map(|_| "WAVE".to_owned()),
+                title: None,
+                performer: None,
+                songwriter: None,
+                isrc: Some(track.isrc.clone()).filter(|isrc| !isrc.is_empty()),
+                flags: if track.pre_emphasis {
+                    vec!["PRE".to_owned()]
+                } else {
+                    Vec::new()
+                },
+                pregap: None,
+                postgap: None,
+                indices: track
+                    .indices
+                    .iter()
+                    .map(|index| CueIndex {
+                        number: index.number,
+                        time: to_millis(track.offset + index.offset),
+                    })
+                    .collect(),
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Year at the start of a `REM DATE` value, e.g. `1999` or `1999-05-01`
+fn date_year(date: &str) -> Option<f64> {
+    let year = date.trim().get(..4)?;
+    year.parse::<u16>().ok().map(f64::from)
+}
+
+impl MusicFile {
+    /// File name of the loaded path, used to match `FILE` commands
+    fn file_name(&self) -> Option<String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.path().and_then(|path| {
+                Path::new(path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            None
+        }
+    }
+
+    /// Audio tracks of a cue sheet that are stored in this file
+    fn own_cue_tracks<'a>(&self, cue: &'a CueSheet) -> Result<Vec<&'a CueTrack>> {
+        let audio: Vec<&CueTrack> = cue
+            .tracks
+            .iter()
+            .filter(|track| track.data_type.eq_ignore_ascii_case("AUDIO"))
+            .collect();
+
+        let mut files: Vec<Option<&str>> =
+            audio.iter().map(|track| track.file.as_deref()).collect();
+        files.dedup();
+        if files.len() <= 1 {
+            return Ok(audio);
+        }
+
+        // A sheet for several files only describes this file by name
+        let name = self.file_name();
+        let own: Vec<&CueTrack> = audio
+            .into_iter()
+            .filter(|track| {
+                track
+                    .file
+                    .as_deref()
+                    .zip(name.as_deref())
+                    .is_some_and(|(file, name)| file.eq_ignore_ascii_case(name))
+            })
+            .collect();
+        if own.is_empty() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "The cue sheet references several files, none of which is this file",
+            ));
+        }
+        Ok(own)
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// Cue sheet embedded in the file, or `null` if there is none
+    ///
+    /// Read from the `CUESHEET` item of APE tags (Monkey's Audio, WavPack), Vorbis comments, and the
+    /// ID3v2 `TXXX` frame described `CUESHEET`. In FLAC files, a `CUESHEET` Vorbis comment is preferred
+    /// over the CUESHEET metadata block, which only has track positions, ISRCs and the catalog number.
+    ///
+    /// @note The `cue ` chunk of WAV files is not read. Cue sheets added after loading are not read
+    /// until the file is saved in place.
+    #[napi(getter)]
+    pub fn embedded_cue_sheet(&self) -> Option<CueSheet> {
+        let Some(flac_blocks) = self.flac_blocks.as_ref() else {
+            return self
+                .cue_sheet_text
+                .as_deref()
+                .and_then(|text| parse_text(text).ok());
+        };
+        if let Some(sheet) = flac_blocks
+            .cue_sheet_text()
+            .and_then(|text| parse_text(text).ok())
+        {
+            return Some(sheet);
+        }
+
+        flac_blocks
+            .cue_sheet()
+            .map(|cue| from_flac_cue_sheet(cue, flac_blocks.sample_rate(), self.file_name()))
+    }
+
+    /// Read the sidecar cue sheet of the file, `<name>.cue` or `<name>.<extension>.cue` in the same directory
+    ///
+    /// @returns The cue sheet, or `null` if there is no sidecar cue sheet
+    ///
+    /// @throws If the file was loaded from a buffer, or the cue sheet is invalid
+    /// @throws In WebAssembly environments
+    #[napi]
+    pub fn read_sidecar_cue_sheet(&self) -> Result<Option<CueSheet>> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            Err(Error::new(Status::GenericFailure, ERR_INVALID_IN_WASM))
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(path) = self.path().map(Path::new) else {
+                return Err(Error::new(
+                    Status::GenericFailure,
+                    ERR_FILE_LOADED_FROM_BUFFER,
+                ));
+            };
+
+            let candidates = [
+                path.with_extension("cue"),
+                path.with_extension(match path.extension() {
+                    Some(extension) => format!("{}.cue", extension.to_string_lossy()),
+                    None => "cue".to_owned(),
+                }),
+            ];
+            for candidate in candidates {
+                if candidate.is_file() {
+                    let data =
+                        fs::read(&candidate).map_err(|error| path_error(&candidate, error))?;
+                    return parse_text(&decode_text(&data)).map(Some);
+                }
+            }
+            Ok(None)
+        }
+    }
+
+    /// Split the audio of the file into the tracks of a cue sheet
+    ///
+    /// @param cue Cue sheet of the file, defaults to {@link embeddedCueSheet}
+    ///
+    /// @returns Audio tracks stored in this file, in cue sheet order
+    ///
+    /// @throws If no cue sheet is given or embedded, or a track has no index
+    #[napi]
+    pub fn cue_tracks(&self, cue: Option<CueSheet>) -> Result<Vec<CueTrackEntry>> {
+        let cue = cue.or_else(|| self.embedded_cue_sheet()).ok_or_else(|| {
+            Error::new(
+                Status::GenericFailure,
+                "No cue sheet was given and none is embedded",
+            )
+        })?;
+        let tracks = self.own_cue_tracks(&cue)?;
+
+        let starts = tracks
+            .iter()
+            .map(|track| {
+                track.start().ok_or_else(|| {
+                    Error::new(
+                        Status::InvalidArg,
+                        format!("Track {} has no index", track.number),
+                    )
+                })
+            })
+            .collect::<Result<Vec<f64>>>()?;
+        let file_end = self.duration();
+
+        Ok(tracks
+            .iter()
+            .enumerate()
+            .map(|(index, track)| {
+                let start = starts[index];
+                let end = starts
+                    .get(index + 1)
+                    .copied()
+                    .unwrap_or(file_end)
+                    .max(start);
+                CueTrackEntry {
+                    number: track.number,
+                    title: track.title.clone(),
+                    performer: track.performer.clone().or_else(|| cue.performer.clone()),
+                    album: cue.title.clone(),
+                    isrc: track.isrc.clone(),
+                    start,
+                    end,
+                    duration: end - start,
+                }
+            })
+            .collect())
+    }
+
+    /// Set the metadata of one cue sheet track, e.g. on a file split from the cue sheet image
+    ///
+    /// Sets the title, artist, album, album artist, composer, genre, year, track number
+    /// and track total from the cue sheet. Fields the cue sheet doesn't have are left untouched.
+    ///
+    /// @param cue Cue sheet to take the metadata from
+    /// @param number Track number in the cue sheet
+    ///
+    /// @returns Keys of the fields whose value actually changed, see {@link update}
+    ///
+    /// @throws If the cue sheet has no audio track with that number
+    #[napi]
+    pub fn apply_cue_track(&mut self, cue: CueSheet, number: u32) -> Result<Vec<String>> {
+        let audio: Vec<&CueTrack> = cue
+            .tracks
+            .iter()
+            .filter(|track| track.data_type.eq_ignore_ascii_case("AUDIO"))
+            .collect();
+        let track = audio
+            .iter()
+            .find(|track| track.number == number)
+            .ok_or_else(|| {
+                Error::new(
+                    Status::InvalidArg,
+                    format!("The cue sheet has no audio track {number}"),
+                )
+            })?;
+
+        let text = |value: Option<&String>| value.cloned().map(Either::A);
+        self.update(MetadataPatch {
+            title: text(track.title.as_ref()),
+            artist: text(track.performer.as_ref().or(cue.performer.as_ref())),
+            album: text(cue.title.as_ref()),
+            album_artist: text(cue.performer.as_ref()),
+            composer: text(track.songwriter.as_ref().or(cue.songwriter.as_ref())),
+            genre: text(cue.genre.as_ref()),
+            year: cue.date.as_deref().and_then(date_year).map(Either::A),
+            track_number: Some(Either::A(number as f64)),
+            track_total: Some(Either::A(audio.len() as f64)),
+            ..Default::default()
+        })
+    }
+}
+
+/// Parse a CUE sheet
+///
+/// @param text Cue sheet text, or its bytes in UTF-8 or Latin-1
+///
+/// @throws If a command has an invalid number or time
+#[napi]
+pub fn parse_cue_sheet(text: Either<Uint8Array, String>) -> Result<CueSheet> {
+    match text {
+        Either::A(data) => parse_text(&decode_text(&data)),
+        Either::B(text) => parse_text(&text),
+    }
+}
+
+/// Write a cue sheet as CUE text
+///
+/// Times are rounded to whole CD frames (1/75 second). Double quotes in text values are
+/// replaced by single quotes, as CUE has no way to escape them.
+#[napi]
+pub fn format_cue_sheet(cue: CueSheet) -> String {
+    format_sheet(&cue)
+}
@@ -0,0 +1,126 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use lofty::picture::{Picture, PictureType};
+use napi::Result;
+use napi_derive::napi;
+
+use crate::{artwork::content_hash, music_file::MusicFile};
+
+/// Where a picture of a {@link PictureGroup} is embedded
+#[napi(object)]
+pub struct PictureOccurrence {
+    /// Index of the file in the list passed to {@link groupPictures}
+    pub file_index: u32,
+    /// Index of the picture in {@link MusicFile.pictureInfo} of that file
+    pub picture_index: u32,
+    #[napi(
+        ts_type = r#""Cover Art (Other)" | "Cover Art (Png Icon)" | "Cover Art (Icon)" | "Cover Art (Front)" | "Cover Art (Back)" | "Cover Art (Leaflet)" | "Cover Art (Media)" | "Cover Art (Lead Artist)" | "Cover Art (Artist)" | "Cover Art (Conductor)" | "Cover Art (Band)" | "Cover Art (Composer)" | "Cover Art (Lyricist)" | "Cover Art (Recording Location)" | "Cover Art (During Recording)" | "Cover Art (During Performance)" | "Cover Art (Video Capture)" | "Cover Art (Fish)" | "Cover Art (Illustration)" | "Cover Art (Band Logotype)" | "Cover Art (Publisher Logotype)" | "Unknown""#
+    )]
+    pub cover_type: String,
+}
+
+/// Identical pictures embedded in one or more files
+#[napi(object)]
+pub struct PictureGroup {
+    /// SHA-256 of the image data as lowercase hex, same as {@link MetaPicture.hash}
+    pub hash: String,
+    pub mime_type: Option<String>,
+    /// Image data size in bytes
+    pub size: u32,
+    /// Every place the picture is embedded, in file and picture order
+    pub occurrences: Vec<PictureOccurrence>,
+}
+
+/// Indices of pictures that have the same data as another picture that is kept
+///
+/// Of identical pictures the front cover is kept, otherwise the first one.
+fn duplicate_indices(pictures: &[Picture]) -> Vec<usize> {
+    let mut kept: HashMap<String, usize> = HashMap::new();
+    let mut duplicates = Vec::new();
+
+    for (index, pic) in pictures.iter().enumerate() {
+        match kept.entry(content_hash(pic.data())) {
+            Entry::Occupied(mut entry) => {
+                let kept_index = *entry.get();
+                if pic.pic_type() == PictureType::CoverFront
+                    && pictures[kept_index].pic_type() != PictureType::CoverFront
+                {
+                    duplicates.push(kept_index);
+                    entry.insert(index);
+                } else {
+                    duplicates.push(index);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(index);
+            }
+        }
+    }
+
+    duplicates.sort_unstable();
+    duplicates
+}
+
+#[napi]
+impl MusicFile {
+    /// Remove embedded pictures whose image data is identical to another embedded picture
+    ///
+    /// Of identical pictures the front cover is kept, otherwise the first one.
+    ///
+    /// @returns Number of removed pictures
+    #[napi]
+    pub fn dedupe_pictures(&mut self) -> Result<u32> {
+        let duplicates = self
+            .tag(|tag| Some(duplicate_indices(tag.pictures())))
+            .unwrap_or_default();
+        if duplicates.is_empty() {
+            return Ok(0);
+        }
+
+        self.tag_mut(|tag| {
+            for &index in duplicates.iter().rev() {
+                tag.remove_picture(index);
+            }
+        })?;
+
+        Ok(duplicates.len() as u32)
+    }
+}
+
+/// Group the embedded pictures of several files by their image data
+///
+/// Each distinct image is reported once, with every file and position it is embedded at,
+/// so shared cover art can be stored once. Groups are in the order the images first appear.
+///
+/// @param files Files to compare
+#[napi]
+pub fn group_pictures(files: Vec<&MusicFile>) -> Vec<PictureGroup> {
+    let mut groups: Vec<PictureGroup> = Vec::new();
+    let mut by_hash: HashMap<String, usize> = HashMap::new();
+
+    for (file_index, file) in files.iter().enumerate() {
+        file.tag(|tag| {
+            for (picture_index, pic) in tag.pictures().iter().enumerate() {
+                let hash = content_hash(pic.data());
+                let group_index = *by_hash.entry(hash.clone()).or_insert_with(|| {
+                    groups.push(PictureGroup {
+                        hash,
+                        mime_type: pic.mime_type().map(|mime| mime.as_str().to_owned()),
+                        size: pic.data().len() as u32,
+                        occurrences: Vec::new(),
+                    });
+                    groups.len() - 1
+                });
+
+                groups[group_index].occurrences.push(PictureOccurrence {
+                    file_index: file_index as u32,
+                    picture_index: picture_index as u32,
+                    cover_type: pic.pic_type().as_ape_key().unwrap_or("Unknown").to_owned(),
+                });
+            }
+            Some(())
+        });
+    }
+
+    groups
+}
@@ -0,0 +1,295 @@
+use napi::{bindgen_prelude::Null, Either, Error, Result, Status};
+use napi_derive::napi;
+
+use crate::music_file::MusicFile;
+
+/// Keys of all metadata fields that can be read and written by key
+pub(crate) const FIELD_KEYS: &[&str] = &[
+    "title",
+    "artist",
+    "album",
+    "albumArtist",
+    "year",
+    "genre",
+    "trackNumber",
+    "trackTotal",
+    "discNumber",
+    "discsTotal",
+    "comment",
+    "composer",
+    "conductor",
+    "lyricist",
+    "publisher",
+    "lyrics",
+    "copyright",
+    "rating",
+    "trackReplayGain",
+    "trackReplayPeak",
+    "albumReplayGain",
+    "albumReplayPeak",
+];
+
+/// Value of a single metadata field
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FieldValue {
+    Text(String),
+    Number(f64),
+}
+
+/// Partial metadata update, fields set to `null` are removed and missing fields are left untouched
+#[napi(object)]
+#[derive(Default)]
+pub struct MetadataPatch {
+    pub title: Option<Either<String, Null>>,
+    pub artist: Option<Either<String, Null>>,
+    pub album: Option<Either<String, Null>>,
+    pub album_artist: Option<Either<String, Null>>,
+    pub year: Option<Either<f64, Null>>,
+    pub genre: Option<Either<String, Null>>,
+    pub track_number: Option<Either<f64, Null>>,
+    pub track_total: Option<Either<f64, Null>>,
+    pub disc_number: Option<Either<f64, Null>>,
+    pub discs_total: Option<Either<f64, Null>>,
+    pub comment: Option<Either<String, Null>>,
+    pub composer: Option<Either<String, Null>>,
+    pub conductor: Option<Either<String, Null>>,
+    pub lyricist: Option<Either<String, Null>>,
+    pub publisher: Option<Either<String, Null>>,
+    pub lyrics: Option<Either<String, Null>>,
+    pub copyright: Option<Either<String, Null>>,
+    pub rating: Option<Either<f64, Null>>,
+    pub track_replay_gain: Option<Either<f64, Null>>,
+    pub track_replay_peak: Option<Either<f64, Null>>,
+    pub album_replay_gain: Option<Either<f64, Null>>,
+    pub album_replay_peak: Option<Either<f64, Null>>,
+}
+
+fn text_entry(
+    key: &'static str,
+    value: Option<Either<String, Null>>,
+) -> Option<(&'static str, Option<FieldValue>)> {
+    value.map(|value| match value {
+        Either::A(text) => (key, Some(FieldValue::Text(text))),
+        Either::B(_) => (key, None),
+    })
+}
+
+fn number_entry(
+    key: &'static str,
+    value: Option<Either<f64, Null>>,
+) -> Option<(&'static str, Option<FieldValue>)> {
+    value.map(|value| match value {
+        Either::A(number) => (key, Some(FieldValue::Number(number))),
+        Either::B(_) => (key, None),
+    })
+}
+
+impl MetadataPatch {
+    /// Flatten the patch into `(key, value)` entries, `None` values remove the field
+    pub(crate) fn into_entries(self) -> Vec<(&'static str, Option<FieldValue>)> {
+        [
+            text_entry("title", self.title),
+            text_entry("artist", self.artist),
+            text_entry("album", self.album),
+            text_entry("albumArtist", self.album_artist),
+            number_entry("year", self.year),
+            text_entry("genre", self.genre),
+            number_entry("trackNumber", self.track_number),
+            number_entry("trackTotal", self.track_total),
+            number_entry("discNumber", self.disc_number),
+            number_entry("discsTotal", self.discs_total),
+            text_entry("comment", self.comment),
+            text_entry("composer", self.composer),
+            text_entry("conductor", self.conductor),
+            text_entry("lyricist", self.lyricist),
+            text_entry("publisher", self.publisher),
+            text_entry("lyrics", self.lyrics),
+            text_entry("copyright", self.copyright),
+            number_entry("rating", self.rating),
+            number_entry("trackReplayGain", self.track_replay_gain),
+            number_entry("trackReplayPeak", self.track_replay_peak),
+            number_entry("albumReplayGain", self.album_replay_gain),
+            number_entry("albumReplayPeak", self.album_replay_peak),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+fn expect_text(key: &str, value: Option<FieldValue>) -> Result<Either<String, Null>> {
+    match value {
+        Some(FieldValue::Text(text)) => Ok(Either::A(text)),
+        Some(FieldValue::Number(_)) => Err(Error::new(
+            Status::InvalidArg,
+            format!("{key} must be a string"),
+        )),
+        None => Ok(Either::B(Null)),
+    }
+}
+
+fn expect_number(key: &str, value: Option<FieldValue>) -> Result<Either<f64, Null>> {
+    match value {
+        Some(FieldValue::Number(number)) => Ok(Either::A(number)),
+        Some(FieldValue::Text(_)) => Err(Error::new(
+            Status::InvalidArg,
+            format!("{key} must be a number"),
+        )),
+        None => Ok(Either::B(Null)),
+    }
+}
+
+fn validate_count(value: Option<f64>, field: &str) -> Result<()> {
+    value.map_or(Ok(()), |value| {
+        MusicFile::validate_count(value, field).map(drop)
+    })
+}
+
+fn unknown_field(key: &str) -> Error {
+    Error::new(
+        Status::InvalidArg,
+        format!("Unknown metadata field '{key}'"),
+    )
+}
+
+impl MusicFile {
+    /// Read a metadata field by its key
+    pub(crate) fn get_field(&self, key: &str) -> Option<FieldValue> {
+        let text = |value: Option<String>| value.map(FieldValue::Text);
+        let number = |value: Option<f64>| value.map(FieldValue::Number);
+
+        match key {
+            "title" => text(self.title()),
+            "artist" => text(self.artist()),
+            "album" => text(self.album()),
+            "albumArtist" => text(self.album_artist()),
+            "year" => number(self.year().map(f64::from)),
+            "genre" => text(self.genre()),
+            "trackNumber" => number(self.track_number().map(f64::from)),
+            "trackTotal" => number(self.track_total().map(f64::from)),
+            "discNumber" => number(self.disc_number().map(f64::from)),
+            "discsTotal" => number(self.discs_total().map(f64::from)),
+            "comment" => text(self.comment()),
+            "composer" => text(self.composer()),
+            "conductor" => text(self.conductor()),
+            "lyricist" => text(self.lyricist()),
+            "publisher" => text(self.publisher()),
+            "lyrics" => text(self.lyrics()),
+            "copyright" => text(self.copyright()),
+            "rating" => number(self.rating().map(f64::from)),
+            "trackReplayGain" => number(self.track_replay_gain()),
+            "trackReplayPeak" => number(self.track_replay_peak()),
+            "albumReplayGain" => number(self.album_replay_gain()),
+            "albumReplayPeak" => number(self.album_replay_peak()),
+            _ => None,
+        }
+    }
+
+    /// Check a metadata field value with the rules of its setter, without writing it
+    pub(crate) fn validate_field(&self, key: &str, value: &Option<FieldValue>) -> Result<()> {
+        let number = || -> Result<Option<f64>> {
+            match expect_number(key, value.clone())? {
+                Either::A(number) => Ok(Some(number)),
+                Either::B(_) => Ok(None),
+            }
+        };
+
+        match key {
+            "year" => number()?.map_or(Ok(()), |year| Self::validate_year(year).map(drop)),
+            "trackNumber" => validate_count(number()?, "Track number"),
+            "trackTotal" => validate_count(number()?, "Track total"),
+            "discNumber" => validate_count(number()?, "Disc number"),
+            "discsTotal" => validate_count(number()?, "Disc total"),
+            "rating" => number()?.map_or(Ok(()), |rating| Self::validate_rating(rating).map(drop)),
+            "trackReplayGain" | "albumReplayGain" => {
+                number()?.map_or(Ok(()), |gain| self.replay_gain_text(gain).map(drop))
+            }
+            "trackReplayPeak" | "albumReplayPeak" => {
+                number()?.map_or(Ok(()), |peak| Self::validate_gain_value(peak).map(drop))
+            }
+            _ if FIELD_KEYS.contains(&key) => expect_text(key, value.clone()).map(drop),
+            _ => Err(unknown_field(key)),
+        }
+    }
+
+    /// Write a metadata field by its key through the matching setter, `None` removes the field
+    pub(crate) fn set_field(&mut self, key: &str, value: Option<FieldValue>) -> Result<()> {
+        match key {
+            "title" => self.set_title(expect_text(key, value)?),
+            "artist" => self.set_artist(expect_text(key, value)?),
+            "album" => self.set_album(expect_text(key, value)?),
+            "albumArtist" => self.set_album_artist(expect_text(key, value)?),
+            "year" => self.set_year(expect_number(key, value)?),
+            "genre" => self.set_genre(expect_text(key, value)?),
+            "trackNumber" => self.set_track_number(expect_number(key, value)?),
+            "trackTotal" => self.set_track_total(expect_number(key, value)?),
+            "discNumber" => self.set_disc_number(expect_number(key, value)?),
+            "discsTotal" => self.set_discs_total(expect_number(key, value)?),
+            "comment" => self.set_comment(expect_text(key, value)?),
+            "composer" => self.set_composer(expect_text(key, value)?),
+            "conductor" => self.set_conductor(expect_text(key, value)?),
+            "lyricist" => self.set_lyricist(expect_text(key, value)?),
+            "publisher" => self.set_publisher(expect_text(key, value)?),
+            "lyrics" => self.set_lyrics(expect_text(key, value)?),
+            "copyright" => self.set_copyright(expect_text(key, value)?),
+            "rating" => self.set_rating(expect_number(key, value)?),
+            "trackReplayGain" => self.set_track_replay_gain(expect_number(key, value)?),
+            "trackReplayPeak" => self.set_track_replay_peak(expect_number(key, value)?),
+            "albumReplayGain" => self.set_album_replay_gain(expect_number(key, value)?),
+            "albumReplayPeak" => self.set_album_replay_peak(expect_number(key, value)?),
+            _ => Err(unknown_field(key)),
+        }
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// Apply multiple metadata fields at once
+    ///
+    /// Every field is validated with the same rules as its setter before anything is
+    /// applied, so either all fields are written or none of them. A patch that changes nothing
+    /// is not recorded in the undo history.
+    ///
+    /// @param patch Fields to update, set a field to `null` to remove it
+    ///
+    /// @returns Keys of the fields whose value actually changed
+    ///
+    /// @throws If any field is invalid, listing every invalid field
+    #[napi]
+    pub fn update(&mut self, patch: MetadataPatch) -> Result<Vec<String>> {
+        let entries = patch.into_entries();
+
+        let errors: Vec<String> = entries
+            .iter()
+            .filter_map(|(key, value)| self.validate_field(key, value).err())
+            .map(|error| error.reason)
+            .collect();
+        if !errors.is_empty() {
+            return Err(Error::new(Status::InvalidArg, errors.join("; ")));
+        }
+
+        let previous: Vec<_> = entries.iter().map(|(key, _)| self.get_field(key)).collect();
+        // An empty or unchanged patch is not a modification
+        if entries
+            .iter()
+            .zip(&previous)
+            .all(|((_, value), previous)| value == previous)
+        {
+            return Ok(Vec::new());
+        }
+        let keys: Vec<_> = entries.iter().map(|(key, _)| *key).collect();
+        self.as_history_step(|file| {
+            file.track_original();
+            entries
+                .into_iter()
+                .try_for_each(|(key, value)| file.set_field(key, value))
+        })?;
+
+        Ok(keys
+            .into_iter()
+            .zip(previous)
+            .filter(|(key, previous)| self.get_field(key) != *previous)
+            .map(|(key, _)| key.to_owned())
+            .collect())
+    }
+}
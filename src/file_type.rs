@@ -0,0 +1,258 @@
+#![cfg_attr(target_arch = "wasm32", allow(dead_code, unused_imports))]
+
+use std::io::{Cursor, Read, Seek};
+
+use std::path::Path;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, ErrorKind},
+    path::PathBuf,
+};
+
+use lofty::{
+    file::{FileType, TaggedFileExt},
+    probe::Probe,
+};
+use napi::{bindgen_prelude::Uint8Array, Either, Error, Result, Status};
+use napi_derive::napi;
+
+#[cfg(target_arch = "wasm32")]
+use crate::music_file::ERR_INVALID_IN_WASM;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::music_file::{path_error, ERR_FILE_LOADED_FROM_BUFFER};
+use crate::{
+    music_file::{MusicFile, MusicFileInner},
+    utils::{file_type_extension, file_type_extensions, file_type_mime, file_type_name},
+};
+
+/// File type detected from the content of a file
+#[napi(object)]
+pub struct FileTypeInfo {
+    #[napi(
+        ts_type = r#""Aac" | "Aiff" | "Ape" | "Flac" | "Mpeg" | "Mp4" | "Mpc" | "Opus" | "Vorbis" | "Speex" | "Wav" | "WavPack""#
+    )]
+    pub file_type: String,
+    /// Canonical file extension, without the leading dot
+    pub extension: String,
+    pub mime_type: String,
+}
+
+impl FileTypeInfo {
+    fn new(file_type: &FileType) -> Option<Self> {
+        Some(Self {
+            file_type: file_type_name(file_type)?.to_owned(),
+            extension: file_type_extension(file_type)?.to_owned(),
+            mime_type: file_type_mime(file_type)?.to_owned(),
+        })
+    }
+}
+
+/// Result of comparing a file name extension with the detected file type
+#[napi(object)]
+pub struct ExtensionCheck {
+    /// Extension of the file name, lowercased and without the leading dot, `null` if it has none
+    pub extension: Option<String>,
+    /// Canonical extension of the detected file type
+    pub expected: String,
+    /// All extensions accepted for the detected file type, e.g. `"ogg"` for Opus
+    pub accepted: Vec<String>,
+    /// Whether the extension is one of the accepted ones
+    pub matches: bool,
+}
+
+/// Lowercased extension of a file name, without the leading dot
+fn name_extension(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+/// Guess the file type from the header of a stream, ignoring any file name
+fn guess_file_type(reader: impl Read + Seek) -> std::io::Result<Option<FileType>> {
+    Ok(Probe::new(reader).guess_file_type()?.file_type())
+}
+
+#[napi]
+impl MusicFile {
+    /// File type detected from the content when loading
+    ///
+    /// Unlike {@link tagType}, this is the audio container, e.g. an MP3 and a WAV file can both have ID3v2 tags.
+    #[napi(
+        getter,
+        ts_return_type = r#""Aac" | "Aiff" | "Ape" | "Flac" | "Mpeg" | "Mp4" | "Mpc" | "Opus" | "Vorbis" | "Speex" | "Wav" | "WavPack""#
+    )]
+    pub fn file_type(&self) -> &str {
+        file_type_name(&self.file.file_type()).unwrap_or("Unknown")
+    }
+
+    /// Canonical file extension of the {@link fileType}, without the leading dot, e.g. `"m4a"`
+    #[napi(getter)]
+    pub fn extension(&self) -> &str {
+        file_type_extension(&self.file.file_type()).unwrap_or("bin")
+    }
+
+    /// MIME type of the {@link fileType}, e.g. `"audio/flac"`
+    #[napi(getter)]
+    pub fn mime_type(&self) -> &str {
+        file_type_mime(&self.file.file_type()).unwrap_or("application/octet-stream")
+    }
+
+    /// Compare the extension of the file name with the {@link fileType} detected from the content
+    ///
+    /// Files are often named after the wrong format, e.g. an M4A file named `.mp3`.
+    /// The comparison ignores case and accepts common alternatives, like `.ogg` for Opus.
+    ///
+    /// @param name File name to check instead of the path, required for files loaded from a buffer
+    ///
+    /// @throws If no name is given and the file was loaded from a buffer
+    #[napi]
+    pub fn check_extension(&self, name: Option<String>) -> Result<ExtensionCheck> {
+        let name = match name {
+            Some(name) => name,
+            None => self.path().cloned().ok_or_else(|| {
+                Error::new(
+                    Status::InvalidArg,
+                    "A file name is required for files loaded from a buffer",
+                )
+            })?,
+        };
+
+        let accepted = file_type_extensions(&self.file.file_type());
+        let extension = name_extension(&name);
+        let matches = extension
+            .as_deref()
+            .is_some_and(|extension| accepted.contains(&extension));
+
+        Ok(ExtensionCheck {
+            extension,
+            expected: self.extension().to_owned(),
+            accepted: accepted
+                .iter()
+                .map(|&extension| extension.to_owned())
+                .collect(),
+            matches,
+        })
+    }
+
+    /// Rename the file to the canonical extension of its {@link fileType} if the current one doesn't match
+    ///
+    /// The rest of the file name is kept, e.g. `song.mp3` containing FLAC audio becomes `song.flac`.
+    /// An existing file is never overwritten. Afterwards {@link path} and saving use the new name.
+    ///
+    /// @returns The new path, or `null` if the extension already matches
+    ///
+    /// @throws If the file was loaded from a buffer
+    /// @throws If a file with the new name already exists
+    /// @throws If the file can't be renamed
+    /// @throws In WebAssembly environments
+    #[napi]
+    pub fn fix_extension(&mut self) -> Result<Option<String>> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            return Err(Error::new(Status::GenericFailure, ERR_INVALID_IN_WASM));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = match &self.inner {
+                MusicFileInner::Buffer { .. } => {
+                    return Err(Error::new(Status::InvalidArg, ERR_FILE_LOADED_FROM_BUFFER));
+                }
+                MusicFileInner::Path(path) => PathBuf::from(path),
+            };
+
+            let check = self.check_extension(None)?;
+            if check.matches || file_type_extension(&self.file.file_type()).is_none() {
+                return Ok(None);
+            }
+
+            let target = path.with_extension(&check.expected);
+            move_no_replace(&path, &target).map_err(|error| {
+                if error.kind() == ErrorKind::AlreadyExists {
+                    Error::new(
+                        Status::GenericFailure,
+                        format!(
+                            "Failed renaming to '{}': file already exists",
+                            target.display()
+                        ),
+                    )
+                } else {
+                    path_error(&path, error)
+                }
+            })?;
+
+            let target = target.to_string_lossy().into_owned();
+            self.inner = MusicFileInner::Path(target.clone());
+            Ok(Some(target))
+        }
+    }
+}
+
+/// Move a file to `target`, failing with `AlreadyExists` instead of replacing an existing file
+///
+/// `rename` replaces the target, and checking for it first races with other processes. A hard
+/// link fails atomically when the target exists instead. File systems without hard links get a
+/// copy opened with `create_new`, which fails the same way.
+#[cfg(not(target_arch = "wasm32"))]
+fn move_no_replace(path: &Path, target: &Path) -> io::Result<()> {
+    match fs::hard_link(path, target) {
+        Ok(()) => {}
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => return Err(error),
+        Err(_) => {
+            let mut source = File::open(path)?;
+            let mut copy = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(target)?;
+            let copied = io::copy(&mut source, &mut copy)
+                .and_then(|_| copy.set_permissions(source.metadata()?.permissions()))
+                .and_then(|()| copy.sync_all());
+            if let Err(error) = copied {
+                let _ = fs::remove_file(target);
+                return Err(error);
+            }
+        }
+    }
+
+    fs::remove_file(path).inspect_err(|_| {
+        // Keep the file under its old name only
+        let _ = fs::remove_file(target);
+    })
+}
+
+/// Detect the type of an audio file from its header, without reading tags or audio properties
+///
+/// The file name is ignored, only the content is used.
+///
+/// @param source The file system path or a Uint8Array containing the start of the file
+///
+/// @returns The detected type, or `null` if the content is not a supported audio format
+///
+/// @throws If the path doesn't exist or isn't accessible
+/// @throws If a path is given in WebAssembly environments
+#[napi]
+pub fn detect_file_type(source: Either<Uint8Array, String>) -> Result<Option<FileTypeInfo>> {
+    let file_type = match source {
+        Either::A(buffer) => guess_file_type(Cursor::new(buffer.as_ref()))
+            .map_err(|error| Error::new(Status::GenericFailure, error.to_string()))?,
+        Either::B(path) => {
+            #[cfg(target_arch = "wasm32")]
+            {
+                let _ = path;
+                return Err(Error::new(Status::GenericFailure, ERR_INVALID_IN_WASM));
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let path = Path::new(&path);
+                File::open(path)
+                    .and_then(|file| guess_file_type(BufReader::new(file)))
+                    .map_err(|error| path_error(path, error))?
+            }
+        }
+    };
+
+    Ok(file_type.as_ref().and_then(FileTypeInfo::new))
+}
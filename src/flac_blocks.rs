@@ -0,0 +1,793 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use lofty::{
+    file::FileType,
+    io::FileLike,
+    ogg::tag::VorbisComments,
+    tag::{ItemKey, Tag, TagType},
+};
+use napi::{bindgen_prelude::Uint8Array, Error, Result, Status};
+use napi_derive::napi;
+
+use crate::{
+    artwork::image_info,
+    music_file::{
+        ogg_pictures::{comment_fields, is_unmapped_comment, unmapped_comment_keys},
+        MusicFile,
+    },
+    utils::skip_id3v2,
+};
+
+const ERR_NOT_FLAC: &str = "This file is not a FLAC file";
+
+const BLOCK_STREAMINFO: u8 = 0;
+const BLOCK_PADDING: u8 = 1;
+const BLOCK_APPLICATION: u8 = 2;
+const BLOCK_SEEKTABLE: u8 = 3;
+const BLOCK_VORBIS_COMMENT: u8 = 4;
+const BLOCK_CUESHEET: u8 = 5;
+const BLOCK_PICTURE: u8 = 6;
+
+/// Vorbis comment holding an embedded cue sheet
+pub(crate) const CUESHEET_KEY: &str = "CUESHEET";
+/// Vorbis comments written by MQA encoders
+const MQA_ENCODER_KEY: &[u8] = b"MQAENCODER";
+const MQA_ORIGINAL_SAMPLE_RATE_KEY: &[u8] = b"ORIGINALSAMPLERATE";
+
+pub(crate) const BLOCK_HEADER_SIZE: usize = 4;
+/// Largest block content, the length is stored in 24 bits
+const MAX_BLOCK_SIZE: usize = (1 << 24) - 1;
+/// Padding added to files without any, the default of lofty
+const DEFAULT_PADDING: usize = 1024;
+/// Chunk size for moving the audio frames when the metadata size changes
+const MOVE_BUFFER_SIZE: usize = 64 * 1024;
+const SEEK_POINT_SIZE: usize = 18;
+const PLACEHOLDER_SEEK_POINT: u64 = u64::MAX;
+
+/// Details of the STREAMINFO block
+#[napi(object)]
+#[derive(Clone)]
+pub struct FlacStreamInfo {
+    /// Minimum block size in samples
+    pub min_block_size: u32,
+    /// Maximum block size in samples
+    pub max_block_size: u32,
+    /// Minimum frame size in bytes, `0` if unknown
+    pub min_frame_size: u32,
+    /// Maximum frame size in bytes, `0` if unknown
+    pub max_frame_size: u32,
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    pub channels: u32,
+    pub bits_per_sample: u32,
+    /// Total samples per channel, `0` if unknown
+    pub total_samples: f64,
+    /// MD5 of the decoded audio as lowercase hex, all zeros if not computed by the encoder
+    pub md5: String,
+}
+
+/// Point of the SEEKTABLE block, placeholder points are omitted
+#[napi(object)]
+#[derive(Clone)]
+pub struct FlacSeekPoint {
+    /// Number of the first sample in the target frame
+    pub sample_number: f64,
+    /// Offset in bytes from the first frame to the target frame
+    pub offset: f64,
+    /// Number of samples in the target frame
+    pub samples: u32,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FlacCueIndex {
+    /// Offset in samples, relative to the track offset
+    pub offset: f64,
+    pub number: u32,
+}
+
+#[napi(object)]
+#[derive(Clone)]
+pub struct FlacCueTrack {
+    /// Offset in samples, relative to the beginning of the audio
+    pub offset: f64,
+    /// Track number, `170` (CD-DA) or `255` for the lead-out track
+    pub number: u32,
+    pub isrc: String,
+    pub is_audio: bool,
+    pub pre_emphasis: bool,
+    pub indices: Vec<FlacCueIndex>,
+}
+
+/// Contents of the CUESHEET block
+#[napi(object)]
+#[derive(Clone)]
+pub struct FlacCueSheet {
+    pub media_catalog_number: String,
+    /// Number of lead-in samples
+    pub lead_in_samples: f64,
+    /// Whether the cue sheet corresponds to a Compact Disc
+    pub is_cd: bool,
+    pub tracks: Vec<FlacCueTrack>,
+}
+
+#[napi(object)]
+pub struct FlacApplication {
+    /// Registered application ID, four ASCII characters
+    pub id: String,
+    pub data: Uint8Array,
+}
+
+/// Metadata blocks of a FLAC file besides Vorbis comments and pictures
+#[napi(object)]
+pub struct FlacMetadata {
+    pub stream_info: FlacStreamInfo,
+    pub seek_points: Vec<FlacSeekPoint>,
+    pub cue_sheet: Option<FlacCueSheet>,
+    /// APPLICATION blocks, including changes that are written on the next save
+    pub applications: Vec<FlacApplication>,
+    /// Total size of the PADDING blocks in bytes, including changes that are written on the next save
+    pub padding: u32,
+}
+
+#[derive(Clone, PartialEq)]
+struct Application {
+    id: [u8; 4],
+    data: Vec<u8>,
+}
+
+/// APPLICATION and PADDING changes, journaled by the edit history
+pub(crate) struct FlacBlockChanges {
+    applications: Vec<Application>,
+    padding: Option<u32>,
+}
+
+/// FLAC metadata blocks of the loaded file, and block changes to apply on save
+#[derive(Clone)]
+pub(crate) struct FlacBlocks {
+    stream_info: FlacStreamInfo,
+    seek_points: Vec<FlacSeekPoint>,
+    cue_sheet: Option<FlacCueSheet>,
+    /// Text of a `CUESHEET` Vorbis comment, which lofty drops from the tag
+    cue_sheet_text: Option<String>,
+    /// `MQAENCODER` Vorbis comment, written by MQA encoders
+    mqa_encoder: Option<String>,
+    /// `ORIGINALSAMPLERATE` Vorbis comment of MQA files
+    mqa_original_sample_rate: Option<u32>,
+    /// Keys of the Vorbis comments lofty drops from the tag
+    unmapped_comments: Vec<String>,
+    loaded_applications: Vec<Application>,
+    loaded_padding: u32,
+    applications: Vec<Application>,
+    padding: Option<u32>,
+}
+
+impl FlacBlocks {
+    /// Whether applications or padding were changed since loading
+    pub(crate) fn is_modified(&self) -> bool {
+        self.applications != self.loaded_applications
+            || self
+                .padding
+                .is_some_and(|padding| padding != self.loaded_padding)
+    }
+
+    /// Discard application and padding changes
+    pub(crate) fn revert(&mut self) {
+        self.applications = self.loaded_applications.clone();
+        self.padding = None;
+    }
+
+    pub(crate) fn changes(&self) -> FlacBlockChanges {
+        FlacBlockChanges {
+            applications: self.applications.clone(),
+            padding: self.padding,
+        }
+    }
+
+    pub(crate) fn restore_changes(&mut self, changes: FlacBlockChanges) {
+        self.applications = changes.applications;
+        self.padding = changes.padding;
+    }
+
+    pub(crate) fn cue_sheet(&self) -> Option<&FlacCueSheet> {
+        self.cue_sheet.as_ref()
+    }
+
+    pub(crate) fn cue_sheet_text(&self) -> Option<&str> {
+        self.cue_sheet_text.as_deref()
+    }
+
+    pub(crate) fn mqa_encoder(&self) -> Option<&str> {
+        self.mqa_encoder.as_deref()
+    }
+
+    pub(crate) fn mqa_original_sample_rate(&self) -> Option<u32> {
+        self.mqa_original_sample_rate
+    }
+
+    pub(crate) fn sample_rate(&self) -> u32 {
+        self.stream_info.sample_rate
+    }
+
+    pub(crate) fn unmapped_comments(&self) -> &[String] {
+        &self.unmapped_comments
+    }
+}
+
+fn u64_be(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+/// Text of a fixed-size, NUL-padded ASCII field
+fn ascii_field(data: &[u8]) -> String {
+    let end = data
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+fn parse_stream_info(data: &[u8]) -> Option<FlacStreamInfo> {
+    let data = data.get(..34)?;
+    let u24 =
+        |offset: usize| u32::from_be_bytes([0, data[offset], data[offset + 1], data[offset + 2]]);
+    let packed = u64_be(data, 10)?;
+
+    Some(FlacStreamInfo {
+        min_block_size: u16::from_be_bytes([data[0], data[1]]).into(),
+        max_block_size: u16::from_be_bytes([data[2], data[3]]).into(),
+        min_frame_size: u24(4),
+        max_frame_size: u24(7),
+        sample_rate: (packed >> 44) as u32,
+        channels: ((packed >> 41) & 0x7) as u32 + 1,
+        bits_per_sample: ((packed >> 36) & 0x1F) as u32 + 1,
+        total_samples: (packed & 0xF_FFFF_FFFF) as f64,
+        md5: data[18..34]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+    })
+}
+
+fn parse_seek_table(data: &[u8]) -> Vec<FlacSeekPoint> {
+    data.chunks_exact(SEEK_POINT_SIZE)
+        .filter_map(|point| {
+            let sample_number = u64_be(point, 0)?;
+            (sample_number != PLACEHOLDER_SEEK_POINT).then(|| FlacSeekPoint {
+                sample_number: sample_number as f64,
+                offset: u64_be(point, 8).unwrap_or_default() as f64,
+                samples: u16::from_be_bytes([point[16], point[17]]).into(),
+            })
+        })
+        .collect()
+}
+
+fn parse_cue_sheet(data: &[u8]) -> Option<FlacCueSheet> {
+    let media_catalog_number = ascii_field(data.get(..128)?);
+    let lead_in_samples = u64_be(data, 128)?;
+    let is_cd = data.get(136)? & 0x80 != 0;
+    let track_count = *data.get(395)?;
+
+    let mut offset = 396;
+    let mut tracks = Vec::with_capacity(track_count.into());
+    for _ in 0..track_count {
+        let track = data.get(offset..offset + 36)?;
+        let flags = track[21];
+        let index_count = track[35];
+        offset += 36;
+
+        let mut indices = Vec::with_capacity(index_count.into());
+        for _ in 0..index_count {
+            let index = data.get(offset..offset + 12)?;
+            indices.push(FlacCueIndex {
+                offset: u64_be(index, 0)? as f64,
+                number: index[8].into(),
+            });
+            offset += 12;
+        }
+
+        tracks.push(FlacCueTrack {
+            offset: u64_be(track, 0)? as f64,
+            number: track[8].into(),
+            isrc: ascii_field(&track[9..21]),
+            is_audio: flags & 0x80 == 0,
+            pre_emphasis: flags & 0x40 != 0,
+            indices,
+        });
+    }
+
+    Some(FlacCueSheet {
+        media_catalog_number,
+        lead_in_samples: lead_in_samples as f64,
+        is_cd,
+        tracks,
+    })
+}
+
+/// Skip an ID3v2 tag in front of the FLAC stream, and the `fLaC` marker
+fn skip_to_first_block(reader: &mut (impl Read + Seek)) -> std::io::Result<u64> {
+    let start = skip_id3v2(reader)?;
+    let mut marker = [0; 4];
+    reader.read_exact(&mut marker)?;
+    if &marker != b"fLaC" {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Missing FLAC stream marker",
+        ));
+    }
+
+    Ok(start + 4)
+}
+
+/// Header of a metadata block, with the content if it was read
+pub(crate) struct RawBlock {
+    pub(crate) ty: u8,
+    pub(crate) len: usize,
+    content: Option<Vec<u8>>,
+}
+
+/// Name of a block holding metadata rather than stream structure, as reported by `audioLayout`
+pub(crate) fn metadata_block_kind(ty: u8) -> Option<&'static str> {
+    match ty {
+        BLOCK_PADDING => Some("Padding"),
+        BLOCK_APPLICATION => Some("Application"),
+        BLOCK_VORBIS_COMMENT => Some("VorbisComment"),
+        BLOCK_CUESHEET => Some("CueSheet"),
+        BLOCK_PICTURE => Some("Picture"),
+        _ => None,
+    }
+}
+
+/// Read the metadata block chain, only reading the content of blocks accepted by `read_content`
+///
+/// Returns the offset of the first block, the end of the last block and the blocks.
+pub(crate) fn read_blocks(
+    reader: &mut (impl Read + Seek),
+    read_content: impl Fn(u8) -> bool,
+) -> std::io::Result<(u64, u64, Vec<RawBlock>)> {
+    let start = skip_to_first_block(reader)?;
+    let mut end = start;
+    let mut blocks = Vec::new();
+
+    loop {
+        let mut header = [0; BLOCK_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        let last = header[0] & 0x80 != 0;
+        let ty = header[0] & 0x7F;
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+
+        let content = if read_content(ty) {
+            let mut content = vec![0; len];
+            reader.read_exact(&mut content)?;
+            Some(content)
+        } else {
+            reader.seek(SeekFrom::Current(len as i64))?;
+            None
+        };
+
+        end += (BLOCK_HEADER_SIZE + len) as u64;
+        blocks.push(RawBlock { ty, len, content });
+        if last {
+            break;
+        }
+    }
+
+    Ok((start, end, blocks))
+}
+
+/// Metadata block chain of a FLAC file, read once while loading it
+pub(crate) struct FlacBlockChain {
+    /// Offset of the first block
+    pub(crate) start: u64,
+    /// End of the last block
+    pub(crate) end: u64,
+    pub(crate) blocks: Vec<RawBlock>,
+}
+
+impl FlacBlockChain {
+    /// Content of the VORBIS_COMMENT block
+    pub(crate) fn comments(&self) -> Option<&[u8]> {
+        self.blocks
+            .iter()
+            .find(|block| block.ty == BLOCK_VORBIS_COMMENT)?
+            .content
+            .as_deref()
+    }
+}
+
+/// Read the metadata block chain of a FLAC file, `None` for other file types or broken files
+///
+/// Pictures and padding are skipped, only their size is read.
+pub(crate) fn read_flac_block_chain(
+    file_type: FileType,
+    reader: &mut (impl Read + Seek),
+) -> Option<FlacBlockChain> {
+    if file_type != FileType::Flac {
+        return None;
+    }
+
+    reader.rewind().ok()?;
+    let (start, end, blocks) = read_blocks(reader, |ty| {
+        matches!(
+            ty,
+            BLOCK_STREAMINFO
+                | BLOCK_APPLICATION
+                | BLOCK_SEEKTABLE
+                | BLOCK_VORBIS_COMMENT
+                | BLOCK_CUESHEET
+        )
+    })
+    .ok()?;
+
+    Some(FlacBlockChain { start, end, blocks })
+}
+
+/// Collect the metadata blocks of a FLAC file, `None` if it has no STREAMINFO block
+pub(crate) fn load_flac_blocks(chain: &FlacBlockChain) -> Option<FlacBlocks> {
+    let mut stream_info = None;
+    let mut seek_points = Vec::new();
+    let mut cue_sheet = None;
+    let mut cue_sheet_text = None;
+    let mut mqa_encoder = None;
+    let mut mqa_original_sample_rate = None;
+    let mut unmapped_comments = Vec::new();
+    let mut applications = Vec::new();
+    let mut padding = 0u32;
+    for block in &chain.blocks {
+        let content = block.content.as_deref().unwrap_or_default();
+        match block.ty {
+            BLOCK_STREAMINFO => stream_info = parse_stream_info(content),
+            BLOCK_PADDING => padding = padding.saturating_add(block.len as u32),
+            BLOCK_APPLICATION if content.len() >= 4 => applications.push(Application {
+                id: content[..4].try_into().ok()?,
+                data: content[4..].to_vec(),
+            }),
+            BLOCK_SEEKTABLE => seek_points.extend(parse_seek_table(content)),
+            BLOCK_VORBIS_COMMENT => {
+                let fields = comment_fields(FileType::Flac, content).unwrap_or_default();
+                unmapped_comments = unmapped_comment_keys(&fields);
+                for (key, value) in fields {
+                    let value = || String::from_utf8_lossy(value).into_owned();
+                    if key.eq_ignore_ascii_case(CUESHEET_KEY.as_bytes()) {
+                        cue_sheet_text.get_or_insert_with(value);
+                    } else if key.eq_ignore_ascii_case(MQA_ENCODER_KEY) {
+                        mqa_encoder.get_or_insert_with(value);
+                    } else if key.eq_ignore_ascii_case(MQA_ORIGINAL_SAMPLE_RATE_KEY) {
+                        mqa_original_sample_rate = value().trim().parse().ok();
+                    }
+                }
+            }
+            BLOCK_CUESHEET => cue_sheet = parse_cue_sheet(content),
+            _ => {}
+        }
+    }
+
+    Some(FlacBlocks {
+        stream_info: stream_info?,
+        seek_points,
+        cue_sheet,
+        cue_sheet_text,
+        mqa_encoder,
+        mqa_original_sample_rate,
+        unmapped_comments,
+        loaded_applications: applications.clone(),
+        loaded_padding: padding,
+        applications,
+        padding: None,
+    })
+}
+
+fn encode_block(encoded: &mut Vec<u8>, ty: u8, content: &[u8]) {
+    let len = (content.len() as u32).to_be_bytes();
+    encoded.extend([ty, len[1], len[2], len[3]]);
+    encoded.extend_from_slice(content);
+}
+
+/// Vendor string of a VORBIS_COMMENT block
+fn comment_vendor(content: &[u8]) -> Option<&str> {
+    let len = u32::from_le_bytes(content.get(..4)?.try_into().ok()?) as usize;
+    std::str::from_utf8(content.get(4..4 + len)?).ok()
+}
+
+/// Encode the VORBIS_COMMENT block of a tag, keeping the vendor and unmapped comments of `loaded`
+fn encode_comments(tag: &Tag, loaded: Option<&[u8]>) -> Vec<u8> {
+    let mut items = Tag::new(TagType::VorbisComments);
+    for item in tag.items() {
+        items.push_unchecked(item.clone());
+    }
+    let encoder = items
+        .get_string(ItemKey::EncoderSoftware)
+        .map(str::to_owned);
+    let comments = VorbisComments::from(items);
+
+    let mut fields: Vec<(String, String)> = comments
+        .items()
+        .map(|(key, value)| (key.to_owned(), value.to_owned()))
+        .collect();
+    // The conversion turns the encoder into the vendor string, which is kept from the loaded block
+    if let Some(encoder) = encoder {
+        fields.push(("ENCODER".to_owned(), encoder));
+    }
+    let loaded_fields = loaded
+        .and_then(|content| comment_fields(FileType::Flac, content))
+        .unwrap_or_default();
+    for (key, value) in loaded_fields {
+        let key = String::from_utf8_lossy(key);
+        let written = fields
+            .iter()
+            .any(|(other, _)| other.eq_ignore_ascii_case(&key));
+        if is_unmapped_comment(&key) && !written {
+            fields.push((
+                key.into_owned(),
+                String::from_utf8_lossy(value).into_owned(),
+            ));
+        }
+    }
+
+    let vendor = loaded
+        .and_then(comment_vendor)
+        .unwrap_or_else(|| comments.vendor());
+    let mut encoded = Vec::new();
+    encoded.extend((vendor.len() as u32).to_le_bytes());
+    encoded.extend_from_slice(vendor.as_bytes());
+    encoded.extend((fields.len() as u32).to_le_bytes());
+    for (key, value) in fields {
+        encoded.extend(((key.len() + 1 + value.len()) as u32).to_le_bytes());
+        encoded.extend_from_slice(key.as_bytes());
+        encoded.push(b'=');
+        encoded.extend_from_slice(value.as_bytes());
+    }
+    encoded
+}
+
+/// Replace the bytes between `start` and `end` with `replacement`, moving the rest of the file
+fn replace_range<F: FileLike>(
+    target: &mut F,
+    start: u64,
+    end: u64,
+    replacement: &[u8],
+) -> std::io::Result<()> {
+    let len = target.seek(SeekFrom::End(0))?;
+    let new_end = start + replacement.len() as u64;
+    let mut buffer = vec![0; MOVE_BUFFER_SIZE];
+
+    if new_end > end {
+        // Move the tail backwards from the end, so no unread data is overwritten
+        let mut remaining = len - end;
+        while remaining > 0 {
+            let chunk = remaining.min(MOVE_BUFFER_SIZE as u64);
+            remaining -= chunk;
+            let chunk = &mut buffer[..chunk as usize];
+            target.seek(SeekFrom::Start(end + remaining))?;
+            target.read_exact(chunk)?;
+            target.seek(SeekFrom::Start(new_end + remaining))?;
+            target.write_all(chunk)?;
+        }
+    } else if new_end < end {
+        let mut moved = 0;
+        while end + moved < len {
+            let chunk = (len - end - moved).min(MOVE_BUFFER_SIZE as u64);
+            let chunk = &mut buffer[..chunk as usize];
+            target.seek(SeekFrom::Start(end + moved))?;
+            target.read_exact(chunk)?;
+            target.seek(SeekFrom::Start(new_end + moved))?;
+            target.write_all(chunk)?;
+            moved += chunk.len() as u64;
+        }
+        target.truncate(len - (end - new_end))?;
+    }
+
+    target.seek(SeekFrom::Start(start))?;
+    target.write_all(replacement)
+}
+
+/// Write the metadata blocks of a FLAC file in a single pass
+///
+/// `tag` replaces the VORBIS_COMMENT and PICTURE blocks, which are kept as they are when `None`.
+/// APPLICATION blocks are only replaced when they were changed, and all padding is merged into
+/// a last PADDING block. Unless its size was set, the padding absorbs size changes of the other
+/// blocks when possible, so the audio frames stay in place.
+pub(crate) fn write_flac_blocks<F: FileLike>(
+    target: &mut F,
+    flac: &FlacBlocks,
+    tag: Option<&Tag>,
+) -> std::io::Result<()> {
+    target.rewind()?;
+    let (start, end, blocks) = read_blocks(target, |ty| match ty {
+        BLOCK_PADDING => false,
+        BLOCK_PICTURE => tag.is_none(),
+        _ => true,
+    })?;
+
+    let applications_modified = flac.applications != flac.loaded_applications;
+    let mut loaded_padding: Option<usize> = None;
+    let mut loaded_comments = None;
+    let mut kept: Vec<(u8, Vec<u8>)> = Vec::new();
+    for block in blocks {
+        match block.ty {
+            BLOCK_PADDING => *loaded_padding.get_or_insert(0) += block.len,
+            BLOCK_APPLICATION if applications_modified => {}
+            BLOCK_VORBIS_COMMENT if tag.is_some() => loaded_comments = block.content,
+            BLOCK_PICTURE if tag.is_some() => {}
+            _ => kept.push((block.ty, block.content.unwrap_or_default())),
+        }
+    }
+
+    if let Some(tag) = tag {
+        kept.push((
+            BLOCK_VORBIS_COMMENT,
+            encode_comments(tag, loaded_comments.as_deref()),
+        ));
+        for pic in tag.pictures() {
+            let info = image_info(pic.data())
+                .map(|info| info.picture_information())
+                .unwrap_or_default();
+            kept.push((BLOCK_PICTURE, pic.as_flac_bytes(info, false)));
+        }
+    }
+    if applications_modified {
+        kept.extend(flac.applications.iter().map(|application| {
+            let mut content = application.id.to_vec();
+            content.extend_from_slice(&application.data);
+            (BLOCK_APPLICATION, content)
+        }));
+    }
+    if kept
+        .iter()
+        .any(|(_, content)| content.len() > MAX_BLOCK_SIZE)
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "A FLAC metadata block exceeds 16 MiB",
+        ));
+    }
+
+    let size: usize = kept
+        .iter()
+        .map(|(_, content)| BLOCK_HEADER_SIZE + content.len())
+        .sum();
+    // Padding that keeps the metadata at its current size
+    let available = ((end - start) as usize).checked_sub(size + BLOCK_HEADER_SIZE);
+    let padding = match (flac.padding, loaded_padding) {
+        (Some(padding), _) => padding as usize,
+        (None, Some(padding)) => available.unwrap_or(padding),
+        // Like lofty, add padding to files without any
+        (None, None) => available.unwrap_or_default().max(DEFAULT_PADDING),
+    };
+    if padding > 0 {
+        kept.push((BLOCK_PADDING, vec![0; padding.min(MAX_BLOCK_SIZE)]));
+    }
+
+    let mut encoded = Vec::new();
+    let last_index = kept.len() - 1;
+    for (index, (ty, content)) in kept.iter().enumerate() {
+        let flag = if index == last_index { 0x80 } else { 0 };
+        encode_block(&mut encoded, ty | flag, content);
+    }
+
+    replace_range(target, start, end, &encoded)
+}
+
+/// Parse a four character application ID
+fn application_id(id: &str) -> Result<[u8; 4]> {
+    id.as_bytes()
+        .try_into()
+        .ok()
+        .filter(|id: &[u8; 4]| id.is_ascii())
+        .ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                "Application ID should be four ASCII characters",
+            )
+        })
+}
+
+#[napi]
+impl MusicFile {
+    /// FLAC metadata blocks besides Vorbis comments and pictures, or `null` for other formats
+    ///
+    /// STREAMINFO, SEEKTABLE and CUESHEET are read from the loaded file,
+    /// APPLICATION blocks and padding include pending changes.
+    #[napi(getter)]
+    pub fn flac_metadata(&self) -> Option<FlacMetadata> {
+        let flac = self.flac_blocks.as_ref()?;
+
+        Some(FlacMetadata {
+            stream_info: flac.stream_info.clone(),
+            seek_points: flac.seek_points.clone(),
+            cue_sheet: flac.cue_sheet.clone(),
+            applications: flac
+                .applications
+                .iter()
+                .map(|application| FlacApplication {
+                    id: String::from_utf8_lossy(&application.id).into_owned(),
+                    data: application.data.clone().into(),
+                })
+                .collect(),
+            padding: flac.padding.unwrap_or(flac.loaded_padding),
+        })
+    }
+
+    /// Set the total size of the PADDING block written on the next save
+    ///
+    /// Padding lets later tag edits fit without rewriting the audio data. By default, the existing
+    /// padding grows or shrinks to keep the audio data in place when possible.
+    ///
+    /// @param bytes Padding size in bytes, `0` removes all padding
+    ///
+    /// @throws If the file is not a FLAC file, or the size exceeds 16 MiB
+    #[napi]
+    pub fn set_flac_padding(&mut self, bytes: u32) -> Result<()> {
+        self.flac_blocks_mut()?;
+        if bytes as usize > MAX_BLOCK_SIZE {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Padding should be at most {MAX_BLOCK_SIZE} bytes"),
+            ));
+        }
+
+        if self.flac_blocks_mut()?.padding != Some(bytes) {
+            self.record_history();
+            self.flac_blocks_mut()?.padding = Some(bytes);
+        }
+        Ok(())
+    }
+
+    /// Add an APPLICATION block, written on the next save
+    ///
+    /// @param id Registered application ID, four ASCII characters
+    /// @param data Application data
+    ///
+    /// @throws If the file is not a FLAC file, the ID is invalid, or the data is too large
+    #[napi]
+    pub fn add_flac_application(&mut self, id: String, data: Uint8Array) -> Result<()> {
+        let id = application_id(&id)?;
+        self.flac_blocks_mut()?;
+        if data.len() + id.len() > MAX_BLOCK_SIZE {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Application data is too large for a FLAC metadata block",
+            ));
+        }
+
+        self.record_history();
+        self.flac_blocks_mut()?.applications.push(Application {
+            id,
+            data: data.to_vec(),
+        });
+        Ok(())
+    }
+
+    /// Remove APPLICATION blocks on the next save
+    ///
+    /// @param id Only remove blocks with this application ID, all blocks are removed by default
+    ///
+    /// @returns Number of removed blocks
+    ///
+    /// @throws If the file is not a FLAC file, or the ID is invalid
+    #[napi]
+    pub fn remove_flac_applications(&mut self, id: Option<String>) -> Result<u32> {
+        let id = id.as_deref().map(application_id).transpose()?;
+        let keep = |application: &Application| id.is_some_and(|id| application.id != id);
+        let before = self.flac_blocks_mut()?.applications.len();
+        let after = self
+            .flac_blocks_mut()?
+            .applications
+            .iter()
+            .filter(|application| keep(application))
+            .count();
+        if before != after {
+            self.record_history();
+            self.flac_blocks_mut()?.applications.retain(keep);
+        }
+        Ok((before - after) as u32)
+    }
+
+    fn flac_blocks_mut(&mut self) -> Result<&mut FlacBlocks> {
+        self.flac_blocks
+            .as_mut()
+            .ok_or_else(|| Error::new(Status::InvalidArg, ERR_NOT_FLAC))
+    }
+}
@@ -0,0 +1,132 @@
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::music_file::MusicFile;
+
+/// iTunes comment holding the gapless playback values
+pub(crate) const ITUNES_SMPB_KEY: &str = "iTunSMPB";
+/// Largest sample count exactly represented by a JavaScript number
+const MAX_SAFE_SAMPLES: f64 = 9_007_199_254_740_991.0;
+
+/// Encoder delay and padding to trim for gapless playback
+#[napi(object)]
+#[derive(Clone)]
+pub struct GaplessInfo {
+    /// Where the values were read from, in this order of precedence
+    #[napi(ts_type = r#""iTunSMPB" | "LAME" | "Opus""#)]
+    pub source: String,
+    /// Samples to discard from the start of the decoded stream
+    ///
+    /// Opus counts samples at 48 kHz, other formats at the sample rate of the file.
+    pub encoder_delay: u32,
+    /// Samples to discard from the end of the decoded stream, or `null` if unknown
+    pub encoder_padding: Option<u32>,
+    /// Samples of the original audio, without delay and padding, or `null` if unknown
+    pub original_samples: Option<f64>,
+}
+
+/// Gapless values of an `iTunSMPB` comment, `None` if it is malformed
+fn gapless_from_itunes_smpb(text: &str) -> Option<GaplessInfo> {
+    let mut words = text.split_whitespace().skip(1);
+    let mut next = || words.next();
+    let encoder_delay = u32::from_str_radix(next()?, 16).ok()?;
+    let encoder_padding = u32::from_str_radix(next()?, 16).ok()?;
+    let original_samples = u64::from_str_radix(next()?, 16).ok()?;
+
+    Some(GaplessInfo {
+        source: "iTunSMPB".to_owned(),
+        encoder_delay,
+        encoder_padding: Some(encoder_padding),
+        original_samples: Some(original_samples as f64),
+    })
+}
+
+/// `iTunSMPB` comment of the gapless values, with the trailing zeros written by iTunes
+fn itunes_smpb_text(encoder_delay: u32, encoder_padding: u32, original_samples: u64) -> String {
+    let mut text =
+        format!(" 00000000 {encoder_delay:08X} {encoder_padding:08X} {original_samples:016X}");
+    text.push_str(&" 00000000".repeat(8));
+    text
+}
+
+impl MusicFile {
+    fn lame_gapless(&self) -> Option<GaplessInfo> {
+        let mpeg = self.mpeg_info()?;
+        let vbr_header = mpeg.vbr_header.as_ref()?;
+        let lame = vbr_header.lame.as_ref()?;
+        let original_samples = vbr_header.frames.and_then(|frames| {
+            (u64::from(frames) * u64::from(mpeg.samples_per_frame()))
+                .checked_sub(u64::from(lame.encoder_delay) + u64::from(lame.encoder_padding))
+        });
+
+        Some(GaplessInfo {
+            source: "LAME".to_owned(),
+            encoder_delay: lame.encoder_delay,
+            encoder_padding: Some(lame.encoder_padding),
+            original_samples: original_samples.map(|samples| samples as f64),
+        })
+    }
+
+    fn opus_gapless(&self) -> Option<GaplessInfo> {
+        let opus = self.codec_info.as_ref()?.opus.as_ref()?;
+        Some(GaplessInfo {
+            source: "Opus".to_owned(),
+            encoder_delay: opus.pre_skip,
+            encoder_padding: None,
+            original_samples: None,
+        })
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// Encoder delay and padding for gapless playback, or `null` if the file has none
+    ///
+    /// Read from the `iTunSMPB` comment of MP4 files and ID3v2 tags, then from the LAME header
+    /// of MP3 files, then from the pre-skip of the Opus identification header.
+    /// The values are reported as stored, MP3 decoders add their own delay of 529 samples.
+    #[napi(getter)]
+    pub fn gapless(&self) -> Option<GaplessInfo> {
+        self.itunes_comment(ITUNES_SMPB_KEY)
+            .and_then(|text| gapless_from_itunes_smpb(&text))
+            .or_else(|| self.lame_gapless())
+            .or_else(|| self.opus_gapless())
+    }
+
+    /// Write the gapless values as an `iTunSMPB` comment
+    ///
+    /// @param encoderDelay Samples to discard from the start
+    /// @param encoderPadding Samples to discard from the end
+    /// @param originalSamples Samples of the original audio
+    ///
+    /// @throws If the file is not an MP4 file and has no ID3v2 tag, or the sample count is invalid
+    #[napi]
+    pub fn set_gapless(
+        &mut self,
+        encoder_delay: u32,
+        encoder_padding: u32,
+        original_samples: f64,
+    ) -> Result<()> {
+        if !(0.0..=MAX_SAFE_SAMPLES).contains(&original_samples) || original_samples.fract() != 0.0
+        {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "Original samples should be a non-negative integer",
+            ));
+        }
+        let text = itunes_smpb_text(encoder_delay, encoder_padding, original_samples as u64);
+        self.set_itunes_comment(ITUNES_SMPB_KEY, Some(text))
+    }
+
+    /// Remove the `iTunSMPB` comment
+    ///
+    /// @returns Whether a comment was removed
+    #[napi]
+    pub fn remove_gapless(&mut self) -> Result<bool> {
+        if self.itunes_comment(ITUNES_SMPB_KEY).is_none() {
+            return Ok(false);
+        }
+        self.set_itunes_comment(ITUNES_SMPB_KEY, None)?;
+        Ok(true)
+    }
+}
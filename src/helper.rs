@@ -1,10 +1,13 @@
 use lofty::{
-    file::{AudioFile, TaggedFileExt},
+    file::{AudioFile, FileType, TaggedFileExt},
     tag::{ItemKey, Tag},
 };
 use napi::{bindgen_prelude::Null, Either, Error, Result, Status};
 
-use crate::music_file::MusicFile;
+use crate::{
+    music_file::MusicFile,
+    utils::{format_r128_gain, format_replaygain_gain, parse_r128_gain, parse_replaygain_value},
+};
 
 impl MusicFile {
     pub(crate) fn validate_integer(value: f64, field: &str, min: u64, max: u64) -> Result<u32> {
@@ -18,19 +21,37 @@ impl MusicFile {
         Ok(value as u32)
     }
 
+    /// Validate a track or disc number or total
+    pub(crate) fn validate_count(value: f64, field: &str) -> Result<u32> {
+        Self::validate_integer(value, field, 0, u32::MAX as u64)
+    }
+
+    /// Validate a ReplayGain gain or peak written as is
+    pub(crate) fn validate_gain_value(value: f64) -> Result<f64> {
+        if !value.is_finite() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "ReplayGain value must be finite",
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Primary or first available tag
+    pub(crate) fn current_tag(&self) -> Option<&Tag> {
+        if !self.file.contains_tag() {
+            return None;
+        }
+
+        self.file.primary_tag().or_else(|| self.file.first_tag())
+    }
+
     /// Execute a function on the primary or first available tag
     pub(crate) fn tag<R, F>(&self, f: F) -> Option<R>
     where
         F: FnOnce(&Tag) -> Option<R>,
     {
-        if !self.file.contains_tag() {
-            return None;
-        }
-
-        self.file
-            .primary_tag()
-            .or_else(|| self.file.first_tag())
-            .and_then(f)
+        self.current_tag().and_then(f)
     }
 
     /// Execute a mutable function on the primary or first available tag
@@ -38,6 +59,9 @@ impl MusicFile {
     where
         F: FnOnce(&mut Tag),
     {
+        self.record_history();
+        self.track_original();
+
         if !self.file.contains_tag() {
             // If no tag is available in the file, insert an empty one.
             self.file
@@ -79,15 +103,7 @@ impl MusicFile {
         F: FnOnce(f64) -> String,
     {
         let value = match value {
-            Either::A(value) => {
-                if !value.is_finite() {
-                    return Err(Error::new(
-                        Status::InvalidArg,
-                        "ReplayGain value must be finite",
-                    ));
-                }
-                Some(value)
-            }
+            Either::A(value) => Some(Self::validate_gain_value(value)?),
             Either::B(_) => None,
         };
 
@@ -100,4 +116,74 @@ impl MusicFile {
             }
         })
     }
+
+    /// Read a ReplayGain gain in dB, preferring the R128 gain of Opus files
+    pub(crate) fn replay_gain(&self, item_key: ItemKey, r128_key: ItemKey) -> Option<f64> {
+        let opus = self.file.file_type() == FileType::Opus;
+        self.tag(|tag| read_replay_gain(tag, opus, item_key, r128_key))
+    }
+
+    /// Format a ReplayGain gain in dB as `set_replay_gain` writes it, failing if it cannot be written
+    pub(crate) fn replay_gain_text(&self, value: f64) -> Result<String> {
+        if self.file.file_type() != FileType::Opus {
+            return Ok(format_replaygain_gain(Self::validate_gain_value(value)?));
+        }
+
+        format_r128_gain(value).ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                "ReplayGain value of Opus files must be in [-123, 132.99] dB",
+            )
+        })
+    }
+
+    /// Write a ReplayGain gain in dB, as an R128 gain relative to -23 LUFS for Opus files
+    pub(crate) fn set_replay_gain(
+        &mut self,
+        item_key: ItemKey,
+        r128_key: ItemKey,
+        value: Either<f64, Null>,
+    ) -> Result<()> {
+        let value = match value {
+            Either::A(value) => Some(self.replay_gain_text(value)?),
+            Either::B(_) => None,
+        };
+        let opus = self.file.file_type() == FileType::Opus;
+        self.tag_mut(|tag| write_replay_gain(tag, opus, item_key, r128_key, value))
+    }
+}
+
+/// Read a gain in dB, preferring the R128 gain of Opus files
+pub(crate) fn read_replay_gain(
+    tag: &Tag,
+    opus: bool,
+    item_key: ItemKey,
+    r128_key: ItemKey,
+) -> Option<f64> {
+    opus.then(|| tag.get_string(r128_key).and_then(parse_r128_gain))
+        .flatten()
+        .or_else(|| parse_replaygain_value(tag.get_string(item_key)?))
+}
+
+/// Store a gain formatted by `replay_gain_text`, `None` removes it
+pub(crate) fn write_replay_gain(
+    tag: &mut Tag,
+    opus: bool,
+    item_key: ItemKey,
+    r128_key: ItemKey,
+    value: Option<String>,
+) {
+    let key = if opus {
+        // Opus players ignore REPLAYGAIN_* gains, drop them so they can't disagree
+        tag.remove_key(item_key);
+        r128_key
+    } else {
+        item_key
+    };
+    match value {
+        Some(value) => {
+            tag.insert_text(key, value);
+        }
+        None => tag.remove_key(key),
+    }
 }
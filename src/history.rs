@@ -0,0 +1,332 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use lofty::{file::TaggedFileExt, picture::Picture, tag::Tag};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+use crate::music_file::{flac_blocks::FlacBlockChanges, MusicFile};
+
+/// Maximum number of undo steps kept, older steps are dropped
+const MAX_HISTORY: usize = 100;
+
+/// Tag without its pictures, which are shared with the other steps holding the same pictures
+struct TagState {
+    tag: Tag,
+    pictures: Arc<[Picture]>,
+}
+
+impl TagState {
+    fn to_tag(&self) -> Tag {
+        let mut tag = self.tag.clone();
+        for picture in self.pictures.iter() {
+            tag.push_picture(picture.clone());
+        }
+        tag
+    }
+}
+
+/// Changes applied on save besides the tags
+#[derive(Default)]
+struct PendingChanges {
+    convert_legacy_cover_art: bool,
+    flac_blocks: Option<FlacBlockChanges>,
+    opus_output_gain: Option<i16>,
+    itunes_atoms: Option<BTreeMap<String, Option<String>>>,
+}
+
+struct HistoryEntry {
+    state: u32,
+    tags: Vec<TagState>,
+    pending: PendingChanges,
+}
+
+/// In-memory journal of tag states for undo and redo
+pub(crate) struct History {
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    /// Identifier of the current tag state
+    state: u32,
+    last_state: u32,
+    enabled: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            state: 0,
+            last_state: 0,
+            enabled: true,
+        }
+    }
+}
+
+impl History {
+    /// History that never records, used by detached copies
+    pub(crate) fn disabled() -> Self {
+        History {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// Copy the tags for a step, sharing pictures with the last steps or `entry` when unchanged
+    fn tag_states(&self, tags: &[Tag], entry: Option<&HistoryEntry>) -> Vec<TagState> {
+        let neighbours: Vec<&TagState> = [entry, self.undo.last(), self.redo.last()]
+            .into_iter()
+            .flatten()
+            .flat_map(|entry| &entry.tags)
+            .collect();
+
+        tags.iter()
+            .map(|tag| {
+                let pictures = neighbours
+                    .iter()
+                    .find(|state| *state.pictures == *tag.pictures())
+                    .map_or_else(|| tag.pictures().into(), |state| state.pictures.clone());
+                let mut tag = tag.clone();
+                for index in (0..tag.pictures().len()).rev() {
+                    tag.remove_picture(index);
+                }
+                TagState { tag, pictures }
+            })
+            .collect()
+    }
+
+    /// Record a state before a modification, starting a new state
+    fn record(&mut self, tags: Vec<TagState>, pending: PendingChanges) {
+        if self.undo.len() >= MAX_HISTORY {
+            self.undo.remove(0);
+        }
+        self.undo.push(HistoryEntry {
+            state: self.state,
+            tags,
+            pending,
+        });
+        self.redo.clear();
+        self.last_state += 1;
+        self.state = self.last_state;
+    }
+}
+
+impl MusicFile {
+    /// Record the current tags and pending changes in the edit history before they are modified
+    pub(crate) fn record_history(&mut self) {
+        if !self.history.enabled {
+            return;
+        }
+
+        let tags = self.history.tag_states(self.file.tags(), None);
+        let pending = self.pending_changes();
+        self.history.record(tags, pending);
+    }
+
+    /// Run several modifications as a single step of the edit history
+    pub(crate) fn as_history_step<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.record_history();
+        let enabled = std::mem::replace(&mut self.history.enabled, false);
+        let result = f(self);
+        self.history.enabled = enabled;
+        result
+    }
+
+    fn pending_changes(&self) -> PendingChanges {
+        PendingChanges {
+            convert_legacy_cover_art: self
+                .ogg_pictures
+                .as_ref()
+                .is_some_and(|state| state.convert_pending),
+            flac_blocks: self.flac_blocks.as_ref().map(|flac| flac.changes()),
+            opus_output_gain: self
+                .opus_header
+                .as_ref()
+                .and_then(|header| header.changes()),
+            itunes_atoms: self.itunes_atoms.as_ref().map(|atoms| atoms.changes()),
+        }
+    }
+
+    /// Restore a recorded step, returning the current one in its place
+    fn restore_entry(&mut self, entry: HistoryEntry) -> HistoryEntry {
+        self.track_original();
+
+        let current = HistoryEntry {
+            state: self.history.state,
+            tags: self.history.tag_states(self.file.tags(), Some(&entry)),
+            pending: self.pending_changes(),
+        };
+
+        self.file.clear();
+        for state in &entry.tags {
+            self.file.insert_tag(state.to_tag());
+        }
+        self.apply_pending(entry.pending);
+        self.history.state = entry.state;
+
+        current
+    }
+
+    fn apply_pending(&mut self, pending: PendingChanges) {
+        if let Some(state) = self.ogg_pictures.as_mut() {
+            state.convert_pending = pending.convert_legacy_cover_art;
+        }
+        if let (Some(flac), Some(changes)) = (self.flac_blocks.as_mut(), pending.flac_blocks) {
+            flac.restore_changes(changes);
+        }
+        if let Some(header) = self.opus_header.as_mut() {
+            header.restore_changes(pending.opus_output_gain);
+        }
+        if let (Some(atoms), Some(changes)) = (self.itunes_atoms.as_mut(), pending.itunes_atoms) {
+            atoms.restore_changes(changes);
+        }
+    }
+
+    /// Take over the file as it was re-read after saving it in place
+    ///
+    /// Modifications made after the save started at `history_state` stay pending on top of it.
+    pub(crate) fn commit_save(&mut self, history_state: u32, saved: MusicFile) {
+        let modified = self.history.state != history_state;
+        let pending = self.pending_changes();
+
+        if modified {
+            self.original = Some(saved.file.tags().to_vec());
+        } else {
+            self.file = saved.file;
+            self.original = None;
+            self.pictures_skipped = saved.pictures_skipped;
+        }
+        self.ogg_pictures = saved.ogg_pictures;
+        self.flac_blocks = saved.flac_blocks;
+        self.opus_header = saved.opus_header;
+        self.itunes_atoms = saved.itunes_atoms;
+        self.layout = saved.layout;
+        self.cue_sheet_text = saved.cue_sheet_text;
+        if let (Some(opus), Some(saved)) = (
+            self.codec_info.as_mut().and_then(|info| info.opus.as_mut()),
+            saved.codec_info.and_then(|info| info.opus),
+        ) {
+            opus.output_gain = saved.output_gain;
+        }
+        if modified {
+            self.apply_pending(pending);
+        }
+    }
+
+    /// Identifier of the current tag state
+    pub(crate) fn history_state(&self) -> u32 {
+        self.history.state
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// Whether there is a modification that can be undone
+    #[napi(getter)]
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    /// Whether there is an undone modification that can be redone
+    #[napi(getter)]
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// Undo the last metadata modification
+    ///
+    /// Every setter call, {@link update} and {@link revert} is one step. Only the last 100 steps are kept.
+    /// Pending FLAC block, Opus output gain, MP4 iTunes atom and {@link convertLegacyCoverArt} changes are undone too.
+    ///
+    /// @returns `false` if there is nothing to undo
+    #[napi]
+    pub fn undo(&mut self) -> bool {
+        let Some(entry) = self.history.undo.pop() else {
+            return false;
+        };
+
+        let current = self.restore_entry(entry);
+        self.history.redo.push(current);
+        true
+    }
+
+    /// Redo the last undone metadata modification
+    ///
+    /// @returns `false` if there is nothing to redo
+    #[napi]
+    pub fn redo(&mut self) -> bool {
+        let Some(entry) = self.history.redo.pop() else {
+            return false;
+        };
+
+        let current = self.restore_entry(entry);
+        self.history.undo.push(current);
+        true
+    }
+
+    /// Mark the current metadata state, to return to it later with {@link restoreCheckpoint}
+    #[napi]
+    pub fn checkpoint(&self) -> u32 {
+        self.history.state
+    }
+
+    /// Undo or redo modifications until the metadata is back at a checkpoint
+    ///
+    /// @param checkpoint Value returned by {@link checkpoint}
+    ///
+    /// @throws If the checkpoint is no longer in the history, e.g. after new modifications were made on top of an undone state
+    #[napi]
+    pub fn restore_checkpoint(&mut self, checkpoint: u32) -> Result<()> {
+        if self
+            .history
+            .undo
+            .iter()
+            .any(|entry| entry.state == checkpoint)
+        {
+            while self.history.state != checkpoint {
+                self.undo();
+            }
+        } else if self
+            .history
+            .redo
+            .iter()
+            .any(|entry| entry.state == checkpoint)
+        {
+            while self.history.state != checkpoint {
+                self.redo();
+            }
+        } else if self.history.state != checkpoint {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("Checkpoint {checkpoint} is no longer in the history"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Drop all undo and redo steps, keeping the current metadata
+    #[napi]
+    pub fn clear_history(&mut self) {
+        self.history.undo.clear();
+        self.history.redo.clear();
+    }
+}
+
+#[cfg(test)]
+impl MusicFile {
+    /// Number of picture lists held by the undo and redo steps, shared lists are counted once
+    pub(crate) fn history_picture_copies(&self) -> usize {
+        let mut lists: Vec<*const [Picture]> = self
+            .history
+            .undo
+            .iter()
+            .chain(&self.history.redo)
+            .flat_map(|entry| &entry.tags)
+            .filter(|state| !state.pictures.is_empty())
+            .map(|state| Arc::as_ptr(&state.pictures))
+            .collect();
+        lists.sort();
+        lists.dedup();
+        lists.len()
+    }
+}
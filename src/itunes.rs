@@ -0,0 +1,214 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use lofty::{
+    config::{ParseOptions, WriteOptions},
+    error::FileEncodingError,
+    file::{AudioFile, TaggedFileExt},
+    io::FileLike,
+    mp4::{Atom, AtomData, AtomIdent, Ilst, Mp4File},
+    tag::{ItemKey, ItemValue, TagExt, TagItem, TagType},
+};
+use napi::{Error, Result, Status};
+
+use crate::music_file::MusicFile;
+
+/// Mean of the freeform atoms written by iTunes
+const ITUNES_MEAN: &str = "com.apple.iTunes";
+
+fn freeform_ident(name: &str) -> AtomIdent<'_> {
+    AtomIdent::Freeform {
+        mean: Cow::Borrowed(ITUNES_MEAN),
+        name: Cow::Borrowed(name),
+    }
+}
+
+/// Whether a comment item is an iTunes comment of an ID3v2 tag, like `iTunNORM`
+pub(crate) fn is_itunes_comment(item: &TagItem) -> bool {
+    item.key() == ItemKey::Comment && item.description().starts_with("iTun")
+}
+
+/// Freeform iTunes atoms of the loaded MP4 file, which lofty keeps out of the generic tag
+#[derive(Clone, Default)]
+pub(crate) struct ItunesAtoms {
+    loaded: BTreeMap<String, String>,
+    /// Atoms set or removed since loading
+    changes: BTreeMap<String, Option<String>>,
+}
+
+impl ItunesAtoms {
+    fn get(&self, name: &str) -> Option<&str> {
+        match self.changes.get(name) {
+            Some(value) => value.as_deref(),
+            None => self.loaded.get(name).map(String::as_str),
+        }
+    }
+
+    fn set(&mut self, name: &str, value: Option<String>) {
+        if self.loaded.get(name) == value.as_ref() {
+            self.changes.remove(name);
+        } else {
+            self.changes.insert(name.to_owned(), value);
+        }
+    }
+
+    /// Whether an atom was changed since loading
+    pub(crate) fn is_modified(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    /// Discard the atom changes
+    pub(crate) fn revert(&mut self) {
+        self.changes.clear();
+    }
+
+    pub(crate) fn changes(&self) -> BTreeMap<String, Option<String>> {
+        self.changes.clone()
+    }
+
+    pub(crate) fn restore_changes(&mut self, changes: BTreeMap<String, Option<String>>) {
+        self.changes = changes;
+    }
+
+    /// Atoms to write, with `None` for the removed ones
+    fn effective(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        let kept = self
+            .loaded
+            .iter()
+            .filter(|(name, _)| !self.changes.contains_key(*name))
+            .map(|(name, value)| (name.as_str(), Some(value.as_str())));
+        let changed = self
+            .changes
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_deref()));
+        kept.chain(changed)
+    }
+
+    /// Whether there is nothing to write
+    pub(crate) fn is_empty(&self) -> bool {
+        self.loaded.is_empty() && self.changes.is_empty()
+    }
+
+    /// Names of the atoms without an item key, which lofty keeps out of the generic tag
+    pub(crate) fn unmapped_names(&self) -> impl Iterator<Item = &str> {
+        self.effective()
+            .filter(|(_, value)| value.is_some())
+            .map(|(name, _)| name)
+            .filter(|name| !is_mapped(name))
+    }
+
+    /// Atoms to write back after lofty, with `None` for the removed ones
+    ///
+    /// Loaded atoms with an item key are written by lofty from the generic tag, writing the loaded
+    /// value back would undo edits made through the setters.
+    fn to_write(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.effective()
+            .filter(|(name, _)| self.changes.contains_key(*name) || !is_mapped(name))
+    }
+}
+
+/// Whether lofty maps a freeform iTunes atom to an item key of the generic tag
+fn is_mapped(name: &str) -> bool {
+    ItemKey::from_key(TagType::Mp4Ilst, &format!("----:{ITUNES_MEAN}:{name}")).is_some()
+}
+
+/// Collect the freeform iTunes atoms of an MP4 file, before it is converted to a generic file
+pub(crate) fn load_itunes_atoms(file: &Mp4File) -> ItunesAtoms {
+    let loaded = file
+        .ilst()
+        .into_iter()
+        .flatten()
+        .filter_map(|atom| {
+            let AtomIdent::Freeform { mean, name } = atom.ident() else {
+                return None;
+            };
+            let text = atom.data().find_map(|data| match data {
+                AtomData::UTF8(text) | AtomData::UTF16(text) => Some(text.clone()),
+                _ => None,
+            })?;
+            (mean == ITUNES_MEAN).then(|| (name.to_string(), text))
+        })
+        .collect();
+
+    ItunesAtoms {
+        loaded,
+        changes: BTreeMap::new(),
+    }
+}
+
+/// Write the atoms into an MP4 file that was just written by lofty
+///
+/// lofty rebuilds `ilst` from the generic tag, dropping the freeform atoms it doesn't map, so the
+/// loaded unmapped atoms are written back along with the changes.
+pub(crate) fn write_itunes_atoms<F: FileLike>(
+    target: &mut F,
+    atoms: &ItunesAtoms,
+) -> std::result::Result<(), FileEncodingError> {
+    target.rewind()?;
+    let mut file = Mp4File::read_from(target, ParseOptions::new().read_properties(false))?;
+    let mut ilst = file.remove_ilst().unwrap_or_else(Ilst::new);
+
+    for (name, value) in atoms.to_write() {
+        let ident = freeform_ident(name);
+        ilst.remove(&ident).for_each(drop);
+        if let Some(value) = value {
+            ilst.insert(Atom::new(
+                ident.into_owned(),
+                AtomData::UTF8(value.to_owned()),
+            ));
+        }
+    }
+
+    target.rewind()?;
+    ilst.save_to(target, WriteOptions::default())
+}
+
+impl MusicFile {
+    /// Whether iTunes comments are stored in a freeform MP4 atom or an ID3v2 `COMM` frame
+    pub(crate) fn supports_itunes_comments(&self) -> bool {
+        self.itunes_atoms.is_some() || self.file.primary_tag_type() == TagType::Id3v2
+    }
+
+    /// Text of an iTunes comment, like `iTunNORM`
+    pub(crate) fn itunes_comment(&self, name: &str) -> Option<String> {
+        if let Some(atoms) = &self.itunes_atoms {
+            return atoms.get(name).map(str::to_owned);
+        }
+        if self.file.primary_tag_type() != TagType::Id3v2 {
+            return None;
+        }
+
+        self.tag(|tag| {
+            tag.get_items(ItemKey::Comment)
+                .find(|item| item.description() == name)
+                .and_then(|item| item.value().text())
+                .map(str::to_owned)
+        })
+    }
+
+    /// Set or remove an iTunes comment, written on the next save
+    pub(crate) fn set_itunes_comment(&mut self, name: &str, value: Option<String>) -> Result<()> {
+        if self.itunes_atoms.is_some() {
+            self.record_history();
+            if let Some(atoms) = &mut self.itunes_atoms {
+                atoms.set(name, value);
+            }
+            return Ok(());
+        }
+        if self.file.primary_tag_type() != TagType::Id3v2 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("{name} is only supported in MP4 files and ID3v2 tags"),
+            ));
+        }
+
+        self.tag_mut(|tag| {
+            tag.retain(|item| !(item.key() == ItemKey::Comment && item.description() == name));
+            if let Some(value) = value {
+                let mut item = TagItem::new(ItemKey::Comment, ItemValue::Text(value));
+                item.set_description(name.to_owned());
+                item.set_lang(*b"eng");
+                tag.push(item);
+            }
+        })
+    }
+}
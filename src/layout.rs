@@ -0,0 +1,555 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use lofty::file::{FileType, TaggedFile as LoftyTaggedFile, TaggedFileExt};
+use napi_derive::napi;
+
+use crate::{
+    music_file::{
+        flac_blocks::{metadata_block_kind, FlacBlockChain, BLOCK_HEADER_SIZE},
+        ogg_pictures::{comment_fields, OggHeaders, OggPictureState, BLOCK_PICTURE_KEY},
+        MusicFile,
+    },
+    utils::skip_id3v2,
+};
+
+const ID3V1_SIZE: u64 = 128;
+const APE_FOOTER_SIZE: u64 = 32;
+const APE_HAS_HEADER_FLAG: u32 = 1 << 31;
+/// `LYRICS200` marker and the 6 digit size before it
+const LYRICS3_FOOTER_SIZE: u64 = 15;
+/// Prefix of APE item keys holding pictures, e.g. `Cover Art (Front)`
+const APE_PICTURE_KEY_PREFIX: &[u8] = b"Cover Art";
+/// Chunks of AIFF text fields
+const AIFF_TEXT_CHUNKS: [&[u8; 4]; 5] = [b"NAME", b"AUTH", b"(c) ", b"ANNO", b"COMT"];
+
+/// A metadata block of the loaded file
+#[napi(object)]
+#[derive(Clone)]
+pub struct MetadataBlock {
+    #[napi(
+        ts_type = r#""ID3v2" | "ID3v1" | "APE" | "Lyrics3" | "VorbisComment" | "Picture" | "Padding" | "Application" | "CueSheet" | "Ilst" | "RiffInfo" | "AiffText""#
+    )]
+    pub kind: String,
+    /// Offset from the start of the file in bytes
+    pub offset: f64,
+    /// Size in bytes, including the block header
+    pub size: f64,
+}
+
+/// Position of the audio stream and the metadata in the loaded file
+#[napi(object)]
+#[derive(Clone)]
+pub struct AudioLayout {
+    pub file_size: f64,
+    /// Offset of the audio stream from the start of the file in bytes
+    pub audio_offset: f64,
+    /// Size of the audio stream in bytes
+    pub audio_size: f64,
+    /// Tags, pictures and padding, in file order
+    pub blocks: Vec<MetadataBlock>,
+    /// Total size of the metadata blocks in bytes
+    pub metadata_size: f64,
+    /// Size of the embedded picture data in bytes, `0` when loaded with `skipPictures`
+    pub picture_size: f64,
+    /// Bitrate of the audio stream alone in kbps, `null` if the duration is unknown
+    pub audio_bit_rate: Option<u32>,
+}
+
+/// Blocks and audio stream bounds collected while reading a file
+#[derive(Default)]
+struct LayoutBuilder {
+    blocks: Vec<(&'static str, u64, u64)>,
+    audio: Option<(u64, u64)>,
+}
+
+impl LayoutBuilder {
+    fn block(&mut self, kind: &'static str, offset: u64, size: u64) {
+        self.blocks.push((kind, offset, size));
+    }
+
+    /// Add the audio stream, summing the size of several parts
+    fn audio(&mut self, offset: u64, size: u64) {
+        self.audio = Some(match self.audio {
+            Some((first, total)) => (first, total + size),
+            None => (offset, size),
+        });
+    }
+}
+
+fn read_at<const N: usize>(reader: &mut (impl Read + Seek), offset: u64) -> Option<[u8; N]> {
+    reader.seek(SeekFrom::Start(offset)).ok()?;
+    let mut data = [0; N];
+    reader.read_exact(&mut data).ok()?;
+    Some(data)
+}
+
+/// Add an ID3v2 tag at the start of the file, returning the offset after it
+fn leading_id3v2(reader: &mut (impl Read + Seek), layout: &mut LayoutBuilder) -> Option<u64> {
+    let start = skip_id3v2(reader).ok()?;
+    if start > 0 {
+        layout.block("ID3v2", 0, start);
+    }
+    Some(start)
+}
+
+/// Add the ID3v1, APE and Lyrics3 tags at the end of the file, returning the offset before them
+fn trailing_tags(reader: &mut (impl Read + Seek), mut end: u64, layout: &mut LayoutBuilder) -> u64 {
+    loop {
+        if end >= ID3V1_SIZE && read_at::<3>(reader, end - ID3V1_SIZE).as_ref() == Some(b"TAG") {
+            end -= ID3V1_SIZE;
+            layout.block("ID3v1", end, ID3V1_SIZE);
+            continue;
+        }
+
+        if end >= APE_FOOTER_SIZE {
+            if let Some(footer) = read_at::<32>(reader, end - APE_FOOTER_SIZE) {
+                if footer.starts_with(b"APETAGEX") {
+                    let size = u64::from(u32::from_le_bytes(footer[12..16].try_into().unwrap()));
+                    let flags = u32::from_le_bytes(footer[20..24].try_into().unwrap());
+                    let header = if flags & APE_HAS_HEADER_FLAG != 0 {
+                        APE_FOOTER_SIZE
+                    } else {
+                        0
+                    };
+                    let Some(start) = end.checked_sub(size + header) else {
+                        break;
+                    };
+                    layout.block("APE", start, end - start);
+                    end = start;
+                    continue;
+                }
+            }
+        }
+
+        if end >= LYRICS3_FOOTER_SIZE {
+            if let Some(footer) = read_at::<15>(reader, end - LYRICS3_FOOTER_SIZE) {
+                let size = std::str::from_utf8(&footer[..6])
+                    .ok()
+                    .and_then(|size| size.parse::<u64>().ok());
+                if let (b"LYRICS200", Some(size)) = (&footer[6..], size) {
+                    let Some(start) = end.checked_sub(size + LYRICS3_FOOTER_SIZE) else {
+                        break;
+                    };
+                    layout.block("Lyrics3", start, end - start);
+                    end = start;
+                    continue;
+                }
+            }
+        }
+
+        break;
+    }
+
+    end
+}
+
+/// Audio between an optional ID3v2 tag and the trailing tags, e.g. MP3, AAC or WavPack
+fn read_tagged_stream(
+    reader: &mut (impl Read + Seek),
+    file_size: u64,
+    layout: &mut LayoutBuilder,
+) -> Option<()> {
+    let start = leading_id3v2(reader, layout)?;
+    let end = trailing_tags(reader, file_size, layout).max(start);
+    layout.audio(start, end - start);
+    Some(())
+}
+
+fn read_flac(
+    reader: &mut (impl Read + Seek),
+    file_size: u64,
+    chain: &FlacBlockChain,
+    layout: &mut LayoutBuilder,
+) -> Option<()> {
+    leading_id3v2(reader, layout)?;
+
+    let mut offset = chain.start;
+    for block in &chain.blocks {
+        let size = (BLOCK_HEADER_SIZE + block.len) as u64;
+        if let Some(kind) = metadata_block_kind(block.ty) {
+            layout.block(kind, offset, size);
+        }
+        offset += size;
+    }
+
+    let end = chain.end;
+    let audio_end = trailing_tags(reader, file_size, layout).max(end);
+    layout.audio(end, audio_end - end);
+    Some(())
+}
+
+/// Read the header of an MP4 atom at `offset`, returning its name, header size and total size
+///
+/// `None` if the atom is smaller than its header or doesn't end before `end`, so the size is never
+/// `0` and walking the atoms always moves forward.
+fn mp4_atom(reader: &mut (impl Read + Seek), offset: u64, end: u64) -> Option<([u8; 4], u64, u64)> {
+    let header = read_at::<8>(reader, offset)?;
+    let name = header[4..8].try_into().ok()?;
+    let (header_size, size) = match u32::from_be_bytes(header[..4].try_into().ok()?) {
+        // Extends to the end of the file
+        0 => (8, end.checked_sub(offset)?),
+        1 => (16, u64::from_be_bytes(read_at::<8>(reader, offset + 8)?)),
+        size => (8, u64::from(size)),
+    };
+    let atom_end = offset.checked_add(size)?;
+    (size >= header_size && atom_end <= end).then_some((name, header_size, size))
+}
+
+/// Whether an atom header fits between `offset` and `end`
+fn has_atom_header(offset: u64, end: u64) -> bool {
+    offset
+        .checked_add(8)
+        .is_some_and(|header_end| header_end <= end)
+}
+
+fn read_mp4_atoms(
+    reader: &mut (impl Read + Seek),
+    mut offset: u64,
+    end: u64,
+    depth: usize,
+    layout: &mut LayoutBuilder,
+) -> Option<()> {
+    while has_atom_header(offset, end) {
+        let (name, header_size, size) = mp4_atom(reader, offset, end)?;
+        match (&name, depth) {
+            (b"mdat", 0) => layout.audio(offset + header_size, size - header_size),
+            (b"moov", 0) | (b"udta", 1) => read_mp4_atoms(
+                reader,
+                offset + header_size,
+                offset + size,
+                depth + 1,
+                layout,
+            )?,
+            // The `meta` atom has 4 bytes of version and flags before its children
+            (b"meta", 2) => read_mp4_atoms(
+                reader,
+                offset + header_size + 4,
+                offset + size,
+                depth + 1,
+                layout,
+            )?,
+            (b"ilst", 3) => layout.block("Ilst", offset, size),
+            (b"free", _) => layout.block("Padding", offset, size),
+            _ => {}
+        }
+        offset += size;
+    }
+    Some(())
+}
+
+/// Read the chunks of a RIFF (WAV) or FORM (AIFF) file
+fn read_iff_chunks(
+    reader: &mut (impl Read + Seek),
+    file_size: u64,
+    big_endian: bool,
+    layout: &mut LayoutBuilder,
+) -> Option<()> {
+    let mut offset = 12;
+    while offset + 8 <= file_size {
+        let header = read_at::<8>(reader, offset)?;
+        let size = header[4..8].try_into().ok()?;
+        let size = u64::from(if big_endian {
+            u32::from_be_bytes(size)
+        } else {
+            u32::from_le_bytes(size)
+        });
+        let content = offset + 8;
+        // Chunks are padded to an even size
+        let total = (8 + size + (size & 1)).min(file_size - offset);
+
+        let id: &[u8; 4] = header[..4].try_into().ok()?;
+        match id {
+            b"data" if !big_endian => layout.audio(content, size.min(file_size - content)),
+            // Sound data is preceded by its offset and block size
+            b"SSND" if big_endian => layout.audio(content + 8, size.saturating_sub(8)),
+            b"id3 " | b"ID3 " => layout.block("ID3v2", offset, total),
+            b"LIST" if read_at::<4>(reader, content).as_ref() == Some(b"INFO") => {
+                layout.block("RiffInfo", offset, total)
+            }
+            _ if big_endian && AIFF_TEXT_CHUNKS.contains(&id) => {
+                layout.block("AiffText", offset, total)
+            }
+            _ => {}
+        }
+        offset += total;
+    }
+    Some(())
+}
+
+fn read_ogg(headers: &OggHeaders, file_size: u64, layout: &mut LayoutBuilder) {
+    let (comment_start, audio_start) = (headers.comment_start, headers.audio_start);
+    if audio_start > comment_start {
+        layout.block("VorbisComment", comment_start, audio_start - comment_start);
+    }
+    layout.audio(audio_start, file_size.saturating_sub(audio_start));
+}
+
+/// Locate the audio stream and the metadata blocks of a file, `None` if the structure cannot be read
+///
+/// FLAC blocks and Ogg headers are taken from the structures read by the other loaders.
+pub(crate) fn load_layout(
+    file: &LoftyTaggedFile,
+    reader: &mut (impl Read + Seek),
+    flac: Option<&FlacBlockChain>,
+    ogg: Option<&OggHeaders>,
+) -> Option<AudioLayout> {
+    let file_size = reader.seek(SeekFrom::End(0)).ok()?;
+    let mut layout = LayoutBuilder::default();
+    match file.file_type() {
+        FileType::Flac => read_flac(reader, file_size, flac?, &mut layout)?,
+        FileType::Mp4 => read_mp4_atoms(reader, 0, file_size, 0, &mut layout)?,
+        FileType::Wav => read_iff_chunks(reader, file_size, false, &mut layout)?,
+        FileType::Aiff => read_iff_chunks(reader, file_size, true, &mut layout)?,
+        FileType::Opus | FileType::Vorbis | FileType::Speex => {
+            read_ogg(ogg?, file_size, &mut layout)
+        }
+        _ => read_tagged_stream(reader, file_size, &mut layout)?,
+    }
+
+    let (audio_offset, audio_size) = layout.audio?;
+    layout.blocks.sort_by_key(|(_, offset, _)| *offset);
+    let metadata_size = layout.blocks.iter().map(|(_, _, size)| size).sum::<u64>();
+    let picture_size = file
+        .tags()
+        .iter()
+        .flat_map(|tag| tag.pictures())
+        .map(|picture| picture.data().len() as u64)
+        .sum::<u64>();
+
+    Some(AudioLayout {
+        file_size: file_size as f64,
+        audio_offset: audio_offset as f64,
+        audio_size: audio_size as f64,
+        blocks: layout
+            .blocks
+            .into_iter()
+            .map(|(kind, offset, size)| MetadataBlock {
+                kind: kind.to_owned(),
+                offset: offset as f64,
+                size: size as f64,
+            })
+            .collect(),
+        metadata_size: metadata_size as f64,
+        picture_size: picture_size as f64,
+        audio_bit_rate: None,
+    })
+}
+
+/// Header of an ID3v2 frame
+pub(crate) struct Id3v2Frame {
+    /// 3 characters in ID3v2.2, 4 in later versions
+    pub(crate) id: Vec<u8>,
+    /// Compression, encryption and unsynchronisation flags of ID3v2.3 and ID3v2.4 frames
+    pub(crate) format_flags: u8,
+    /// Offset of the frame content
+    pub(crate) offset: u64,
+    pub(crate) size: u64,
+}
+
+/// Walk the frame headers of the ID3v2 tag at `offset`, returning the version and the frames
+///
+/// `None` for tags whose frames cannot be walked.
+pub(crate) fn id3v2_frames(
+    reader: &mut (impl Read + Seek),
+    offset: u64,
+    end: u64,
+) -> Option<(u8, Vec<Id3v2Frame>)> {
+    let header = read_at::<10>(reader, offset)?;
+    let version = header[3];
+    let flags = header[5];
+    // Unsynchronised tags and extended headers are rare, don't try to walk them
+    if !header.starts_with(b"ID3") || !(2..=4).contains(&version) || flags & 0xC0 != 0 {
+        return None;
+    }
+
+    let (id_size, header_size) = if version == 2 { (3, 6) } else { (4, 10) };
+    let mut frames = Vec::new();
+    let mut frame = offset + 10;
+    while frame + header_size <= end {
+        let frame_header = read_at::<10>(reader, frame)?;
+        let id = &frame_header[..id_size];
+        if id[0] == 0 {
+            // Padding
+            break;
+        }
+
+        let size = match version {
+            2 => frame_header[3..6]
+                .iter()
+                .fold(0u64, |size, byte| (size << 8) | u64::from(*byte)),
+            3 => u64::from(u32::from_be_bytes(frame_header[4..8].try_into().unwrap())),
+            _ => frame_header[4..8]
+                .iter()
+                .fold(0u64, |size, byte| (size << 7) | u64::from(byte & 0x7F)),
+        };
+        frames.push(Id3v2Frame {
+            id: id.to_vec(),
+            format_flags: if version == 2 { 0 } else { frame_header[9] },
+            offset: frame + header_size,
+            size,
+        });
+        frame += header_size + size;
+    }
+    Some((version, frames))
+}
+
+/// Offset of the ID3v2 tag of a layout block at `offset`
+pub(crate) fn id3v2_tag_offset(file_type: FileType, offset: u64) -> u64 {
+    match file_type {
+        // The tag follows the chunk header
+        FileType::Wav | FileType::Aiff => offset + 8,
+        _ => offset,
+    }
+}
+
+/// Whether the ID3v2 tag at `offset` has an `APIC` frame
+///
+/// Only the frame headers are read. Returns `true` for tags whose frames cannot be walked.
+fn id3v2_has_pictures(reader: &mut (impl Read + Seek), offset: u64, end: u64) -> bool {
+    id3v2_frames(reader, offset, end).is_none_or(|(version, frames)| {
+        let picture_id: &[u8] = if version == 2 { b"PIC" } else { b"APIC" };
+        frames.iter().any(|frame| frame.id == picture_id)
+    })
+}
+
+/// Header of an APE tag item
+pub(crate) struct ApeItem {
+    pub(crate) key: Vec<u8>,
+    pub(crate) flags: u32,
+    /// Offset of the item value
+    pub(crate) offset: u64,
+    pub(crate) size: u64,
+}
+
+/// Walk the item headers of the APE tag ending at `end`
+///
+/// `None` for tags whose items cannot be walked.
+pub(crate) fn ape_items(reader: &mut (impl Read + Seek), end: u64) -> Option<Vec<ApeItem>> {
+    let footer = read_at::<32>(reader, end.checked_sub(APE_FOOTER_SIZE)?)?;
+    let size = u64::from(u32::from_le_bytes(footer[12..16].try_into().unwrap()));
+    let count = u32::from_le_bytes(footer[16..20].try_into().unwrap());
+    let mut item = end.checked_sub(size)?;
+
+    let items_end = end - APE_FOOTER_SIZE;
+    let mut items = Vec::new();
+    for _ in 0..count {
+        let item_header = read_at::<8>(reader, item)?;
+        let value_size = u64::from(u32::from_le_bytes(item_header[..4].try_into().unwrap()));
+        let flags = u32::from_le_bytes(item_header[4..].try_into().unwrap());
+        // Keys are at most 255 ASCII characters, followed by a NUL byte
+        let mut key = Vec::new();
+        reader.by_ref().take(256).read_to_end(&mut key).ok()?;
+        let key_len = key.iter().position(|byte| *byte == 0)?;
+        key.truncate(key_len);
+
+        let offset = item + 8 + key_len as u64 + 1;
+        item = offset + value_size;
+        if item > items_end {
+            return None;
+        }
+        items.push(ApeItem {
+            key,
+            flags,
+            offset,
+            size: value_size,
+        });
+    }
+    Some(items)
+}
+
+/// Whether the APE tag ending at `end` has a cover art item
+///
+/// Only the item keys are read. Returns `true` for tags whose items cannot be walked.
+fn ape_has_pictures(reader: &mut (impl Read + Seek), end: u64) -> bool {
+    ape_items(reader, end).is_none_or(|items| {
+        items
+            .iter()
+            .any(|item| item.key.starts_with(APE_PICTURE_KEY_PREFIX))
+    })
+}
+
+/// Whether the MP4 `ilst` atom at `offset` has a `covr` atom
+fn ilst_has_pictures(reader: &mut (impl Read + Seek), offset: u64, end: u64) -> bool {
+    let mut child = offset + 8;
+    while has_atom_header(child, end) {
+        let Some((name, _, size)) = mp4_atom(reader, child, end) else {
+            return true;
+        };
+        if &name == b"covr" {
+            return true;
+        }
+        child += size;
+    }
+    false
+}
+
+/// Whether the content of a FLAC VORBIS_COMMENT block has a `METADATA_BLOCK_PICTURE` field
+fn flac_comments_have_pictures(content: &[u8]) -> bool {
+    comment_fields(FileType::Flac, content).is_none_or(|fields| {
+        fields
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case(BLOCK_PICTURE_KEY))
+    })
+}
+
+/// Whether the loaded file has embedded pictures, without reading the image data
+///
+/// Used for files loaded with `skipPictures`. Returns `true` when the structure of the file or
+/// of one of its tags is not known well enough to tell.
+pub(crate) fn has_embedded_pictures(
+    reader: &mut (impl Read + Seek),
+    file_type: FileType,
+    layout: Option<&AudioLayout>,
+    flac: Option<&FlacBlockChain>,
+    ogg_pictures: Option<&OggPictureState>,
+) -> bool {
+    if let Some(state) = ogg_pictures {
+        return state.has_pictures();
+    }
+    let Some(layout) = layout else {
+        return true;
+    };
+
+    layout.blocks.iter().any(|block| {
+        let offset = block.offset as u64;
+        let end = offset + block.size as u64;
+        match block.kind.as_str() {
+            "Picture" => true,
+            "ID3v2" => id3v2_has_pictures(reader, id3v2_tag_offset(file_type, offset), end),
+            "APE" => ape_has_pictures(reader, end),
+            "Ilst" => ilst_has_pictures(reader, offset, end),
+            "VorbisComment" if file_type == FileType::Flac => flac
+                .and_then(FlacBlockChain::comments)
+                .is_none_or(flac_comments_have_pictures),
+            // Ogg comments are covered by the picture state
+            "VorbisComment" => true,
+            _ => false,
+        }
+    })
+}
+
+impl MusicFile {
+    /// Size of the audio stream of the loaded file in bytes, without tags and pictures
+    pub(crate) fn audio_stream_size(&self) -> Option<f64> {
+        self.layout.as_ref().map(|layout| layout.audio_size)
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// Position of the audio stream and the metadata blocks in the loaded file, or `null` if the file
+    /// structure could not be read
+    ///
+    /// Describes the file as it was loaded or last saved to its path, saving to a buffer or another
+    /// path doesn't update it.
+    /// For Ogg files, the comment block covers the header pages after the identification header.
+    #[napi(getter)]
+    pub fn audio_layout(&self) -> Option<AudioLayout> {
+        let mut layout = self.layout.clone()?;
+        let duration = self.duration();
+        if duration > 0.0 {
+            layout.audio_bit_rate = Some((layout.audio_size * 8.0 / duration).round() as u32);
+        }
+        Some(layout)
+    }
+}
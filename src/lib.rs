@@ -1,5 +1,7 @@
+mod artwork;
 mod meta_picture;
 mod music_file;
+mod scan;
 mod utils;
 
 #[cfg(test)]
@@ -0,0 +1,751 @@
+use std::{
+    collections::VecDeque,
+    f64::consts::PI,
+    io::Cursor,
+    sync::{Arc, Mutex},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs::File, path::Path};
+
+use lofty::file::TaggedFileExt;
+use napi::{
+    bindgen_prelude::{PromiseRaw, Reference, ToNapiValue, Uint8Array},
+    Either, Env, Error, Result, Status, Task,
+};
+use napi_derive::napi;
+use symphonia::core::{
+    audio::{Channels, SampleBuffer},
+    codecs::{DecoderOptions, CODEC_TYPE_NULL},
+    errors::Error as SymphoniaError,
+    formats::FormatOptions,
+    io::{MediaSource, MediaSourceStream},
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::music_file::path_error;
+use crate::{
+    music_file::{MusicFile, MusicFileInner, ERR_FILE_LOADED_FROM_BUFFER},
+    utils::file_type_extension,
+};
+
+/// ReplayGain 2.0 reference level in LUFS
+const DEFAULT_REFERENCE_LOUDNESS: f64 = -18.0;
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+/// Gating blocks are 400 ms long and overlap by 75%
+const SUB_BLOCKS_PER_BLOCK: usize = 4;
+const SUB_BLOCK_SECONDS: f64 = 0.1;
+/// Taps of the true peak interpolation filter, per oversampling phase
+const TRUE_PEAK_TAPS: usize = 12;
+
+/// Options of {@link MusicFile.analyzeLoudness} and {@link analyzeAlbumLoudness}
+#[napi(object)]
+#[derive(Default)]
+pub struct LoudnessOptions {
+    /// Target loudness in LUFS the gain is computed against, defaults to `-18` (ReplayGain 2.0)
+    pub reference_loudness: Option<f64>,
+    /// Measure the true peak by 4x oversampling, defaults to `true`
+    pub true_peak: Option<bool>,
+    /// Write the ReplayGain gain and peak tags, defaults to `false`
+    ///
+    /// The sample peak is written, as ReplayGain players expect.
+    pub write: Option<bool>,
+    /// With `write`, also write the iTunes Sound Check from the track gain, defaults to `false`
+    ///
+    /// Skipped for files whose tag is neither MP4 nor ID3v2, see {@link MusicFile.soundCheck}.
+    pub sound_check: Option<bool>,
+}
+
+/// Loudness of a track or an album
+#[napi(object)]
+pub struct LoudnessResult {
+    /// Integrated loudness in LUFS, or `null` if the audio is silent
+    pub loudness: Option<f64>,
+    /// Gain in dB to reach the reference loudness, or `null` if the audio is silent
+    pub gain: Option<f64>,
+    /// Sample peak, where `1.0` is full scale
+    pub peak: f64,
+    /// True peak, where `1.0` is full scale, or `null` if not measured
+    pub true_peak: Option<f64>,
+}
+
+/// Result of {@link analyzeAlbumLoudness}
+#[napi(object)]
+pub struct AlbumLoudnessResult {
+    /// Loudness of each file, in the order given
+    pub tracks: Vec<LoudnessResult>,
+    /// Loudness of all files together
+    pub album: LoudnessResult,
+}
+
+/// Gating block energies and peaks of decoded audio
+pub struct Measurement {
+    blocks: Vec<f64>,
+    peak: f64,
+    true_peak: Option<f64>,
+}
+
+impl Measurement {
+    fn result(&self, reference: f64) -> LoudnessResult {
+        let loudness = integrated_loudness(&self.blocks);
+        LoudnessResult {
+            loudness,
+            gain: loudness.map(|loudness| reference - loudness),
+            peak: self.peak,
+            true_peak: self.true_peak,
+        }
+    }
+}
+
+fn energy_to_loudness(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+/// Gated loudness of block energies as in ITU-R BS.1770-4
+fn integrated_loudness(blocks: &[f64]) -> Option<f64> {
+    let gated_mean = |threshold: f64| {
+        let (sum, count) = blocks
+            .iter()
+            .filter(|&&energy| energy > 0.0 && energy_to_loudness(energy) > threshold)
+            .fold((0.0, 0usize), |(sum, count), energy| {
+                (sum + energy, count + 1)
+            });
+        (count > 0).then(|| sum / count as f64)
+    };
+
+    let relative = energy_to_loudness(gated_mean(ABSOLUTE_GATE)?) + RELATIVE_GATE;
+    gated_mean(relative.max(ABSOLUTE_GATE)).map(energy_to_loudness)
+}
+
+/// Second order IIR filter in transposed direct form II
+#[derive(Clone)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// K-weighting filters for a sample rate, as derived by libebur128
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let rate = f64::from(sample_rate);
+
+    let (f0, gain, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    [shelf, high_pass]
+}
+
+/// Oversampling factor to measure the true peak at, targeting at least 192 kHz
+fn oversampling_factor(sample_rate: u32) -> usize {
+    match sample_rate {
+        0..96_000 => 4,
+        96_000..192_000 => 2,
+        _ => 1,
+    }
+}
+
+/// Polyphase windowed sinc interpolation filter, `phases[p][k]` applies to the `k`-th previous sample
+fn interpolation_filter(factor: usize) -> Vec<Vec<f64>> {
+    let length = TRUE_PEAK_TAPS * factor;
+    let center = (length - 1) as f64 / 2.0;
+    let taps: Vec<f64> = (0..length)
+        .map(|i| {
+            let x = (i as f64 - center) / factor as f64;
+            let sinc = if x == 0.0 {
+                1.0
+            } else {
+                (PI * x).sin() / (PI * x)
+            };
+            let window = 0.5 - 0.5 * (2.0 * PI * (i as f64 + 0.5) / length as f64).cos();
+            sinc * window
+        })
+        .collect();
+
+    (0..factor)
+        .map(|phase| {
+            let phase_taps: Vec<f64> = (0..TRUE_PEAK_TAPS)
+                .map(|k| taps[k * factor + phase])
+                .collect();
+            // Unity gain at DC for every phase
+            let sum: f64 = phase_taps.iter().sum();
+            phase_taps.into_iter().map(|tap| tap / sum).collect()
+        })
+        .collect()
+}
+
+struct ChannelState {
+    weight: f64,
+    filters: [Biquad; 2],
+    /// Sum of squared K-weighted samples in the current sub-block
+    square_sum: f64,
+    /// Last input samples for the true peak filter, newest first
+    history: VecDeque<f64>,
+}
+
+/// BS.1770 loudness meter fed with interleaved samples
+struct LoudnessMeter {
+    channels: Vec<ChannelState>,
+    sub_block_length: usize,
+    sub_block_position: usize,
+    /// Weighted energy sums of the last sub-blocks
+    sub_blocks: VecDeque<f64>,
+    blocks: Vec<f64>,
+    peak: f64,
+    true_peak: Option<(Vec<Vec<f64>>, f64)>,
+}
+
+impl LoudnessMeter {
+    fn new(sample_rate: u32, channels: Channels, true_peak: bool) -> Self {
+        let count = channels.count().max(1);
+        let weights: Vec<f64> = if channels.count() == 1 {
+            // ReplayGain 2.0 measures mono as played on both speakers
+            vec![2.0]
+        } else if channels.is_empty() {
+            vec![1.0; count]
+        } else {
+            channels.iter().map(channel_weight).collect()
+        };
+
+        let factor = oversampling_factor(sample_rate);
+        LoudnessMeter {
+            channels: weights
+                .into_iter()
+                .map(|weight| ChannelState {
+                    weight,
+                    filters: k_weighting(sample_rate),
+                    square_sum: 0.0,
+                    history: VecDeque::from(vec![0.0; TRUE_PEAK_TAPS]),
+                })
+                .collect(),
+            sub_block_length: ((f64::from(sample_rate) * SUB_BLOCK_SECONDS).round() as usize)
+                .max(1),
+            sub_block_position: 0,
+            sub_blocks: VecDeque::with_capacity(SUB_BLOCKS_PER_BLOCK),
+            blocks: Vec::new(),
+            peak: 0.0,
+            true_peak: true_peak.then(|| (interpolation_filter(factor), 0.0)),
+        }
+    }
+
+    fn process(&mut self, samples: &[f32]) {
+        let channel_count = self.channels.len();
+        for frame in samples.chunks_exact(channel_count) {
+            for (channel, &sample) in self.channels.iter_mut().zip(frame) {
+                let sample = f64::from(sample);
+                self.peak = self.peak.max(sample.abs());
+
+                if let Some((phases, true_peak)) = &mut self.true_peak {
+                    channel.history.pop_back();
+                    channel.history.push_front(sample);
+                    for phase in phases.iter() {
+                        let value: f64 = phase
+                            .iter()
+                            .zip(&channel.history)
+                            .map(|(tap, sample)| tap * sample)
+                            .sum();
+                        *true_peak = true_peak.max(value.abs());
+                    }
+                }
+
+                let filtered = channel
+                    .filters
+                    .iter_mut()
+                    .fold(sample, |value, filter| filter.process(value));
+                channel.square_sum += filtered * filtered;
+            }
+
+            self.sub_block_position += 1;
+            if self.sub_block_position == self.sub_block_length {
+                self.finish_sub_block();
+            }
+        }
+    }
+
+    fn finish_sub_block(&mut self) {
+        let energy = self.channels.iter_mut().fold(0.0, |energy, channel| {
+            let sum = channel.square_sum;
+            channel.square_sum = 0.0;
+            energy + channel.weight * sum
+        });
+        self.sub_block_position = 0;
+
+        if self.sub_blocks.len() == SUB_BLOCKS_PER_BLOCK {
+            self.sub_blocks.pop_front();
+        }
+        self.sub_blocks.push_back(energy);
+        if self.sub_blocks.len() == SUB_BLOCKS_PER_BLOCK {
+            let sum: f64 = self.sub_blocks.iter().sum();
+            self.blocks
+                .push(sum / (SUB_BLOCKS_PER_BLOCK * self.sub_block_length) as f64);
+        }
+    }
+
+    fn finish(self) -> Measurement {
+        Measurement {
+            blocks: self.blocks,
+            // The true peak is never below the sample peak
+            true_peak: self
+                .true_peak
+                .map(|(_, true_peak)| true_peak.max(self.peak)),
+            peak: self.peak,
+        }
+    }
+}
+
+/// Channel weight of ITU-R BS.1770, surround channels are louder and LFE is excluded
+fn channel_weight(channel: Channels) -> f64 {
+    if channel.intersects(Channels::LFE1 | Channels::LFE2) {
+        0.0
+    } else if channel.intersects(
+        Channels::SIDE_LEFT | Channels::SIDE_RIGHT | Channels::REAR_LEFT | Channels::REAR_RIGHT,
+    ) {
+        1.41
+    } else {
+        1.0
+    }
+}
+
+fn decode_error(error: SymphoniaError) -> Error {
+    match error {
+        SymphoniaError::Unsupported(what) => Error::new(
+            Status::InvalidArg,
+            format!("Loudness analysis is not supported for this file: {what}"),
+        ),
+        error => Error::new(
+            Status::GenericFailure,
+            format!("Failed decoding audio: {error}"),
+        ),
+    }
+}
+
+/// Decode the first audio track and measure its loudness
+pub(crate) fn measure(
+    source: Box<dyn MediaSource>,
+    extension: Option<&str>,
+    true_peak: bool,
+) -> Result<Measurement> {
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+    let stream = MediaSourceStream::new(source, Default::default());
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(decode_error)?;
+    let mut format = probed.format;
+
+    let track = format
+        .default_track()
+        .filter(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .or_else(|| {
+            format
+                .tracks()
+                .iter()
+                .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        })
+        .ok_or_else(|| Error::new(Status::InvalidArg, "No audio track found"))?;
+    let track_id = track.id;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(decode_error)?;
+
+    let mut meter: Option<LoudnessMeter> = None;
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(error))
+                if error.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                break
+            }
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(error) => return Err(decode_error(error)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // Skip corrupt packets, as players do
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(error) => return Err(decode_error(error)),
+        };
+        let spec = *decoded.spec();
+        let buffer = match &mut buffer {
+            Some(buffer) if buffer.capacity() >= decoded.capacity() * spec.channels.count() => {
+                buffer
+            }
+            buffer => buffer.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+        };
+        buffer.copy_interleaved_ref(decoded);
+        meter
+            .get_or_insert_with(|| LoudnessMeter::new(spec.rate, spec.channels, true_peak))
+            .process(buffer.samples());
+    }
+
+    let meter = meter.ok_or_else(|| Error::new(Status::InvalidArg, "No audio could be decoded"))?;
+    Ok(meter.finish())
+}
+
+/// Audio to decode for a loudness measurement, opened when measuring
+pub struct LoudnessSource {
+    media: LoudnessMedia,
+    extension: Option<&'static str>,
+}
+
+enum LoudnessMedia {
+    Buffer(Vec<u8>),
+    #[cfg(not(target_arch = "wasm32"))]
+    Path(String),
+}
+
+impl LoudnessSource {
+    fn measure(self, true_peak: bool) -> Result<Measurement> {
+        let media: Box<dyn MediaSource> = match self.media {
+            LoudnessMedia::Buffer(buffer) => Box::new(Cursor::new(buffer)),
+            #[cfg(not(target_arch = "wasm32"))]
+            LoudnessMedia::Path(path) => {
+                Box::new(File::open(&path).map_err(|error| path_error(Path::new(&path), error))?)
+            }
+        };
+        measure(media, self.extension, true_peak)
+    }
+}
+
+pub struct AsyncLoudness {
+    sources: Vec<LoudnessSource>,
+    true_peak: bool,
+    /// Measurements of the sources, taken over when the analysis resolves
+    measurements: Arc<Mutex<Option<Vec<Measurement>>>>,
+}
+
+#[napi]
+impl Task for AsyncLoudness {
+    type Output = Vec<Measurement>;
+
+    type JsValue = ();
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        std::mem::take(&mut self.sources)
+            .into_iter()
+            .map(|source| source.measure(self.true_peak))
+            .collect()
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        if let Ok(mut measurements) = self.measurements.lock() {
+            *measurements = Some(output);
+        }
+        Ok(())
+    }
+}
+
+/// Measure the sources on a worker thread, then finish with the measurements on the main thread
+fn spawn_loudness<'env, T: ToNapiValue>(
+    env: &'env Env,
+    sources: Vec<LoudnessSource>,
+    true_peak: bool,
+    finish: impl FnOnce(Vec<Measurement>) -> Result<T> + 'env,
+) -> Result<PromiseRaw<'env, T>> {
+    let measurements = Arc::new(Mutex::new(None));
+    env.spawn(AsyncLoudness {
+        sources,
+        true_peak,
+        measurements: measurements.clone(),
+    })?
+    .promise_object()
+    .then(move |_| {
+        let measurements = measurements
+            .lock()
+            .ok()
+            .and_then(|mut measurements| measurements.take())
+            .ok_or_else(|| Error::from_reason("Loudness measurements are missing"))?;
+        finish(measurements)
+    })
+}
+
+fn reference_loudness(options: &LoudnessOptions) -> f64 {
+    options
+        .reference_loudness
+        .unwrap_or(DEFAULT_REFERENCE_LOUDNESS)
+}
+
+impl MusicFile {
+    /// The audio of the file, or `source` for files loaded from a buffer
+    pub(crate) fn loudness_source(&self, source: Option<Uint8Array>) -> Result<LoudnessSource> {
+        let media = match (source, &self.inner) {
+            (Some(buffer), _) => LoudnessMedia::Buffer(buffer.to_vec()),
+            (None, MusicFileInner::Buffer { .. }) => {
+                return Err(Error::new(Status::InvalidArg, ERR_FILE_LOADED_FROM_BUFFER));
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            (None, MusicFileInner::Path(path)) => LoudnessMedia::Path(path.clone()),
+        };
+        Ok(LoudnessSource {
+            media,
+            extension: file_type_extension(&self.file.file_type()),
+        })
+    }
+
+    /// Decode the audio of the file, or of `source` for files loaded from a buffer
+    pub(crate) fn measure_loudness(
+        &self,
+        source: Option<Uint8Array>,
+        true_peak: bool,
+    ) -> Result<Measurement> {
+        self.loudness_source(source)?.measure(true_peak)
+    }
+
+    /// Check that the ReplayGain tags of a result can be written, before writing any of them
+    fn check_loudness(&self, result: &LoudnessResult) -> Result<()> {
+        if let Some(gain) = result.gain {
+            self.replay_gain_text(gain)?;
+        }
+        Ok(())
+    }
+
+    fn write_track_loudness(&mut self, result: &LoudnessResult, sound_check: bool) -> Result<()> {
+        if let Some(gain) = result.gain {
+            self.set_track_replay_gain(Either::A(gain))?;
+            self.set_track_replay_peak(Either::A(result.peak))?;
+            if sound_check && self.supports_itunes_comments() {
+                self.set_sound_check(Either::A(gain), Some(result.peak))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_album_loudness(&mut self, result: &LoudnessResult) -> Result<()> {
+        if let Some(gain) = result.gain {
+            self.set_album_replay_gain(Either::A(gain))?;
+            self.set_album_replay_peak(Either::A(result.peak))?;
+        }
+        Ok(())
+    }
+
+    /// Compute the result of a track measurement, and write it if requested
+    fn finish_track_loudness(
+        &mut self,
+        measurement: &Measurement,
+        options: &LoudnessOptions,
+    ) -> Result<LoudnessResult> {
+        let result = measurement.result(reference_loudness(options));
+        if options.write.unwrap_or(false) {
+            self.check_loudness(&result)?;
+            self.write_track_loudness(&result, options.sound_check.unwrap_or(false))?;
+        }
+        Ok(result)
+    }
+}
+
+/// Compute the results of album measurements, and write them to every file if requested
+///
+/// All files are checked before any is written, so a failure leaves every file unchanged.
+fn finish_album_loudness(
+    files: &mut [&mut MusicFile],
+    measurements: &[Measurement],
+    options: &LoudnessOptions,
+) -> Result<AlbumLoudnessResult> {
+    let reference = reference_loudness(options);
+    let true_peak = options.true_peak.unwrap_or(true);
+    let album = Measurement {
+        blocks: measurements
+            .iter()
+            .flat_map(|measurement| measurement.blocks.iter().copied())
+            .collect(),
+        peak: measurements
+            .iter()
+            .map(|measurement| measurement.peak)
+            .fold(0.0, f64::max),
+        true_peak: true_peak.then(|| {
+            measurements
+                .iter()
+                .filter_map(|measurement| measurement.true_peak)
+                .fold(0.0, f64::max)
+        }),
+    }
+    .result(reference);
+    let tracks: Vec<LoudnessResult> = measurements
+        .iter()
+        .map(|measurement| measurement.result(reference))
+        .collect();
+
+    if options.write.unwrap_or(false) {
+        for (file, track) in files.iter().zip(&tracks) {
+            file.check_loudness(track)?;
+            file.check_loudness(&album)?;
+        }
+        for (file, track) in files.iter_mut().zip(&tracks) {
+            file.write_track_loudness(track, options.sound_check.unwrap_or(false))?;
+            file.write_album_loudness(&album)?;
+        }
+    }
+
+    Ok(AlbumLoudnessResult { tracks, album })
+}
+
+fn album_sources(
+    files: &[&mut MusicFile],
+    sources: Option<Vec<Option<Uint8Array>>>,
+) -> Result<Vec<LoudnessSource>> {
+    let mut sources = sources.unwrap_or_default().into_iter();
+    files
+        .iter()
+        .map(|file| file.loudness_source(sources.next().flatten()))
+        .collect()
+}
+
+#[napi]
+impl MusicFile {
+    /// Measure the loudness of the audio as in ReplayGain 2.0 and EBU R128
+    ///
+    /// The audio is decoded and measured with the gating of ITU-R BS.1770, on a worker thread.
+    /// Opus, APE, WavPack and Musepack audio can't be decoded.
+    ///
+    /// @param options Reference loudness, true peak and whether to write the track ReplayGain tags
+    /// @param source The file content, required for files loaded from a buffer
+    ///
+    /// @throws If the file was loaded from a buffer and no source is given
+    /// @throws If the audio can't be decoded
+    #[napi(ts_return_type = "Promise<LoudnessResult>")]
+    pub fn analyze_loudness<'env>(
+        &self,
+        env: &'env Env,
+        mut this: Reference<MusicFile>,
+        options: Option<LoudnessOptions>,
+        source: Option<Uint8Array>,
+    ) -> Result<PromiseRaw<'env, LoudnessResult>> {
+        let options = options.unwrap_or_default();
+        let source = self.loudness_source(source)?;
+        spawn_loudness(
+            env,
+            vec![source],
+            options.true_peak.unwrap_or(true),
+            move |measurements| this.finish_track_loudness(&measurements[0], &options),
+        )
+    }
+
+    /// Measure the loudness of the audio as in ReplayGain 2.0 and EBU R128
+    ///
+    /// This is the synchronous version of {@link analyzeLoudness}
+    ///
+    /// @param options Reference loudness, true peak and whether to write the track ReplayGain tags
+    /// @param source The file content, required for files loaded from a buffer
+    ///
+    /// @throws If the file was loaded from a buffer and no source is given
+    /// @throws If the audio can't be decoded
+    #[napi]
+    pub fn analyze_loudness_sync(
+        &mut self,
+        options: Option<LoudnessOptions>,
+        source: Option<Uint8Array>,
+    ) -> Result<LoudnessResult> {
+        let options = options.unwrap_or_default();
+        let measurement = self.measure_loudness(source, options.true_peak.unwrap_or(true))?;
+        self.finish_track_loudness(&measurement, &options)
+    }
+}
+
+/// Measure the loudness of each file and of all files together as an album
+///
+/// The audio is decoded on a worker thread. The album loudness is gated over the audio of all files,
+/// and the album peak is the highest track peak. With `write`, both the track and the album
+/// ReplayGain tags of every file are written, after checking that all of them can be written.
+///
+/// @param files Files of the album
+/// @param options Reference loudness, true peak and whether to write the ReplayGain tags
+/// @param sources The content of each file, required for files loaded from a buffer
+///
+/// @throws If a file was loaded from a buffer and no source is given
+/// @throws If the audio of a file can't be decoded
+#[napi(ts_return_type = "Promise<AlbumLoudnessResult>")]
+#[cfg_attr(test, allow(dead_code))]
+pub fn analyze_album_loudness<'env>(
+    env: &'env Env,
+    #[napi(ts_arg_type = "Array<MusicFile>")] mut files: Vec<Reference<MusicFile>>,
+    options: Option<LoudnessOptions>,
+    #[napi(ts_arg_type = "Array<Uint8Array | undefined | null>")] sources: Option<
+        Vec<Option<Uint8Array>>,
+    >,
+) -> Result<PromiseRaw<'env, AlbumLoudnessResult>> {
+    let options = options.unwrap_or_default();
+    let sources = {
+        let files: Vec<&mut MusicFile> = files.iter_mut().map(|file| &mut **file).collect();
+        album_sources(&files, sources)?
+    };
+    spawn_loudness(
+        env,
+        sources,
+        options.true_peak.unwrap_or(true),
+        move |measurements| {
+            let mut files: Vec<&mut MusicFile> = files.iter_mut().map(|file| &mut **file).collect();
+            finish_album_loudness(&mut files, &measurements, &options)
+        },
+    )
+}
+
+/// Measure the loudness of each file and of all files together as an album
+///
+/// This is the synchronous version of {@link analyzeAlbumLoudness}
+///
+/// @param files Files of the album
+/// @param options Reference loudness, true peak and whether to write the ReplayGain tags
+/// @param sources The content of each file, required for files loaded from a buffer
+///
+/// @throws If a file was loaded from a buffer and no source is given
+/// @throws If the audio of a file can't be decoded
+#[napi]
+pub fn analyze_album_loudness_sync(
+    mut files: Vec<&mut MusicFile>,
+    options: Option<LoudnessOptions>,
+    #[napi(ts_arg_type = "Array<Uint8Array | undefined | null>")] sources: Option<
+        Vec<Option<Uint8Array>>,
+    >,
+) -> Result<AlbumLoudnessResult> {
+    let options = options.unwrap_or_default();
+    let true_peak = options.true_peak.unwrap_or(true);
+    let measurements = album_sources(&files, sources)?
+        .into_iter()
+        .map(|source| source.measure(true_peak))
+        .collect::<Result<Vec<_>>>()?;
+    finish_album_loudness(&mut files, &measurements, &options)
+}
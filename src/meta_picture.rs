@@ -1,29 +1,431 @@
+use std::sync::{Arc, Mutex};
+
 use lofty::picture::{MimeType, Picture, PictureType};
-use napi::bindgen_prelude::Uint8Array;
+use napi::{
+    bindgen_prelude::{AsyncTask, PromiseRaw, ToNapiValue, Uint8Array},
+    Env, Error, Result, Status, Task,
+};
 use napi_derive::napi;
 
+use crate::artwork::{content_hash, image_info, recompress_as_jpeg, ImageInfo, Recompressed};
+
+const DEFAULT_JPEG_QUALITY: u32 = 85;
+
+/// Type of an embedded picture, the values match {@link MetaPicture.coverType}
+#[napi(string_enum, js_name = "PictureType")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverType {
+    #[napi(value = "Cover Art (Other)")]
+    Other,
+    #[napi(value = "Cover Art (Png Icon)")]
+    PngIcon,
+    #[napi(value = "Cover Art (Icon)")]
+    Icon,
+    #[napi(value = "Cover Art (Front)")]
+    Front,
+    #[napi(value = "Cover Art (Back)")]
+    Back,
+    #[napi(value = "Cover Art (Leaflet)")]
+    Leaflet,
+    #[napi(value = "Cover Art (Media)")]
+    Media,
+    #[napi(value = "Cover Art (Lead Artist)")]
+    LeadArtist,
+    #[napi(value = "Cover Art (Artist)")]
+    Artist,
+    #[napi(value = "Cover Art (Conductor)")]
+    Conductor,
+    #[napi(value = "Cover Art (Band)")]
+    Band,
+    #[napi(value = "Cover Art (Composer)")]
+    Composer,
+    #[napi(value = "Cover Art (Lyricist)")]
+    Lyricist,
+    #[napi(value = "Cover Art (Recording Location)")]
+    RecordingLocation,
+    #[napi(value = "Cover Art (During Recording)")]
+    DuringRecording,
+    #[napi(value = "Cover Art (During Performance)")]
+    DuringPerformance,
+    #[napi(value = "Cover Art (Video Capture)")]
+    VideoCapture,
+    #[napi(value = "Cover Art (Fish)")]
+    Fish,
+    #[napi(value = "Cover Art (Illustration)")]
+    Illustration,
+    #[napi(value = "Cover Art (Band Logotype)")]
+    BandLogotype,
+    #[napi(value = "Cover Art (Publisher Logotype)")]
+    PublisherLogotype,
+    #[napi(value = "Unknown")]
+    Unknown,
+}
+
+impl CoverType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CoverType::Other => "Cover Art (Other)",
+            CoverType::PngIcon => "Cover Art (Png Icon)",
+            CoverType::Icon => "Cover Art (Icon)",
+            CoverType::Front => "Cover Art (Front)",
+            CoverType::Back => "Cover Art (Back)",
+            CoverType::Leaflet => "Cover Art (Leaflet)",
+            CoverType::Media => "Cover Art (Media)",
+            CoverType::LeadArtist => "Cover Art (Lead Artist)",
+            CoverType::Artist => "Cover Art (Artist)",
+            CoverType::Conductor => "Cover Art (Conductor)",
+            CoverType::Band => "Cover Art (Band)",
+            CoverType::Composer => "Cover Art (Composer)",
+            CoverType::Lyricist => "Cover Art (Lyricist)",
+            CoverType::RecordingLocation => "Cover Art (Recording Location)",
+            CoverType::DuringRecording => "Cover Art (During Recording)",
+            CoverType::DuringPerformance => "Cover Art (During Performance)",
+            CoverType::VideoCapture => "Cover Art (Video Capture)",
+            CoverType::Fish => "Cover Art (Fish)",
+            CoverType::Illustration => "Cover Art (Illustration)",
+            CoverType::BandLogotype => "Cover Art (Band Logotype)",
+            CoverType::PublisherLogotype => "Cover Art (Publisher Logotype)",
+            CoverType::Unknown => "Unknown",
+        }
+    }
+
+    pub fn to_lofty(self) -> PictureType {
+        PictureType::from_ape_key(self.as_str())
+    }
+
+    pub fn from_lofty(pic_type: PictureType) -> Self {
+        match pic_type {
+            PictureType::Other => CoverType::Other,
+            PictureType::Icon => CoverType::PngIcon,
+            PictureType::OtherIcon => CoverType::Icon,
+            PictureType::CoverFront => CoverType::Front,
+            PictureType::CoverBack => CoverType::Back,
+            PictureType::Leaflet => CoverType::Leaflet,
+            PictureType::Media => CoverType::Media,
+            PictureType::LeadArtist => CoverType::LeadArtist,
+            PictureType::Artist => CoverType::Artist,
+            PictureType::Conductor => CoverType::Conductor,
+            PictureType::Band => CoverType::Band,
+            PictureType::Composer => CoverType::Composer,
+            PictureType::Lyricist => CoverType::Lyricist,
+            PictureType::RecordingLocation => CoverType::RecordingLocation,
+            PictureType::DuringRecording => CoverType::DuringRecording,
+            PictureType::DuringPerformance => CoverType::DuringPerformance,
+            PictureType::ScreenCapture => CoverType::VideoCapture,
+            PictureType::BrightFish => CoverType::Fish,
+            PictureType::Illustration => CoverType::Illustration,
+            PictureType::BandLogo => CoverType::BandLogotype,
+            PictureType::PublisherLogo => CoverType::PublisherLogotype,
+            _ => CoverType::Unknown,
+        }
+    }
+
+    /// Conventional file name, without extension, for pictures of this type
+    pub fn file_stem(self) -> &'static str {
+        match self {
+            CoverType::Other => "other",
+            CoverType::PngIcon => "icon",
+            CoverType::Icon => "other-icon",
+            CoverType::Front => "cover",
+            CoverType::Back => "back",
+            CoverType::Leaflet => "leaflet",
+            CoverType::Media => "media",
+            CoverType::LeadArtist => "lead-artist",
+            CoverType::Artist => "artist",
+            CoverType::Conductor => "conductor",
+            CoverType::Band => "band",
+            CoverType::Composer => "composer",
+            CoverType::Lyricist => "lyricist",
+            CoverType::RecordingLocation => "recording-location",
+            CoverType::DuringRecording => "during-recording",
+            CoverType::DuringPerformance => "during-performance",
+            CoverType::VideoCapture => "video-capture",
+            CoverType::Fish => "fish",
+            CoverType::Illustration => "illustration",
+            CoverType::BandLogotype => "band-logo",
+            CoverType::PublisherLogotype => "publisher-logo",
+            CoverType::Unknown => "unknown",
+        }
+    }
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct OptimizePictureOptions {
+    /// Maximum width and height in pixels, at least `1`, larger images are downscaled keeping the
+    /// aspect ratio
+    pub max_dimension: Option<u32>,
+    /// JPEG quality in [1, 100], defaults to `85`
+    pub quality: Option<u32>,
+}
+
+impl OptimizePictureOptions {
+    pub(crate) fn jpeg_quality(&self) -> Result<u8> {
+        match self.quality.unwrap_or(DEFAULT_JPEG_QUALITY) {
+            quality @ 1..=100 => Ok(quality as u8),
+            _ => Err(Error::new(
+                Status::InvalidArg,
+                "Quality should be integer in [1, 100]",
+            )),
+        }
+    }
+
+    pub(crate) fn max_dimension(&self) -> Result<Option<u32>> {
+        match self.max_dimension {
+            Some(0) => Err(Error::new(
+                Status::InvalidArg,
+                "Max dimension should be at least 1",
+            )),
+            max_dimension => Ok(max_dimension),
+        }
+    }
+}
+
+/// Result of {@link MusicFile.optimizePictures}
+#[napi(object)]
+pub struct OptimizePicturesReport {
+    /// Number of pictures that were replaced by a downscaled or smaller version
+    pub optimized: u32,
+    /// Total size of all pictures before optimizing, in bytes
+    pub bytes_before: f64,
+    /// Total size of all pictures after optimizing, in bytes
+    pub bytes_after: f64,
+    /// Number of bytes saved, negative if downscaled pictures are larger than the originals
+    pub bytes_saved: f64,
+}
+
 #[napi]
 pub struct MetaPicture {
     #[napi(
         ts_type = r#""Cover Art (Other)" | "Cover Art (Png Icon)" | "Cover Art (Icon)" | "Cover Art (Front)" | "Cover Art (Back)" | "Cover Art (Leaflet)" | "Cover Art (Media)" | "Cover Art (Lead Artist)" | "Cover Art (Artist)" | "Cover Art (Conductor)" | "Cover Art (Band)" | "Cover Art (Composer)" | "Cover Art (Lyricist)" | "Cover Art (Recording Location)" | "Cover Art (During Recording)" | "Cover Art (During Performance)" | "Cover Art (Video Capture)" | "Cover Art (Fish)" | "Cover Art (Illustration)" | "Cover Art (Band Logotype)" | "Cover Art (Publisher Logotype)" | "Unknown""#
     )]
     pub cover_type: String,
+    /// MIME type written to the file when the data is not a recognised image format,
+    /// {@link detectedMimeType} is written otherwise
     pub mime_type: Option<String>,
     pub description: Option<String>,
+    /// Image data, create a new picture to change it
+    #[napi(readonly)]
     pub data: Uint8Array,
+    /// Format and header of `data`, parsed once when the picture is created
+    info: Option<ImageInfo>,
 }
 
 #[napi]
 impl MetaPicture {
+    /// @param mime MIME type, kept as given, see {@link detectedMimeType} for the type of the data
+    /// @param coverType Picture type, defaults to `PictureType.Front`
     #[napi(constructor)]
-    pub fn new(mime: String, data: Uint8Array, desc: Option<String>) -> Self {
+    pub fn new(
+        mime: String,
+        data: Uint8Array,
+        desc: Option<String>,
+        cover_type: Option<CoverType>,
+    ) -> Self {
         MetaPicture {
-            cover_type: PictureType::CoverFront.as_ape_key().unwrap().to_owned(),
+            cover_type: cover_type.unwrap_or(CoverType::Front).as_str().to_owned(),
             mime_type: Some(mime),
             description: desc,
+            info: image_info(&data),
             data,
         }
     }
+
+    /// SHA-256 of the image data as lowercase hex, identical images have the same hash
+    #[napi(getter)]
+    pub fn hash(&self) -> String {
+        content_hash(&self.data)
+    }
+
+    /// MIME type detected from the image data, or `null` if it is not a JPEG, PNG, GIF, BMP, WebP
+    /// or TIFF image
+    #[napi(getter)]
+    pub fn detected_mime_type(&self) -> Option<String> {
+        self.info.map(|info| info.mime_type.to_owned())
+    }
+
+    /// Image width in pixels, detected from the image data
+    #[napi(getter)]
+    pub fn width(&self) -> Option<u32> {
+        self.info.map(|info| info.width)
+    }
+
+    /// Image height in pixels, detected from the image data
+    #[napi(getter)]
+    pub fn height(&self) -> Option<u32> {
+        self.info.map(|info| info.height)
+    }
+
+    /// Color depth in bits per pixel, detected from the image data
+    #[napi(getter)]
+    pub fn color_depth(&self) -> Option<u32> {
+        self.info.map(|info| info.color_depth)
+    }
+
+    /// Number of colors for indexed-color images, `0` otherwise
+    #[napi(getter)]
+    pub fn num_colors(&self) -> Option<u32> {
+        self.info.map(|info| info.num_colors)
+    }
+
+    /// Downscale and re-encode the picture as JPEG, on a worker thread
+    ///
+    /// The EXIF orientation is applied to the pixels, as the JPEG is written without EXIF data.
+    ///
+    /// @param options Maximum dimension and JPEG quality, the size is kept by default
+    ///
+    /// @returns A new JPEG picture with the same type and description
+    ///
+    /// @throws If the image cannot be decoded
+    #[napi]
+    pub fn optimize(
+        &self,
+        options: Option<OptimizePictureOptions>,
+    ) -> Result<AsyncTask<AsyncOptimizePicture>> {
+        self.optimize_task(options).map(AsyncTask::new)
+    }
+
+    /// Downscale and re-encode the picture as JPEG
+    ///
+    /// This is the synchronous version of {@link optimize}
+    ///
+    /// @param options Maximum dimension and JPEG quality, the size is kept by default
+    ///
+    /// @returns A new JPEG picture with the same type and description
+    ///
+    /// @throws If the image cannot be decoded
+    #[napi]
+    pub fn optimize_sync(&self, options: Option<OptimizePictureOptions>) -> Result<MetaPicture> {
+        let mut task = self.optimize_task(options)?;
+        let data = task.compute()?;
+        Ok(task.picture(data))
+    }
+}
+
+impl MetaPicture {
+    fn optimize_task(
+        &self,
+        options: Option<OptimizePictureOptions>,
+    ) -> Result<AsyncOptimizePicture> {
+        let options = options.unwrap_or_default();
+        Ok(AsyncOptimizePicture {
+            data: self.data.to_vec(),
+            max_dimension: options.max_dimension()?,
+            quality: options.jpeg_quality()?,
+            cover_type: self.cover_type.clone(),
+            description: self.description.clone(),
+        })
+    }
+}
+
+pub struct AsyncOptimizePicture {
+    data: Vec<u8>,
+    max_dimension: Option<u32>,
+    quality: u8,
+    cover_type: String,
+    description: Option<String>,
+}
+
+impl AsyncOptimizePicture {
+    fn picture(&mut self, data: Vec<u8>) -> MetaPicture {
+        let mut picture = MetaPicture::new(
+            "image/jpeg".to_owned(),
+            data.into(),
+            self.description.take(),
+            None,
+        );
+        picture.cover_type = std::mem::take(&mut self.cover_type);
+        picture
+    }
+}
+
+#[napi]
+impl Task for AsyncOptimizePicture {
+    type Output = Vec<u8>;
+
+    type JsValue = MetaPicture;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        recompress_as_jpeg(&self.data, self.max_dimension, self.quality)
+            .map(|recompressed| recompressed.data)
+            .map_err(|error| Error::new(Status::InvalidArg, error.to_string()))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(self.picture(output))
+    }
+}
+
+pub struct AsyncOptimizePictures {
+    pictures: Vec<Picture>,
+    options: OptimizePictureOptions,
+    /// Optimized pictures, taken over when the optimization resolves
+    optimized: Arc<Mutex<Option<Vec<Option<Picture>>>>>,
+}
+
+#[napi]
+impl Task for AsyncOptimizePictures {
+    type Output = Vec<Option<Picture>>;
+
+    type JsValue = ();
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        optimize_lofty_pictures(&self.pictures, &self.options)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        if let Ok(mut optimized) = self.optimized.lock() {
+            *optimized = Some(output);
+        }
+        Ok(())
+    }
+}
+
+/// Optimize the pictures on a worker thread, then finish with the results on the main thread
+pub(crate) fn spawn_optimize_pictures<'env, T: ToNapiValue>(
+    env: &'env Env,
+    pictures: Vec<Picture>,
+    options: OptimizePictureOptions,
+    finish: impl FnOnce(Vec<Picture>, Vec<Option<Picture>>) -> Result<T> + 'env,
+) -> Result<PromiseRaw<'env, T>> {
+    options.jpeg_quality()?;
+    options.max_dimension()?;
+    let optimized = Arc::new(Mutex::new(None));
+    env.spawn(AsyncOptimizePictures {
+        pictures: pictures.clone(),
+        options,
+        optimized: optimized.clone(),
+    })?
+    .promise_object()
+    .then(move |_| {
+        let optimized = optimized
+            .lock()
+            .ok()
+            .and_then(|mut optimized| optimized.take())
+            .ok_or_else(|| Error::from_reason("Optimized pictures are missing"))?;
+        finish(pictures, optimized)
+    })
+}
+
+/// Picture descriptor without the image data
+#[napi(object)]
+pub struct PictureInfo {
+    #[napi(
+        ts_type = r#""Cover Art (Other)" | "Cover Art (Png Icon)" | "Cover Art (Icon)" | "Cover Art (Front)" | "Cover Art (Back)" | "Cover Art (Leaflet)" | "Cover Art (Media)" | "Cover Art (Lead Artist)" | "Cover Art (Artist)" | "Cover Art (Conductor)" | "Cover Art (Band)" | "Cover Art (Composer)" | "Cover Art (Lyricist)" | "Cover Art (Recording Location)" | "Cover Art (During Recording)" | "Cover Art (During Performance)" | "Cover Art (Video Capture)" | "Cover Art (Fish)" | "Cover Art (Illustration)" | "Cover Art (Band Logotype)" | "Cover Art (Publisher Logotype)" | "Unknown""#
+    )]
+    pub cover_type: String,
+    pub mime_type: Option<String>,
+    pub description: Option<String>,
+    /// Image data size in bytes
+    pub size: u32,
+    /// Image width in pixels, detected from the image data
+    pub width: Option<u32>,
+    /// Image height in pixels, detected from the image data
+    pub height: Option<u32>,
+    /// SHA-256 of the image data as lowercase hex, only present when requested
+    pub hash: Option<String>,
+    /// Image data, only present when requested
+    pub data: Option<Uint8Array>,
 }
 
 pub fn from_lofty_picture_slice(pics: &[Picture]) -> Option<Vec<MetaPicture>> {
@@ -37,11 +439,16 @@ pub fn from_lofty_picture_slice(pics: &[Picture]) -> Option<Vec<MetaPicture>> {
 }
 
 pub fn from_lofty_picture(pic: &Picture) -> MetaPicture {
+    let info = image_info(pic.data());
     MetaPicture {
         cover_type: pic.pic_type().as_ape_key().unwrap_or("Unknown").to_owned(),
-        mime_type: pic.mime_type().map(|mime| mime.as_str().to_owned()),
+        mime_type: pic
+            .mime_type()
+            .map(|mime| mime.as_str().to_owned())
+            .or_else(|| info.map(|info| info.mime_type.to_owned())),
         description: pic.description().map(ToOwned::to_owned),
         data: pic.data().into(),
+        info,
     }
 }
 
@@ -49,7 +456,13 @@ pub fn to_lofty_picture(pic: &MetaPicture) -> Picture {
     let mut pic_builder = Picture::unchecked(pic.data.to_vec())
         .pic_type(PictureType::from_ape_key(pic.cover_type.as_str()));
 
-    if let Some(mime_str) = pic.mime_type.as_deref() {
+    // The detected type replaces a missing or mismatching one, the given type is kept for
+    // formats that are not recognised
+    let mime_type = pic
+        .info
+        .map(|info| info.mime_type)
+        .or_else(|| pic.mime_type.as_deref().filter(|mime| !mime.is_empty()));
+    if let Some(mime_str) = mime_type {
         pic_builder = pic_builder.mime_type(MimeType::from_str(mime_str));
     }
 
@@ -59,3 +472,59 @@ pub fn to_lofty_picture(pic: &MetaPicture) -> Picture {
 
     pic_builder.build()
 }
+
+pub fn picture_info(pic: &Picture, with_data: bool, with_hash: bool) -> PictureInfo {
+    let info = image_info(pic.data());
+
+    PictureInfo {
+        cover_type: pic.pic_type().as_ape_key().unwrap_or("Unknown").to_owned(),
+        mime_type: pic
+            .mime_type()
+            .map(|mime| mime.as_str().to_owned())
+            .or_else(|| info.map(|info| info.mime_type.to_owned())),
+        description: pic.description().map(ToOwned::to_owned),
+        size: pic.data().len() as u32,
+        width: info.map(|info| info.width),
+        height: info.map(|info| info.height),
+        hash: with_hash.then(|| content_hash(pic.data())),
+        data: with_data.then(|| pic.data().into()),
+    }
+}
+
+/// Downscale and re-encode a picture as JPEG
+///
+/// A downscaled picture is always returned. Otherwise returns `None` if the re-encoded picture is
+/// not smaller than the original, or the image cannot be decoded.
+pub fn optimize_lofty_picture(
+    pic: &Picture,
+    options: &OptimizePictureOptions,
+) -> Result<Option<Picture>> {
+    let quality = options.jpeg_quality()?;
+    let max_dimension = options.max_dimension()?;
+    let Ok(Recompressed { data, resized }) = recompress_as_jpeg(pic.data(), max_dimension, quality)
+    else {
+        return Ok(None);
+    };
+    if !resized && data.len() >= pic.data().len() {
+        return Ok(None);
+    }
+
+    let mut pic_builder = Picture::unchecked(data)
+        .pic_type(pic.pic_type())
+        .mime_type(MimeType::Jpeg);
+    if let Some(desc) = pic.description() {
+        pic_builder = pic_builder.description(desc.to_owned());
+    }
+
+    Ok(Some(pic_builder.build()))
+}
+
+/// Optimized version of each picture, see `optimize_lofty_picture`
+pub fn optimize_lofty_pictures(
+    pics: &[Picture],
+    options: &OptimizePictureOptions,
+) -> Result<Vec<Option<Picture>>> {
+    pics.iter()
+        .map(|pic| optimize_lofty_picture(pic, options))
+        .collect()
+}
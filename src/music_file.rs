@@ -1,34 +1,94 @@
-use std::io::Cursor;
+use std::{
+    io::{Cursor, Read, Seek},
+    sync::{Arc, Mutex},
+};
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::{
     fs,
+    io::BufReader,
     path::{Path, PathBuf},
 };
 
 use lofty::{
-    config::WriteOptions,
-    file::{AudioFile, TaggedFile as LoftyTaggedFile, TaggedFileExt},
+    config::{ParseOptions, WriteOptions},
+    error::FileEncodingError,
+    file::{AudioFile, FileType, TaggedFile as LoftyTaggedFile, TaggedFileExt},
+    iff::wav::WavFile,
+    io::FileLike,
+    mp4::Mp4File,
+    ogg::{tag::VorbisComments, OggPictureStorage},
     probe::Probe,
+    tag::{Tag, TagExt, TagType},
 };
 use napi::{
-    bindgen_prelude::{AsyncTask, Uint8Array},
+    bindgen_prelude::{AsyncTask, PromiseRaw, Reference, Uint8Array},
     Either, Env, Error, Result, Status, Task,
 };
 use napi_derive::napi;
 #[cfg(not(target_arch = "wasm32"))]
 use tempfile::Builder;
 
+use crate::artwork::image_info;
+use codec_info::{load_codec_info, mp4_codec_info, wav_codec_info, CodecInfo};
+use cue::load_cue_sheet_text;
+use flac_blocks::{load_flac_blocks, read_flac_block_chain, write_flac_blocks, FlacBlocks};
+use history::History;
+use itunes::{load_itunes_atoms, write_itunes_atoms, ItunesAtoms};
+use layout::{has_embedded_pictures, load_layout, AudioLayout};
+use ogg_pictures::{load_picture_state, read_ogg_headers, OggPictureState};
+use opus_header::{load_opus_header, write_opus_header, OpusHeader};
+
+#[path = "changes.rs"]
+pub(crate) mod changes;
+#[path = "codec_info.rs"]
+pub(crate) mod codec_info;
+#[path = "copy.rs"]
+pub(crate) mod copy;
+#[path = "cue.rs"]
+pub(crate) mod cue;
+#[path = "dedupe.rs"]
+pub(crate) mod dedupe;
+#[path = "fields.rs"]
+pub(crate) mod fields;
+#[path = "file_type.rs"]
+pub(crate) mod file_type;
+#[path = "flac_blocks.rs"]
+pub(crate) mod flac_blocks;
+#[path = "gapless.rs"]
+pub(crate) mod gapless;
 #[path = "helper.rs"]
 mod helper;
+#[path = "history.rs"]
+pub(crate) mod history;
+#[path = "itunes.rs"]
+pub(crate) mod itunes;
+#[path = "layout.rs"]
+pub(crate) mod layout;
+#[path = "loudness.rs"]
+pub(crate) mod loudness;
+#[path = "ogg_pictures.rs"]
+pub(crate) mod ogg_pictures;
+#[path = "opus_header.rs"]
+pub(crate) mod opus_header;
 #[path = "properties.rs"]
 mod properties;
+#[path = "quality.rs"]
+pub(crate) mod quality;
+#[path = "sidecar.rs"]
+pub(crate) mod sidecar;
+#[path = "snapshot.rs"]
+pub(crate) mod snapshot;
+#[path = "sound_check.rs"]
+pub(crate) mod sound_check;
 #[path = "tag.rs"]
 mod tag;
 
 #[cfg(target_arch = "wasm32")]
-const ERR_INVALID_IN_WASM: &str = "This method is invalid in wasm build";
+pub(crate) const ERR_INVALID_IN_WASM: &str = "This method is invalid in wasm build";
 const ERR_FILE_LOADED_FROM_BUFFER: &str = "This file was loaded from a buffer";
+const ERR_PICTURES_SKIPPED: &str =
+    "Pictures were skipped when loading this file, saving would remove them";
 
 #[cfg(not(target_arch = "wasm32"))]
 fn path_error(path: &Path, error: impl std::fmt::Display) -> Error {
@@ -38,6 +98,110 @@ fn path_error(path: &Path, error: impl std::fmt::Display) -> Error {
     )
 }
 
+/// Vorbis comments to write instead of the generic tag, `None` if lofty can write the tag itself
+///
+/// lofty only reads the dimensions of PNG and JPEG pictures, and writes zeros for anything else.
+/// The unmapped comments of the loaded Ogg comment header are written back along with the tag.
+fn vorbis_comments_for_writing(
+    tag: &Tag,
+    ogg_pictures: Option<&OggPictureState>,
+) -> Option<VorbisComments> {
+    if tag.tag_type() != TagType::VorbisComments {
+        return None;
+    }
+    let unmapped = ogg_pictures.map_or(&[][..], |state| state.unmapped_fields.as_slice());
+    let needs_info = tag.pictures().iter().any(|pic| {
+        image_info(pic.data())
+            .is_some_and(|info| !matches!(info.mime_type, "image/png" | "image/jpeg"))
+    });
+    if !needs_info && unmapped.is_empty() {
+        return None;
+    }
+
+    let mut items = tag.clone();
+    while items.picture_count() > 0 {
+        items.remove_picture(0);
+    }
+    let mut comments = VorbisComments::from(items);
+    for pic in tag.pictures() {
+        let info = image_info(pic.data())
+            .map(|info| info.picture_information())
+            .unwrap_or_default();
+        // Cannot fail, the information is provided
+        let _ = comments.insert_picture(pic.clone(), Some(info));
+    }
+    let unmapped: Vec<_> = unmapped
+        .iter()
+        .filter(|(key, _)| comments.get(key).is_none())
+        .cloned()
+        .collect();
+    for (key, value) in unmapped {
+        comments.push(key, value);
+    }
+    Some(comments)
+}
+
+/// Write all tags of a file, like `TaggedFile::save_to`, but with the dimensions of all
+/// supported image formats in FLAC and Ogg picture blocks.
+///
+/// The metadata blocks of a FLAC file, including its Vorbis comments, are written in one pass.
+fn write_tags<F: FileLike>(
+    file: &LoftyTaggedFile,
+    flac_blocks: Option<&FlacBlocks>,
+    ogg_pictures: Option<&OggPictureState>,
+    opus_header: Option<&OpusHeader>,
+    itunes_atoms: Option<&ItunesAtoms>,
+    target: &mut F,
+) -> std::result::Result<(), FileEncodingError> {
+    let mut flac_comments = None;
+    for tag in file.tags() {
+        if !file.file_type().tag_support(tag.tag_type()).is_writable() {
+            continue;
+        }
+
+        if flac_blocks.is_some() && tag.tag_type() == TagType::VorbisComments {
+            flac_comments = Some(tag);
+            continue;
+        }
+        target.rewind()?;
+        match vorbis_comments_for_writing(tag, ogg_pictures) {
+            Some(comments) => comments.save_to(target, WriteOptions::default())?,
+            None => tag.save_to(target, WriteOptions::default())?,
+        }
+    }
+
+    if let Some(flac_blocks) = flac_blocks {
+        write_flac_blocks(target, flac_blocks, flac_comments)?;
+    }
+    if let Some(opus_header) = opus_header.filter(|opus_header| opus_header.is_modified()) {
+        write_opus_header(target, opus_header)?;
+    }
+    if let Some(itunes_atoms) = itunes_atoms.filter(|itunes_atoms| !itunes_atoms.is_empty()) {
+        write_itunes_atoms(target, itunes_atoms)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_tags_to_path(
+    file: &LoftyTaggedFile,
+    flac_blocks: Option<&FlacBlocks>,
+    ogg_pictures: Option<&OggPictureState>,
+    opus_header: Option<&OpusHeader>,
+    itunes_atoms: Option<&ItunesAtoms>,
+    path: impl AsRef<Path>,
+) -> std::result::Result<(), FileEncodingError> {
+    write_tags(
+        file,
+        flac_blocks,
+        ogg_pictures,
+        opus_header,
+        itunes_atoms,
+        &mut fs::OpenOptions::new().read(true).write(true).open(path)?,
+    )
+}
+
 /// Resolve an output path to the real file when it already exists. This keeps a
 /// symlink directory entry intact while allowing the target file to be replaced
 /// atomically. For a new path, canonicalize its parent so the temporary file is
@@ -65,7 +229,15 @@ fn resolve_target_path(path: &Path) -> Result<PathBuf> {
 /// Save a source file to a custom path by writing a temporary copy and then
 /// atomically replacing the resolved destination.
 #[cfg(not(target_arch = "wasm32"))]
-fn save_to_custom_path_impl(src_path: &str, dest_path: &str, file: &LoftyTaggedFile) -> Result<()> {
+fn save_to_custom_path_impl(
+    src_path: &str,
+    dest_path: &str,
+    file: &LoftyTaggedFile,
+    flac_blocks: Option<&FlacBlocks>,
+    ogg_pictures: Option<&OggPictureState>,
+    opus_header: Option<&OpusHeader>,
+    itunes_atoms: Option<&ItunesAtoms>,
+) -> Result<()> {
     let target = resolve_target_path(Path::new(dest_path))?;
     let parent = target.parent().ok_or_else(|| {
         Error::new(
@@ -98,13 +270,20 @@ fn save_to_custom_path_impl(src_path: &str, dest_path: &str, file: &LoftyTaggedF
         ));
     }
 
-    file.save_to_path(temporary_path, WriteOptions::default())
-        .map_err(|error| {
-            Error::new(
-                Status::GenericFailure,
-                format!("Failed saving to file '{}': {}", dest_path, error),
-            )
-        })?;
+    write_tags_to_path(
+        file,
+        flac_blocks,
+        ogg_pictures,
+        opus_header,
+        itunes_atoms,
+        temporary_path,
+    )
+    .map_err(|error| {
+        Error::new(
+            Status::GenericFailure,
+            format!("Failed saving to file '{}': {}", dest_path, error),
+        )
+    })?;
 
     temporary.persist(&target).map_err(|error| {
         Error::new(
@@ -116,6 +295,7 @@ fn save_to_custom_path_impl(src_path: &str, dest_path: &str, file: &LoftyTaggedF
     Ok(())
 }
 
+#[derive(Clone)]
 pub(crate) enum MusicFileInner {
     Buffer {
         source_len: usize,
@@ -130,37 +310,149 @@ pub enum AsyncLoadSource {
     Buffer(Vec<u8>),
 }
 
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct LoadOptions {
+    /// Skip reading embedded pictures, defaults to `false`
+    ///
+    /// Saves memory when only text metadata is needed. If the file has pictures it cannot be saved,
+    /// because writing the tags would remove the skipped pictures.
+    pub skip_pictures: Option<bool>,
+    /// Read every MPEG frame for the exact duration and bitrate, defaults to `false`
+    ///
+    /// Without it, MP3 files lacking a Xing or VBRI header have an estimated duration.
+    /// Slower, because the whole file is read. Ignored for other formats.
+    pub accurate_duration: Option<bool>,
+}
+
+impl LoadOptions {
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions::new().read_cover_art(!self.skip_pictures.unwrap_or(false))
+    }
+
+    fn accurate(&self) -> bool {
+        self.accurate_duration.unwrap_or(false)
+    }
+}
+
 pub struct AsyncLoad {
     source: AsyncLoadSource,
+    options: LoadOptions,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-fn load_from_path_impl(path: &String) -> Result<MusicFile> {
-    let file = Probe::open(path)
-        .map_err(|e| Error::new(Status::InvalidArg, e))?
+/// Load a file from a single reader, reading each of its structures once
+///
+/// `extension_type` is used when the file type cannot be guessed from the content.
+fn load_from_reader<R: Read + Seek>(
+    mut reader: R,
+    inner: MusicFileInner,
+    extension_type: Option<FileType>,
+    options: &LoadOptions,
+) -> Result<MusicFile> {
+    let mut probe = Probe::new(&mut reader);
+    if let Some(file_type) = extension_type {
+        probe = probe.set_file_type(file_type);
+    }
+    let probe = probe
+        .options(options.parse_options())
         .guess_file_type()
-        .map_err(|e| Error::new(Status::InvalidArg, e))?
-        .read()
         .map_err(|e| Error::new(Status::InvalidArg, e))?;
+    // MP4 and WAV files are read here rather than by the probe, to keep the codec details and the
+    // freeform atoms lost in the conversion to a generic file
+    let (mut file, parsed_codec_info, itunes_atoms) = match probe.file_type() {
+        Some(FileType::Mp4) => {
+            let mp4 = Mp4File::read_from(probe.into_inner(), options.parse_options())
+                .map_err(|e| Error::new(Status::InvalidArg, e))?;
+            let (codec_info, itunes_atoms) = (mp4_codec_info(&mp4), load_itunes_atoms(&mp4));
+            (
+                LoftyTaggedFile::from(mp4),
+                Some(codec_info),
+                Some(itunes_atoms),
+            )
+        }
+        Some(FileType::Wav) => {
+            let wav = WavFile::read_from(probe.into_inner(), options.parse_options())
+                .map_err(|e| Error::new(Status::InvalidArg, e))?;
+            let codec_info = wav_codec_info(&wav);
+            (LoftyTaggedFile::from(wav), Some(codec_info), None)
+        }
+        _ => {
+            let file = probe
+                .read()
+                .map_err(|e| Error::new(Status::InvalidArg, e))?;
+            (file, None, None)
+        }
+    };
+
+    let file_type = file.file_type();
+    let flac = read_flac_block_chain(file_type, &mut reader);
+    let ogg = read_ogg_headers(file_type, &mut reader);
+    let ogg_pictures = ogg
+        .as_ref()
+        .and_then(|ogg| load_picture_state(&mut file, ogg));
+    let flac_blocks = flac.as_ref().and_then(load_flac_blocks);
+    let codec_info = parsed_codec_info
+        .or_else(|| load_codec_info(file_type, &mut reader, options.accurate(), ogg.as_ref()));
+    let opus_header = load_opus_header(codec_info.as_ref());
+    let layout = load_layout(&file, &mut reader, flac.as_ref(), ogg.as_ref());
+    let cue_sheet_text = load_cue_sheet_text(&mut reader, file_type, layout.as_ref(), ogg.as_ref());
+    let pictures_skipped = options.skip_pictures.unwrap_or(false)
+        && has_embedded_pictures(
+            &mut reader,
+            file_type,
+            layout.as_ref(),
+            flac.as_ref(),
+            ogg_pictures.as_ref(),
+        );
 
     Ok(MusicFile {
         file,
-        inner: MusicFileInner::Path(path.clone()),
+        inner,
+        original: None,
+        history: History::default(),
+        pictures_skipped,
+        ogg_pictures,
+        flac_blocks,
+        opus_header,
+        itunes_atoms,
+        codec_info,
+        layout,
+        cue_sheet_text,
+        load_options: options.clone(),
     })
 }
 
-fn load_from_buffer_impl(buffer: &[u8]) -> Result<MusicFile> {
-    let file = Probe::new(Cursor::new(buffer))
-        .guess_file_type()
-        .map_err(|e| Error::new(Status::InvalidArg, e))?
-        .read()
+#[cfg(not(target_arch = "wasm32"))]
+fn load_from_path_impl(path: &String, options: &LoadOptions) -> Result<MusicFile> {
+    let reader = fs::File::open(path)
+        .map(BufReader::new)
         .map_err(|e| Error::new(Status::InvalidArg, e))?;
+    load_from_reader(
+        reader,
+        MusicFileInner::Path(path.clone()),
+        FileType::from_path(path),
+        options,
+    )
+}
 
-    Ok(MusicFile {
-        file,
-        inner: MusicFileInner::Buffer {
-            source_len: buffer.len(),
-        },
+fn load_from_buffer_impl(buffer: &[u8], options: &LoadOptions) -> Result<MusicFile> {
+    let inner = MusicFileInner::Buffer {
+        source_len: buffer.len(),
+    };
+    load_from_reader(Cursor::new(buffer), inner, None, options)
+}
+
+/// Re-read a file after saving it in place, with the options it was loaded with
+#[cfg(not(target_arch = "wasm32"))]
+fn reload_saved_file(path: &String, options: &LoadOptions) -> Result<MusicFile> {
+    load_from_path_impl(path, options).map_err(|error| {
+        Error::new(
+            Status::GenericFailure,
+            format!(
+                "Saved to file '{}', but failed reading it back: {}",
+                path, error
+            ),
+        )
     })
 }
 
@@ -173,8 +465,8 @@ impl Task for AsyncLoad {
     fn compute(&mut self) -> napi::Result<Self::Output> {
         match &self.source {
             #[cfg(not(target_arch = "wasm32"))]
-            AsyncLoadSource::Path(path) => load_from_path_impl(path),
-            AsyncLoadSource::Buffer(buffer) => load_from_buffer_impl(buffer),
+            AsyncLoadSource::Path(path) => load_from_path_impl(path, &self.options),
+            AsyncLoadSource::Buffer(buffer) => load_from_buffer_impl(buffer, &self.options),
         }
     }
 
@@ -186,6 +478,9 @@ impl Task for AsyncLoad {
 pub enum AsyncSaveTarget {
     #[cfg(not(target_arch = "wasm32"))]
     InPlace(String),
+    /// Saving in place without any modification, nothing to write
+    #[cfg(not(target_arch = "wasm32"))]
+    Unchanged,
     #[cfg(not(target_arch = "wasm32"))]
     CustomPath {
         src_path: String,
@@ -197,6 +492,13 @@ pub enum AsyncSaveTarget {
 pub struct AsyncSave {
     target: AsyncSaveTarget,
     file: LoftyTaggedFile,
+    flac_blocks: Option<FlacBlocks>,
+    ogg_pictures: Option<OggPictureState>,
+    opus_header: Option<OpusHeader>,
+    itunes_atoms: Option<ItunesAtoms>,
+    load_options: LoadOptions,
+    /// The file re-read after saving in place, taken over when the save resolves
+    saved: Arc<Mutex<Option<MusicFile>>>,
 }
 
 #[napi]
@@ -209,30 +511,56 @@ impl Task for AsyncSave {
         match &mut self.target {
             #[cfg(not(target_arch = "wasm32"))]
             AsyncSaveTarget::InPlace(path) => {
-                self.file
-                    .save_to_path(path.as_str(), WriteOptions::default())
-                    .map_err(|error| {
-                        Error::new(
-                            Status::GenericFailure,
-                            format!("Failed saving to file '{}': {}", path, error),
-                        )
-                    })?;
+                write_tags_to_path(
+                    &self.file,
+                    self.flac_blocks.as_ref(),
+                    self.ogg_pictures.as_ref(),
+                    self.opus_header.as_ref(),
+                    self.itunes_atoms.as_ref(),
+                    path.as_str(),
+                )
+                .map_err(|error| {
+                    Error::new(
+                        Status::GenericFailure,
+                        format!("Failed saving to file '{}': {}", path, error),
+                    )
+                })?;
+                let reloaded = reload_saved_file(path, &self.load_options)?;
+                if let Ok(mut saved) = self.saved.lock() {
+                    *saved = Some(reloaded);
+                }
                 Ok(None)
             }
             #[cfg(not(target_arch = "wasm32"))]
+            AsyncSaveTarget::Unchanged => Ok(None),
+            #[cfg(not(target_arch = "wasm32"))]
             AsyncSaveTarget::CustomPath {
                 src_path,
                 dest_path,
             } => {
-                save_to_custom_path_impl(src_path.as_str(), dest_path.as_str(), &self.file)?;
+                save_to_custom_path_impl(
+                    src_path.as_str(),
+                    dest_path.as_str(),
+                    &self.file,
+                    self.flac_blocks.as_ref(),
+                    self.ogg_pictures.as_ref(),
+                    self.opus_header.as_ref(),
+                    self.itunes_atoms.as_ref(),
+                )?;
                 Ok(None)
             }
             AsyncSaveTarget::Buffer(buffer) => {
                 let mut cursor = Cursor::new(std::mem::take(buffer));
 
-                self.file
-                    .save_to(&mut cursor, WriteOptions::default())
-                    .map_err(|error| Error::from_reason(error.to_string()))?;
+                write_tags(
+                    &self.file,
+                    self.flac_blocks.as_ref(),
+                    self.ogg_pictures.as_ref(),
+                    self.opus_header.as_ref(),
+                    self.itunes_atoms.as_ref(),
+                    &mut cursor,
+                )
+                .map_err(|error| Error::from_reason(error.to_string()))?;
 
                 Ok(Some(cursor.into_inner()))
             }
@@ -251,12 +579,47 @@ impl Task for AsyncSave {
 pub struct MusicFile {
     file: LoftyTaggedFile,
     inner: MusicFileInner,
+    /// Tags as they were when loaded or saved in place, captured before the first modification
+    original: Option<Vec<Tag>>,
+    history: History,
+    /// Whether the file was loaded without its embedded pictures
+    pictures_skipped: bool,
+    /// Picture fields of the loaded Ogg comment header, `None` for other formats
+    ogg_pictures: Option<OggPictureState>,
+    /// Metadata blocks of the loaded FLAC file, `None` for other formats
+    flac_blocks: Option<FlacBlocks>,
+    /// Output gain of the loaded Opus identification header, `None` for other formats
+    opus_header: Option<OpusHeader>,
+    /// Freeform iTunes atoms of the loaded MP4 file, `None` for other formats
+    itunes_atoms: Option<ItunesAtoms>,
+    /// Codec details read from the stream headers when loading
+    codec_info: Option<CodecInfo>,
+    /// Position of the audio stream and metadata in the loaded file
+    layout: Option<AudioLayout>,
+    /// `CUESHEET` text of the loaded APE, ID3v2 or Ogg tags, FLAC files keep theirs in `flac_blocks`
+    cue_sheet_text: Option<String>,
+    /// Options the file was loaded with, reused to read it back after saving in place
+    load_options: LoadOptions,
 }
 
 #[cfg(test)]
 impl MusicFile {
     pub(crate) fn new_for_test(file: LoftyTaggedFile, inner: MusicFileInner) -> Self {
-        Self { file, inner }
+        Self {
+            file,
+            inner,
+            original: None,
+            history: History::default(),
+            pictures_skipped: false,
+            ogg_pictures: None,
+            flac_blocks: None,
+            opus_header: None,
+            itunes_atoms: None,
+            codec_info: None,
+            layout: None,
+            cue_sheet_text: None,
+            load_options: LoadOptions::default(),
+        }
     }
 }
 
@@ -265,13 +628,19 @@ impl MusicFile {
     /// Load music file from a file path or byte buffer
     ///
     /// @param source The file system path or a Uint8Array containing the audio file data
+    /// @param options Load options, e.g. to skip reading embedded pictures
     ///
     /// @throws If the path doesn't exist or isn't accessible
     /// @throws If the file doesn't contain a valid audio format
     /// @throws If runs in WebAssembly environments (due to file system restrictions).
-    #[napi(ts_type = r#"(path: string): Promise<MusicFile>
-  static load(buffer: Uint8Array): Promise<MusicFile>"#)]
-    pub fn load(source: Either<Uint8Array, String>) -> Result<AsyncTask<AsyncLoad>> {
+    #[napi(
+        ts_type = r#"(path: string, options?: LoadOptions | undefined | null): Promise<MusicFile>
+  static load(buffer: Uint8Array, options?: LoadOptions | undefined | null): Promise<MusicFile>"#
+    )]
+    pub fn load(
+        source: Either<Uint8Array, String>,
+        options: Option<LoadOptions>,
+    ) -> Result<AsyncTask<AsyncLoad>> {
         let source = match source {
             Either::A(buffer) => AsyncLoadSource::Buffer(buffer.to_vec()),
             Either::B(path) => {
@@ -288,7 +657,10 @@ impl MusicFile {
             }
         };
 
-        Ok(AsyncTask::new(AsyncLoad { source }))
+        Ok(AsyncTask::new(AsyncLoad {
+            source,
+            options: options.unwrap_or_default(),
+        }))
     }
 
     /// Load music file from a file path or byte buffer
@@ -296,15 +668,22 @@ impl MusicFile {
     /// This is the synchronous version of {@link load}
     ///
     /// @param source The file system path or a Uint8Array containing the audio file data
+    /// @param options Load options, e.g. to skip reading embedded pictures
     ///
     /// @throws If the path doesn't exist or isn't accessible
     /// @throws If the file doesn't contain a valid audio format
     /// @throws If runs in WebAssembly environments (due to file system restrictions).
-    #[napi(ts_type = r#"(path: string): MusicFile
-  static loadSync(buffer: Uint8Array): MusicFile"#)]
-    pub fn load_sync(source: Either<Uint8Array, String>) -> Result<MusicFile> {
+    #[napi(
+        ts_type = r#"(path: string, options?: LoadOptions | undefined | null): MusicFile
+  static loadSync(buffer: Uint8Array, options?: LoadOptions | undefined | null): MusicFile"#
+    )]
+    pub fn load_sync(
+        source: Either<Uint8Array, String>,
+        options: Option<LoadOptions>,
+    ) -> Result<MusicFile> {
+        let options = options.unwrap_or_default();
         match source {
-            Either::A(buffer) => load_from_buffer_impl(&buffer),
+            Either::A(buffer) => load_from_buffer_impl(&buffer, &options),
             Either::B(path) => {
                 #[cfg(target_arch = "wasm32")]
                 {
@@ -314,7 +693,7 @@ impl MusicFile {
 
                 #[cfg(not(target_arch = "wasm32"))]
                 {
-                    load_from_path_impl(&path)
+                    load_from_path_impl(&path, &options)
                 }
             }
         }
@@ -333,12 +712,35 @@ impl MusicFile {
         }
     }
 
+    /// Copy the file type, properties and tags of the loaded file
+    fn clone_file(&self) -> LoftyTaggedFile {
+        LoftyTaggedFile::new(
+            self.file.file_type(),
+            self.file.properties().clone(),
+            self.file.tags().to_owned().to_vec(),
+        )
+    }
+
+    /// Saving would drop the pictures that were skipped when loading
+    fn ensure_pictures_loaded(&self) -> Result<()> {
+        if self.pictures_skipped {
+            return Err(Error::new(Status::GenericFailure, ERR_PICTURES_SKIPPED));
+        }
+        Ok(())
+    }
+
     /// Save tags into a buffer, returning the new buffer contents.
     fn save_to_new_buffer(&self, mut buf: Vec<u8>) -> Result<Vec<u8>> {
         let mut cursor = Cursor::new(&mut buf);
-        self.file
-            .save_to(&mut cursor, WriteOptions::default())
-            .map_err(|x| Error::from_reason(x.to_string()))?;
+        write_tags(
+            &self.file,
+            self.flac_blocks.as_ref(),
+            self.ogg_pictures.as_ref(),
+            self.opus_header.as_ref(),
+            self.itunes_atoms.as_ref(),
+            &mut cursor,
+        )
+        .map_err(|x| Error::from_reason(x.to_string()))?;
         Ok(buf)
     }
 
@@ -347,22 +749,34 @@ impl MusicFile {
     /// @param bufferOrPath Optional output file path (Node.js only) or source buffer. If provided,
     /// saves to this path (or a new buffer that creates from the source buffer with new tags) for this call.
     ///
+    /// @note Saving to the existing path is skipped when the metadata is unchanged, see {@link isDirty}.
+    /// After saving to the existing path, the saved metadata becomes the loaded state.
+    ///
     /// @throws If the file was loaded from a buffer and no buffer is provided.
     /// @throws If the file was loaded from a buffer and wants to save to a custom path.
     /// @throws If custom path is provided in WebAssembly environments
+    /// @throws If the file was loaded with `skipPictures` and has pictures
     /// @throws If saving fails due to file format constraints
+    /// @throws If the file saved to the existing path cannot be read back
     #[napi(ts_type = r#"(path?: string | null): Promise<void>
   save(buffer: Uint8Array): Promise<Uint8Array>"#)]
-    pub fn save(
+    pub fn save<'env>(
         &self,
+        env: &'env Env,
+        mut this: Reference<MusicFile>,
         buffer_or_path: Option<Either<Uint8Array, String>>,
-    ) -> Result<AsyncTask<AsyncSave>> {
+    ) -> Result<PromiseRaw<'env, Either<(), Uint8Array>>> {
+        self.ensure_pictures_loaded()?;
+        self.validate_ogg_pictures()?;
+
         let target = match buffer_or_path {
             None => match &self.inner {
                 MusicFileInner::Buffer { .. } => {
                     Err(Error::new(Status::InvalidArg, ERR_FILE_LOADED_FROM_BUFFER))
                 }
                 #[cfg(not(target_arch = "wasm32"))]
+                MusicFileInner::Path(_) if !self.is_dirty() => Ok(AsyncSaveTarget::Unchanged),
+                #[cfg(not(target_arch = "wasm32"))]
                 MusicFileInner::Path(path) => Ok(AsyncSaveTarget::InPlace(path.clone())),
             },
             Some(buffer_or_path) => match buffer_or_path {
@@ -400,13 +814,29 @@ impl MusicFile {
         }?;
 
         // Create a snapshot of MusicFile, to send to the background task.
-        let file = LoftyTaggedFile::new(
-            self.file.file_type(),
-            self.file.properties().clone(),
-            self.file.tags().to_owned().to_vec(),
-        );
-
-        Ok(AsyncTask::new(AsyncSave { target, file }))
+        let file = self.clone_file();
+
+        let history_state = self.history_state();
+        let saved = Arc::new(Mutex::new(None));
+
+        let mut promise = env
+            .spawn(AsyncSave {
+                target,
+                file,
+                flac_blocks: self.flac_blocks.clone(),
+                ogg_pictures: self.ogg_pictures.clone(),
+                opus_header: self.opus_header.clone(),
+                itunes_atoms: self.itunes_atoms.clone(),
+                load_options: self.load_options.clone(),
+                saved: saved.clone(),
+            })?
+            .promise_object();
+        promise.finally(move |_| {
+            if let Some(saved) = saved.lock().ok().and_then(|mut saved| saved.take()) {
+                this.commit_save(history_state, saved);
+            }
+            Ok(())
+        })
     }
 
     /// Save metadata changes to the provided buffer, existing path, or a custom path
@@ -416,31 +846,49 @@ impl MusicFile {
     /// @param bufferOrPath Optional output file path (Node.js only) or source buffer. If provided,
     /// saves to this path (or a new buffer that creates from the source buffer with new tags) for this call.
     ///
+    /// @note Saving to the existing path is skipped when the metadata is unchanged, see {@link isDirty}.
+    /// After saving to the existing path, the saved metadata becomes the loaded state.
+    ///
     /// @throws If the file was loaded from a buffer and no buffer is provided.
     /// @throws If the file was loaded from a buffer and wants to save to a custom path.
     /// @throws If custom path is provided in WebAssembly environments
+    /// @throws If the file was loaded with `skipPictures` and has pictures
     /// @throws If saving fails due to file format constraints
+    /// @throws If the file saved to the existing path cannot be read back
     #[napi(ts_type = r#"(path?: string | null): void
   saveSync(buffer: Uint8Array): Uint8Array"#)]
     pub fn save_sync(
-        &self,
+        &mut self,
         buffer_or_path: Option<Either<Uint8Array, String>>,
     ) -> Result<Either<(), Uint8Array>> {
+        self.ensure_pictures_loaded()?;
+        self.validate_ogg_pictures()?;
+
         match buffer_or_path {
             None => match &self.inner {
                 MusicFileInner::Buffer { .. } => {
                     Err(Error::new(Status::InvalidArg, ERR_FILE_LOADED_FROM_BUFFER))
                 }
                 #[cfg(not(target_arch = "wasm32"))]
+                MusicFileInner::Path(_) if !self.is_dirty() => Ok(Either::A(())),
+                #[cfg(not(target_arch = "wasm32"))]
                 MusicFileInner::Path(path) => {
-                    self.file
-                        .save_to_path(path, WriteOptions::default())
-                        .map_err(|e| {
-                            Error::new(
-                                Status::GenericFailure,
-                                format!("Failed saving to file '{}': {}", path, e),
-                            )
-                        })?;
+                    write_tags_to_path(
+                        &self.file,
+                        self.flac_blocks.as_ref(),
+                        self.ogg_pictures.as_ref(),
+                        self.opus_header.as_ref(),
+                        self.itunes_atoms.as_ref(),
+                        path,
+                    )
+                    .map_err(|e| {
+                        Error::new(
+                            Status::GenericFailure,
+                            format!("Failed saving to file '{}': {}", path, e),
+                        )
+                    })?;
+                    let saved = reload_saved_file(path, &self.load_options)?;
+                    self.commit_save(self.history_state(), saved);
                     Ok(Either::A(()))
                 }
             },
@@ -472,7 +920,15 @@ impl MusicFile {
                             ),
                         };
 
-                        save_to_custom_path_impl(src_path, &path, &self.file)?;
+                        save_to_custom_path_impl(
+                            src_path,
+                            &path,
+                            &self.file,
+                            self.flac_blocks.as_ref(),
+                            self.ogg_pictures.as_ref(),
+                            self.opus_header.as_ref(),
+                            self.itunes_atoms.as_ref(),
+                        )?;
                         Ok(Either::A(()))
                     }
                 }
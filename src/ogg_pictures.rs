@@ -0,0 +1,377 @@
+use std::io::{Read, Seek};
+
+use data_encoding::BASE64;
+use lofty::{
+    config::ParsingMode,
+    file::{FileType, TaggedFile as LoftyTaggedFile, TaggedFileExt},
+    picture::{MimeType, Picture},
+    tag::{ItemKey, Tag, TagType},
+};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use ogg_pager::{Packets, PageHeader};
+
+use crate::{
+    artwork::{content_hash, image_info},
+    meta_picture::CoverType,
+    music_file::MusicFile,
+};
+
+pub(crate) const BLOCK_PICTURE_KEY: &[u8] = b"METADATA_BLOCK_PICTURE";
+const COVERART_KEY: &[u8] = b"COVERART";
+const COVERART_MIME_KEY: &[u8] = b"COVERARTMIME";
+/// Comment keys holding pictures
+const PICTURE_KEYS: [&[u8]; 3] = [BLOCK_PICTURE_KEY, COVERART_KEY, COVERART_MIME_KEY];
+
+/// Key and value of a comment
+pub(crate) type Comment = (String, String);
+
+/// How the pictures of an Ogg file are stored in its comment header
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct OggPictureFields {
+    /// Number of `METADATA_BLOCK_PICTURE` fields
+    pub block_pictures: u32,
+    /// Number of legacy `COVERART` fields
+    pub legacy_cover_art: u32,
+    /// Number of picture fields that could not be decoded, these are dropped on save
+    pub invalid: u32,
+}
+
+/// Picture fields of the loaded comment header, used to recognize legacy pictures
+#[derive(Clone, Default)]
+pub(crate) struct OggPictureState {
+    fields: OggPictureFields,
+    /// Content hashes of the pictures stored in `COVERART` fields
+    legacy_hashes: Vec<String>,
+    /// Whether the legacy pictures are rewritten as `METADATA_BLOCK_PICTURE` on the next save
+    pub(crate) convert_pending: bool,
+    /// Keys of the comments lofty drops from the tag
+    pub(crate) unmapped_comments: Vec<String>,
+    /// Those comments as loaded, written back when saving
+    pub(crate) unmapped_fields: Vec<Comment>,
+}
+
+impl OggPictureState {
+    /// Whether the comment header has picture fields, including ones that cannot be decoded
+    pub(crate) fn has_pictures(&self) -> bool {
+        self.fields.block_pictures + self.fields.legacy_cover_art + self.fields.invalid > 0
+    }
+}
+
+/// Picture stored in a legacy `COVERART` field
+struct LegacyCoverArt {
+    data: Vec<u8>,
+    mime_type: Option<String>,
+}
+
+fn is_ogg(file_type: FileType) -> bool {
+    matches!(
+        file_type,
+        FileType::Opus | FileType::Vorbis | FileType::Speex
+    )
+}
+
+fn read_u32(data: &mut &[u8]) -> Option<usize> {
+    let (value, rest) = data.split_first_chunk::<4>()?;
+    *data = rest;
+    Some(u32::from_le_bytes(*value) as usize)
+}
+
+/// Split a comment header packet, or a FLAC VORBIS_COMMENT block, into `KEY=VALUE` fields
+pub(crate) fn comment_fields(file_type: FileType, packet: &[u8]) -> Option<Vec<(&[u8], &[u8])>> {
+    let mut data = match file_type {
+        FileType::Opus => packet.strip_prefix(b"OpusTags")?,
+        FileType::Vorbis => packet.strip_prefix(b"\x03vorbis")?,
+        _ => packet,
+    };
+
+    let vendor_len = read_u32(&mut data)?;
+    data = data.get(vendor_len..)?;
+    let count = read_u32(&mut data)?;
+
+    let mut fields = Vec::new();
+    for _ in 0..count {
+        let len = read_u32(&mut data)?;
+        let comment = data.get(..len)?;
+        data = &data[len..];
+
+        let separator = comment.iter().position(|byte| *byte == b'=')?;
+        fields.push((&comment[..separator], &comment[separator + 1..]));
+    }
+
+    Some(fields)
+}
+
+/// Whether a comment has no item key and is not a picture, so the tag cannot hold it
+///
+/// lofty drops such comments, like `CUESHEET`, `MQAENCODER` and `ORIGINALSAMPLERATE`.
+pub(crate) fn is_unmapped_comment(key: &str) -> bool {
+    ItemKey::from_key(TagType::VorbisComments, key).is_none()
+        && !PICTURE_KEYS
+            .iter()
+            .any(|&picture_key| key.as_bytes().eq_ignore_ascii_case(picture_key))
+}
+
+/// Keys of the unmapped comments among `fields`, each once
+pub(crate) fn unmapped_comment_keys<K: AsRef<[u8]>, V>(fields: &[(K, V)]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for (key, _) in fields {
+        let key = String::from_utf8_lossy(key.as_ref()).to_ascii_uppercase();
+        if is_unmapped_comment(&key) && !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Read the picture fields and the unmapped comments from the comment header of an Ogg stream
+fn read_picture_fields(
+    file_type: FileType,
+    comment_header: &[u8],
+) -> Option<(OggPictureFields, Vec<LegacyCoverArt>, Vec<Comment>)> {
+    let fields = comment_fields(file_type, comment_header)?;
+    let unmapped = fields
+        .iter()
+        .map(|(key, value)| {
+            (
+                String::from_utf8_lossy(key).into_owned(),
+                String::from_utf8_lossy(value).into_owned(),
+            )
+        })
+        .filter(|(key, _)| is_unmapped_comment(key))
+        .collect();
+
+    let mut result = OggPictureFields::default();
+    let mut legacy = Vec::new();
+    let mut legacy_mime_types = Vec::new();
+    for (key, value) in fields {
+        if key.eq_ignore_ascii_case(BLOCK_PICTURE_KEY) {
+            match Picture::from_flac_bytes(value, true, ParsingMode::Strict) {
+                Ok(_) => result.block_pictures += 1,
+                Err(_) => result.invalid += 1,
+            }
+        } else if key.eq_ignore_ascii_case(COVERART_KEY) {
+            match BASE64.decode(value) {
+                Ok(data) => {
+                    result.legacy_cover_art += 1;
+                    legacy.push(LegacyCoverArt {
+                        data,
+                        mime_type: None,
+                    });
+                }
+                Err(_) => result.invalid += 1,
+            }
+        } else if key.eq_ignore_ascii_case(COVERART_MIME_KEY) {
+            legacy_mime_types.push(String::from_utf8_lossy(value).into_owned());
+        }
+    }
+
+    // `COVERARTMIME` fields describe the `COVERART` fields in the same order
+    for (pic, mime_type) in legacy.iter_mut().zip(legacy_mime_types) {
+        pic.mime_type = Some(mime_type).filter(|mime| !mime.is_empty());
+    }
+
+    Some((result, legacy, unmapped))
+}
+
+/// Give pictures converted from `COVERART` the MIME type of their `COVERARTMIME` field
+///
+/// lofty ignores `COVERARTMIME`, and only detects the MIME type of common image formats.
+fn apply_legacy_mime_types(tag: &mut Tag, legacy: &[LegacyCoverArt]) {
+    for index in 0..tag.pictures().len() {
+        let pic = &tag.pictures()[index];
+        let known = pic
+            .mime_type()
+            .is_some_and(|mime| !matches!(mime, MimeType::Unknown(_)));
+        if known {
+            continue;
+        }
+
+        let mime_type = legacy
+            .iter()
+            .find(|legacy| legacy.data == pic.data())
+            .and_then(|legacy| legacy.mime_type.as_deref())
+            .or_else(|| image_info(pic.data()).map(|info| info.mime_type));
+        let Some(mime_type) = mime_type else {
+            continue;
+        };
+
+        let mut builder = Picture::unchecked(pic.data().to_vec())
+            .pic_type(pic.pic_type())
+            .mime_type(MimeType::from_str(mime_type));
+        if let Some(desc) = pic.description() {
+            builder = builder.description(desc.to_owned());
+        }
+        tag.set_picture(index, builder.build());
+    }
+}
+
+/// Header packets of an Ogg file, read once while loading it
+pub(crate) struct OggHeaders {
+    /// Identification and comment headers, and the setup header of Vorbis
+    pub(crate) packets: Packets,
+    /// Offset of the page after the identification header
+    pub(crate) comment_start: u64,
+    /// Offset of the first audio page
+    pub(crate) audio_start: u64,
+}
+
+/// Read the header packets of an Ogg file, `None` for other file types or broken files
+pub(crate) fn read_ogg_headers(
+    file_type: FileType,
+    reader: &mut (impl Read + Seek),
+) -> Option<OggHeaders> {
+    if !is_ogg(file_type) {
+        return None;
+    }
+
+    reader.rewind().ok()?;
+    // The identification header has a page of its own
+    let first = PageHeader::read(reader).ok()?;
+    let comment_start = reader.stream_position().ok()? + first.content_size() as u64;
+
+    // Vorbis has a setup header after the comment header, ending on a page boundary too
+    let header_packets = if file_type == FileType::Vorbis { 3 } else { 2 };
+    reader.rewind().ok()?;
+    let packets = Packets::read_count(reader, header_packets).ok()?;
+    let audio_start = reader.stream_position().ok()?;
+
+    Some(OggHeaders {
+        packets,
+        comment_start,
+        audio_start,
+    })
+}
+
+/// Inspect the picture fields of an Ogg file after loading it with lofty
+///
+/// Returns `None` if the comment header cannot be read.
+pub(crate) fn load_picture_state(
+    file: &mut LoftyTaggedFile,
+    headers: &OggHeaders,
+) -> Option<OggPictureState> {
+    let (fields, legacy, unmapped_fields) =
+        read_picture_fields(file.file_type(), headers.packets.get(1)?)?;
+    if !legacy.is_empty() {
+        if let Some(tag) = file.tag_mut(TagType::VorbisComments) {
+            apply_legacy_mime_types(tag, &legacy);
+        }
+    }
+
+    Some(OggPictureState {
+        fields,
+        legacy_hashes: legacy.iter().map(|pic| content_hash(&pic.data)).collect(),
+        convert_pending: false,
+        unmapped_comments: unmapped_comment_keys(&unmapped_fields),
+        unmapped_fields,
+    })
+}
+
+/// Check that every picture of a Vorbis comments tag survives the `METADATA_BLOCK_PICTURE` encoding
+///
+/// Pictures in `loaded` were read from the file and are not checked again.
+pub(crate) fn validate_block_pictures(tag: &Tag, loaded: &[&Picture]) -> std::io::Result<()> {
+    if tag.tag_type() != TagType::VorbisComments {
+        return Ok(());
+    }
+
+    for (index, pic) in tag.pictures().iter().enumerate() {
+        if loaded.contains(&pic) {
+            continue;
+        }
+        let info = image_info(pic.data())
+            .map(|info| info.picture_information())
+            .unwrap_or_default();
+        let encoded = pic.as_flac_bytes(info, true);
+
+        let message = match Picture::from_flac_bytes(&encoded, true, ParsingMode::Strict) {
+            Ok((decoded, _)) if decoded.data() == pic.data() => continue,
+            Ok(_) => "the image data changed".to_owned(),
+            Err(error) => error.to_string(),
+        };
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Picture {index} cannot be stored as METADATA_BLOCK_PICTURE: {message}"),
+        ));
+    }
+
+    Ok(())
+}
+
+impl MusicFile {
+    /// Check the pictures set since an Ogg file was loaded, before saving it
+    pub(crate) fn validate_ogg_pictures(&self) -> Result<()> {
+        let Some(original) = self
+            .original
+            .as_ref()
+            .filter(|_| is_ogg(self.file.file_type()))
+        else {
+            return Ok(());
+        };
+
+        let loaded: Vec<&Picture> = original.iter().flat_map(|tag| tag.pictures()).collect();
+        for tag in self.file.tags() {
+            validate_block_pictures(tag, &loaded)
+                .map_err(|error| Error::new(Status::InvalidArg, error.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// How pictures are stored in the comment header of the loaded Ogg file, or `null` for other formats
+    ///
+    /// Pictures in both `METADATA_BLOCK_PICTURE` and legacy `COVERART` fields are available in {@link pictures}.
+    /// Saving always writes `METADATA_BLOCK_PICTURE` fields.
+    #[napi(getter)]
+    pub fn ogg_picture_fields(&self) -> Option<OggPictureFields> {
+        self.ogg_pictures.as_ref().map(|state| state.fields.clone())
+    }
+
+    /// Convert pictures from legacy `COVERART` fields to standard `METADATA_BLOCK_PICTURE` fields
+    ///
+    /// The pictures are written in the standard form on the next save, even if nothing else was modified.
+    ///
+    /// @param coverType Picture type of the converted pictures, defaults to `PictureType.Front`
+    ///
+    /// @returns Number of converted pictures
+    #[napi]
+    pub fn convert_legacy_cover_art(&mut self, cover_type: Option<CoverType>) -> Result<u32> {
+        let legacy_hashes = match &self.ogg_pictures {
+            Some(state) if !state.legacy_hashes.is_empty() => state.legacy_hashes.clone(),
+            _ => return Ok(0),
+        };
+        let pic_type = cover_type.unwrap_or(CoverType::Front).to_lofty();
+
+        let indices: Vec<usize> = self
+            .tag(|tag| {
+                Some(
+                    tag.pictures()
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, pic)| legacy_hashes.contains(&content_hash(pic.data())))
+                        .map(|(index, _)| index)
+                        .collect(),
+                )
+            })
+            .unwrap_or_default();
+        if indices.is_empty() {
+            return Ok(0);
+        }
+
+        self.tag_mut(|tag| {
+            for &index in &indices {
+                let mut pic = tag.pictures()[index].clone();
+                pic.set_pic_type(pic_type);
+                tag.set_picture(index, pic);
+            }
+        })?;
+
+        if let Some(state) = self.ogg_pictures.as_mut() {
+            state.convert_pending = true;
+        }
+        Ok(indices.len() as u32)
+    }
+}
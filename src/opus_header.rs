@@ -0,0 +1,137 @@
+use std::io::SeekFrom;
+
+use lofty::io::FileLike;
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+use ogg_pager::{crc32, PAGE_HEADER_SIZE};
+
+use crate::{
+    music_file::{codec_info::CodecInfo, MusicFile},
+    utils::gain_to_q78,
+};
+
+const ERR_NOT_OPUS: &str = "This file is not an Opus file";
+
+/// Offset of the output gain in the identification header packet
+const OUTPUT_GAIN_OFFSET: usize = 16;
+/// Offset of the checksum in the Ogg page header
+const CHECKSUM_OFFSET: usize = 22;
+
+/// Output gain of the Opus identification header, in Q7.8 dB
+#[derive(Clone)]
+pub(crate) struct OpusHeader {
+    loaded_output_gain: i16,
+    output_gain: Option<i16>,
+}
+
+impl OpusHeader {
+    /// Whether the output gain was changed since loading
+    pub(crate) fn is_modified(&self) -> bool {
+        self.output_gain
+            .is_some_and(|gain| gain != self.loaded_output_gain)
+    }
+
+    /// Discard the output gain change
+    pub(crate) fn revert(&mut self) {
+        self.output_gain = None;
+    }
+
+    pub(crate) fn changes(&self) -> Option<i16> {
+        self.output_gain
+    }
+
+    pub(crate) fn restore_changes(&mut self, output_gain: Option<i16>) {
+        self.output_gain = output_gain;
+    }
+
+    fn output_gain(&self) -> i16 {
+        self.output_gain.unwrap_or(self.loaded_output_gain)
+    }
+}
+
+pub(crate) fn load_opus_header(codec_info: Option<&CodecInfo>) -> Option<OpusHeader> {
+    let opus = codec_info?.opus.as_ref()?;
+    Some(OpusHeader {
+        // Parsed from Q7.8, so the conversion is exact
+        loaded_output_gain: (opus.output_gain * 256.0) as i16,
+        output_gain: None,
+    })
+}
+
+/// Write the output gain into the first page of an Opus file that was just written by lofty
+///
+/// The page keeps its size, only the gain and the page checksum change.
+pub(crate) fn write_opus_header<F: FileLike>(
+    target: &mut F,
+    header: &OpusHeader,
+) -> std::io::Result<()> {
+    let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid Opus header");
+
+    target.rewind()?;
+    let mut page = vec![0; PAGE_HEADER_SIZE];
+    target.read_exact(&mut page)?;
+    if !page.starts_with(b"OggS") {
+        return Err(invalid());
+    }
+    let mut segments = vec![0; usize::from(page[PAGE_HEADER_SIZE - 1])];
+    target.read_exact(&mut segments)?;
+    let content_size: usize = segments.iter().map(|&size| usize::from(size)).sum();
+    page.extend(segments);
+    let content_start = page.len();
+    page.resize(content_start + content_size, 0);
+    target.read_exact(&mut page[content_start..])?;
+
+    let content = &mut page[content_start..];
+    if !content.starts_with(b"OpusHead") || content.len() < OUTPUT_GAIN_OFFSET + 2 {
+        return Err(invalid());
+    }
+    content[OUTPUT_GAIN_OFFSET..OUTPUT_GAIN_OFFSET + 2]
+        .copy_from_slice(&header.output_gain().to_le_bytes());
+
+    page[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4].fill(0);
+    let checksum = crc32(&page);
+    page[CHECKSUM_OFFSET..CHECKSUM_OFFSET + 4].copy_from_slice(&checksum.to_le_bytes());
+
+    target.seek(SeekFrom::Start(0))?;
+    target.write_all(&page)
+}
+
+#[napi]
+impl MusicFile {
+    /// Gain in dB applied by every Opus decoder, from the identification header, or `null` for other formats
+    ///
+    /// Includes a change made with {@link setOpusOutputGain} that is not saved yet.
+    #[napi(getter)]
+    pub fn opus_output_gain(&self) -> Option<f64> {
+        self.opus_header
+            .as_ref()
+            .map(|header| f64::from(header.output_gain()) / 256.0)
+    }
+
+    /// Set the output gain of the Opus identification header, written on the next save
+    ///
+    /// Unlike `R128_TRACK_GAIN`, the output gain is applied by every player.
+    /// It is stored in steps of 1/256 dB.
+    ///
+    /// @param gain Gain in dB, from `-128` to `127.99`
+    ///
+    /// @throws If the file is not an Opus file, or the gain is out of range
+    #[napi]
+    pub fn set_opus_output_gain(&mut self, gain: f64) -> Result<()> {
+        if self.opus_header.is_none() {
+            return Err(Error::new(Status::InvalidArg, ERR_NOT_OPUS));
+        }
+        let gain = gain_to_q78(gain).ok_or_else(|| {
+            Error::new(
+                Status::InvalidArg,
+                "Output gain should be in [-128, 127.99] dB",
+            )
+        })?;
+
+        self.record_history();
+        if let Some(header) = self.opus_header.as_mut() {
+            header.output_gain = Some(gain);
+        }
+        Ok(())
+    }
+}
@@ -7,18 +7,16 @@ use crate::music_file::{MusicFile, MusicFileInner};
 impl MusicFile {
     /// Audio quality classification ("HQ", "SQ", or "HiRes")
     ///
-    /// Quality is determined based on file format, sample rate, and bit depth:
+    /// Quality is determined based on codec, sample rate, and bit depth:
     /// - HQ: Lossy formats (MP3, AAC, etc.)
     /// - SQ: Lossless formats at CD quality (44.1kHz, 16-bit)
     /// - HiRes: Lossless formats exceeding CD quality (>44.1kHz, >=16-bit)
+    ///
+    /// ALAC in MP4 is lossless, WavPack hybrid files are lossy. Use {@link classifyQuality} for other
+    /// thresholds, more tiers and the reasons of the classification.
     #[napi(getter, ts_return_type = r#""HQ" | "SQ" | "HiRes""#)]
     pub fn quality(&self) -> &str {
-        let is_lossless = matches!(
-            self.file.file_type(),
-            FileType::Flac | FileType::Ape | FileType::Aiff | FileType::Wav | FileType::WavPack
-        );
-
-        if !is_lossless {
+        if !self.is_lossless() {
             "HQ"
         } else {
             match (self.sample_rate(), self.bit_depth()) {
@@ -38,11 +36,17 @@ impl MusicFile {
 
     /// Audio bit rate in kbps, or `null` if not available
     ///
+    /// For MPEG files loaded with `accurateDuration`, this is the average bitrate of the audio frames.
+    ///
     /// @note If the audio properties don't provide a bitrate, this method calculates
-    /// an approximate bitrate based on file size and duration, including metadata.
+    /// an approximate bitrate based on the size of the audio stream and duration. If the
+    /// audio stream could not be located, the file size is used, including metadata.
     /// The calculated bitrate is constrained between 8 and 10,000 kbps.
     #[napi(getter)]
     pub fn bit_rate(&self) -> Option<u32> {
+        if let Some(frame_scan) = self.mpeg_frame_scan() {
+            return Some(frame_scan.bit_rate);
+        }
         if let Some(bitrate) = self.file.properties().audio_bitrate() {
             return Some(bitrate);
         }
@@ -57,14 +61,15 @@ impl MusicFile {
             return None;
         }
 
-        let file_size_bytes = match &self.inner {
-            MusicFileInner::Buffer { source_len } => *source_len as f64,
+        let size_bytes = match (self.audio_stream_size(), &self.inner) {
+            (Some(audio_size), _) => audio_size,
+            (None, MusicFileInner::Buffer { source_len }) => *source_len as f64,
             #[cfg(not(target_arch = "wasm32"))]
-            MusicFileInner::Path(path) => std::fs::metadata(path)
+            (None, MusicFileInner::Path(path)) => std::fs::metadata(path)
                 .map(|m| m.len() as f64)
                 .unwrap_or(0.0),
         };
-        let bitrate_kbps = ((file_size_bytes * 8.0) / (duration_secs * 1000.0)).round() as u32;
+        let bitrate_kbps = ((size_bytes * 8.0) / (duration_secs * 1000.0)).round() as u32;
 
         (8..=10_000).contains(&bitrate_kbps).then_some(bitrate_kbps)
     }
@@ -86,8 +91,35 @@ impl MusicFile {
     }
 
     /// Audio duration in milliseconds, 0 if not available
+    ///
+    /// See {@link durationMethod} for how it was determined.
     #[napi(getter)]
     pub fn duration(&self) -> f64 {
+        if let Some(frame_scan) = self.mpeg_frame_scan() {
+            return frame_scan.duration;
+        }
         self.file.properties().duration().as_secs_f64() * 1000.0
     }
+
+    /// How {@link duration} and {@link bitRate} were determined
+    ///
+    /// - FrameScan: Every MPEG frame was read, when loaded with `accurateDuration`
+    /// - VbrHeader: Frame count of the Xing, Info or VBRI header of an MPEG stream
+    /// - Estimate: MPEG stream without frame count, estimated from the first frame and the file size
+    /// - Header: Stream headers of other formats
+    #[napi(
+        getter,
+        ts_return_type = r#""FrameScan" | "VbrHeader" | "Estimate" | "Header""#
+    )]
+    pub fn duration_method(&self) -> &str {
+        if self.file.file_type() != FileType::Mpeg {
+            "Header"
+        } else if self.mpeg_frame_scan().is_some() {
+            "FrameScan"
+        } else if self.has_mpeg_frame_count() {
+            "VbrHeader"
+        } else {
+            "Estimate"
+        }
+    }
 }
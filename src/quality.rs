@@ -0,0 +1,264 @@
+use lofty::file::{FileType, TaggedFileExt};
+use napi_derive::napi;
+
+use crate::{music_file::MusicFile, utils::file_type_name};
+
+const DEFAULT_MIN_HI_RES_SAMPLE_RATE: u32 = 48_000;
+const DEFAULT_MIN_HI_RES_BIT_DEPTH: u8 = 24;
+/// Lossless audio below this bit depth is never HiRes, whatever its sample rate
+const MIN_HI_RES_BASE_BIT_DEPTH: u8 = 16;
+
+/// Thresholds and labels of {@link MusicFile.classifyQuality}
+#[napi(object)]
+#[derive(Default)]
+pub struct QualityOptions {
+    /// Lossy files below this bit rate in kbps are "LQ", defaults to `0` so every lossy file is "HQ"
+    pub min_hq_bit_rate: Option<u32>,
+    /// Lossless files with at least this sample rate in Hz are "HiRes", defaults to `48000`
+    pub min_hi_res_sample_rate: Option<u32>,
+    /// Lossless files with at least this bit depth are "HiRes", defaults to `24`
+    pub min_hi_res_bit_depth: Option<u8>,
+    /// Report DSD audio as "DSD" instead of "HiRes", defaults to `true`
+    pub dsd: Option<bool>,
+    /// Report FLAC files tagged by an MQA encoder as "MQA?", defaults to `true`
+    pub mqa: Option<bool>,
+    /// Custom labels of the tiers
+    pub labels: Option<QualityLabels>,
+}
+
+/// Labels of the quality tiers, e.g. `{ sq: "Lossless" }`
+#[napi(object)]
+#[derive(Default)]
+pub struct QualityLabels {
+    /// Lossy below `minHqBitRate`, defaults to `"LQ"`
+    pub lq: Option<String>,
+    /// Lossy, defaults to `"HQ"`
+    pub hq: Option<String>,
+    /// Lossless at CD quality, defaults to `"SQ"`
+    pub sq: Option<String>,
+    /// Lossless beyond CD quality, defaults to `"HiRes"`
+    pub hi_res: Option<String>,
+    /// DSD audio, defaults to `"DSD"`
+    pub dsd: Option<String>,
+    /// Possibly MQA encoded audio, defaults to `"MQA?"`
+    pub mqa: Option<String>,
+}
+
+/// Result of {@link MusicFile.classifyQuality}
+#[napi(object)]
+pub struct QualityClassification {
+    /// Label of the tier, custom labels applied
+    pub label: String,
+    #[napi(ts_type = r#""LQ" | "HQ" | "SQ" | "HiRes" | "DSD" | "MQA?""#)]
+    pub tier: String,
+    pub lossless: bool,
+    /// Why the file was classified this way, e.g. `"ALAC is lossless"`
+    pub reasons: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum QualityTier {
+    Lq,
+    Hq,
+    Sq,
+    HiRes,
+    Dsd,
+    Mqa,
+}
+
+impl QualityTier {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            QualityTier::Lq => "LQ",
+            QualityTier::Hq => "HQ",
+            QualityTier::Sq => "SQ",
+            QualityTier::HiRes => "HiRes",
+            QualityTier::Dsd => "DSD",
+            QualityTier::Mqa => "MQA?",
+        }
+    }
+
+    fn label(self, labels: Option<&QualityLabels>) -> String {
+        let custom = labels.and_then(|labels| match self {
+            QualityTier::Lq => labels.lq.as_ref(),
+            QualityTier::Hq => labels.hq.as_ref(),
+            QualityTier::Sq => labels.sq.as_ref(),
+            QualityTier::HiRes => labels.hi_res.as_ref(),
+            QualityTier::Dsd => labels.dsd.as_ref(),
+            QualityTier::Mqa => labels.mqa.as_ref(),
+        });
+        custom.map_or_else(|| self.name().to_owned(), String::clone)
+    }
+}
+
+impl MusicFile {
+    /// Whether the audio codec is lossless
+    pub(crate) fn is_lossless(&self) -> bool {
+        self.audio_codec().1
+    }
+
+    /// Name of the audio codec, and whether it is lossless
+    fn audio_codec(&self) -> (String, bool) {
+        let codec_info = self.codec_info.as_ref();
+        let file_type = self.file.file_type();
+        let codec = codec_info
+            .map(|info| info.codec.clone())
+            .or_else(|| file_type_name(&file_type).map(str::to_owned))
+            .unwrap_or_else(|| "Unknown".to_owned());
+
+        let lossless = match file_type {
+            FileType::Flac | FileType::Ape | FileType::Aiff => true,
+            // A-law, µ-law and MP3 can be stored in WAV too
+            FileType::Wav => codec_info
+                .and_then(|info| info.wav.as_ref())
+                .is_none_or(|wav| matches!(wav.format_tag, 1 | 3)),
+            FileType::WavPack => {
+                let lossless = codec_info
+                    .and_then(|info| info.wavpack.as_ref())
+                    .is_none_or(|wavpack| wavpack.lossless);
+                if !lossless {
+                    return ("WavPack hybrid".to_owned(), false);
+                }
+                true
+            }
+            FileType::Mp4 => matches!(codec.as_str(), "ALAC" | "FLAC"),
+            _ => false,
+        };
+        (codec, lossless)
+    }
+
+    /// Whether the samples are floating point
+    fn is_float(&self) -> bool {
+        self.codec_info.as_ref().is_some_and(|info| {
+            info.wav.as_ref().is_some_and(|wav| wav.format_tag == 3)
+                || info.wavpack.as_ref().is_some_and(|wavpack| wavpack.float)
+        })
+    }
+
+    fn is_dsd(&self) -> bool {
+        self.codec_info
+            .as_ref()
+            .and_then(|info| info.wavpack.as_ref())
+            .is_some_and(|wavpack| wavpack.dsd)
+    }
+
+    /// Quality tier of the audio, whether it is lossless, and the reasons for both
+    pub(crate) fn classify(&self, options: &QualityOptions) -> (QualityTier, bool, Vec<String>) {
+        let (codec, lossless) = self.audio_codec();
+        let mut reasons = vec![format!(
+            "{codec} is {}",
+            if lossless { "lossless" } else { "lossy" }
+        )];
+
+        if !lossless {
+            let min_bit_rate = options.min_hq_bit_rate.unwrap_or(0);
+            let tier = match self.bit_rate() {
+                Some(bit_rate) if bit_rate < min_bit_rate => {
+                    reasons.push(format!(
+                        "Bit rate {bit_rate} kbps is below {min_bit_rate} kbps"
+                    ));
+                    QualityTier::Lq
+                }
+                Some(bit_rate) if min_bit_rate > 0 => {
+                    reasons.push(format!(
+                        "Bit rate {bit_rate} kbps is at least {min_bit_rate} kbps"
+                    ));
+                    QualityTier::Hq
+                }
+                _ => QualityTier::Hq,
+            };
+            return (tier, false, reasons);
+        }
+
+        if self.is_dsd() {
+            reasons.push("DSD audio".to_owned());
+            if options.dsd.unwrap_or(true) {
+                return (QualityTier::Dsd, true, reasons);
+            }
+            return (QualityTier::HiRes, true, reasons);
+        }
+        if self.is_float() {
+            reasons.push("Floating point samples".to_owned());
+        }
+
+        let min_sample_rate = options
+            .min_hi_res_sample_rate
+            .unwrap_or(DEFAULT_MIN_HI_RES_SAMPLE_RATE);
+        let min_bit_depth = options
+            .min_hi_res_bit_depth
+            .unwrap_or(DEFAULT_MIN_HI_RES_BIT_DEPTH);
+        let mut tier = QualityTier::Sq;
+        match (self.sample_rate(), self.bit_depth()) {
+            (Some(sample_rate), Some(bit_depth)) if bit_depth >= MIN_HI_RES_BASE_BIT_DEPTH => {
+                if sample_rate >= min_sample_rate {
+                    reasons.push(format!(
+                        "Sample rate {sample_rate} Hz is at least {min_sample_rate} Hz"
+                    ));
+                    tier = QualityTier::HiRes;
+                }
+                if bit_depth >= min_bit_depth {
+                    reasons.push(format!(
+                        "Bit depth {bit_depth} is at least {min_bit_depth} bits"
+                    ));
+                    tier = QualityTier::HiRes;
+                }
+                if tier == QualityTier::Sq {
+                    reasons.push(format!(
+                        "{sample_rate} Hz at {bit_depth} bits does not exceed CD quality"
+                    ));
+                }
+            }
+            (_, Some(bit_depth)) => {
+                reasons.push(format!("Bit depth {bit_depth} is below CD quality"));
+            }
+            _ => reasons.push("Bit depth is unknown".to_owned()),
+        }
+
+        if let Some(encoder) = self
+            .flac_blocks
+            .as_ref()
+            .and_then(|blocks| blocks.mqa_encoder())
+        {
+            reasons.push(format!("Tagged by MQA encoder \"{encoder}\""));
+            if let Some(original) = self
+                .flac_blocks
+                .as_ref()
+                .and_then(|blocks| blocks.mqa_original_sample_rate())
+            {
+                reasons.push(format!("MQA original sample rate is {original} Hz"));
+            }
+            if options.mqa.unwrap_or(true) {
+                tier = QualityTier::Mqa;
+            }
+        }
+
+        (tier, true, reasons)
+    }
+}
+
+#[napi]
+impl MusicFile {
+    /// Classify the audio quality with configurable thresholds and labels
+    ///
+    /// Lossless detection is codec-aware: ALAC in MP4 is lossless, WavPack hybrid and A-law or µ-law WAV are lossy.
+    /// Tiers are:
+    /// - LQ: Lossy below `minHqBitRate`
+    /// - HQ: Lossy
+    /// - SQ: Lossless at CD quality
+    /// - HiRes: Lossless with at least `minHiResSampleRate` or `minHiResBitDepth`, including 32-bit float
+    /// - DSD: DSD audio in WavPack
+    /// - MQA?: FLAC tagged by an MQA encoder, the audio itself is not checked
+    ///
+    /// @param options Thresholds and custom labels
+    #[napi]
+    pub fn classify_quality(&self, options: Option<QualityOptions>) -> QualityClassification {
+        let options = options.unwrap_or_default();
+        let (tier, lossless, reasons) = self.classify(&options);
+        QualityClassification {
+            label: tier.label(options.labels.as_ref()),
+            tier: tier.name().to_owned(),
+            lossless,
+            reasons,
+        }
+    }
+}
@@ -0,0 +1,398 @@
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
+
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use lofty::{
+    config::ParseOptions,
+    file::{AudioFile, TaggedFileExt},
+    probe::Probe,
+    tag::Accessor,
+};
+use napi::{
+    bindgen_prelude::AsyncTask,
+    threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode},
+    Env, Error, Result, Status, Task,
+};
+use napi_derive::napi;
+
+#[cfg(target_arch = "wasm32")]
+use crate::music_file::ERR_INVALID_IN_WASM;
+use crate::utils::file_type_name;
+
+#[napi(object)]
+#[derive(Default)]
+pub struct ScanOptions {
+    /// Glob patterns a file must match to be reported, relative to the root.
+    /// Patterns without `/` are matched against the file name only.
+    pub include: Option<Vec<String>>,
+    /// Glob patterns of files and directories to skip, relative to the root.
+    /// Patterns without `/` are matched against the entry name only.
+    pub exclude: Option<Vec<String>>,
+    /// File extensions (without the leading dot) to consider, case-insensitive.
+    /// All files are probed by content if omitted.
+    pub extensions: Option<Vec<String>>,
+    /// Content-detected file types to report, e.g. `["Flac", "Mpeg"]`
+    pub file_types: Option<Vec<String>>,
+    /// Maximum directory depth below the root, `0` only scans the root itself.
+    /// Unlimited if omitted.
+    pub max_depth: Option<u32>,
+    /// Whether to follow symbolic links, defaults to `false`
+    pub follow_symlinks: Option<bool>,
+    /// Whether to read title, artist and album, defaults to `true`
+    pub read_tags: Option<bool>,
+}
+
+#[napi(object)]
+pub struct ScanEntry {
+    /// Full path of the audio file
+    pub path: String,
+    /// File type detected from the file content
+    #[napi(
+        ts_type = r#""Aac" | "Aiff" | "Ape" | "Flac" | "Mpeg" | "Mp4" | "Mpc" | "Opus" | "Vorbis" | "Speex" | "Wav" | "WavPack""#
+    )]
+    pub file_type: String,
+    /// File size in bytes
+    pub size: f64,
+    /// Audio duration in milliseconds
+    pub duration: f64,
+    /// Audio bit rate in kbps
+    pub bit_rate: Option<u32>,
+    /// Audio sample rate in Hz
+    pub sample_rate: Option<u32>,
+    /// Number of audio channels
+    pub channels: Option<u8>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+}
+
+/// Match `text` against a glob `pattern`.
+///
+/// Supports `?` (any character except `/`), `*` (any sequence except `/`) and
+/// `**` (any sequence including `/`). Matching is case-sensitive.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    fn matches(pattern: &[char], text: &[char]) -> bool {
+        match pattern.first() {
+            None => text.is_empty(),
+            Some('*') if pattern.get(1) == Some(&'*') => {
+                // `**/` also matches zero directories
+                let rest = &pattern[2..];
+                if rest.first() == Some(&'/') && matches(&rest[1..], text) {
+                    return true;
+                }
+                (0..=text.len()).any(|i| matches(rest, &text[i..]))
+            }
+            Some('*') => {
+                let rest = &pattern[1..];
+                for i in 0..=text.len() {
+                    if matches(rest, &text[i..]) {
+                        return true;
+                    }
+                    if text.get(i) == Some(&'/') {
+                        break;
+                    }
+                }
+                false
+            }
+            Some('?') => {
+                matches!(text.first(), Some(c) if *c != '/') && matches(&pattern[1..], &text[1..])
+            }
+            Some(c) => text.first() == Some(c) && matches(&pattern[1..], &text[1..]),
+        }
+    }
+
+    matches(&pattern, &text)
+}
+
+fn any_glob_match(patterns: &[String], relative: &str, name: &str) -> bool {
+    patterns.iter().any(|pattern| {
+        if pattern.contains('/') {
+            glob_match(pattern, relative)
+        } else {
+            glob_match(pattern, name)
+        }
+    })
+}
+
+pub(crate) struct Scanner {
+    root: PathBuf,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    extensions: Vec<String>,
+    file_types: Vec<String>,
+    max_depth: Option<u32>,
+    follow_symlinks: bool,
+    read_tags: bool,
+}
+
+impl Scanner {
+    pub(crate) fn new(root: String, options: Option<ScanOptions>) -> Self {
+        let options = options.unwrap_or_default();
+
+        Scanner {
+            root: PathBuf::from(root),
+            include: options.include.unwrap_or_default(),
+            exclude: options.exclude.unwrap_or_default(),
+            extensions: options
+                .extensions
+                .unwrap_or_default()
+                .into_iter()
+                .map(|ext| ext.trim_start_matches('.').to_ascii_lowercase())
+                .collect(),
+            file_types: options
+                .file_types
+                .unwrap_or_default()
+                .into_iter()
+                .map(|file_type| file_type.to_ascii_lowercase())
+                .collect(),
+            max_depth: options.max_depth,
+            follow_symlinks: options.follow_symlinks.unwrap_or(false),
+            read_tags: options.read_tags.unwrap_or(true),
+        }
+    }
+
+    /// Walk the directory tree, calling `on_entry` for every audio file found.
+    /// Returns the number of reported files.
+    pub(crate) fn walk<F>(&self, mut on_entry: F) -> Result<u32>
+    where
+        F: FnMut(ScanEntry),
+    {
+        let metadata = fs::metadata(&self.root).map_err(|error| {
+            Error::new(
+                Status::InvalidArg,
+                format!("Failed accessing '{}': {}", self.root.display(), error),
+            )
+        })?;
+        if !metadata.is_dir() {
+            return Err(Error::new(
+                Status::InvalidArg,
+                format!("'{}' is not a directory", self.root.display()),
+            ));
+        }
+
+        let mut visited = HashSet::new();
+        if let Ok(canonical) = self.root.canonicalize() {
+            visited.insert(canonical);
+        }
+
+        let mut count = 0;
+        self.walk_dir(&self.root, "", 0, &mut visited, &mut |entry| {
+            count += 1;
+            on_entry(entry);
+        });
+
+        Ok(count)
+    }
+
+    fn walk_dir<F>(
+        &self,
+        dir: &Path,
+        relative_dir: &str,
+        depth: u32,
+        visited: &mut HashSet<PathBuf>,
+        on_entry: &mut F,
+    ) where
+        F: FnMut(ScanEntry),
+    {
+        // Unreadable directories below the root are skipped
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return;
+        };
+        let mut entries: Vec<_> = read_dir.filter_map(|entry| entry.ok()).collect();
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let relative = if relative_dir.is_empty() {
+                name.clone()
+            } else {
+                format!("{relative_dir}/{name}")
+            };
+
+            if any_glob_match(&self.exclude, &relative, &name) {
+                continue;
+            }
+
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let metadata = if file_type.is_symlink() {
+                if !self.follow_symlinks {
+                    continue;
+                }
+                match fs::metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                }
+            } else {
+                match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                }
+            };
+
+            if metadata.is_dir() {
+                if self.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+                    continue;
+                }
+                // Guard against symlink cycles
+                if let Ok(canonical) = path.canonicalize() {
+                    if !visited.insert(canonical) {
+                        continue;
+                    }
+                }
+                self.walk_dir(&path, &relative, depth + 1, visited, on_entry);
+            } else if metadata.is_file() {
+                if !self.include.is_empty() && !any_glob_match(&self.include, &relative, &name) {
+                    continue;
+                }
+                if !self.extensions.is_empty() {
+                    let extension = path
+                        .extension()
+                        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+                    if !extension.is_some_and(|ext| self.extensions.contains(&ext)) {
+                        continue;
+                    }
+                }
+                if let Some(entry) = self.probe_entry(&path, metadata.len()) {
+                    on_entry(entry);
+                }
+            }
+        }
+    }
+
+    /// Detect the file type by content and read lightweight metadata.
+    /// Returns `None` for files that are not recognized as audio.
+    fn probe_entry(&self, path: &Path, size: u64) -> Option<ScanEntry> {
+        let reader = BufReader::new(File::open(path).ok()?);
+        let probe = Probe::new(reader)
+            .options(
+                ParseOptions::new()
+                    .read_tags(self.read_tags)
+                    .read_cover_art(false),
+            )
+            .guess_file_type()
+            .ok()?;
+        let file_type = file_type_name(&probe.file_type()?)?;
+
+        if !self.file_types.is_empty()
+            && !self
+                .file_types
+                .iter()
+                .any(|expected| expected.eq_ignore_ascii_case(file_type))
+        {
+            return None;
+        }
+
+        let file = probe.read().ok()?;
+        let properties = file.properties();
+        let tag = file.primary_tag().or_else(|| file.first_tag());
+
+        Some(ScanEntry {
+            path: path.to_string_lossy().into_owned(),
+            file_type: file_type.to_owned(),
+            size: size as f64,
+            duration: properties.duration().as_secs_f64() * 1000.0,
+            bit_rate: properties.audio_bitrate(),
+            sample_rate: properties.sample_rate(),
+            channels: properties.channels(),
+            title: tag.and_then(|tag| tag.title().map(String::from)),
+            artist: tag.and_then(|tag| tag.artist().map(String::from)),
+            album: tag.and_then(|tag| tag.album().map(String::from)),
+        })
+    }
+}
+
+// Free napi functions are not registered in test builds
+#[cfg_attr(test, allow(dead_code))]
+pub struct AsyncScan {
+    scanner: Scanner,
+    on_entry: ThreadsafeFunction<ScanEntry, (), ScanEntry, Status, false>,
+}
+
+#[napi]
+impl Task for AsyncScan {
+    type Output = u32;
+
+    type JsValue = u32;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        self.scanner.walk(|entry| {
+            self.on_entry
+                .call(entry, ThreadsafeFunctionCallMode::Blocking);
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Recursively scan a directory for audio files
+///
+/// Files are detected by their content rather than their extension. Each audio file
+/// is streamed to `onEntry` with lightweight metadata as soon as it is found.
+///
+/// @param root The directory to scan
+/// @param onEntry Called for every audio file found
+/// @param options Filtering and traversal options
+///
+/// @returns The number of audio files found
+///
+/// @throws If the root doesn't exist or isn't a directory
+/// @throws If runs in WebAssembly environments (due to file system restrictions).
+#[napi(ts_return_type = "Promise<number>")]
+#[cfg_attr(test, allow(dead_code))]
+pub fn scan_directory(
+    root: String,
+    on_entry: ThreadsafeFunction<ScanEntry, (), ScanEntry, Status, false>,
+    options: Option<ScanOptions>,
+) -> Result<AsyncTask<AsyncScan>> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (root, on_entry, options);
+        return Err(Error::new(Status::GenericFailure, ERR_INVALID_IN_WASM));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Ok(AsyncTask::new(AsyncScan {
+            scanner: Scanner::new(root, options),
+            on_entry,
+        }))
+    }
+}
+
+/// Recursively scan a directory for audio files
+///
+/// This is the synchronous version of {@link scanDirectory}, returning all entries at once.
+///
+/// @param root The directory to scan
+/// @param options Filtering and traversal options
+///
+/// @throws If the root doesn't exist or isn't a directory
+/// @throws If runs in WebAssembly environments (due to file system restrictions).
+#[napi]
+pub fn scan_directory_sync(root: String, options: Option<ScanOptions>) -> Result<Vec<ScanEntry>> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (root, options);
+        return Err(Error::new(Status::GenericFailure, ERR_INVALID_IN_WASM));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut entries = Vec::new();
+        Scanner::new(root, options).walk(|entry| entries.push(entry))?;
+        Ok(entries)
+    }
+}
@@ -0,0 +1,267 @@
+#![cfg_attr(target_arch = "wasm32", allow(dead_code, unused_imports))]
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use lofty::picture::{MimeType, Picture};
+use napi::{Error, Result, Status};
+use napi_derive::napi;
+
+#[cfg(target_arch = "wasm32")]
+use crate::music_file::ERR_INVALID_IN_WASM;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::music_file::{MusicFileInner, ERR_FILE_LOADED_FROM_BUFFER};
+use crate::{artwork::image_info, meta_picture::CoverType, music_file::MusicFile};
+
+const DEFAULT_TEMPLATE: &str = "{type}";
+
+/// Sidecar image names, without extension, and the picture type they are embedded as.
+/// Earlier names take precedence when several files map to the same type.
+const SIDECAR_NAMES: &[(&str, CoverType)] = &[
+    ("cover", CoverType::Front),
+    ("folder", CoverType::Front),
+    ("front", CoverType::Front),
+    ("albumart", CoverType::Front),
+    ("back", CoverType::Back),
+    ("leaflet", CoverType::Leaflet),
+    ("booklet", CoverType::Leaflet),
+    ("disc", CoverType::Media),
+    ("cd", CoverType::Media),
+    ("media", CoverType::Media),
+    ("artist", CoverType::Artist),
+];
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "bmp", "webp", "tif", "tiff"];
+
+#[napi(object)]
+#[derive(Default)]
+pub struct SidecarArtOptions {
+    /// Replace embedded pictures of the same type, defaults to `false` which keeps them
+    pub replace: Option<bool>,
+}
+
+/// File extension for picture data, detected from the content or the MIME type
+fn picture_extension(pic: &Picture) -> &'static str {
+    let mime_type = image_info(pic.data())
+        .map(|info| info.mime_type)
+        .or_else(|| pic.mime_type().map(|mime| mime.as_str()));
+
+    match mime_type {
+        Some("image/jpeg") => "jpg",
+        Some("image/png") => "png",
+        Some("image/gif") => "gif",
+        Some("image/bmp") => "bmp",
+        Some("image/webp") => "webp",
+        Some("image/tiff") => "tiff",
+        _ => "bin",
+    }
+}
+
+/// Find the sidecar image to use for each picture type in a directory
+#[cfg(not(target_arch = "wasm32"))]
+fn find_sidecar_images(dir: &Path) -> std::io::Result<Vec<(CoverType, PathBuf)>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .collect();
+    entries.sort();
+
+    let mut found: Vec<(usize, CoverType, PathBuf)> = Vec::new();
+    for path in entries {
+        let (Some(stem), Some(extension)) = (
+            path.file_stem().and_then(|stem| stem.to_str()),
+            path.extension().and_then(|extension| extension.to_str()),
+        ) else {
+            continue;
+        };
+        if !IMAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()) {
+            continue;
+        }
+        let stem = stem.to_ascii_lowercase();
+        let Some(rank) = SIDECAR_NAMES.iter().position(|(name, _)| *name == stem) else {
+            continue;
+        };
+
+        let cover_type = SIDECAR_NAMES[rank].1;
+        match found.iter_mut().find(|(_, ty, _)| *ty == cover_type) {
+            Some(existing) if rank < existing.0 => *existing = (rank, cover_type, path),
+            Some(_) => {}
+            None => found.push((rank, cover_type, path)),
+        }
+    }
+
+    Ok(found
+        .into_iter()
+        .map(|(_, cover_type, path)| (cover_type, path))
+        .collect())
+}
+
+#[napi]
+impl MusicFile {
+    /// Write embedded pictures to image files
+    ///
+    /// @param dir Directory to write to, created if missing
+    /// @param template File name without extension, `{type}` is replaced by the picture type
+    /// (`cover`, `back`, `artist`, ...) and `{index}` by the 1-based picture index. Defaults to `"{type}"`.
+    /// The extension is detected from the image data.
+    ///
+    /// @returns Paths of the written files
+    ///
+    /// @throws If the template contains a path separator, or writing fails
+    /// @throws In WebAssembly environments
+    #[napi]
+    pub fn export_pictures(&self, dir: String, template: Option<String>) -> Result<Vec<String>> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = (dir, template);
+            return Err(Error::new(Status::GenericFailure, ERR_INVALID_IN_WASM));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let template = template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+            if template.contains(['/', '\\']) {
+                return Err(Error::new(
+                    Status::InvalidArg,
+                    "Template must be a file name without path separators",
+                ));
+            }
+
+            let pictures = self
+                .tag(|tag| Some(tag.pictures().to_vec()))
+                .unwrap_or_default();
+            if pictures.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let dir = Path::new(&dir);
+            fs::create_dir_all(dir).map_err(|error| {
+                Error::new(
+                    Status::GenericFailure,
+                    format!("Failed creating '{}': {}", dir.display(), error),
+                )
+            })?;
+
+            let mut used: HashSet<String> = HashSet::new();
+            let mut written = Vec::with_capacity(pictures.len());
+            for (index, pic) in pictures.iter().enumerate() {
+                let stem = template
+                    .replace("{type}", CoverType::from_lofty(pic.pic_type()).file_stem())
+                    .replace("{index}", &(index + 1).to_string());
+                let extension = picture_extension(pic);
+
+                // Several pictures of the same type get a numeric suffix
+                let mut name = format!("{stem}.{extension}");
+                let mut suffix = 2;
+                while !used.insert(name.clone()) {
+                    name = format!("{stem}-{suffix}.{extension}");
+                    suffix += 1;
+                }
+
+                let path = dir.join(&name);
+                fs::write(&path, pic.data()).map_err(|error| {
+                    Error::new(
+                        Status::GenericFailure,
+                        format!("Failed writing '{}': {}", path.display(), error),
+                    )
+                })?;
+                written.push(path.to_string_lossy().into_owned());
+            }
+
+            Ok(written)
+        }
+    }
+
+    /// Embed images next to the audio file, such as `cover.jpg` or `folder.png`
+    ///
+    /// `cover`, `folder`, `front` and `albumart` are embedded as front cover, `back` as back cover,
+    /// `leaflet` and `booklet` as leaflet, `disc`, `cd` and `media` as media, and `artist` as artist picture.
+    /// Names are matched case-insensitively, files that are not valid images are ignored.
+    ///
+    /// @param options Whether to replace embedded pictures of the same type
+    ///
+    /// @returns Paths of the embedded image files
+    ///
+    /// @throws If the file was loaded from a buffer
+    /// @throws In WebAssembly environments
+    #[napi]
+    pub fn import_sidecar_art(
+        &mut self,
+        options: Option<SidecarArtOptions>,
+    ) -> Result<Vec<String>> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = options;
+            return Err(Error::new(Status::GenericFailure, ERR_INVALID_IN_WASM));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let replace = options.unwrap_or_default().replace.unwrap_or(false);
+            let path = match &self.inner {
+                MusicFileInner::Buffer { .. } => {
+                    return Err(Error::new(Status::InvalidArg, ERR_FILE_LOADED_FROM_BUFFER));
+                }
+                MusicFileInner::Path(path) => PathBuf::from(path),
+            };
+
+            let dir = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or_else(|| Path::new("."));
+            let candidates = find_sidecar_images(dir).map_err(|error| {
+                Error::new(
+                    Status::GenericFailure,
+                    format!("Failed reading '{}': {}", dir.display(), error),
+                )
+            })?;
+
+            let mut imported: Vec<(Picture, String)> = Vec::new();
+            for (cover_type, image_path) in candidates {
+                let pic_type = cover_type.to_lofty();
+                let exists = self
+                    .tag(|tag| Some(tag.pictures().iter().any(|pic| pic.pic_type() == pic_type)))
+                    .unwrap_or(false);
+                if exists && !replace {
+                    continue;
+                }
+
+                let data = fs::read(&image_path).map_err(|error| {
+                    Error::new(
+                        Status::GenericFailure,
+                        format!("Failed reading '{}': {}", image_path.display(), error),
+                    )
+                })?;
+                let Some(info) = image_info(&data) else {
+                    continue;
+                };
+
+                let picture = Picture::unchecked(data)
+                    .pic_type(pic_type)
+                    .mime_type(MimeType::from_str(info.mime_type))
+                    .build();
+                imported.push((picture, image_path.to_string_lossy().into_owned()));
+            }
+
+            if imported.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let mut paths = Vec::with_capacity(imported.len());
+            self.tag_mut(|tag| {
+                for (picture, image_path) in imported {
+                    tag.remove_picture_type(picture.pic_type());
+                    tag.push_picture(picture);
+                    paths.push(image_path);
+                }
+            })?;
+
+            Ok(paths)
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use lofty::{
+    file::{FileType, TaggedFileExt},
+    tag::{Accessor, ItemKey},
+};
+use napi_derive::napi;
+
+use crate::{
+    meta_picture::{picture_info, PictureInfo},
+    music_file::{
+        helper::read_replay_gain,
+        tag::{comment_text, lyrics_text, star_rating, tag_type_name},
+        MusicFile,
+    },
+    utils::parse_replaygain_value,
+};
+
+#[napi(object)]
+#[derive(Default)]
+pub struct SnapshotOptions {
+    /// Whether to include image data of embedded pictures, defaults to `false`
+    pub include_picture_data: Option<bool>,
+    /// Whether to include the SHA-256 hash of embedded pictures, defaults to `false`
+    pub include_picture_hashes: Option<bool>,
+}
+
+/// Plain object with all common metadata and audio properties of a {@link MusicFile}
+#[napi(object)]
+pub struct MusicFileSnapshot {
+    #[napi(ts_type = r#""AIFF" | "APE" | "ID3V1" | "ID3V2" | "ILST" | "RIFF" | "VORBIS""#)]
+    pub tag_type: Option<String>,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub year: Option<u16>,
+    pub genre: Option<String>,
+    pub track_number: Option<u32>,
+    pub track_total: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub discs_total: Option<u32>,
+    pub comment: Option<String>,
+    pub composer: Option<String>,
+    pub conductor: Option<String>,
+    pub lyricist: Option<String>,
+    pub publisher: Option<String>,
+    pub lyrics: Option<String>,
+    pub copyright: Option<String>,
+    #[napi(ts_type = "1 | 2 | 3 | 4 | 5")]
+    pub rating: Option<u8>,
+    pub track_replay_gain: Option<f64>,
+    pub track_replay_peak: Option<f64>,
+    pub album_replay_gain: Option<f64>,
+    pub album_replay_peak: Option<f64>,
+    /// Audio duration in milliseconds
+    pub duration: f64,
+    /// Audio bit rate in kbps
+    pub bit_rate: Option<u32>,
+    /// Audio sample rate in Hz
+    pub sample_rate: Option<u32>,
+    /// Audio bit depth in bits
+    pub bit_depth: Option<u8>,
+    /// Number of audio channels
+    pub channels: Option<u8>,
+    #[napi(ts_type = r#""HQ" | "SQ" | "HiRes""#)]
+    pub quality: String,
+    /// Embedded pictures, image data and hashes are only included when requested
+    pub pictures: Vec<PictureInfo>,
+}
+
+#[napi]
+impl MusicFile {
+    /// Read all common metadata fields, audio properties and picture descriptors at once
+    ///
+    /// @param options Snapshot options, image data of pictures is omitted by default
+    #[napi]
+    pub fn snapshot(&self, options: Option<SnapshotOptions>) -> MusicFileSnapshot {
+        let options = options.unwrap_or_default();
+        let include_picture_data = options.include_picture_data.unwrap_or(false);
+        let include_picture_hashes = options.include_picture_hashes.unwrap_or(false);
+
+        let tag = self.current_tag();
+        let text = |key: ItemKey| tag.and_then(|tag| tag.get_string(key)).map(String::from);
+        let opus = self.file.file_type() == FileType::Opus;
+        let replay_gain = |item_key: ItemKey, r128_key: ItemKey| {
+            tag.and_then(|tag| read_replay_gain(tag, opus, item_key, r128_key))
+        };
+        let peak = |key: ItemKey| tag.and_then(|tag| parse_replaygain_value(tag.get_string(key)?));
+
+        MusicFileSnapshot {
+            tag_type: tag.and_then(tag_type_name),
+            title: tag.and_then(|tag| tag.title()).map(String::from),
+            artist: tag.and_then(|tag| tag.artist()).map(String::from),
+            album: tag.and_then(|tag| tag.album()).map(String::from),
+            album_artist: text(ItemKey::AlbumArtist),
+            year: tag.and_then(|tag| tag.date()).map(|date| date.year),
+            genre: tag.and_then(|tag| tag.genre()).map(String::from),
+            track_number: tag.and_then(|tag| tag.track()),
+            track_total: tag.and_then(|tag| tag.track_total()),
+            disc_number: tag.and_then(|tag| tag.disk()),
+            discs_total: tag.and_then(|tag| tag.disk_total()),
+            comment: tag.and_then(comment_text),
+            composer: text(ItemKey::Composer),
+            conductor: text(ItemKey::Conductor),
+            lyricist: text(ItemKey::Lyricist),
+            publisher: text(ItemKey::Publisher),
+            lyrics: tag.and_then(lyrics_text),
+            copyright: text(ItemKey::CopyrightMessage),
+            rating: tag.and_then(star_rating),
+            track_replay_gain: replay_gain(ItemKey::ReplayGainTrackGain, ItemKey::R128TrackGain),
+            track_replay_peak: peak(ItemKey::ReplayGainTrackPeak),
+            album_replay_gain: replay_gain(ItemKey::ReplayGainAlbumGain, ItemKey::R128AlbumGain),
+            album_replay_peak: peak(ItemKey::ReplayGainAlbumPeak),
+            duration: self.duration(),
+            bit_rate: self.bit_rate(),
+            sample_rate: self.sample_rate(),
+            bit_depth: self.bit_depth(),
+            channels: self.channels(),
+            quality: self.quality().to_owned(),
+            pictures: tag
+                .map(|tag| {
+                    tag.pictures()
+                        .iter()
+                        .map(|pic| picture_info(pic, include_picture_data, include_picture_hashes))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Plain object representation used by `JSON.stringify`, same as {@link snapshot} without picture data
+    #[napi(js_name = "toJSON")]
+    pub fn to_json(&self) -> MusicFileSnapshot {
+        self.snapshot(None)
+    }
+}
@@ -0,0 +1,170 @@
+use napi::{bindgen_prelude::Null, Either, Error, Result, Status};
+use napi_derive::napi;
+
+use crate::music_file::MusicFile;
+
+/// iTunes comment holding the Sound Check values
+pub(crate) const SOUND_CHECK_KEY: &str = "iTunNORM";
+const SOUND_CHECK_VALUES: usize = 10;
+/// Largest energy ratio written, as iTunes does
+const MAX_ENERGY_RATIO: f64 = 65534.0;
+/// Peak of a full scale sample
+const FULL_SCALE_PEAK: f64 = 32767.0;
+
+/// iTunes Sound Check values of an `iTunNORM` comment
+#[napi(object)]
+pub struct SoundCheck {
+    /// Gain in dB applied by Apple players, from the louder channel
+    pub gain: f64,
+    /// Peak of the louder channel, where `1.0` is full scale
+    pub peak: f64,
+    /// The ten values of the comment
+    pub values: Vec<u32>,
+}
+
+fn parse_values(text: &str) -> Option<[u32; SOUND_CHECK_VALUES]> {
+    let mut values = [0; SOUND_CHECK_VALUES];
+    let mut words = text.split_whitespace();
+    for value in &mut values {
+        *value = u32::from_str_radix(words.next()?, 16).ok()?;
+    }
+    words.next().is_none().then_some(values)
+}
+
+/// Sound Check of an `iTunNORM` comment, `None` if it is malformed
+pub(crate) fn sound_check_from_text(text: &str) -> Option<SoundCheck> {
+    let values = parse_values(text)?;
+    // Energy relative to a 1/1000 W reference, in 1/1000 W and 1/2500 W steps
+    let energy = f64::from(values[0].max(values[1]));
+    let energy = if energy > 0.0 {
+        energy
+    } else {
+        f64::from(values[2].max(values[3])) / 2.5
+    };
+    if energy <= 0.0 {
+        return None;
+    }
+
+    Some(SoundCheck {
+        gain: -10.0 * (energy / 1000.0).log10(),
+        peak: f64::from(values[6].max(values[7])) / FULL_SCALE_PEAK,
+        values: values.to_vec(),
+    })
+}
+
+/// `iTunNORM` comment of a gain in dB and a peak, the same for both channels
+pub(crate) fn sound_check_text(gain: f64, peak: f64) -> Result<String> {
+    if !gain.is_finite() || !peak.is_finite() || peak < 0.0 {
+        return Err(Error::new(
+            Status::InvalidArg,
+            "Sound Check gain and peak must be finite, and the peak not negative",
+        ));
+    }
+
+    let energy = |reference: f64| {
+        (reference * 10f64.powf(-gain / 10.0))
+            .round()
+            .clamp(0.0, MAX_ENERGY_RATIO) as u32
+    };
+    let peak = (peak * FULL_SCALE_PEAK).round().min(f64::from(u32::MAX)) as u32;
+    let values = [
+        energy(1000.0),
+        energy(1000.0),
+        energy(2500.0),
+        energy(2500.0),
+        0,
+        0,
+        peak,
+        peak,
+        0,
+        0,
+    ];
+    Ok(values.iter().map(|value| format!(" {value:08X}")).collect())
+}
+
+#[napi]
+impl MusicFile {
+    /// iTunes Sound Check from the `iTunNORM` comment, or `null` if not set or malformed
+    ///
+    /// Apple players ignore ReplayGain and normalize with Sound Check. It is stored in a freeform atom
+    /// in MP4 files and in a `COMM` frame in ID3v2 tags.
+    #[napi(getter)]
+    pub fn sound_check(&self) -> Option<SoundCheck> {
+        sound_check_from_text(&self.itunes_comment(SOUND_CHECK_KEY)?)
+    }
+
+    /// Set or remove the iTunes Sound Check
+    ///
+    /// Attenuation is limited to about 18 dB, like iTunes does.
+    ///
+    /// @param gain Gain in dB, or `null` to remove the `iTunNORM` comment
+    /// @param peak Peak where `1.0` is full scale, defaults to {@link trackReplayPeak} or `1.0`
+    ///
+    /// @throws If the file is not an MP4 file and has no ID3v2 tag, or a value is invalid
+    #[napi]
+    pub fn set_sound_check(&mut self, gain: Either<f64, Null>, peak: Option<f64>) -> Result<()> {
+        let text = match gain {
+            Either::A(gain) => {
+                let peak = peak.or_else(|| self.track_replay_peak()).unwrap_or(1.0);
+                Some(sound_check_text(gain, peak)?)
+            }
+            Either::B(_) => None,
+        };
+        self.set_itunes_comment(SOUND_CHECK_KEY, text)
+    }
+
+    /// Set the iTunes Sound Check from the track ReplayGain gain and peak
+    ///
+    /// The ReplayGain gain is used as is, so Apple players reach the same loudness as ReplayGain players.
+    ///
+    /// @returns `false` if no track ReplayGain gain is set
+    ///
+    /// @throws If the file is not an MP4 file and has no ID3v2 tag
+    #[napi]
+    pub fn sound_check_from_replay_gain(&mut self) -> Result<bool> {
+        let Some(gain) = self.track_replay_gain() else {
+            return Ok(false);
+        };
+        self.set_sound_check(Either::A(gain), None)?;
+        Ok(true)
+    }
+
+    /// Set the track ReplayGain gain and peak from the iTunes Sound Check
+    ///
+    /// @returns `false` if no valid `iTunNORM` comment is set
+    #[napi]
+    pub fn replay_gain_from_sound_check(&mut self) -> Result<bool> {
+        let Some(sound_check) = self.sound_check() else {
+            return Ok(false);
+        };
+        // Validate both first, so neither is written if the other is invalid
+        self.replay_gain_text(sound_check.gain)?;
+        Self::validate_gain_value(sound_check.peak)?;
+        self.as_history_step(|file| {
+            file.set_track_replay_gain(Either::A(sound_check.gain))?;
+            file.set_track_replay_peak(Either::A(sound_check.peak))
+        })?;
+        Ok(true)
+    }
+}
+
+/// Parse an iTunes `iTunNORM` comment
+///
+/// @param text The ten hexadecimal values of the comment
+///
+/// @returns The Sound Check, or `null` if the text is malformed
+#[napi]
+pub fn parse_sound_check(text: String) -> Option<SoundCheck> {
+    sound_check_from_text(&text)
+}
+
+/// Generate an iTunes `iTunNORM` comment
+///
+/// @param gain Gain in dB
+/// @param peak Peak where `1.0` is full scale
+///
+/// @throws If a value is not finite, or the peak is negative
+#[napi]
+pub fn format_sound_check(gain: f64, peak: f64) -> Result<String> {
+    sound_check_text(gain, peak)
+}
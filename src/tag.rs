@@ -1,21 +1,91 @@
+use lofty::picture::Picture;
 use lofty::tag::{
     items::{
         popularimeter::{Popularimeter, StarRating},
         Timestamp,
     },
-    Accessor, ItemKey, TagType as LoftyTagType,
+    Accessor, ItemKey, Tag, TagType as LoftyTagType,
+};
+use napi::{
+    bindgen_prelude::{Null, PromiseRaw, Reference, Uint8Array},
+    Either, Env, Error, Result, Status,
 };
-use napi::{bindgen_prelude::Null, Either, Error, Result, Status};
 use napi_derive::napi;
 
 use crate::{
-    meta_picture::{from_lofty_picture_slice, to_lofty_picture, MetaPicture},
-    music_file::MusicFile,
-    utils::{format_replaygain_gain, format_replaygain_peak, parse_replaygain_value},
+    artwork::content_hash,
+    meta_picture::{
+        from_lofty_picture, from_lofty_picture_slice, optimize_lofty_pictures, picture_info,
+        spawn_optimize_pictures, to_lofty_picture, CoverType, MetaPicture, OptimizePictureOptions,
+        OptimizePicturesReport, PictureInfo,
+    },
+    music_file::{itunes::is_itunes_comment, MusicFile},
+    utils::{format_replaygain_peak, parse_replaygain_value},
 };
 
 const ERR_INVALID_RATING: &str = "Rating should be integer in [1, 5]";
 
+/// Name of the tag type, as returned by {@link MusicFile.tagType}
+pub(crate) fn tag_type_name(tag: &Tag) -> Option<String> {
+    match tag.tag_type() {
+        LoftyTagType::AiffText => Some("AIFF".to_string()),
+        LoftyTagType::Ape => Some("APE".to_string()),
+        LoftyTagType::Id3v1 => Some("ID3V1".to_string()),
+        LoftyTagType::Id3v2 => Some("ID3V2".to_string()),
+        LoftyTagType::Mp4Ilst => Some("ILST".to_string()),
+        LoftyTagType::RiffInfo => Some("RIFF".to_string()),
+        LoftyTagType::VorbisComments => Some("VORBIS".to_string()),
+        _ => None,
+    }
+}
+
+/// First comment that is not an iTunes comment
+pub(crate) fn comment_text(tag: &Tag) -> Option<String> {
+    tag.get_items(ItemKey::Comment)
+        .find(|item| !is_itunes_comment(item))
+        .and_then(|item| item.value().text())
+        .map(String::from)
+}
+
+/// Unsynchronised lyrics
+pub(crate) fn lyrics_text(tag: &Tag) -> Option<String> {
+    match tag.tag_type() {
+        LoftyTagType::Id3v2 => tag.get_string(ItemKey::UnsyncLyrics).map(String::from),
+        _ => tag.get_string(ItemKey::Lyrics).map(String::from),
+    }
+}
+
+/// First star rating, from 1 to 5
+pub(crate) fn star_rating(tag: &Tag) -> Option<u8> {
+    match tag.ratings().next().map(|p| p.rating) {
+        Some(StarRating::One) => Some(1),
+        Some(StarRating::Two) => Some(2),
+        Some(StarRating::Three) => Some(3),
+        Some(StarRating::Four) => Some(4),
+        Some(StarRating::Five) => Some(5),
+        _ => None,
+    }
+}
+
+impl MusicFile {
+    pub(crate) fn validate_year(year: f64) -> Result<u16> {
+        Ok(Self::validate_integer(year, "Year", 0, u16::MAX as u64)? as u16)
+    }
+
+    pub(crate) fn validate_rating(rating: f64) -> Result<StarRating> {
+        let value = Self::validate_integer(rating, "Rating", 1, 5)
+            .map_err(|_| Error::new(Status::InvalidArg, ERR_INVALID_RATING))?;
+        Ok(match value {
+            1 => StarRating::One,
+            2 => StarRating::Two,
+            3 => StarRating::Three,
+            4 => StarRating::Four,
+            5 => StarRating::Five,
+            _ => unreachable!("rating validation guarantees the range"),
+        })
+    }
+}
+
 #[napi]
 impl MusicFile {
     /// File's metadata tag type, or `null` if not recognized or no available tag
@@ -24,16 +94,7 @@ impl MusicFile {
         ts_return_type = r#""AIFF" | "APE" | "ID3V1" | "ID3V2" | "ILST" | "RIFF" | "VORBIS" | null"#
     )]
     pub fn tag_type(&self) -> Option<String> {
-        self.tag(|tag| match tag.tag_type() {
-            LoftyTagType::AiffText => Some("AIFF".to_string()),
-            LoftyTagType::Ape => Some("APE".to_string()),
-            LoftyTagType::Id3v1 => Some("ID3V1".to_string()),
-            LoftyTagType::Id3v2 => Some("ID3V2".to_string()),
-            LoftyTagType::Mp4Ilst => Some("ILST".to_string()),
-            LoftyTagType::RiffInfo => Some("RIFF".to_string()),
-            LoftyTagType::VorbisComments => Some("VORBIS".to_string()),
-            _ => None,
-        })
+        self.tag(tag_type_name)
     }
 
     /// Title, or `null` if not set or no available tag
@@ -87,9 +148,7 @@ impl MusicFile {
     #[napi(setter)]
     pub fn set_year(&mut self, year: Either<f64, Null>) -> Result<()> {
         let year = match year {
-            Either::A(year) => {
-                Some(Self::validate_integer(year, "Year", 0, u16::MAX as u64)? as u16)
-            }
+            Either::A(year) => Some(Self::validate_year(year)?),
             Either::B(_) => None,
         };
 
@@ -125,12 +184,7 @@ impl MusicFile {
     #[napi(setter)]
     pub fn set_track_number(&mut self, track_number: Either<f64, Null>) -> Result<()> {
         let track_number = match track_number {
-            Either::A(track_number) => Some(Self::validate_integer(
-                track_number,
-                "Track number",
-                0,
-                u32::MAX as u64,
-            )?),
+            Either::A(track_number) => Some(Self::validate_count(track_number, "Track number")?),
             Either::B(_) => None,
         };
 
@@ -149,12 +203,7 @@ impl MusicFile {
     #[napi(setter)]
     pub fn set_disc_number(&mut self, disc_number: Either<f64, Null>) -> Result<()> {
         let disc_number = match disc_number {
-            Either::A(disc_number) => Some(Self::validate_integer(
-                disc_number,
-                "Disc number",
-                0,
-                u32::MAX as u64,
-            )?),
+            Either::A(disc_number) => Some(Self::validate_count(disc_number, "Disc number")?),
             Either::B(_) => None,
         };
 
@@ -173,12 +222,7 @@ impl MusicFile {
     #[napi(setter)]
     pub fn set_track_total(&mut self, track_total: Either<f64, Null>) -> Result<()> {
         let track_total = match track_total {
-            Either::A(track_total) => Some(Self::validate_integer(
-                track_total,
-                "Track total",
-                0,
-                u32::MAX as u64,
-            )?),
+            Either::A(track_total) => Some(Self::validate_count(track_total, "Track total")?),
             Either::B(_) => None,
         };
 
@@ -197,12 +241,7 @@ impl MusicFile {
     #[napi(setter)]
     pub fn set_discs_total(&mut self, discs_total: Either<f64, Null>) -> Result<()> {
         let discs_total = match discs_total {
-            Either::A(discs_total) => Some(Self::validate_integer(
-                discs_total,
-                "Disc total",
-                0,
-                u32::MAX as u64,
-            )?),
+            Either::A(discs_total) => Some(Self::validate_count(discs_total, "Disc total")?),
             Either::B(_) => None,
         };
 
@@ -213,16 +252,28 @@ impl MusicFile {
     }
 
     /// Comment, or `null` if not set or no available tag
+    ///
+    /// iTunes comments of ID3v2 tags, like `iTunNORM`, are not included.
     #[napi(getter)]
     pub fn comment(&self) -> Option<String> {
-        self.tag(|tag| tag.comment().map(String::from))
+        self.tag(comment_text)
     }
 
     #[napi(setter)]
     pub fn set_comment(&mut self, comment: Either<String, Null>) -> Result<()> {
-        self.tag_mut(|tag| match comment {
-            Either::A(c) => tag.set_comment(c),
-            _ => tag.remove_comment(),
+        self.tag_mut(|tag| {
+            let itunes_comments: Vec<_> = tag
+                .get_items(ItemKey::Comment)
+                .filter(|item| is_itunes_comment(item))
+                .cloned()
+                .collect();
+            match comment {
+                Either::A(c) => tag.set_comment(c),
+                _ => tag.remove_comment(),
+            }
+            for item in itunes_comments {
+                tag.push(item);
+            }
         })
     }
 
@@ -286,10 +337,7 @@ impl MusicFile {
     /// Lyrics, or `null` if not set or no available tag
     #[napi(getter)]
     pub fn lyrics(&self) -> Option<String> {
-        self.tag(|tag| match tag.tag_type() {
-            LoftyTagType::Id3v2 => tag.get_string(ItemKey::UnsyncLyrics).map(String::from),
-            _ => tag.get_string(ItemKey::Lyrics).map(String::from),
-        })
+        self.tag(lyrics_text)
     }
 
     #[napi(setter)]
@@ -314,31 +362,13 @@ impl MusicFile {
     /// User star ratings, or `null` if not set or no available tag
     #[napi(getter, ts_return_type = "1 | 2 | 3 | 4 | 5 | null")]
     pub fn rating(&self) -> Option<u8> {
-        self.tag(|tag| match tag.ratings().next().map(|p| p.rating) {
-            Some(StarRating::One) => Some(1),
-            Some(StarRating::Two) => Some(2),
-            Some(StarRating::Three) => Some(3),
-            Some(StarRating::Four) => Some(4),
-            Some(StarRating::Five) => Some(5),
-            _ => None,
-        })
+        self.tag(star_rating)
     }
 
     #[napi(setter)]
     pub fn set_rating(&mut self, rating: Either<f64, Null>) -> Result<()> {
         let star_rating = match rating {
-            Either::A(value) => {
-                let value = Self::validate_integer(value, "Rating", 1, 5)
-                    .map_err(|_| Error::new(Status::InvalidArg, ERR_INVALID_RATING))?;
-                Some(match value {
-                    1 => StarRating::One,
-                    2 => StarRating::Two,
-                    3 => StarRating::Three,
-                    4 => StarRating::Four,
-                    5 => StarRating::Five,
-                    _ => unreachable!("rating validation guarantees the range"),
-                })
-            }
+            Either::A(value) => Some(Self::validate_rating(value)?),
             Either::B(_) => None,
         };
 
@@ -356,17 +386,19 @@ impl MusicFile {
     /// Track replay gain in dB, or `null` if not set or no available tag
     ///
     /// @note Replay gain is used to normalize playback volume across different tracks.
+    /// For Opus files it is read from and written to `R128_TRACK_GAIN`, converted from -23 LUFS to the
+    /// ReplayGain 2.0 reference of -18 LUFS. The header output gain is applied on top, see {@link opusOutputGain}.
     #[napi(getter)]
     pub fn track_replay_gain(&self) -> Option<f64> {
-        self.tag(|tag| parse_replaygain_value(tag.get_string(ItemKey::ReplayGainTrackGain)?))
+        self.replay_gain(ItemKey::ReplayGainTrackGain, ItemKey::R128TrackGain)
     }
 
     #[napi(setter)]
     pub fn set_track_replay_gain(&mut self, track_replay_gain: Either<f64, Null>) -> Result<()> {
-        self.set_gain_value(
+        self.set_replay_gain(
             ItemKey::ReplayGainTrackGain,
+            ItemKey::R128TrackGain,
             track_replay_gain,
-            format_replaygain_gain,
         )
     }
 
@@ -390,17 +422,18 @@ impl MusicFile {
     /// Album replay gain in dB, or `null` if not set or no available tag
     ///
     /// @note Album replay gain normalizes playback volume across different albums.
+    /// For Opus files it is read from and written to `R128_ALBUM_GAIN`, like {@link trackReplayGain}.
     #[napi(getter)]
     pub fn album_replay_gain(&self) -> Option<f64> {
-        self.tag(|tag| parse_replaygain_value(tag.get_string(ItemKey::ReplayGainAlbumGain)?))
+        self.replay_gain(ItemKey::ReplayGainAlbumGain, ItemKey::R128AlbumGain)
     }
 
     #[napi(setter)]
     pub fn set_album_replay_gain(&mut self, album_replay_gain: Either<f64, Null>) -> Result<()> {
-        self.set_gain_value(
+        self.set_replay_gain(
             ItemKey::ReplayGainAlbumGain,
+            ItemKey::R128AlbumGain,
             album_replay_gain,
-            format_replaygain_gain,
         )
     }
 
@@ -451,4 +484,218 @@ impl MusicFile {
             }
         })
     }
+
+    /// Descriptors of the embedded pictures without copying the image data
+    ///
+    /// The list is in the same order as {@link pictures}, use {@link getPictureData} to read the data of one picture
+    /// and {@link getPictureHash} to hash it.
+    #[napi(getter)]
+    pub fn picture_info(&self) -> Vec<PictureInfo> {
+        self.tag(|tag| {
+            Some(
+                tag.pictures()
+                    .iter()
+                    .map(|pic| picture_info(pic, false, false))
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+    }
+
+    /// Image data of the embedded picture at `index`, or `null` if there is no such picture
+    ///
+    /// @param index Index in {@link pictureInfo}
+    #[napi]
+    pub fn get_picture_data(&self, index: u32) -> Option<Uint8Array> {
+        self.tag(|tag| {
+            tag.pictures()
+                .get(index as usize)
+                .map(|pic| pic.data().into())
+        })
+    }
+
+    /// SHA-256 of the image data of the embedded picture at `index` as lowercase hex, or `null` if
+    /// there is no such picture
+    ///
+    /// @param index Index in {@link pictureInfo}
+    #[napi]
+    pub fn get_picture_hash(&self, index: u32) -> Option<String> {
+        self.tag(|tag| {
+            tag.pictures()
+                .get(index as usize)
+                .map(|pic| content_hash(pic.data()))
+        })
+    }
+
+    /// Front cover picture, or `null` if there is none
+    #[napi(getter)]
+    pub fn front_cover(&self) -> Option<MetaPicture> {
+        self.get_pictures_by_type(CoverType::Front)
+            .into_iter()
+            .next()
+    }
+
+    /// Embedded pictures of the given type
+    #[napi]
+    pub fn get_pictures_by_type(&self, cover_type: CoverType) -> Vec<MetaPicture> {
+        let pic_type = cover_type.to_lofty();
+        self.tag(|tag| {
+            Some(
+                tag.pictures()
+                    .iter()
+                    .filter(|pic| pic.pic_type() == pic_type)
+                    .map(from_lofty_picture)
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+    }
+
+    /// Replace all embedded pictures of the given type with one picture
+    ///
+    /// The picture is stored with the given type, regardless of its own `coverType`.
+    /// Pictures of other types are kept.
+    #[napi]
+    pub fn set_picture(&mut self, cover_type: CoverType, picture: &MetaPicture) -> Result<()> {
+        let pic_type = cover_type.to_lofty();
+        let mut new_pic = to_lofty_picture(picture);
+        new_pic.set_pic_type(pic_type);
+
+        self.tag_mut(|tag| {
+            match tag
+                .pictures()
+                .iter()
+                .position(|pic| pic.pic_type() == pic_type)
+            {
+                Some(index) => {
+                    tag.set_picture(index, new_pic);
+                    // Drop any further pictures of the same type, keeping the replaced one
+                    let mut i = tag.picture_count() as usize;
+                    while i > index + 1 {
+                        i -= 1;
+                        if tag.pictures()[i].pic_type() == pic_type {
+                            tag.remove_picture(i);
+                        }
+                    }
+                }
+                None => tag.push_picture(new_pic),
+            }
+        })
+    }
+
+    /// Remove all embedded pictures of the given type
+    ///
+    /// @returns Number of removed pictures
+    #[napi]
+    pub fn remove_picture(&mut self, cover_type: CoverType) -> Result<u32> {
+        let pic_type = cover_type.to_lofty();
+        let count = self
+            .tag(|tag| {
+                Some(
+                    tag.pictures()
+                        .iter()
+                        .filter(|pic| pic.pic_type() == pic_type)
+                        .count() as u32,
+                )
+            })
+            .unwrap_or(0);
+        if count == 0 {
+            return Ok(0);
+        }
+
+        self.tag_mut(|tag| tag.remove_picture_type(pic_type))?;
+        Ok(count)
+    }
+
+    /// Downscale and re-encode all embedded pictures as JPEG, on a worker thread
+    ///
+    /// Downscaled pictures always replace the original. Pictures that already fit are only
+    /// replaced when the re-encoded picture is smaller, and pictures that cannot be decoded are
+    /// kept as they are. The EXIF orientation is applied to the pixels, as the JPEG is written
+    /// without EXIF data. Pictures changed while optimizing are kept. Changes are written on the
+    /// next save.
+    ///
+    /// @param options Maximum dimension and JPEG quality, the size is kept by default
+    ///
+    /// @returns Number of replaced pictures and the picture sizes before and after
+    #[napi(ts_return_type = "Promise<OptimizePicturesReport>")]
+    pub fn optimize_pictures<'env>(
+        &self,
+        env: &'env Env,
+        mut this: Reference<MusicFile>,
+        options: Option<OptimizePictureOptions>,
+    ) -> Result<PromiseRaw<'env, OptimizePicturesReport>> {
+        spawn_optimize_pictures(
+            env,
+            self.current_pictures(),
+            options.unwrap_or_default(),
+            move |pictures, optimized| this.replace_optimized_pictures(&pictures, optimized),
+        )
+    }
+
+    /// Downscale and re-encode all embedded pictures as JPEG
+    ///
+    /// This is the synchronous version of {@link optimizePictures}
+    ///
+    /// @param options Maximum dimension and JPEG quality, the size is kept by default
+    ///
+    /// @returns Number of replaced pictures and the picture sizes before and after
+    #[napi]
+    pub fn optimize_pictures_sync(
+        &mut self,
+        options: Option<OptimizePictureOptions>,
+    ) -> Result<OptimizePicturesReport> {
+        let pictures = self.current_pictures();
+        let optimized = optimize_lofty_pictures(&pictures, &options.unwrap_or_default())?;
+        self.replace_optimized_pictures(&pictures, optimized)
+    }
+}
+
+impl MusicFile {
+    fn current_pictures(&self) -> Vec<Picture> {
+        self.tag(|tag| Some(tag.pictures().to_vec()))
+            .unwrap_or_default()
+    }
+
+    /// Replace the pictures that are still the ones that were optimized
+    fn replace_optimized_pictures(
+        &mut self,
+        pictures: &[Picture],
+        optimized: Vec<Option<Picture>>,
+    ) -> Result<OptimizePicturesReport> {
+        let current = self.current_pictures();
+
+        let mut bytes_before = 0;
+        let mut bytes_after = 0;
+        let mut replacements = Vec::new();
+        for (index, (pic, optimized)) in pictures.iter().zip(optimized).enumerate() {
+            if current.get(index) != Some(pic) {
+                continue;
+            }
+            bytes_before += pic.data().len();
+            match optimized {
+                Some(optimized) => {
+                    bytes_after += optimized.data().len();
+                    replacements.push((index, optimized));
+                }
+                None => bytes_after += pic.data().len(),
+            }
+        }
+
+        let optimized = replacements.len() as u32;
+        if !replacements.is_empty() {
+            self.tag_mut(|tag| {
+                for (index, pic) in replacements {
+                    tag.set_picture(index, pic);
+                }
+            })?;
+        }
+
+        Ok(OptimizePicturesReport {
+            optimized,
+            bytes_before: bytes_before as f64,
+            bytes_after: bytes_after as f64,
+            bytes_saved: bytes_before as f64 - bytes_after as f64,
+        })
+    }
 }
@@ -4,10 +4,30 @@ use napi::Either;
 
 use crate::music_file::MusicFile;
 
+mod artwork;
+mod changes;
+mod codec_info;
+mod copy;
+mod cue;
+mod dedupe;
+mod fields;
 mod file;
+mod file_type;
+mod flac_blocks;
+mod gapless;
+mod history;
+mod layout;
+mod loudness;
 mod meta_picture;
 mod metadata;
+mod ogg_pictures;
+mod opus_header;
 mod properties;
+mod quality;
+mod scan;
+mod sidecar;
+mod snapshot;
+mod sound_check;
 mod tag_type;
 mod utils;
 
@@ -17,10 +37,78 @@ fn samples_dir() -> PathBuf {
 
 fn music_file_from_path(name: &str) -> MusicFile {
     let path = samples_dir().join(name).to_str().unwrap().to_string();
-    MusicFile::load_sync(Either::B(path)).expect("load failed")
+    MusicFile::load_sync(Either::B(path), None).expect("load failed")
 }
 
 fn music_file_from_buffer(name: &str) -> MusicFile {
     let data: Vec<u8> = std::fs::read(samples_dir().join(name)).expect("read failed");
-    MusicFile::load_sync(Either::A(data.into())).expect("load_sync failed")
+    MusicFile::load_sync(Either::A(data.into()), None).expect("load_sync failed")
+}
+
+fn atom(name: &[u8; 4], content: &[u8]) -> Vec<u8> {
+    let mut data = ((content.len() + 8) as u32).to_be_bytes().to_vec();
+    data.extend(name);
+    data.extend(content);
+    data
+}
+
+/// Smallest AAC file lofty reads: one audio track without samples
+fn empty_mp4() -> Vec<u8> {
+    let mut mvhd = vec![0; 4];
+    mvhd.extend([0; 8]);
+    mvhd.extend(1000u32.to_be_bytes()); // Timescale
+    mvhd.extend(0u32.to_be_bytes()); // Duration
+    mvhd.extend([0; 80]);
+
+    let mut mdhd = vec![0; 4];
+    mdhd.extend([0; 8]);
+    mdhd.extend(44_100u32.to_be_bytes());
+    mdhd.extend(0u32.to_be_bytes());
+    mdhd.extend([0; 4]);
+
+    let mut hdlr = vec![0; 8];
+    hdlr.extend(b"soun");
+    hdlr.extend([0; 13]);
+
+    let mut mp4a = vec![0; 6];
+    mp4a.extend(1u16.to_be_bytes()); // Data reference index
+    mp4a.extend([0; 8]);
+    mp4a.extend(2u16.to_be_bytes()); // Channels
+    mp4a.extend(16u16.to_be_bytes()); // Sample size
+    mp4a.extend([0; 4]);
+    mp4a.extend((44_100u32 << 16).to_be_bytes());
+    let mut stsd = vec![0; 4];
+    stsd.extend(1u32.to_be_bytes());
+    stsd.extend(atom(b"mp4a", &mp4a));
+
+    let stbl = atom(b"stbl", &atom(b"stsd", &stsd));
+    let minf = atom(b"minf", &stbl);
+    let mdia = atom(
+        b"mdia",
+        &[atom(b"mdhd", &mdhd), atom(b"hdlr", &hdlr), minf].concat(),
+    );
+    let moov = atom(
+        b"moov",
+        &[atom(b"mvhd", &mvhd), atom(b"trak", &mdia)].concat(),
+    );
+
+    [
+        atom(b"ftyp", b"M4A \0\0\0\0M4A mp42isom"),
+        moov,
+        atom(b"mdat", &[]),
+    ]
+    .concat()
+}
+
+/// Load a file from its bytes
+fn load(data: Vec<u8>) -> MusicFile {
+    MusicFile::load_sync(Either::A(data.into()), None).unwrap()
+}
+
+/// Save a file loaded with [`load`] into `data`, returning the new bytes
+fn save(t: &mut MusicFile, data: Vec<u8>) -> Vec<u8> {
+    match t.save_sync(Some(Either::A(data.into()))).unwrap() {
+        Either::B(buffer) => buffer.to_vec(),
+        Either::A(()) => panic!("expected buffer"),
+    }
 }
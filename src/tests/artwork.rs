@@ -0,0 +1,393 @@
+use std::io::Cursor;
+
+use image::ImageEncoder;
+use lofty::{
+    config::ParseOptions, file::AudioFile, flac::FlacFile, ogg::OggPictureStorage,
+    picture::MimeType,
+};
+use napi::Either;
+
+use crate::{
+    artwork::{content_hash, image_info, recompress_as_jpeg, sniff_mime_type},
+    meta_picture::{to_lofty_picture, CoverType, MetaPicture, OptimizePictureOptions},
+    music_file::MusicFile,
+    tests::music_file_from_buffer,
+};
+
+fn png_header() -> Vec<u8> {
+    let mut data = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    data.extend_from_slice(&13u32.to_be_bytes());
+    data.extend_from_slice(b"IHDR");
+    data.extend_from_slice(&2u32.to_be_bytes());
+    data.extend_from_slice(&3u32.to_be_bytes());
+    // 8 bits per sample, RGBA
+    data.extend_from_slice(&[8, 6, 0, 0, 0]);
+    data.extend_from_slice(&[0; 4]);
+    data
+}
+
+fn gif_header() -> Vec<u8> {
+    let mut data = b"GIF89a".to_vec();
+    data.extend_from_slice(&10u16.to_le_bytes());
+    data.extend_from_slice(&20u16.to_le_bytes());
+    // Global color table with 2^8 entries
+    data.extend_from_slice(&[0xF7, 0, 0]);
+    data
+}
+
+fn bmp_header() -> Vec<u8> {
+    let mut data = vec![0u8; 54];
+    data[..2].copy_from_slice(b"BM");
+    // File size and pixel data offset, right after the headers
+    data[2..6].copy_from_slice(&54u32.to_le_bytes());
+    data[10..14].copy_from_slice(&54u32.to_le_bytes());
+    data[14..18].copy_from_slice(&40u32.to_le_bytes());
+    data[18..22].copy_from_slice(&4i32.to_le_bytes());
+    // Top-down bitmap
+    data[22..26].copy_from_slice(&(-5i32).to_le_bytes());
+    data[26..28].copy_from_slice(&1u16.to_le_bytes());
+    data[28..30].copy_from_slice(&24u16.to_le_bytes());
+    data
+}
+
+fn webp_header() -> Vec<u8> {
+    let mut data = b"RIFF".to_vec();
+    data.extend_from_slice(&22u32.to_le_bytes());
+    data.extend_from_slice(b"WEBPVP8X");
+    data.extend_from_slice(&10u32.to_le_bytes());
+    // Alpha flag and reserved bytes
+    data.extend_from_slice(&[0x10, 0, 0, 0]);
+    data.extend_from_slice(&[99, 0, 0]);
+    data.extend_from_slice(&[49, 0, 0]);
+    data
+}
+
+fn tiff_header() -> Vec<u8> {
+    let mut data = b"II*\0".to_vec();
+    data.extend_from_slice(&8u32.to_le_bytes());
+    data.extend_from_slice(&4u16.to_le_bytes());
+    for (tag, field_type, value) in [(256u16, 3u16, 7u32), (257, 4, 9), (258, 3, 8), (277, 3, 3)] {
+        data.extend_from_slice(&tag.to_le_bytes());
+        data.extend_from_slice(&field_type.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data
+}
+
+// ── MIME sniffing ───────────────────────────────────────────────────────
+
+#[test]
+fn test_sniff_mime_type() {
+    assert_eq!(
+        sniff_mime_type(&[0xFF, 0xD8, 0xFF, 0xE0]),
+        Some("image/jpeg")
+    );
+    assert_eq!(sniff_mime_type(&png_header()), Some("image/png"));
+    assert_eq!(sniff_mime_type(&gif_header()), Some("image/gif"));
+    assert_eq!(sniff_mime_type(&bmp_header()), Some("image/bmp"));
+    assert_eq!(sniff_mime_type(&webp_header()), Some("image/webp"));
+    assert_eq!(sniff_mime_type(&tiff_header()), Some("image/tiff"));
+    assert_eq!(sniff_mime_type(b"MM\0*"), Some("image/tiff"));
+    assert_eq!(sniff_mime_type(&[1, 2, 3]), None);
+    assert_eq!(sniff_mime_type(&[]), None);
+    // `BM` without consistent BMP headers
+    assert_eq!(
+        sniff_mime_type(b"BMW owners club newsletter, issue 12"),
+        None
+    );
+    let mut truncated = bmp_header();
+    truncated.truncate(40);
+    assert_eq!(sniff_mime_type(&truncated), None);
+}
+
+// ── header parsing ──────────────────────────────────────────────────────
+
+#[test]
+fn test_png_info() {
+    let info = image_info(&png_header()).unwrap();
+    assert_eq!((info.width, info.height, info.color_depth), (2, 3, 32));
+    assert_eq!(info.num_colors, 0);
+}
+
+#[test]
+fn test_gif_info() {
+    let info = image_info(&gif_header()).unwrap();
+    assert_eq!((info.width, info.height, info.color_depth), (10, 20, 8));
+    assert_eq!(info.num_colors, 256);
+}
+
+#[test]
+fn test_bmp_info() {
+    let info = image_info(&bmp_header()).unwrap();
+    assert_eq!((info.width, info.height, info.color_depth), (4, 5, 24));
+    assert_eq!(info.num_colors, 0);
+}
+
+#[test]
+fn test_webp_info() {
+    let info = image_info(&webp_header()).unwrap();
+    assert_eq!((info.width, info.height, info.color_depth), (100, 50, 32));
+}
+
+#[test]
+fn test_tiff_info() {
+    let info = image_info(&tiff_header()).unwrap();
+    assert_eq!((info.width, info.height, info.color_depth), (7, 9, 24));
+}
+
+#[test]
+fn test_truncated_header_keeps_mime_type() {
+    let info = image_info(b"GIF89a").unwrap();
+    assert_eq!(info.mime_type, "image/gif");
+    assert_eq!((info.width, info.height), (0, 0));
+}
+
+// ── MetaPicture ─────────────────────────────────────────────────────────
+
+#[test]
+fn test_meta_picture_detects_format() {
+    let meta = MetaPicture::new("image/jpeg".to_string(), gif_header().into(), None, None);
+
+    assert_eq!(meta.mime_type.as_deref(), Some("image/jpeg"));
+    assert_eq!(meta.detected_mime_type().as_deref(), Some("image/gif"));
+    assert_eq!(meta.width(), Some(10));
+    assert_eq!(meta.height(), Some(20));
+    assert_eq!(meta.num_colors(), Some(256));
+}
+
+#[test]
+fn test_meta_picture_keeps_unknown_mime() {
+    let meta = MetaPicture::new(
+        "image/x-custom".to_string(),
+        vec![1u8, 2].into(),
+        None,
+        None,
+    );
+
+    assert_eq!(meta.mime_type.as_deref(), Some("image/x-custom"));
+    assert_eq!(meta.detected_mime_type(), None);
+    assert_eq!(meta.width(), None);
+}
+
+#[test]
+fn test_meta_picture_writes_detected_mime() {
+    // The detected type replaces a mismatching or missing one
+    let mut meta = MetaPicture::new("image/jpeg".to_string(), gif_header().into(), None, None);
+    assert_eq!(to_lofty_picture(&meta).mime_type(), Some(&MimeType::Gif));
+    meta.mime_type = None;
+    assert_eq!(to_lofty_picture(&meta).mime_type(), Some(&MimeType::Gif));
+
+    // The given type is written for unrecognised data
+    let meta = MetaPicture::new(
+        "image/x-custom".to_string(),
+        vec![1u8, 2].into(),
+        None,
+        None,
+    );
+    assert_eq!(
+        to_lofty_picture(&meta).mime_type().map(MimeType::as_str),
+        Some("image/x-custom")
+    );
+
+    let meta = MetaPicture::new("image/bmp".to_string(), bmp_header().into(), None, None);
+    assert_eq!((meta.width(), meta.height()), (Some(4), Some(5)));
+}
+
+#[test]
+fn test_flac_picture_block_dimensions() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let picture = MetaPicture::new("image/gif".to_string(), gif_header().into(), None, None);
+    t.set_pictures(Either::A(vec![&picture])).unwrap();
+
+    let source = std::fs::read(crate::tests::samples_dir().join("flac.flac")).unwrap();
+    let saved = t.save_sync(Some(Either::A(source.into()))).unwrap();
+    let Either::B(saved) = saved else {
+        panic!("expected a buffer");
+    };
+
+    let reloaded = MusicFile::load_sync(Either::A(saved.to_vec().into()), None).unwrap();
+    assert_eq!(reloaded.title(), t.title());
+    assert_eq!(reloaded.artist(), t.artist());
+
+    let flac = FlacFile::read_from(&mut Cursor::new(saved.to_vec()), ParseOptions::new()).unwrap();
+    let (_, info) = &flac.pictures()[0];
+    assert_eq!((info.width, info.height), (10, 20));
+    assert_eq!(info.num_colors, 256);
+}
+
+// ── recompression ───────────────────────────────────────────────────────
+
+fn encoded_png(width: u32, height: u32) -> Vec<u8> {
+    let image = image::RgbaImage::from_fn(width, height, |x, y| {
+        // Noise-like pattern that compresses poorly as PNG
+        let v = (x.wrapping_mul(31) ^ y.wrapping_mul(17)).wrapping_mul(2654435761) >> 24;
+        image::Rgba([v as u8, (v >> 1) as u8, (x + y) as u8, 255])
+    });
+    let mut data = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut data), image::ImageFormat::Png)
+        .unwrap();
+    data
+}
+
+#[test]
+fn test_recompress_downscales_keeping_aspect_ratio() {
+    let data = recompress_as_jpeg(&encoded_png(400, 200), Some(100), 85)
+        .unwrap()
+        .data;
+
+    let info = image_info(&data).unwrap();
+    assert_eq!(info.mime_type, "image/jpeg");
+    assert_eq!((info.width, info.height), (100, 50));
+}
+
+#[test]
+fn test_recompress_keeps_small_images() {
+    let data = recompress_as_jpeg(&encoded_png(40, 30), Some(100), 85)
+        .unwrap()
+        .data;
+
+    let info = image_info(&data).unwrap();
+    assert_eq!((info.width, info.height), (40, 30));
+}
+
+#[test]
+fn test_recompress_applies_exif_orientation() {
+    // TIFF header with a single IFD entry: orientation 6, rotated 90° clockwise
+    let mut exif = b"II*\0".to_vec();
+    exif.extend_from_slice(&8u32.to_le_bytes());
+    exif.extend_from_slice(&1u16.to_le_bytes());
+    exif.extend_from_slice(&0x0112u16.to_le_bytes());
+    exif.extend_from_slice(&3u16.to_le_bytes());
+    exif.extend_from_slice(&1u32.to_le_bytes());
+    exif.extend_from_slice(&[6, 0, 0, 0]);
+    exif.extend_from_slice(&0u32.to_le_bytes());
+
+    let image = image::RgbImage::new(40, 20);
+    let mut data = Vec::new();
+    let mut encoder = image::codecs::png::PngEncoder::new(&mut data);
+    encoder.set_exif_metadata(exif).unwrap();
+    image.write_with_encoder(encoder).unwrap();
+
+    let recompressed = recompress_as_jpeg(&data, None, 85).unwrap();
+
+    let info = image_info(&recompressed.data).unwrap();
+    assert_eq!((info.width, info.height), (20, 40));
+    assert!(!recompressed.resized);
+}
+
+#[test]
+fn test_meta_picture_optimize() {
+    let picture = MetaPicture::new(
+        "image/png".to_string(),
+        encoded_png(300, 300).into(),
+        Some("Cover".to_string()),
+        Some(CoverType::Back),
+    );
+
+    let optimized = picture
+        .optimize_sync(Some(OptimizePictureOptions {
+            max_dimension: Some(64),
+            quality: Some(70),
+        }))
+        .unwrap();
+
+    assert_eq!(optimized.mime_type.as_deref(), Some("image/jpeg"));
+    assert_eq!(optimized.cover_type, "Cover Art (Back)");
+    assert_eq!(optimized.description.as_deref(), Some("Cover"));
+    assert_eq!(
+        (optimized.width(), optimized.height()),
+        (Some(64), Some(64))
+    );
+}
+
+#[test]
+fn test_meta_picture_optimize_invalid() {
+    let picture = MetaPicture::new("image/png".to_string(), vec![1u8, 2].into(), None, None);
+    assert!(picture.optimize_sync(None).is_err());
+
+    let picture = MetaPicture::new(
+        "image/png".to_string(),
+        encoded_png(8, 8).into(),
+        None,
+        None,
+    );
+    let options = OptimizePictureOptions {
+        max_dimension: None,
+        quality: Some(0),
+    };
+    assert!(picture.optimize_sync(Some(options)).is_err());
+    let options = OptimizePictureOptions {
+        max_dimension: Some(0),
+        quality: None,
+    };
+    assert!(picture.optimize_sync(Some(options)).is_err());
+}
+
+#[test]
+fn test_optimize_pictures() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let large = MetaPicture::new(
+        "image/png".to_string(),
+        encoded_png(500, 500).into(),
+        None,
+        None,
+    );
+    let undecodable = MetaPicture::new(
+        "image/png".to_string(),
+        vec![1u8, 2, 3].into(),
+        None,
+        Some(CoverType::Back),
+    );
+    t.set_pictures(Either::A(vec![&large, &undecodable]))
+        .unwrap();
+
+    let report = t
+        .optimize_pictures_sync(Some(OptimizePictureOptions {
+            max_dimension: Some(128),
+            quality: None,
+        }))
+        .unwrap();
+
+    assert_eq!(report.optimized, 1);
+    assert!(report.bytes_saved > 0.0);
+    assert_eq!(report.bytes_before - report.bytes_after, report.bytes_saved);
+    let pictures = t.pictures().unwrap();
+    assert_eq!(pictures[0].mime_type.as_deref(), Some("image/jpeg"));
+    assert_eq!(pictures[0].width(), Some(128));
+    assert_eq!(pictures[1].data.as_ref(), &[1u8, 2, 3]);
+}
+
+#[test]
+fn test_optimize_pictures_replaces_downscaled_even_if_larger() {
+    // A plain PNG is much smaller than its JPEG encoding
+    let mut plain = Vec::new();
+    image::RgbImage::from_pixel(1000, 1000, image::Rgb([200, 40, 40]))
+        .write_to(&mut Cursor::new(&mut plain), image::ImageFormat::Png)
+        .unwrap();
+    let plain = MetaPicture::new("image/png".to_string(), plain.into(), None, None);
+
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_pictures(Either::A(vec![&plain])).unwrap();
+    let report = t.optimize_pictures_sync(None).unwrap();
+    assert_eq!(report.optimized, 0);
+
+    let report = t
+        .optimize_pictures_sync(Some(OptimizePictureOptions {
+            max_dimension: Some(900),
+            quality: Some(100),
+        }))
+        .unwrap();
+    assert_eq!(report.optimized, 1);
+    assert!(report.bytes_saved < 0.0);
+    assert_eq!(t.pictures().unwrap()[0].width(), Some(900));
+}
+
+#[test]
+fn test_content_hash() {
+    assert_eq!(
+        content_hash(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
@@ -0,0 +1,134 @@
+use napi::{bindgen_prelude::Either3, Either};
+
+use crate::{
+    meta_picture::MetaPicture,
+    music_file::{LoadOptions, MusicFile},
+    tests::{music_file_from_buffer, music_file_from_path, samples_dir},
+};
+
+// ── dirty tracking ──────────────────────────────────────────────────────
+
+#[test]
+fn test_loaded_file_is_clean() {
+    let t = music_file_from_buffer("flac.flac");
+    assert!(!t.is_dirty());
+    assert!(t.changes().is_empty());
+}
+
+#[test]
+fn test_changes_report_old_and_new_values() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let old_title = t.title().unwrap();
+    t.set_title(Either::A("Changed Title".to_string())).unwrap();
+    t.set_track_number(Either::A(9.0)).unwrap();
+
+    assert!(t.is_dirty());
+    let changes = t.changes();
+    let title = changes
+        .iter()
+        .find(|change| change.field == "title")
+        .unwrap();
+    assert!(matches!(&title.old_value, Some(Either3::A(value)) if *value == old_title));
+    assert!(matches!(&title.new_value, Some(Either3::A(value)) if value == "Changed Title"));
+    assert_eq!(title.tag_type.as_deref(), Some("VORBIS"));
+    assert!(changes.iter().any(|change| change.field == "trackNumber"));
+}
+
+#[test]
+fn test_restoring_value_makes_file_clean() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let old_title = t.title().unwrap();
+    t.set_title(Either::A("Changed Title".to_string())).unwrap();
+    t.set_title(Either::A(old_title)).unwrap();
+
+    assert!(!t.is_dirty());
+    assert!(t.changes().is_empty());
+}
+
+#[test]
+fn test_picture_changes() {
+    let mut t = music_file_from_buffer("mp3.mp3");
+    let picture = MetaPicture::new("image/png".to_string(), vec![1u8, 2, 3].into(), None, None);
+    t.set_pictures(Either::A(vec![&picture])).unwrap();
+
+    let changes = t.changes();
+    let pictures = changes
+        .iter()
+        .find(|change| change.field == "pictures")
+        .unwrap();
+    assert!(matches!(&pictures.new_value, Some(Either3::C(infos)) if infos[0].size == 3));
+}
+
+#[test]
+fn test_revert_restores_loaded_tags() {
+    let mut t = music_file_from_buffer("mp3-no-tags.mp3");
+    t.set_title(Either::A("Changed Title".to_string())).unwrap();
+    assert!(t.is_dirty());
+
+    t.revert();
+    assert!(!t.is_dirty());
+    assert_eq!(t.title(), None);
+    assert_eq!(t.tag_type(), None);
+}
+
+#[test]
+fn test_save_in_place_makes_file_clean() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("saved.flac");
+    std::fs::copy(samples_dir().join("flac.flac"), &path).unwrap();
+
+    let mut t = music_file_from_path(path.to_str().unwrap());
+    t.set_title(Either::A("Saved Title".to_string())).unwrap();
+    t.set_flac_padding(100).unwrap();
+    t.save_sync(None).unwrap();
+    assert!(!t.is_dirty());
+    assert!(t.changes().is_empty());
+    assert_eq!(t.flac_metadata().unwrap().padding, 100);
+
+    t.revert();
+    assert_eq!(t.title().as_deref(), Some("Saved Title"));
+
+    t.set_artist(Either::A("Saved Artist".to_string())).unwrap();
+    let changes = t.changes();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].field, "artist");
+}
+
+#[test]
+fn test_save_in_place_reloads_with_load_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("skipped.mp3");
+    std::fs::copy(samples_dir().join("mp3-no-tags.mp3"), &path).unwrap();
+
+    let options = LoadOptions {
+        skip_pictures: Some(true),
+        ..Default::default()
+    };
+    let mut t =
+        MusicFile::load_sync(Either::B(path.to_str().unwrap().to_string()), Some(options)).unwrap();
+    let picture = MetaPicture::new("image/png".to_string(), vec![1u8].into(), None, None);
+    t.set_pictures(Either::A(vec![&picture])).unwrap();
+    t.save_sync(None).unwrap();
+
+    // The saved pictures are skipped when the file is read back
+    assert!(t.pictures().is_none());
+    t.set_title(Either::A("Title".to_string())).unwrap();
+    assert!(t.save_sync(None).is_err());
+}
+
+#[test]
+fn test_clean_save_in_place_does_not_write() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("clean.flac");
+    std::fs::copy(samples_dir().join("flac.flac"), &path).unwrap();
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    let mut t = music_file_from_path(path.to_str().unwrap());
+    t.save_sync(None).unwrap();
+
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().modified().unwrap(),
+        modified
+    );
+}
@@ -0,0 +1,201 @@
+use napi::Either;
+
+use crate::{
+    music_file::{LoadOptions, MusicFile},
+    tests::{music_file_from_buffer, samples_dir},
+};
+
+/// Two MPEG-1 Layer III frames, the first with a Xing header and LAME extension
+fn mp3_with_lame_header() -> Vec<u8> {
+    // 128 kbps, 44.1 kHz, joint stereo, original
+    const HEADER: [u8; 4] = [0xFF, 0xFB, 0x90, 0x64];
+    const FRAME_LEN: usize = 417;
+
+    let mut xing = b"Xing".to_vec();
+    xing.extend(0xFu32.to_be_bytes());
+    xing.extend(100u32.to_be_bytes());
+    xing.extend(41_700u32.to_be_bytes());
+    xing.extend([0; 100]);
+    xing.extend(50u32.to_be_bytes());
+
+    let mut lame = b"LAME3.100".to_vec();
+    // Revision 0, VBR method 4 and a 19.5 kHz lowpass
+    lame.extend([0x04, 195]);
+    lame.extend([0; 10]);
+    // Encoder delay 576 and padding 1000, 12 bits each
+    lame.extend([0x24, 0x03, 0xE8]);
+    xing.extend(lame);
+
+    let mut frame = HEADER.to_vec();
+    frame.resize(36, 0);
+    frame.extend(xing);
+    frame.resize(FRAME_LEN, 0);
+
+    let mut data = frame;
+    data.extend(HEADER);
+    data.resize(FRAME_LEN * 2, 0);
+    data
+}
+
+/// VBR stream of 128 and 320 kbps frames without a Xing header, between an ID3v2 tag with
+/// 10 KiB of padding and an ID3v1 tag, and 3 junk bytes after the fifth frame
+fn mp3_without_vbr_header() -> Vec<u8> {
+    // 44.1 kHz, stereo
+    const FRAMES: [([u8; 4], usize); 2] = [
+        ([0xFF, 0xFB, 0x90, 0x00], 417),
+        ([0xFF, 0xFB, 0xE0, 0x00], 1044),
+    ];
+
+    let mut data = b"ID3\x04\x00\x00".to_vec();
+    // 10240 as a syncsafe integer
+    data.extend([0x00, 0x00, 0x50, 0x00]);
+    data.resize(10 + 10240, 0);
+
+    for i in 0..20 {
+        let (header, len) = FRAMES[i % 2];
+        let start = data.len();
+        data.extend(header);
+        data.resize(start + len, 0x55);
+        if i == 4 {
+            data.extend([0; 3]);
+        }
+    }
+
+    data.extend(b"TAG");
+    data.resize(data.len() + 125, 0);
+    data
+}
+
+#[test]
+fn test_codec_info_mpeg() {
+    let t = music_file_from_buffer("mp3.mp3");
+
+    let info = t.codec_info().expect("codec info");
+    assert_eq!(info.codec, "MP3");
+    let mpeg = info.mpeg.expect("MPEG info");
+    assert_eq!(mpeg.version, "1");
+    assert_eq!(mpeg.layer, 3);
+    let vbr_header = mpeg.vbr_header.expect("VBR header");
+    assert_eq!(vbr_header.kind, "Info");
+    assert!(vbr_header.frames.is_some_and(|frames| frames > 0));
+    assert_eq!(vbr_header.lame.expect("LAME header").encoder, "Lavf");
+    assert!(info.opus.is_none());
+}
+
+#[test]
+fn test_codec_info_lame_header() {
+    let data = mp3_with_lame_header();
+    let t = MusicFile::load_sync(Either::A(data.into()), None).unwrap();
+
+    let mpeg = t.codec_info().unwrap().mpeg.unwrap();
+    assert_eq!(mpeg.channel_mode, "Joint Stereo");
+    assert_eq!(mpeg.mode_extension, Some(2));
+    assert!(mpeg.original);
+    assert!(!mpeg.copyright);
+    assert_eq!(mpeg.emphasis, None);
+
+    let vbr_header = mpeg.vbr_header.unwrap();
+    assert_eq!(vbr_header.kind, "Xing");
+    assert_eq!(vbr_header.frames, Some(100));
+    assert_eq!(vbr_header.bytes, Some(41_700));
+    assert_eq!(vbr_header.quality, Some(50));
+
+    let lame = vbr_header.lame.unwrap();
+    assert_eq!(lame.encoder, "LAME3.100");
+    assert_eq!(lame.vbr_method, "VBR");
+    assert_eq!(lame.lowpass, Some(19_500));
+    assert_eq!(lame.encoder_delay, 576);
+    assert_eq!(lame.encoder_padding, 1000);
+}
+
+#[test]
+fn test_codec_info_opus() {
+    let t = music_file_from_buffer("ogg.opus");
+
+    let info = t.codec_info().unwrap();
+    assert_eq!(info.codec, "Opus");
+    let opus = info.opus.unwrap();
+    assert_eq!(opus.version, 1);
+    assert_eq!(opus.pre_skip, 312);
+    assert_eq!(opus.input_sample_rate, 48_000);
+    assert_eq!(opus.output_gain, 0.0);
+    assert_eq!(opus.channel_mapping_family, 0);
+}
+
+#[test]
+fn test_codec_info_wav() {
+    let t = music_file_from_buffer("wav.wav");
+
+    let info = t.codec_info().unwrap();
+    assert_eq!(info.codec, "PCM");
+    let wav = info.wav.unwrap();
+    // 32-bit float, stored as WAVE_FORMAT_EXTENSIBLE
+    assert_eq!(wav.format_tag, 3);
+    assert_eq!(wav.channel_mask, Some(0b11));
+}
+
+#[test]
+fn test_codec_info_flac() {
+    let t = music_file_from_buffer("flac.flac");
+
+    let info = t.codec_info().unwrap();
+    assert_eq!(info.codec, "FLAC");
+    assert!(info.mpeg.is_none() && info.wav.is_none());
+}
+
+#[test]
+fn test_mpeg_frame_scan() {
+    let data = mp3_without_vbr_header();
+    let estimated = MusicFile::load_sync(Either::A(data.clone().into()), None).unwrap();
+    assert_eq!(estimated.duration_method(), "Estimate");
+    assert!(estimated
+        .codec_info()
+        .unwrap()
+        .mpeg
+        .unwrap()
+        .frame_scan
+        .is_none());
+
+    let options = LoadOptions {
+        accurate_duration: Some(true),
+        ..Default::default()
+    };
+    let t = MusicFile::load_sync(Either::A(data.into()), Some(options)).unwrap();
+    assert_eq!(t.duration_method(), "FrameScan");
+
+    let scan = t.codec_info().unwrap().mpeg.unwrap().frame_scan.unwrap();
+    assert_eq!(scan.frames, 20);
+    assert_eq!(scan.bytes, (10 * 417 + 10 * 1044) as f64);
+    let duration = 20.0 * 1152.0 * 1000.0 / 44100.0;
+    assert!((scan.duration - duration).abs() < 1e-9);
+    assert_eq!(scan.bit_rate, 224);
+
+    assert_eq!(t.duration(), scan.duration);
+    assert_eq!(t.bit_rate(), Some(224));
+}
+
+#[test]
+fn test_mpeg_frame_scan_skips_info_frame() {
+    let options = LoadOptions {
+        accurate_duration: Some(true),
+        ..Default::default()
+    };
+    let data = std::fs::read(samples_dir().join("mp3.mp3")).unwrap();
+    let t = MusicFile::load_sync(Either::A(data.into()), Some(options)).unwrap();
+
+    let mpeg = t.codec_info().unwrap().mpeg.unwrap();
+    let scan = mpeg.frame_scan.expect("frame scan");
+    assert_eq!(Some(scan.frames), mpeg.vbr_header.unwrap().frames);
+    let default = music_file_from_buffer("mp3.mp3");
+    assert_eq!(default.duration_method(), "VbrHeader");
+    assert!((t.duration() - default.duration()).abs() < 100.0);
+
+    // Other formats ignore the option
+    let options = LoadOptions {
+        accurate_duration: Some(true),
+        ..Default::default()
+    };
+    let data = std::fs::read(samples_dir().join("flac.flac")).unwrap();
+    let t = MusicFile::load_sync(Either::A(data.into()), Some(options)).unwrap();
+    assert_eq!(t.duration_method(), "Header");
+}
@@ -0,0 +1,150 @@
+use lofty::tag::ItemKey;
+use napi::{bindgen_prelude::Null, Either};
+
+use crate::{
+    music_file::{copy::CopyTagsOptions, LoadOptions, MusicFile},
+    tests::{empty_mp4, music_file_from_buffer, music_file_from_path, samples_dir},
+};
+
+#[test]
+fn test_copy_flac_to_mp3() {
+    let mut source = music_file_from_path("flac.flac");
+    source
+        .set_title(Either::A("Copied Title".to_string()))
+        .unwrap();
+    source
+        .set_lyrics(Either::A("Line 1\nLine 2".to_string()))
+        .unwrap();
+    source.set_track_replay_gain(Either::A(-6.5)).unwrap();
+    let mut target = music_file_from_buffer("mp3-no-tags.mp3");
+
+    let report = target.copy_tags_from(&source, None).unwrap();
+
+    assert!(report.copied.contains(&"TrackTitle".to_string()));
+    assert!(report.copied.contains(&"Lyrics".to_string()));
+    assert_eq!(target.title().as_deref(), Some("Copied Title"));
+    assert_eq!(target.lyrics().as_deref(), Some("Line 1\nLine 2"));
+    assert_eq!(target.track_replay_gain(), Some(-6.5));
+    assert_eq!(target.artist(), source.artist());
+    assert!(target.is_dirty());
+}
+
+#[test]
+fn test_copy_replaces_existing_values() {
+    let mut source = music_file_from_buffer("flac.flac");
+    source.set_artist(Either::A("Source".to_string())).unwrap();
+    let mut target = music_file_from_buffer("mp3.mp3");
+    target.set_artist(Either::A("Target".to_string())).unwrap();
+
+    target.copy_tags_from(&source, None).unwrap();
+
+    assert_eq!(target.artist().as_deref(), Some("Source"));
+}
+
+#[test]
+fn test_copy_include_exclude() {
+    let mut source = music_file_from_buffer("flac.flac");
+    source.set_title(Either::A("Title".to_string())).unwrap();
+    source.set_album(Either::A("Album".to_string())).unwrap();
+    let mut target = music_file_from_buffer("mp3-no-tags.mp3");
+
+    let report = target
+        .copy_tags_from(
+            &source,
+            Some(CopyTagsOptions {
+                include: Some(vec!["TrackTitle".to_string(), "AlbumTitle".to_string()]),
+                exclude: Some(vec!["AlbumTitle".to_string()]),
+                pictures: Some(false),
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(report.copied, vec!["TrackTitle".to_string()]);
+    assert_eq!(report.pictures, 0);
+    assert_eq!(target.title().as_deref(), Some("Title"));
+    assert_eq!(target.album(), None);
+}
+
+#[test]
+fn test_copy_pictures() {
+    let source = music_file_from_buffer("mp3.mp3");
+    let source_count = source.pictures().map_or(0, |pictures| pictures.len());
+    let mut target = music_file_from_buffer("flac.flac");
+
+    let report = target.copy_tags_from(&source, None).unwrap();
+
+    assert_eq!(report.pictures as usize, source_count);
+    assert_eq!(
+        target.pictures().map_or(0, |pictures| pictures.len()),
+        source_count
+    );
+}
+
+#[test]
+fn test_copy_is_undoable() {
+    let mut source = music_file_from_buffer("flac.flac");
+    source.set_title(Either::A("Copied".to_string())).unwrap();
+    let mut target = music_file_from_buffer("mp3.mp3");
+    let old_title = target.title();
+
+    target.copy_tags_from(&source, None).unwrap();
+    assert!(target.undo());
+
+    assert_eq!(target.title(), old_title);
+}
+
+#[test]
+fn test_copy_replay_gain_to_opus() {
+    let mut source = music_file_from_buffer("flac.flac");
+    source.set_track_replay_gain(Either::A(-6.5)).unwrap();
+    source.set_album_replay_gain(Either::B(Null)).unwrap();
+    let mut target = music_file_from_buffer("ogg.opus");
+
+    let report = target.copy_tags_from(&source, None).unwrap();
+
+    assert!(report.copied.contains(&"ReplayGainTrackGain".to_string()));
+    assert_eq!(target.track_replay_gain(), Some(-6.5));
+    let r128 = target.tag(|tag| tag.get_string(ItemKey::R128TrackGain).map(str::to_owned));
+    assert!(r128.is_some());
+    let gain = target.tag(|tag| {
+        tag.get_string(ItemKey::ReplayGainTrackGain)
+            .map(str::to_owned)
+    });
+    assert_eq!(gain, None);
+}
+
+#[test]
+fn test_copy_reports_custom_fields() {
+    let mut source = MusicFile::load_sync(Either::A(empty_mp4().into()), None).unwrap();
+    source.set_sound_check(Either::A(-3.0), None).unwrap();
+    let mut target = music_file_from_buffer("flac.flac");
+
+    let report = target.copy_tags_from(&source, None).unwrap();
+    assert!(report.unsupported.contains(&"iTunNORM".to_string()));
+
+    let options = CopyTagsOptions {
+        exclude: Some(vec!["iTunNORM".to_string()]),
+        ..Default::default()
+    };
+    let report = target.copy_tags_from(&source, Some(options)).unwrap();
+    assert!(report.unsupported.is_empty());
+}
+
+#[test]
+fn test_copy_from_skipped_pictures() {
+    let data = std::fs::read(samples_dir().join("mp3.mp3")).unwrap();
+    let options = LoadOptions {
+        skip_pictures: Some(true),
+        ..Default::default()
+    };
+    let source = MusicFile::load_sync(Either::A(data.into()), Some(options)).unwrap();
+    let mut target = music_file_from_buffer("flac.flac");
+
+    assert!(target.copy_tags_from(&source, None).is_err());
+
+    let options = CopyTagsOptions {
+        pictures: Some(false),
+        ..Default::default()
+    };
+    assert!(target.copy_tags_from(&source, Some(options)).is_ok());
+}
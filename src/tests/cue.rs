@@ -0,0 +1,314 @@
+use std::io::Cursor;
+
+use lofty::{
+    config::{ParseOptions, WriteOptions},
+    file::AudioFile,
+    flac::FlacFile,
+    tag::TagExt,
+};
+use napi::Either;
+
+use crate::{
+    music_file::{
+        cue::{format_cue_sheet, parse_cue_sheet, CueSheet},
+        MusicFile,
+    },
+    tests::{
+        flac_blocks::{cue_sheet, flac_with_blocks},
+        music_file_from_buffer, music_file_from_path, samples_dir,
+    },
+};
+
+const CUE: &str = r#"REM GENRE "Progressive Rock"
+REM DATE 1999
+CATALOG 1234567890123
+PERFORMER "The Band"
+TITLE "The Album"
+FILE "flac.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "First"
+    ISRC USABC1234567
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Second"
+    PERFORMER "Guest"
+    FLAGS DCP PRE
+    INDEX 00 01:59:50
+    INDEX 01 02:00:00
+  TRACK 03 AUDIO
+    TITLE "Third"
+    SONGWRITER "Writer"
+    INDEX 01 04:00:37
+"#;
+
+fn parse(text: &str) -> CueSheet {
+    parse_cue_sheet(Either::B(text.to_string())).unwrap()
+}
+
+fn load(data: Vec<u8>) -> MusicFile {
+    MusicFile::load_sync(Either::A(data.into()), None).unwrap()
+}
+
+/// FLAC sample with a `CUESHEET` Vorbis comment
+fn flac_with_cue_comment(text: &str) -> Vec<u8> {
+    let data = std::fs::read(samples_dir().join("flac.flac")).unwrap();
+    let mut flac =
+        FlacFile::read_from(&mut Cursor::new(data.clone()), ParseOptions::new()).unwrap();
+    let comments = flac.vorbis_comments_mut().unwrap();
+    comments.push("CUESHEET".to_string(), text.to_string());
+
+    let mut output = Cursor::new(data);
+    comments
+        .save_to(&mut output, WriteOptions::default())
+        .unwrap();
+    output.into_inner()
+}
+
+#[test]
+fn test_parse_cue_sheet() {
+    let cue = parse(CUE);
+
+    assert_eq!(cue.title.as_deref(), Some("The Album"));
+    assert_eq!(cue.performer.as_deref(), Some("The Band"));
+    assert_eq!(cue.catalog.as_deref(), Some("1234567890123"));
+    assert_eq!(cue.genre.as_deref(), Some("Progressive Rock"));
+    assert_eq!(cue.date.as_deref(), Some("1999"));
+    assert_eq!(cue.tracks.len(), 3);
+
+    let first = &cue.tracks[0];
+    assert_eq!(first.number, 1);
+    assert_eq!(first.data_type, "AUDIO");
+    assert_eq!(first.file.as_deref(), Some("flac.flac"));
+    assert_eq!(first.file_type.as_deref(), Some("WAVE"));
+    assert_eq!(first.isrc.as_deref(), Some("USABC1234567"));
+
+    let second = &cue.tracks[1];
+    assert_eq!(second.performer.as_deref(), Some("Guest"));
+    assert_eq!(second.flags, vec!["DCP", "PRE"]);
+    assert_eq!(second.indices.len(), 2);
+    assert_eq!(second.indices[1].time, 120_000.0);
+    assert_eq!(cue.tracks[2].indices[0].time, 240_000.0 + 37_000.0 / 75.0);
+}
+
+#[test]
+fn test_parse_cue_sheet_bytes() {
+    let mut data = b"\xEF\xBB\xBFTITLE \"Caf\xC3\xA9\"\n".to_vec();
+    let cue = parse_cue_sheet(Either::A(data.clone().into())).unwrap();
+    assert_eq!(cue.title.as_deref(), Some("Café"));
+
+    // Not UTF-8, read as Latin-1
+    data = b"TITLE \"Caf\xE9\"\r\n".to_vec();
+    let cue = parse_cue_sheet(Either::A(data.into())).unwrap();
+    assert_eq!(cue.title.as_deref(), Some("Café"));
+}
+
+#[test]
+fn test_parse_cue_sheet_errors() {
+    let result = parse_cue_sheet(Either::B(
+        "TRACK 01 AUDIO\n  INDEX 01 00:61:00\n".to_string(),
+    ));
+    let error = result.err().unwrap();
+    assert!(error.reason.contains("line 2"), "{}", error.reason);
+
+    assert!(parse_cue_sheet(Either::B("INDEX 01 00:00:00".to_string())).is_err());
+    assert!(parse_cue_sheet(Either::B("TRACK AA AUDIO".to_string())).is_err());
+}
+
+#[test]
+fn test_parse_cue_sheet_long_time() {
+    let cue = parse_cue_sheet(Either::B(
+        "FILE \"a.flac\" WAVE\nTRACK 01 AUDIO\n  INDEX 01 4294967295:59:74\n".to_string(),
+    ))
+    .unwrap();
+    let time = cue.tracks[0].indices[0].time;
+    assert!(time > 4294967295.0 * 60_000.0, "{time}");
+}
+
+#[test]
+fn test_format_cue_sheet() {
+    let formatted = format_cue_sheet(parse(CUE));
+    assert!(formatted.contains("REM GENRE \"Progressive Rock\"\nREM DATE 1999\n"));
+    assert!(formatted.contains("FILE \"flac.flac\" WAVE\n  TRACK 01 AUDIO\n"));
+    assert!(formatted.contains("    INDEX 01 04:00:37\n"));
+    assert_eq!(formatted.matches("FILE ").count(), 1);
+
+    // Formatting is stable through a round trip
+    assert_eq!(format_cue_sheet(parse(&formatted)), formatted);
+}
+
+#[test]
+fn test_cue_tracks() {
+    let t = music_file_from_buffer("flac.flac");
+
+    let tracks = t.cue_tracks(Some(parse(CUE))).unwrap();
+    assert_eq!(tracks.len(), 3);
+    assert_eq!(tracks[0].start, 0.0);
+    assert_eq!(tracks[0].end, 120_000.0);
+    assert_eq!(tracks[0].duration, 120_000.0);
+    assert_eq!(tracks[0].performer.as_deref(), Some("The Band"));
+    assert_eq!(tracks[0].album.as_deref(), Some("The Album"));
+    assert_eq!(tracks[0].isrc.as_deref(), Some("USABC1234567"));
+    assert_eq!(tracks[1].performer.as_deref(), Some("Guest"));
+    assert_eq!(tracks[2].end, t.duration());
+    assert_eq!(tracks[2].duration, t.duration() - tracks[2].start);
+}
+
+#[test]
+fn test_cue_tracks_several_files() {
+    let cue = parse(
+        "FILE \"other.flac\" WAVE\n  TRACK 01 AUDIO\n    INDEX 01 00:00:00\n\
+         FILE \"flac.flac\" WAVE\n  TRACK 02 AUDIO\n    INDEX 01 00:00:00\n\
+         TRACK 03 AUDIO\n    INDEX 01 01:00:00\n",
+    );
+
+    // Only a file loaded from a path can be matched by name
+    assert!(music_file_from_buffer("flac.flac")
+        .cue_tracks(Some(cue.clone()))
+        .is_err());
+
+    let tracks = music_file_from_path("flac.flac")
+        .cue_tracks(Some(cue))
+        .unwrap();
+    let numbers: Vec<u32> = tracks.iter().map(|track| track.number).collect();
+    assert_eq!(numbers, vec![2, 3]);
+    assert_eq!(tracks[0].end, 60_000.0);
+}
+
+#[test]
+fn test_cue_tracks_without_cue_sheet() {
+    assert!(music_file_from_buffer("flac.flac")
+        .cue_tracks(None)
+        .is_err());
+    assert!(music_file_from_buffer("mp3.mp3").cue_tracks(None).is_err());
+}
+
+#[test]
+fn test_embedded_cue_sheet_comment() {
+    let data = flac_with_cue_comment(CUE);
+    let mut t = load(data.clone());
+
+    let cue = t.embedded_cue_sheet().expect("embedded cue sheet");
+    assert_eq!(cue.title.as_deref(), Some("The Album"));
+    assert_eq!(t.cue_tracks(None).unwrap().len(), 3);
+
+    // lofty drops the comment from the tag, it must be kept when saving
+    t.set_title(Either::A("New title".to_string())).unwrap();
+    let saved = match t.save_sync(Some(Either::A(data.into()))).unwrap() {
+        Either::B(buffer) => buffer.to_vec(),
+        Either::A(()) => panic!("expected buffer"),
+    };
+    let reloaded = load(saved);
+    assert_eq!(reloaded.title().as_deref(), Some("New title"));
+    assert_eq!(reloaded.embedded_cue_sheet().unwrap().tracks.len(), 3);
+}
+
+#[test]
+fn test_embedded_cue_sheet_block() {
+    let t = load(flac_with_blocks(&[cue_sheet()]));
+
+    let cue = t.embedded_cue_sheet().expect("embedded cue sheet");
+    assert_eq!(cue.catalog.as_deref(), Some("1234567890123"));
+    // The lead-out track is omitted
+    assert_eq!(cue.tracks.len(), 1);
+    assert_eq!(cue.tracks[0].isrc.as_deref(), Some("USABC1234567"));
+    assert_eq!(cue.tracks[0].flags, vec!["PRE"]);
+    assert_eq!(cue.tracks[0].indices[1].time, 588.0 * 1000.0 / 44100.0);
+
+    assert!(music_file_from_buffer("flac.flac")
+        .embedded_cue_sheet()
+        .is_none());
+}
+
+#[test]
+fn test_embedded_cue_sheet_ape_item() {
+    let mut data = std::fs::read(samples_dir().join("mp3-no-tags.mp3")).unwrap();
+
+    // APEv2 tag with a footer and a single `CUESHEET` text item
+    let mut item = (CUE.len() as u32).to_le_bytes().to_vec();
+    item.extend(0u32.to_le_bytes());
+    item.extend(b"Cuesheet\0");
+    item.extend(CUE.as_bytes());
+    data.extend(&item);
+    data.extend(b"APETAGEX");
+    data.extend(2000u32.to_le_bytes());
+    data.extend((item.len() as u32 + 32).to_le_bytes());
+    data.extend(1u32.to_le_bytes());
+    data.extend(0u32.to_le_bytes());
+    data.extend([0; 8]);
+
+    let t = load(data);
+    let cue = t.embedded_cue_sheet().expect("embedded cue sheet");
+    assert_eq!(cue.title.as_deref(), Some("The Album"));
+    assert_eq!(cue.tracks.len(), 3);
+
+    assert!(music_file_from_buffer("mp3-no-tags.mp3")
+        .embedded_cue_sheet()
+        .is_none());
+}
+
+#[test]
+fn test_embedded_cue_sheet_id3v2_frame() {
+    // ID3v2.3 tag with a UTF-8 `TXXX` frame described `CUESHEET`
+    let mut frame = vec![3];
+    frame.extend(b"CUESHEET\0");
+    frame.extend(CUE.as_bytes());
+    let mut tag = b"TXXX".to_vec();
+    tag.extend((frame.len() as u32).to_be_bytes());
+    tag.extend([0, 0]);
+    tag.extend(frame);
+    // Synchsafe tag size
+    let size = tag.len() as u32;
+    let mut data = b"ID3\x03\0\0".to_vec();
+    data.extend([21, 14, 7, 0].map(|shift| (size >> shift & 0x7F) as u8));
+    data.extend(tag);
+    data.extend(std::fs::read(samples_dir().join("mp3-no-tags.mp3")).unwrap());
+
+    let t = load(data);
+    let cue = t.embedded_cue_sheet().expect("embedded cue sheet");
+    assert_eq!(cue.performer.as_deref(), Some("The Band"));
+    assert_eq!(cue.tracks.len(), 3);
+}
+
+#[test]
+fn test_apply_cue_track() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let cue = parse(CUE);
+
+    let changed = t.apply_cue_track(cue.clone(), 2).unwrap();
+    assert!(changed.contains(&"title".to_string()));
+    assert_eq!(t.title().as_deref(), Some("Second"));
+    assert_eq!(t.artist().as_deref(), Some("Guest"));
+    assert_eq!(t.album().as_deref(), Some("The Album"));
+    assert_eq!(t.album_artist().as_deref(), Some("The Band"));
+    assert_eq!(t.genre().as_deref(), Some("Progressive Rock"));
+    assert_eq!(t.year(), Some(1999));
+    assert_eq!(t.track_number(), Some(2));
+    assert_eq!(t.track_total(), Some(3));
+
+    t.apply_cue_track(cue.clone(), 3).unwrap();
+    assert_eq!(t.artist().as_deref(), Some("The Band"));
+    assert_eq!(t.composer().as_deref(), Some("Writer"));
+
+    assert!(t.apply_cue_track(cue, 4).is_err());
+}
+
+#[test]
+fn test_read_sidecar_cue_sheet() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("album.flac");
+    std::fs::copy(samples_dir().join("flac.flac"), &path).unwrap();
+    let t = MusicFile::load_sync(Either::B(path.to_str().unwrap().to_string()), None).unwrap();
+
+    assert!(t.read_sidecar_cue_sheet().unwrap().is_none());
+
+    std::fs::write(dir.path().join("album.flac.cue"), CUE).unwrap();
+    let cue = t
+        .read_sidecar_cue_sheet()
+        .unwrap()
+        .expect("sidecar cue sheet");
+    assert_eq!(cue.tracks.len(), 3);
+
+    assert!(music_file_from_buffer("flac.flac")
+        .read_sidecar_cue_sheet()
+        .is_err());
+}
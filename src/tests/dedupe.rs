@@ -0,0 +1,83 @@
+use napi::{bindgen_prelude::Null, Either};
+
+use crate::{
+    meta_picture::{CoverType, MetaPicture},
+    music_file::dedupe::group_pictures,
+    tests::music_file_from_buffer,
+};
+
+fn picture(data: &[u8], cover_type: CoverType) -> MetaPicture {
+    MetaPicture::new(
+        "image/png".to_string(),
+        data.to_vec().into(),
+        None,
+        Some(cover_type),
+    )
+}
+
+#[test]
+fn test_meta_picture_hash() {
+    let a = picture(&[1, 2, 3], CoverType::Front);
+    let b = picture(&[1, 2, 3], CoverType::Back);
+    let c = picture(&[3, 2, 1], CoverType::Front);
+
+    assert_eq!(a.hash(), b.hash());
+    assert_ne!(a.hash(), c.hash());
+    assert_eq!(a.hash().len(), 64);
+}
+
+#[test]
+fn test_dedupe_pictures_keeps_front_cover() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let other = picture(&[1, 2, 3], CoverType::Other);
+    let back = picture(&[4, 5], CoverType::Back);
+    let front = picture(&[1, 2, 3], CoverType::Front);
+    let other_back = picture(&[4, 5], CoverType::Back);
+    t.set_pictures(Either::A(vec![&other, &back, &front, &other_back]))
+        .unwrap();
+
+    assert_eq!(t.dedupe_pictures().unwrap(), 2);
+
+    let pictures = t.pictures().unwrap();
+    assert_eq!(pictures.len(), 2);
+    assert_eq!(pictures[0].cover_type, "Cover Art (Back)");
+    assert_eq!(pictures[1].cover_type, "Cover Art (Front)");
+    assert_eq!(pictures[1].data.as_ref(), &[1u8, 2, 3]);
+    assert_eq!(t.dedupe_pictures().unwrap(), 0);
+}
+
+#[test]
+fn test_dedupe_pictures_without_pictures() {
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_pictures(Either::B(Null)).unwrap();
+    let before = t.is_dirty();
+
+    assert_eq!(t.dedupe_pictures().unwrap(), 0);
+    assert_eq!(t.is_dirty(), before);
+}
+
+#[test]
+fn test_group_pictures() {
+    let mut first = music_file_from_buffer("flac.flac");
+    let mut second = music_file_from_buffer("mp3.mp3");
+    let cover = picture(&[1, 2, 3], CoverType::Front);
+    let artist = picture(&[7], CoverType::Artist);
+    first
+        .set_pictures(Either::A(vec![&cover, &artist]))
+        .unwrap();
+    second.set_pictures(Either::A(vec![&cover])).unwrap();
+
+    let groups = group_pictures(vec![&first, &second]);
+
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].hash, cover.hash());
+    assert_eq!(groups[0].size, 3);
+    let occurrences: Vec<(u32, u32)> = groups[0]
+        .occurrences
+        .iter()
+        .map(|occurrence| (occurrence.file_index, occurrence.picture_index))
+        .collect();
+    assert_eq!(occurrences, vec![(0, 0), (1, 0)]);
+    assert_eq!(groups[1].occurrences.len(), 1);
+    assert_eq!(groups[1].occurrences[0].cover_type, "Cover Art (Artist)");
+}
@@ -0,0 +1,117 @@
+use napi::{bindgen_prelude::Null, Either};
+
+use crate::{music_file::fields::MetadataPatch, tests::music_file_from_buffer};
+
+// ── bulk update ─────────────────────────────────────────────────────────
+
+#[test]
+fn test_update_applies_all_fields() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let old_artist = t.artist();
+
+    let changed = t
+        .update(MetadataPatch {
+            title: Some(Either::A("Patched Title".to_string())),
+            artist: old_artist.clone().map(Either::A),
+            track_number: Some(Either::A(7.0)),
+            rating: Some(Either::A(4.0)),
+            track_replay_gain: Some(Either::A(-6.5)),
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(t.title().as_deref(), Some("Patched Title"));
+    assert_eq!(t.artist(), old_artist);
+    assert_eq!(t.track_number(), Some(7));
+    assert_eq!(t.rating(), Some(4));
+    assert_eq!(t.track_replay_gain(), Some(-6.5));
+    // unchanged values are not reported
+    assert!(!changed.contains(&"artist".to_string()));
+    assert!(changed.contains(&"title".to_string()));
+    assert!(changed.contains(&"trackNumber".to_string()));
+}
+
+#[test]
+fn test_update_removes_null_fields() {
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_comment(Either::A("To be removed".to_string()))
+        .unwrap();
+
+    let changed = t
+        .update(MetadataPatch {
+            comment: Some(Either::B(Null)),
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(changed, vec!["comment".to_string()]);
+    assert_eq!(t.comment(), None);
+}
+
+#[test]
+fn test_update_is_all_or_nothing() {
+    let mut t = music_file_from_buffer("mp3.mp3");
+    let old_title = t.title();
+
+    let error = t
+        .update(MetadataPatch {
+            title: Some(Either::A("Never Written".to_string())),
+            track_number: Some(Either::A(1.5)),
+            rating: Some(Either::A(9.0)),
+            album_replay_gain: Some(Either::A(f64::NAN)),
+            ..Default::default()
+        })
+        .unwrap_err();
+
+    assert!(error.reason.contains("Track number"));
+    assert!(error.reason.contains("Rating"));
+    assert!(error.reason.contains("ReplayGain"));
+    assert_eq!(t.title(), old_title);
+}
+
+#[test]
+fn test_update_is_one_history_step() {
+    let mut t = music_file_from_buffer("ogg.opus");
+    let old_title = t.title();
+    let old_track_number = t.track_number();
+
+    // Opus gains are range checked before anything is written
+    assert!(t
+        .update(MetadataPatch {
+            title: Some(Either::A("Never Written".to_string())),
+            track_replay_gain: Some(Either::A(500.0)),
+            ..Default::default()
+        })
+        .is_err());
+    assert!(!t.can_undo());
+
+    t.update(MetadataPatch {
+        title: Some(Either::A("Patched Title".to_string())),
+        track_number: Some(Either::A(12.0)),
+        track_replay_gain: Some(Either::A(-2.0)),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(t.track_replay_gain(), Some(-2.0));
+
+    assert!(t.undo());
+    assert!(!t.can_undo());
+    assert_eq!(t.title(), old_title);
+    assert_eq!(t.track_number(), old_track_number);
+}
+
+#[test]
+fn test_update_without_changes() {
+    let mut t = music_file_from_buffer("flac.flac");
+
+    assert!(t.update(MetadataPatch::default()).unwrap().is_empty());
+    let unchanged = t
+        .update(MetadataPatch {
+            title: t.title().map(Either::A),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(unchanged.is_empty());
+    assert!(!t.can_undo());
+    assert!(!t.is_dirty());
+}
@@ -1,10 +1,80 @@
-use napi::{bindgen_prelude::Uint8Array, Either};
+use napi::{
+    bindgen_prelude::{Null, Uint8Array},
+    Either,
+};
 
 use crate::{
-    music_file::MusicFile,
-    tests::{music_file_from_buffer, music_file_from_path, samples_dir},
+    meta_picture::MetaPicture,
+    music_file::{LoadOptions, MusicFile},
+    tests::{empty_mp4, load, music_file_from_buffer, music_file_from_path, samples_dir, save},
 };
 
+// ── load options ───────────────────────────────────────────────────────
+
+#[test]
+fn test_load_skip_pictures() {
+    let buffer = std::fs::read(samples_dir().join("flac.flac")).unwrap();
+    let mut t = MusicFile::load_sync(Either::A(Uint8Array::with_data_copied(&buffer)), None)
+        .expect("load_sync failed");
+    let pic = MetaPicture::new("image/png".to_string(), vec![1, 2, 3].into(), None, None);
+    t.set_pictures(Either::A(vec![&pic])).unwrap();
+    let saved = match t
+        .save_sync(Some(Either::A(Uint8Array::with_data_copied(&buffer))))
+        .expect("save_sync failed")
+    {
+        Either::B(buf) => buf.to_vec(),
+        Either::A(()) => panic!("expected buffer"),
+    };
+
+    let options = LoadOptions {
+        skip_pictures: Some(true),
+        ..Default::default()
+    };
+    let mut t = MusicFile::load_sync(Either::A(saved.clone().into()), Some(options))
+        .expect("load_sync failed");
+
+    assert!(t.pictures().is_none());
+    assert!(t.picture_info().is_empty());
+    assert_eq!(t.title(), music_file_from_buffer("flac.flac").title());
+    assert!(t.save_sync(Some(Either::A(saved.into()))).is_err());
+}
+
+#[test]
+fn test_load_skip_pictures_saves_files_without_pictures() {
+    let skip_pictures = || LoadOptions {
+        skip_pictures: Some(true),
+        ..Default::default()
+    };
+    for name in ["flac.flac", "mp3.mp3", "ogg.opus", "wav.wav"] {
+        let buffer = std::fs::read(samples_dir().join(name)).unwrap();
+        assert!(music_file_from_buffer(name).pictures().is_some(), "{name}");
+        let mut t =
+            MusicFile::load_sync(Either::A(buffer.clone().into()), Some(skip_pictures())).unwrap();
+        assert!(
+            t.save_sync(Some(Either::A(buffer.clone().into()))).is_err(),
+            "{name}"
+        );
+
+        let mut t = MusicFile::load_sync(Either::A(buffer.clone().into()), None).unwrap();
+        t.set_pictures(Either::B(Null)).unwrap();
+        let Either::B(without_pictures) = t.save_sync(Some(Either::A(buffer.into()))).unwrap()
+        else {
+            panic!("expected buffer");
+        };
+        let without_pictures = without_pictures.to_vec();
+        let mut t = MusicFile::load_sync(
+            Either::A(without_pictures.clone().into()),
+            Some(skip_pictures()),
+        )
+        .unwrap();
+        assert!(
+            t.save_sync(Some(Either::A(without_pictures.into())))
+                .is_ok(),
+            "{name}"
+        );
+    }
+}
+
 // ── load from path ─────────────────────────────────────────────────────
 
 #[test]
@@ -78,7 +148,7 @@ fn test_dont_load_garbage_buffer() {
 #[test]
 fn test_mp3_buffer_save_round_trip() {
     let buffer: Vec<u8> = std::fs::read(samples_dir().join("mp3.mp3")).expect("read failed");
-    let mut t = MusicFile::load_sync(Either::A(Uint8Array::with_data_copied(&buffer)))
+    let mut t = MusicFile::load_sync(Either::A(Uint8Array::with_data_copied(&buffer)), None)
         .expect("load_sync failed");
     t.set_title(Either::A("Rust Test Title".to_string()))
         .unwrap();
@@ -87,7 +157,7 @@ fn test_mp3_buffer_save_round_trip() {
         panic!("save_sync did not return a buffer");
     };
 
-    let t2 = MusicFile::load_sync(Either::A(saved_buf)).expect("load_sync failed");
+    let t2 = MusicFile::load_sync(Either::A(saved_buf), None).expect("load_sync failed");
     assert_eq!(t2.title().as_deref(), Some("Rust Test Title"));
 }
 
@@ -95,7 +165,7 @@ fn test_mp3_buffer_save_round_trip() {
 fn test_mp3_insert_new_tag_save_round_trip() {
     let buffer: Vec<u8> =
         std::fs::read(samples_dir().join("mp3-no-tags.mp3")).expect("read failed");
-    let mut t = MusicFile::load_sync(Either::A(Uint8Array::with_data_copied(&buffer)))
+    let mut t = MusicFile::load_sync(Either::A(Uint8Array::with_data_copied(&buffer)), None)
         .expect("load_sync failed");
     t.set_title(Either::A("Rust Test Title".to_string()))
         .unwrap();
@@ -104,14 +174,39 @@ fn test_mp3_insert_new_tag_save_round_trip() {
         panic!("save_sync did not return a buffer");
     };
 
-    let t2 = MusicFile::load_sync(Either::A(saved_buf)).expect("load_sync failed");
+    let t2 = MusicFile::load_sync(Either::A(saved_buf), None).expect("load_sync failed");
     assert_eq!(t2.title().as_deref(), Some("Rust Test Title"));
 }
 
+#[test]
+fn test_mp4_mapped_freeform_atoms_round_trip() {
+    // Both are freeform atoms that lofty maps to item keys
+    let data = empty_mp4();
+    let mut t = load(data.clone());
+    t.set_track_replay_gain(Either::A(-6.0)).unwrap();
+    t.set_conductor(Either::A("Old".to_string())).unwrap();
+    let data = save(&mut t, data);
+
+    let mut t = load(data.clone());
+    assert_eq!(t.track_replay_gain(), Some(-6.0));
+    assert_eq!(t.conductor().as_deref(), Some("Old"));
+    t.set_track_replay_gain(Either::A(-3.0)).unwrap();
+    t.set_conductor(Either::B(Null)).unwrap();
+    let data = save(&mut t, data);
+
+    let mut t = load(data.clone());
+    assert_eq!(t.track_replay_gain(), Some(-3.0));
+    assert_eq!(t.conductor(), None);
+    t.set_track_replay_gain(Either::B(Null)).unwrap();
+    let data = save(&mut t, data);
+
+    assert_eq!(load(data).track_replay_gain(), None);
+}
+
 #[test]
 fn test_flac_buffer_save_round_trip() {
     let buffer: Vec<u8> = std::fs::read(samples_dir().join("flac.flac")).expect("read failed");
-    let mut t = MusicFile::load_sync(Either::A(Uint8Array::with_data_copied(&buffer)))
+    let mut t = MusicFile::load_sync(Either::A(Uint8Array::with_data_copied(&buffer)), None)
         .expect("load_sync failed");
     t.set_title(Either::A("FLAC Rust Title".to_string()))
         .unwrap();
@@ -120,14 +215,14 @@ fn test_flac_buffer_save_round_trip() {
         panic!("save_sync did not return a buffer");
     };
 
-    let t2 = MusicFile::load_sync(Either::A(saved_buf)).expect("load_sync failed");
+    let t2 = MusicFile::load_sync(Either::A(saved_buf), None).expect("load_sync failed");
     assert_eq!(t2.title().as_deref(), Some("FLAC Rust Title"));
 }
 
 #[test]
 fn test_ogg_buffer_save_round_trip() {
     let buffer: Vec<u8> = std::fs::read(samples_dir().join("ogg.opus")).expect("read failed");
-    let mut t = MusicFile::load_sync(Either::A(Uint8Array::with_data_copied(&buffer)))
+    let mut t = MusicFile::load_sync(Either::A(Uint8Array::with_data_copied(&buffer)), None)
         .expect("load_sync failed");
     t.set_title(Either::A("OGG Rust Title".to_string()))
         .unwrap();
@@ -136,6 +231,6 @@ fn test_ogg_buffer_save_round_trip() {
         panic!("save_sync did not return a buffer");
     };
 
-    let t2 = MusicFile::load_sync(Either::A(saved_buf)).expect("load_sync failed");
+    let t2 = MusicFile::load_sync(Either::A(saved_buf), None).expect("load_sync failed");
     assert_eq!(t2.title().as_deref(), Some("OGG Rust Title"));
 }
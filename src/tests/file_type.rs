@@ -0,0 +1,147 @@
+use napi::Either;
+
+use crate::{
+    music_file::file_type::detect_file_type,
+    tests::{music_file_from_buffer, music_file_from_path, samples_dir},
+};
+
+#[test]
+fn test_file_type() {
+    let cases = [
+        ("mp3.mp3", "Mpeg", "mp3", "audio/mpeg"),
+        ("flac.flac", "Flac", "flac", "audio/flac"),
+        ("ogg.opus", "Opus", "opus", "audio/ogg"),
+        ("wav.wav", "Wav", "wav", "audio/wav"),
+    ];
+
+    for (name, file_type, extension, mime_type) in cases {
+        let t = music_file_from_buffer(name);
+        assert_eq!(t.file_type(), file_type, "{name}");
+        assert_eq!(t.extension(), extension, "{name}");
+        assert_eq!(t.mime_type(), mime_type, "{name}");
+    }
+}
+
+#[test]
+fn test_file_type_differs_from_tag_type() {
+    let mp3 = music_file_from_buffer("mp3.mp3");
+    let wav = music_file_from_buffer("wav.wav");
+
+    assert_eq!(mp3.tag_type(), wav.tag_type());
+    assert_ne!(mp3.file_type(), wav.file_type());
+}
+
+#[test]
+fn test_detect_file_type_from_buffer() {
+    let data = std::fs::read(samples_dir().join("flac.flac")).unwrap();
+
+    // Only the header is needed
+    let info = detect_file_type(Either::A(data[..1024].to_vec().into()))
+        .unwrap()
+        .expect("detected type");
+    assert_eq!(info.file_type, "Flac");
+    assert_eq!(info.extension, "flac");
+    assert_eq!(info.mime_type, "audio/flac");
+
+    let unknown = detect_file_type(Either::A(vec![0u8; 64].into())).unwrap();
+    assert!(unknown.is_none());
+}
+
+#[test]
+fn test_detect_file_type_from_path() {
+    let path = |name: &str| samples_dir().join(name).to_str().unwrap().to_string();
+
+    let info = detect_file_type(Either::B(path("ogg.opus")))
+        .unwrap()
+        .unwrap();
+    assert_eq!(info.file_type, "Opus");
+
+    // The extension is ignored
+    let dir = tempfile::tempdir().unwrap();
+    let text = dir.path().join("notes.mp3");
+    std::fs::write(&text, "not audio at all").unwrap();
+    assert!(
+        detect_file_type(Either::B(text.to_str().unwrap().to_string()))
+            .unwrap()
+            .is_none()
+    );
+    assert!(detect_file_type(Either::B(path("missing.mp3"))).is_err());
+}
+
+#[test]
+fn test_check_extension() {
+    let t = music_file_from_path("flac.flac");
+    let check = t.check_extension(None).unwrap();
+    assert_eq!(check.extension.as_deref(), Some("flac"));
+    assert_eq!(check.expected, "flac");
+    assert!(check.matches);
+
+    // Case is ignored and alternatives are accepted
+    assert!(t.check_extension(Some("SONG.FLAC".into())).unwrap().matches);
+    assert!(t.check_extension(Some("song.fla".into())).unwrap().matches);
+
+    let check = t.check_extension(Some("song.wav".into())).unwrap();
+    assert_eq!(check.extension.as_deref(), Some("wav"));
+    assert!(!check.matches);
+
+    let check = t.check_extension(Some("song".into())).unwrap();
+    assert_eq!(check.extension, None);
+    assert!(!check.matches);
+
+    let opus = music_file_from_buffer("ogg.opus");
+    assert!(
+        opus.check_extension(Some("song.ogg".into()))
+            .unwrap()
+            .matches
+    );
+    assert!(opus.check_extension(None).is_err());
+}
+
+#[test]
+fn test_fix_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let wrong = dir.path().join("song.wav");
+    std::fs::copy(samples_dir().join("flac.flac"), &wrong).unwrap();
+
+    let mut t = music_file_from_path(wrong.to_str().unwrap());
+    assert!(!t.check_extension(None).unwrap().matches);
+
+    let fixed = t.fix_extension().unwrap().expect("renamed");
+    let expected = dir.path().join("song.flac");
+    assert_eq!(fixed, expected.to_str().unwrap());
+    assert_eq!(t.path(), Some(&fixed));
+    assert!(expected.exists());
+    assert!(!wrong.exists());
+
+    // Nothing to do once the extension matches
+    assert_eq!(t.fix_extension().unwrap(), None);
+
+    // Saving uses the new path
+    t.set_title(Either::A("Renamed".into())).unwrap();
+    t.save_sync(None).unwrap();
+    assert_eq!(
+        music_file_from_path(&fixed).title().as_deref(),
+        Some("Renamed")
+    );
+}
+
+#[test]
+fn test_fix_extension_does_not_overwrite() {
+    let dir = tempfile::tempdir().unwrap();
+    let wrong = dir.path().join("song.mp3");
+    let existing = dir.path().join("song.flac");
+    std::fs::copy(samples_dir().join("flac.flac"), &wrong).unwrap();
+    std::fs::write(&existing, "keep me").unwrap();
+
+    let mut t = music_file_from_path(wrong.to_str().unwrap());
+    assert!(t.fix_extension().is_err());
+    assert!(wrong.exists());
+    assert_eq!(std::fs::read_to_string(&existing).unwrap(), "keep me");
+    assert_eq!(t.path().map(String::as_str), wrong.to_str());
+}
+
+#[test]
+fn test_fix_extension_from_buffer() {
+    let mut t = music_file_from_buffer("flac.flac");
+    assert!(t.fix_extension().is_err());
+}
@@ -0,0 +1,250 @@
+use napi::Either;
+
+use crate::{
+    music_file::MusicFile,
+    tests::{music_file_from_buffer, samples_dir},
+};
+
+fn block(ty: u8, content: &[u8]) -> Vec<u8> {
+    let len = (content.len() as u32).to_be_bytes();
+    let mut block = vec![ty, len[1], len[2], len[3]];
+    block.extend_from_slice(content);
+    block
+}
+
+/// FLAC sample with extra metadata blocks inserted after STREAMINFO
+pub(super) fn flac_with_blocks(blocks: &[Vec<u8>]) -> Vec<u8> {
+    let data = std::fs::read(samples_dir().join("flac.flac")).unwrap();
+    assert_eq!(&data[..4], b"fLaC");
+    // The sample has more blocks after STREAMINFO, so its last-block flag is not set
+    assert_eq!(data[4] & 0x80, 0);
+
+    let mut result = data[..42].to_vec();
+    for block in blocks {
+        result.extend_from_slice(block);
+    }
+    result.extend_from_slice(&data[42..]);
+    result
+}
+
+pub(super) fn cue_sheet() -> Vec<u8> {
+    let mut content = vec![0u8; 396];
+    content[..13].copy_from_slice(b"1234567890123");
+    content[128..136].copy_from_slice(&88200u64.to_be_bytes());
+    content[136] = 0x80;
+    content[395] = 2;
+
+    // Track 1 with indices 0 and 1
+    let mut track = vec![0u8; 36];
+    track[8] = 1;
+    track[9..21].copy_from_slice(b"USABC1234567");
+    track[21] = 0x40;
+    track[35] = 2;
+    content.extend(track);
+    for (offset, number) in [(0u64, 0u8), (588, 1)] {
+        let mut index = offset.to_be_bytes().to_vec();
+        index.extend([number, 0, 0, 0]);
+        content.extend(index);
+    }
+
+    // Lead-out track
+    let mut lead_out = vec![0u8; 36];
+    lead_out[..8].copy_from_slice(&441000u64.to_be_bytes());
+    lead_out[8] = 170;
+    content.extend(lead_out);
+
+    block(5, &content)
+}
+
+fn seek_table() -> Vec<u8> {
+    let mut content = Vec::new();
+    for (sample, offset) in [(0u64, 0u64), (44100, 1234)] {
+        content.extend(sample.to_be_bytes());
+        content.extend(offset.to_be_bytes());
+        content.extend(4096u16.to_be_bytes());
+    }
+    // Placeholder point
+    content.extend(u64::MAX.to_be_bytes());
+    content.extend([0; 10]);
+    block(3, &content)
+}
+
+fn application(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut content = id.to_vec();
+    content.extend_from_slice(data);
+    block(2, &content)
+}
+
+fn load(data: Vec<u8>) -> MusicFile {
+    MusicFile::load_sync(Either::A(data.into()), None).unwrap()
+}
+
+fn save(t: &mut MusicFile, data: Vec<u8>) -> Vec<u8> {
+    match t.save_sync(Some(Either::A(data.into()))).unwrap() {
+        Either::B(buffer) => buffer.to_vec(),
+        Either::A(()) => panic!("expected buffer"),
+    }
+}
+
+#[test]
+fn test_flac_stream_info() {
+    let t = music_file_from_buffer("flac.flac");
+
+    let flac = t.flac_metadata().expect("FLAC metadata");
+    assert_eq!(Some(flac.stream_info.sample_rate), t.sample_rate());
+    assert_eq!(Some(flac.stream_info.channels as u8), t.channels());
+    assert_eq!(Some(flac.stream_info.bits_per_sample as u8), t.bit_depth());
+    assert!(flac.stream_info.min_block_size <= flac.stream_info.max_block_size);
+    assert!(flac.stream_info.total_samples > 0.0);
+    assert_eq!(flac.stream_info.md5.len(), 32);
+}
+
+#[test]
+fn test_flac_metadata_other_formats() {
+    let mut t = music_file_from_buffer("mp3.mp3");
+
+    assert!(t.flac_metadata().is_none());
+    assert!(t.set_flac_padding(1024).is_err());
+    assert!(t.remove_flac_applications(None).is_err());
+}
+
+#[test]
+fn test_flac_seek_table_and_cue_sheet() {
+    let t = load(flac_with_blocks(&[seek_table(), cue_sheet()]));
+
+    let flac = t.flac_metadata().unwrap();
+    assert_eq!(flac.seek_points.len(), 2);
+    assert_eq!(flac.seek_points[1].sample_number, 44100.0);
+    assert_eq!(flac.seek_points[1].offset, 1234.0);
+    assert_eq!(flac.seek_points[1].samples, 4096);
+
+    let cue_sheet = flac.cue_sheet.expect("cue sheet");
+    assert_eq!(cue_sheet.media_catalog_number, "1234567890123");
+    assert_eq!(cue_sheet.lead_in_samples, 88200.0);
+    assert!(cue_sheet.is_cd);
+    assert_eq!(cue_sheet.tracks.len(), 2);
+    let track = &cue_sheet.tracks[0];
+    assert_eq!(track.number, 1);
+    assert_eq!(track.isrc, "USABC1234567");
+    assert!(track.is_audio);
+    assert!(track.pre_emphasis);
+    assert_eq!(track.indices.len(), 2);
+    assert_eq!(track.indices[1].offset, 588.0);
+    assert_eq!(track.indices[1].number, 1);
+    assert_eq!(cue_sheet.tracks[1].number, 170);
+    assert_eq!(cue_sheet.tracks[1].offset, 441000.0);
+}
+
+#[test]
+fn test_flac_applications() {
+    let data = flac_with_blocks(&[application(b"abcd", &[1, 2]), application(b"wxyz", &[3])]);
+    let mut t = load(data.clone());
+    let title = t.title();
+    assert_eq!(t.flac_metadata().unwrap().applications.len(), 2);
+
+    assert_eq!(
+        t.remove_flac_applications(Some("abcd".to_string()))
+            .unwrap(),
+        1
+    );
+    t.add_flac_application("new!".to_string(), vec![9, 9, 9].into())
+        .unwrap();
+    assert!(t.is_dirty());
+
+    let reloaded = load(save(&mut t, data));
+    let applications = reloaded.flac_metadata().unwrap().applications;
+    let ids: Vec<&str> = applications.iter().map(|app| app.id.as_str()).collect();
+    assert_eq!(ids, vec!["wxyz", "new!"]);
+    assert_eq!(applications[1].data.as_ref(), &[9u8, 9, 9]);
+    assert_eq!(reloaded.title(), title);
+    assert_eq!(reloaded.duration(), t.duration());
+}
+
+#[test]
+fn test_flac_application_invalid_id() {
+    let mut t = music_file_from_buffer("flac.flac");
+
+    assert!(t
+        .add_flac_application("toolong".to_string(), vec![].into())
+        .is_err());
+    assert!(t.remove_flac_applications(Some("ab".to_string())).is_err());
+}
+
+#[test]
+fn test_flac_padding() {
+    let data = std::fs::read(samples_dir().join("flac.flac")).unwrap();
+    let mut t = load(data.clone());
+
+    t.set_flac_padding(4096).unwrap();
+    assert!(t.is_dirty());
+    assert_eq!(t.flac_metadata().unwrap().padding, 4096);
+
+    let saved = save(&mut t, data);
+    let reloaded = load(saved.clone());
+    assert_eq!(reloaded.flac_metadata().unwrap().padding, 4096);
+    assert_eq!(reloaded.duration(), t.duration());
+
+    let mut t = load(saved.clone());
+    t.set_flac_padding(0).unwrap();
+    let reloaded = load(save(&mut t, saved));
+    assert_eq!(reloaded.flac_metadata().unwrap().padding, 0);
+}
+
+#[test]
+fn test_flac_block_changes_revert() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let padding = t.flac_metadata().unwrap().padding;
+
+    t.set_flac_padding(padding + 1).unwrap();
+    t.add_flac_application("abcd".to_string(), vec![1].into())
+        .unwrap();
+    t.revert();
+
+    assert!(!t.is_dirty());
+    let flac = t.flac_metadata().unwrap();
+    assert_eq!(flac.padding, padding);
+    assert!(flac.applications.is_empty());
+}
+
+#[test]
+fn test_flac_block_no_op_changes_keep_history() {
+    let mut t = load(flac_with_blocks(&[application(b"abcd", &[1])]));
+
+    assert_eq!(
+        t.remove_flac_applications(Some("wxyz".to_string()))
+            .unwrap(),
+        0
+    );
+    assert!(!t.can_undo());
+
+    t.set_flac_padding(100).unwrap();
+    t.set_flac_padding(100).unwrap();
+    t.undo();
+    assert!(!t.can_undo());
+    assert!(!t.is_dirty());
+}
+
+#[test]
+fn test_flac_padding_keeps_audio_in_place() {
+    let data = std::fs::read(samples_dir().join("flac.flac")).unwrap();
+    let mut t = load(data.clone());
+    let padding = t.flac_metadata().unwrap().padding;
+
+    t.set_title(Either::A("A much longer title than before".to_string()))
+        .unwrap();
+    t.add_flac_application("abcd".to_string(), vec![1, 2, 3, 4].into())
+        .unwrap();
+    let saved = save(&mut t, data.clone());
+    // The padding shrinks to make room, instead of moving the audio frames
+    assert_eq!(saved.len(), data.len());
+
+    let reloaded = load(saved);
+    let flac = reloaded.flac_metadata().unwrap();
+    assert!(flac.padding < padding);
+    assert_eq!(flac.applications.len(), 1);
+    assert_eq!(
+        reloaded.title().as_deref(),
+        Some("A much longer title than before")
+    );
+    assert_eq!(reloaded.duration(), t.duration());
+}
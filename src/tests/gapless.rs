@@ -0,0 +1,122 @@
+use napi::Either;
+
+use crate::{
+    music_file::gapless::ITUNES_SMPB_KEY,
+    tests::{empty_mp4, load, music_file_from_buffer, samples_dir, save},
+};
+
+#[test]
+fn test_gapless_lame() {
+    let t = music_file_from_buffer("mp3.mp3");
+    let vbr_header = t.codec_info().unwrap().mpeg.unwrap().vbr_header.unwrap();
+    let lame = vbr_header.lame.unwrap();
+
+    let gapless = t.gapless().unwrap();
+    assert_eq!(gapless.source, "LAME");
+    assert_eq!(gapless.encoder_delay, lame.encoder_delay);
+    assert_eq!(gapless.encoder_padding, Some(lame.encoder_padding));
+    let samples = vbr_header.frames.unwrap() * 1152 - lame.encoder_delay - lame.encoder_padding;
+    assert_eq!(gapless.original_samples, Some(f64::from(samples)));
+}
+
+#[test]
+fn test_gapless_opus() {
+    let t = music_file_from_buffer("ogg.opus");
+    let gapless = t.gapless().unwrap();
+    assert_eq!(gapless.source, "Opus");
+    assert_eq!(
+        gapless.encoder_delay,
+        t.codec_info().unwrap().opus.unwrap().pre_skip
+    );
+    assert_eq!(gapless.encoder_padding, None);
+    assert_eq!(gapless.original_samples, None);
+}
+
+#[test]
+fn test_gapless_itunes_smpb_id3v2() {
+    let data = std::fs::read(samples_dir().join("mp3.mp3")).unwrap();
+    let mut t = load(data.clone());
+    t.set_gapless(2112, 1000, 441_000.0).unwrap();
+    assert!(t.is_dirty());
+
+    // iTunSMPB takes precedence over the LAME header
+    let mut t = load(save(&mut t, data.clone()));
+    let gapless = t.gapless().unwrap();
+    assert_eq!(gapless.source, "iTunSMPB");
+    assert_eq!(gapless.encoder_delay, 2112);
+    assert_eq!(gapless.encoder_padding, Some(1000));
+    assert_eq!(gapless.original_samples, Some(441_000.0));
+    assert_eq!(t.comment().as_deref(), Some("test comment"));
+
+    assert!(t.remove_gapless().unwrap());
+    assert!(!t.remove_gapless().unwrap());
+    let t = load(save(&mut t, data));
+    assert_eq!(t.gapless().unwrap().source, "LAME");
+}
+
+#[test]
+fn test_gapless_itunes_smpb_mp4() {
+    let data = empty_mp4();
+    let mut t = load(data.clone());
+    assert!(t.gapless().is_none());
+
+    t.set_gapless(2112, 576, 5_000_000_000.0).unwrap();
+    assert_eq!(
+        t.itunes_comment(ITUNES_SMPB_KEY).as_deref(),
+        Some(concat!(
+            " 00000000 00000840 00000240 000000012A05F200",
+            " 00000000 00000000 00000000 00000000",
+            " 00000000 00000000 00000000 00000000"
+        ))
+    );
+
+    let t = load(save(&mut t, data));
+    let gapless = t.gapless().unwrap();
+    assert_eq!(gapless.encoder_delay, 2112);
+    assert_eq!(gapless.original_samples, Some(5_000_000_000.0));
+}
+
+#[test]
+fn test_gapless_kept_on_save() {
+    let data = empty_mp4();
+    let mut t = load(data.clone());
+    t.set_gapless(2112, 576, 44_100.0).unwrap();
+    let data = save(&mut t, data);
+
+    let mut t = load(data.clone());
+    t.set_title(Either::A("Title".to_string())).unwrap();
+    let data = save(&mut t, data);
+    let mut t = load(data.clone());
+    assert_eq!(t.gapless().unwrap().original_samples, Some(44_100.0));
+
+    t.set_sound_check(Either::A(-1.0), None).unwrap();
+    let t = load(save(&mut t, data));
+    let gapless = t.gapless().unwrap();
+    assert_eq!(gapless.source, "iTunSMPB");
+    assert_eq!(gapless.encoder_delay, 2112);
+    assert!(t.sound_check().is_some());
+}
+
+#[test]
+fn test_gapless_malformed_itunes_smpb() {
+    let mut t = load(empty_mp4());
+    t.set_itunes_comment(ITUNES_SMPB_KEY, Some(" 00000000 0840".to_string()))
+        .unwrap();
+    assert!(t.gapless().is_none());
+}
+
+#[test]
+fn test_gapless_invalid() {
+    let mut t = music_file_from_buffer("flac.flac");
+    assert!(t.gapless().is_none());
+    let error = t.set_gapless(0, 0, 0.0).err().unwrap();
+    assert_eq!(
+        error.reason,
+        "iTunSMPB is only supported in MP4 files and ID3v2 tags"
+    );
+
+    let mut t = music_file_from_buffer("mp3.mp3");
+    assert!(t.set_gapless(0, 0, -1.0).is_err());
+    assert!(t.set_gapless(0, 0, 1.5).is_err());
+    assert!(t.set_gapless(0, 0, f64::NAN).is_err());
+}
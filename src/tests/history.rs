@@ -0,0 +1,177 @@
+use napi::Either;
+
+use crate::{
+    meta_picture::MetaPicture,
+    music_file::MusicFile,
+    tests::{empty_mp4, music_file_from_buffer},
+};
+
+// ── undo / redo ─────────────────────────────────────────────────────────
+
+#[test]
+fn test_undo_redo_setters() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let old_title = t.title();
+    assert!(!t.can_undo());
+
+    t.set_title(Either::A("First".to_string())).unwrap();
+    t.set_title(Either::A("Second".to_string())).unwrap();
+
+    assert!(t.undo());
+    assert_eq!(t.title().as_deref(), Some("First"));
+    assert!(t.undo());
+    assert_eq!(t.title(), old_title);
+    assert!(!t.undo());
+    assert!(!t.is_dirty());
+
+    assert!(t.redo());
+    assert_eq!(t.title().as_deref(), Some("First"));
+    assert!(t.is_dirty());
+}
+
+#[test]
+fn test_new_edit_clears_redo() {
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_title(Either::A("First".to_string())).unwrap();
+    t.undo();
+    assert!(t.can_redo());
+
+    t.set_artist(Either::A("Other".to_string())).unwrap();
+    assert!(!t.can_redo());
+}
+
+#[test]
+fn test_undo_pictures() {
+    let mut t = music_file_from_buffer("mp3.mp3");
+    let old_count = t.pictures().map(|pics| pics.len());
+    let picture = MetaPicture::new("image/png".to_string(), vec![1u8, 2, 3].into(), None, None);
+    t.set_pictures(Either::A(vec![&picture])).unwrap();
+    assert_eq!(t.pictures().map(|pics| pics.len()), Some(1));
+
+    t.undo();
+    assert_eq!(t.pictures().map(|pics| pics.len()), old_count);
+    assert!(!t.is_dirty());
+}
+
+#[test]
+fn test_restore_checkpoint() {
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_title(Either::A("Kept".to_string())).unwrap();
+    let checkpoint = t.checkpoint();
+
+    t.set_title(Either::A("Experiment".to_string())).unwrap();
+    t.set_track_number(Either::A(42.0)).unwrap();
+    t.restore_checkpoint(checkpoint).unwrap();
+    assert_eq!(t.title().as_deref(), Some("Kept"));
+
+    // checkpoints can also be reached by redoing
+    t.undo();
+    t.restore_checkpoint(checkpoint).unwrap();
+    assert_eq!(t.title().as_deref(), Some("Kept"));
+
+    // a new edit on top of an undone state makes later checkpoints unreachable
+    t.set_track_number(Either::A(1.0)).unwrap();
+    let later = t.checkpoint();
+    t.undo();
+    t.set_genre(Either::A("Jazz".to_string())).unwrap();
+    assert!(t.restore_checkpoint(later).is_err());
+}
+
+#[test]
+fn test_revert_is_undoable() {
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_title(Either::A("Changed".to_string())).unwrap();
+    t.revert();
+    assert!(!t.is_dirty());
+
+    t.undo();
+    assert_eq!(t.title().as_deref(), Some("Changed"));
+    assert!(t.is_dirty());
+}
+
+#[test]
+fn test_history_shares_pictures() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let picture = MetaPicture::new(
+        "image/png".to_string(),
+        vec![7u8; 1 << 20].into(),
+        None,
+        None,
+    );
+    t.set_pictures(Either::A(vec![&picture])).unwrap();
+    for i in 0..20 {
+        t.set_title(Either::A(format!("Title {i}"))).unwrap();
+    }
+    // The steps before the pictures were set hold the loaded pictures
+    let copies = t.history_picture_copies();
+    assert!(copies <= 2, "{copies}");
+
+    t.undo();
+    t.undo();
+    assert_eq!(t.history_picture_copies(), copies);
+    assert_eq!(t.pictures().unwrap()[0].data.len(), 1 << 20);
+}
+
+// ── pending changes besides the tags ────────────────────────────────────
+
+#[test]
+fn test_undo_flac_blocks() {
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_flac_padding(4096).unwrap();
+    t.add_flac_application("test".to_string(), vec![1u8].into())
+        .unwrap();
+    assert!(t.is_dirty());
+
+    t.undo();
+    let flac = t.flac_metadata().unwrap();
+    assert!(flac.applications.is_empty());
+    assert_eq!(flac.padding, 4096);
+    t.undo();
+    assert!(!t.is_dirty());
+
+    t.redo();
+    assert_eq!(t.flac_metadata().unwrap().padding, 4096);
+
+    // An invalid change is not a step
+    assert!(t.set_flac_padding(u32::MAX).is_err());
+    t.undo();
+    assert!(!t.is_dirty());
+}
+
+#[test]
+fn test_undo_opus_output_gain() {
+    let mut t = music_file_from_buffer("ogg.opus");
+    t.set_opus_output_gain(-2.0).unwrap();
+    t.set_title(Either::A("Title".to_string())).unwrap();
+
+    t.undo();
+    assert_eq!(t.opus_output_gain(), Some(-2.0));
+    t.undo();
+    assert_eq!(t.opus_output_gain(), Some(0.0));
+    assert!(!t.is_dirty());
+}
+
+#[test]
+fn test_undo_revert_of_pending_changes() {
+    let mut t = music_file_from_buffer("ogg.opus");
+    t.set_opus_output_gain(-2.0).unwrap();
+    t.revert();
+    assert_eq!(t.opus_output_gain(), Some(0.0));
+
+    t.undo();
+    assert_eq!(t.opus_output_gain(), Some(-2.0));
+    assert!(t.is_dirty());
+}
+
+#[test]
+fn test_undo_itunes_atoms() {
+    let mut t = MusicFile::load_sync(Either::A(empty_mp4().into()), None).unwrap();
+    t.set_sound_check(Either::A(-3.0), None).unwrap();
+    assert!(t.sound_check().is_some());
+
+    t.undo();
+    assert!(t.sound_check().is_none());
+    assert!(!t.is_dirty());
+    t.redo();
+    assert!(t.sound_check().is_some());
+}
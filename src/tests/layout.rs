@@ -0,0 +1,152 @@
+use napi::Either;
+
+use crate::{
+    music_file::{layout::AudioLayout, MusicFile},
+    tests::{empty_mp4, music_file_from_buffer, samples_dir},
+};
+
+fn layout(name: &str) -> AudioLayout {
+    music_file_from_buffer(name).audio_layout().expect("layout")
+}
+
+fn kinds(layout: &AudioLayout) -> Vec<&str> {
+    layout
+        .blocks
+        .iter()
+        .map(|block| block.kind.as_str())
+        .collect()
+}
+
+/// Blocks, padding and audio together cover the whole file
+fn assert_covers_file(layout: &AudioLayout) {
+    let mut end = 0.0;
+    for block in &layout.blocks {
+        assert!(block.offset >= end, "{} overlaps", block.kind);
+        end = block.offset + block.size;
+    }
+    assert!(end <= layout.file_size);
+    assert!(layout.audio_offset + layout.audio_size <= layout.file_size);
+    assert_eq!(
+        layout.metadata_size,
+        layout.blocks.iter().map(|block| block.size).sum::<f64>()
+    );
+}
+
+#[test]
+fn test_layout_mp3() {
+    let layout = layout("mp3.mp3");
+    assert_covers_file(&layout);
+    assert_eq!(kinds(&layout), ["ID3v2", "ID3v1"]);
+
+    let id3v2 = &layout.blocks[0];
+    assert_eq!(id3v2.offset, 0.0);
+    assert_eq!(layout.audio_offset, id3v2.size);
+    assert_eq!(
+        layout.audio_offset + layout.audio_size,
+        layout.blocks[1].offset
+    );
+    assert!(layout.picture_size > 0.0);
+    assert!(layout.picture_size < id3v2.size);
+
+    let plain = self::layout("mp3-no-tags.mp3");
+    assert!(plain.blocks.is_empty());
+    assert_eq!(plain.audio_offset, 0.0);
+    assert_eq!(plain.audio_size, plain.file_size);
+    assert_eq!(plain.metadata_size, 0.0);
+}
+
+#[test]
+fn test_layout_trailing_tags() {
+    let mut data = std::fs::read(samples_dir().join("mp3-no-tags.mp3")).unwrap();
+    let audio_size = data.len() as f64;
+
+    // APEv2 tag with header and footer, and no items
+    for flags in [0xA000_0000u32, 0x8000_0000] {
+        data.extend(b"APETAGEX");
+        data.extend(2000u32.to_le_bytes());
+        data.extend(32u32.to_le_bytes());
+        data.extend(0u32.to_le_bytes());
+        data.extend(flags.to_le_bytes());
+        data.extend([0; 8]);
+    }
+    // Lyrics3v2 tag with an empty lyrics field
+    let lyrics = b"LYRICSBEGININD0000200";
+    data.extend(lyrics);
+    data.extend(format!("{:06}LYRICS200", lyrics.len()).as_bytes());
+    let mut id3v1 = b"TAG".to_vec();
+    id3v1.resize(128, 0);
+    data.extend(id3v1);
+
+    let t = MusicFile::load_sync(Either::A(data.into()), None).unwrap();
+    let layout = t.audio_layout().unwrap();
+    assert_covers_file(&layout);
+    assert_eq!(kinds(&layout), ["APE", "Lyrics3", "ID3v1"]);
+    assert_eq!(layout.audio_size, audio_size);
+    assert_eq!(layout.blocks[0].size, 64.0);
+    assert_eq!(layout.blocks[1].size, (lyrics.len() + 15) as f64);
+    assert_eq!(layout.metadata_size, (64 + lyrics.len() + 15 + 128) as f64);
+}
+
+#[test]
+fn test_layout_flac() {
+    let layout = layout("flac.flac");
+    assert_covers_file(&layout);
+    assert_eq!(kinds(&layout), ["VorbisComment", "Picture", "Padding"]);
+
+    // Audio frames follow the last metadata block
+    let last = layout.blocks.last().unwrap();
+    assert_eq!(layout.audio_offset, last.offset + last.size);
+    assert_eq!(layout.audio_offset + layout.audio_size, layout.file_size);
+    assert!(layout.blocks[1].size > layout.picture_size);
+}
+
+#[test]
+fn test_layout_ogg() {
+    let layout = layout("ogg.opus");
+    assert_covers_file(&layout);
+    assert_eq!(kinds(&layout), ["VorbisComment"]);
+
+    let comment = &layout.blocks[0];
+    assert_eq!(layout.audio_offset, comment.offset + comment.size);
+    // Pictures are base64 encoded in the comment header
+    assert!(comment.size > layout.picture_size);
+}
+
+#[test]
+fn test_layout_wav() {
+    let layout = layout("wav.wav");
+    assert_covers_file(&layout);
+    assert_eq!(kinds(&layout), ["RiffInfo", "ID3v2"]);
+    assert!(layout.audio_offset < layout.blocks[0].offset);
+}
+
+#[test]
+fn test_layout_audio_bit_rate() {
+    let t = music_file_from_buffer("mp3.mp3");
+    let layout = t.audio_layout().unwrap();
+
+    // The sample is a 320 kbps CBR stream behind a large picture
+    let file_bit_rate = layout.file_size * 8.0 / t.duration();
+    assert!(file_bit_rate > 500.0);
+    assert!(layout
+        .audio_bit_rate
+        .is_some_and(|bit_rate| bit_rate.abs_diff(320) <= 2));
+}
+
+#[test]
+fn test_layout_mp4_oversized_atom() {
+    let mut data = empty_mp4();
+    let mp4_size = data.len() as f64;
+    // A 64-bit atom size running past the end of the file, and past `u64::MAX` from its offset
+    data.extend(1u32.to_be_bytes());
+    data.extend(b"free");
+    data.extend(u64::MAX.to_be_bytes());
+
+    let t = MusicFile::load_sync(Either::A(data.into()), None).unwrap();
+    assert!(t.audio_layout().is_none());
+
+    let t = MusicFile::load_sync(Either::A(empty_mp4().into()), None).unwrap();
+    let layout = t.audio_layout().unwrap();
+    assert_covers_file(&layout);
+    assert_eq!(layout.file_size, mp4_size);
+}
@@ -0,0 +1,245 @@
+use std::f64::consts::PI;
+
+use napi::Either;
+
+use crate::{
+    music_file::{
+        loudness::{analyze_album_loudness_sync, LoudnessOptions, LoudnessResult},
+        MusicFile,
+    },
+    tests::{music_file_from_path, samples_dir},
+};
+
+/// 16-bit PCM WAV file of interleaved samples
+fn pcm_wav(sample_rate: u32, channels: u16, samples: &[f64]) -> Vec<u8> {
+    let mut fmt = Vec::new();
+    fmt.extend(1u16.to_le_bytes()); // WAVE_FORMAT_PCM
+    fmt.extend(channels.to_le_bytes());
+    fmt.extend(sample_rate.to_le_bytes());
+    fmt.extend((sample_rate * u32::from(channels) * 2).to_le_bytes());
+    fmt.extend((channels * 2).to_le_bytes());
+    fmt.extend(16u16.to_le_bytes());
+
+    let mut body = b"WAVE".to_vec();
+    body.extend(b"fmt ");
+    body.extend((fmt.len() as u32).to_le_bytes());
+    body.extend(fmt);
+    body.extend(b"data");
+    body.extend((samples.len() as u32 * 2).to_le_bytes());
+    for sample in samples {
+        body.extend(((sample * 32767.0).round() as i16).to_le_bytes());
+    }
+
+    let mut data = b"RIFF".to_vec();
+    data.extend((body.len() as u32).to_le_bytes());
+    data.extend(body);
+    data
+}
+
+/// Sine of `seconds` at 48 kHz with a peak level in dBFS, copied to every channel
+fn sine_wav(frequency: f64, level: f64, phase: f64, channels: u16, seconds: f64) -> Vec<u8> {
+    let amplitude = 10f64.powf(level / 20.0);
+    let frames = (48_000.0 * seconds) as usize;
+    let samples: Vec<f64> = (0..frames)
+        .flat_map(|i| {
+            let value = amplitude * (2.0 * PI * frequency * i as f64 / 48_000.0 + phase).sin();
+            std::iter::repeat_n(value, channels as usize)
+        })
+        .collect();
+    pcm_wav(48_000, channels, &samples)
+}
+
+fn load(data: &[u8]) -> MusicFile {
+    MusicFile::load_sync(Either::A(data.to_vec().into()), None).unwrap()
+}
+
+fn analyze(data: &[u8], options: Option<LoudnessOptions>) -> LoudnessResult {
+    load(data)
+        .analyze_loudness_sync(options, Some(data.to_vec().into()))
+        .unwrap()
+}
+
+#[test]
+fn test_loudness_reference_sine() {
+    // EBU Tech 3341: a stereo 1 kHz sine at -23 dBFS measures -23 LUFS
+    let result = analyze(&sine_wav(1000.0, -23.0, 0.0, 2, 5.0), None);
+    let loudness = result.loudness.unwrap();
+    assert!((loudness + 23.0).abs() < 0.1, "{loudness}");
+    assert!((result.gain.unwrap() - 5.0).abs() < 0.1);
+    assert!((result.peak - 10f64.powf(-23.0 / 20.0)).abs() < 0.001);
+    assert!(result.true_peak.unwrap() >= result.peak);
+
+    let result = analyze(
+        &sine_wav(1000.0, -23.0, 0.0, 2, 5.0),
+        Some(LoudnessOptions {
+            reference_loudness: Some(-23.0),
+            true_peak: Some(false),
+            ..Default::default()
+        }),
+    );
+    assert!(result.gain.unwrap().abs() < 0.1);
+    assert_eq!(result.true_peak, None);
+}
+
+#[test]
+fn test_loudness_mono_as_dual_mono() {
+    let mono = analyze(&sine_wav(1000.0, -23.0, 0.0, 1, 2.0), None);
+    let stereo = analyze(&sine_wav(1000.0, -23.0, 0.0, 2, 2.0), None);
+    assert!((mono.loudness.unwrap() - stereo.loudness.unwrap()).abs() < 0.01);
+}
+
+#[test]
+fn test_loudness_silence() {
+    let result = analyze(&pcm_wav(48_000, 2, &vec![0.0; 96_000]), None);
+    assert_eq!(result.loudness, None);
+    assert_eq!(result.gain, None);
+    assert_eq!(result.peak, 0.0);
+    assert_eq!(result.true_peak, Some(0.0));
+}
+
+#[test]
+fn test_loudness_true_peak() {
+    // A quarter of the sample rate shifted by 45° never samples its crests
+    let result = analyze(&sine_wav(12_000.0, -6.0, PI / 4.0, 1, 1.0), None);
+    let amplitude = 10f64.powf(-6.0 / 20.0);
+    assert!((result.peak - amplitude * 0.5f64.sqrt()).abs() < 0.001);
+    let true_peak = result.true_peak.unwrap();
+    assert!((true_peak - amplitude).abs() < 0.02, "{true_peak}");
+}
+
+#[test]
+fn test_loudness_from_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sine.wav");
+    std::fs::write(&path, sine_wav(1000.0, -23.0, 0.0, 2, 2.0)).unwrap();
+
+    let mut t = music_file_from_path(path.to_str().unwrap());
+    let result = t.analyze_loudness_sync(None, None).unwrap();
+    assert!((result.loudness.unwrap() + 23.0).abs() < 0.1);
+    // Nothing is written by default
+    assert_eq!(t.track_replay_gain(), None);
+}
+
+#[test]
+fn test_loudness_mp3() {
+    // The sample is digital silence
+    let mut t = music_file_from_path("mp3-no-tags.mp3");
+    let result = t.analyze_loudness_sync(None, None).unwrap();
+    assert_eq!(result.loudness, None);
+    assert_eq!(result.peak, 0.0);
+}
+
+#[test]
+fn test_loudness_buffer_needs_source() {
+    let data = sine_wav(1000.0, -23.0, 0.0, 2, 1.0);
+    let error = load(&data).analyze_loudness_sync(None, None).err().unwrap();
+    assert_eq!(error.reason, "This file was loaded from a buffer");
+}
+
+#[test]
+fn test_loudness_unsupported() {
+    let data = b"not audio at all".repeat(16);
+    let t = music_file_from_path("flac.flac");
+    let error = t.measure_loudness(Some(data.into()), false).err().unwrap();
+    assert!(error
+        .reason
+        .starts_with("Loudness analysis is not supported"));
+}
+
+#[test]
+fn test_loudness_write() {
+    let data = sine_wav(1000.0, -23.0, 0.0, 2, 2.0);
+    let mut t = load(&data);
+    let result = t
+        .analyze_loudness_sync(
+            Some(LoudnessOptions {
+                write: Some(true),
+                sound_check: Some(true),
+                ..Default::default()
+            }),
+            Some(data.into()),
+        )
+        .unwrap();
+
+    let gain = t.track_replay_gain().unwrap();
+    assert!((gain - result.gain.unwrap()).abs() < 0.01);
+    let peak = t.track_replay_peak().unwrap();
+    assert!((peak - result.peak).abs() < 0.000001);
+    assert_eq!(t.album_replay_gain(), None);
+    // The primary tag of WAV files is ID3v2
+    let sound_check = t.sound_check().unwrap();
+    assert!((sound_check.gain - result.gain.unwrap()).abs() < 0.01);
+}
+
+#[test]
+fn test_loudness_album() {
+    let quiet = sine_wav(1000.0, -23.0, 0.0, 2, 2.0);
+    let loud = sine_wav(1000.0, -13.0, 0.0, 2, 2.0);
+    let mut files = [load(&quiet), load(&loud)];
+
+    let result = analyze_album_loudness_sync(
+        files.iter_mut().collect(),
+        Some(LoudnessOptions {
+            write: Some(true),
+            ..Default::default()
+        }),
+        Some(vec![Some(quiet.into()), Some(loud.into())]),
+    )
+    .unwrap();
+
+    assert_eq!(result.tracks.len(), 2);
+    let quiet_loudness = result.tracks[0].loudness.unwrap();
+    let loud_loudness = result.tracks[1].loudness.unwrap();
+    assert!((quiet_loudness + 23.0).abs() < 0.1);
+    assert!((loud_loudness + 13.0).abs() < 0.1);
+    // Equal lengths average the energy: -13 + 10 * log10(1.1 / 2)
+    let album = result.album.loudness.unwrap();
+    assert!((album + 15.6).abs() < 0.1, "{album}");
+    assert_eq!(result.album.peak, result.tracks[1].peak);
+
+    for (file, track) in files.iter().zip(&result.tracks) {
+        assert!((file.track_replay_gain().unwrap() - track.gain.unwrap()).abs() < 0.01);
+        assert!((file.album_replay_gain().unwrap() - result.album.gain.unwrap()).abs() < 0.01);
+        assert!((file.album_replay_peak().unwrap() - result.album.peak).abs() < 0.000001);
+    }
+}
+
+#[test]
+fn test_loudness_album_missing_source() {
+    let data = sine_wav(1000.0, -23.0, 0.0, 2, 1.0);
+    let mut files = [load(&data), load(&data)];
+    let error = analyze_album_loudness_sync(
+        files.iter_mut().collect(),
+        None,
+        Some(vec![Some(data.into())]),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(error.reason, "This file was loaded from a buffer");
+    assert_eq!(files[0].track_replay_gain(), None);
+}
+
+#[test]
+fn test_loudness_album_checks_before_writing() {
+    let data = sine_wav(1000.0, -23.0, 0.0, 2, 1.0);
+    let opus = std::fs::read(samples_dir().join("ogg.opus")).unwrap();
+    let mut files = [load(&data), load(&opus)];
+
+    // The Opus file can't hold the R128 gain, so no file is written
+    let error = analyze_album_loudness_sync(
+        files.iter_mut().collect(),
+        Some(LoudnessOptions {
+            reference_loudness: Some(200.0),
+            write: Some(true),
+            ..Default::default()
+        }),
+        Some(vec![Some(data.clone().into()), Some(data.into())]),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        error.reason,
+        "ReplayGain value of Opus files must be in [-123, 132.99] dB"
+    );
+    assert!(files.iter().all(|file| !file.is_dirty()));
+}
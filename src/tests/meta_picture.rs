@@ -1,6 +1,7 @@
 use lofty::picture::{MimeType, Picture, PictureType};
+use napi::{bindgen_prelude::Null, Either};
 
-use crate::meta_picture::*;
+use crate::{meta_picture::*, tests::music_file_from_buffer};
 
 fn make_picture(
     data: Vec<u8>,
@@ -135,12 +136,8 @@ fn test_to_lofty_picture_round_trip() {
 
 #[test]
 fn test_to_lofty_picture_no_mime() {
-    let meta = MetaPicture {
-        cover_type: "Cover".to_string(),
-        mime_type: None,
-        description: None,
-        data: vec![1u8, 2, 3].into(),
-    };
+    let mut meta = MetaPicture::new(String::new(), vec![1u8, 2, 3].into(), None, None);
+    meta.mime_type = None;
     let pic = to_lofty_picture(&meta);
     assert_eq!(pic.data(), &[1u8, 2, 3]);
 }
@@ -148,12 +145,126 @@ fn test_to_lofty_picture_no_mime() {
 #[test]
 fn test_to_lofty_picture_preserves_data() {
     let data: Vec<u8> = (0..=255u8).collect();
-    let meta = MetaPicture {
-        cover_type: "Cover".to_string(),
-        mime_type: Some("image/jpeg".to_string()),
-        description: Some("Full range".to_string()),
-        data: data.clone().into(),
-    };
+    let meta = MetaPicture::new(
+        "image/jpeg".to_string(),
+        data.clone().into(),
+        Some("Full range".to_string()),
+        None,
+    );
     let pic = to_lofty_picture(&meta);
     assert_eq!(pic.data(), data.as_slice());
 }
+
+#[test]
+fn test_meta_picture_new_default_type() {
+    let meta = MetaPicture::new("image/png".to_string(), vec![1u8].into(), None, None);
+    assert_eq!(meta.cover_type, "Cover Art (Front)");
+}
+
+#[test]
+fn test_meta_picture_new_with_type() {
+    let meta = MetaPicture::new(
+        "image/png".to_string(),
+        vec![1u8].into(),
+        None,
+        Some(CoverType::Back),
+    );
+    assert_eq!(meta.cover_type, "Cover Art (Back)");
+    assert_eq!(to_lofty_picture(&meta).pic_type(), PictureType::CoverBack);
+}
+
+#[test]
+fn test_cover_type_matches_ape_keys() {
+    assert_eq!(CoverType::PngIcon.to_lofty(), PictureType::Icon);
+    assert_eq!(CoverType::Icon.to_lofty(), PictureType::OtherIcon);
+    assert_eq!(
+        CoverType::VideoCapture.to_lofty(),
+        PictureType::ScreenCapture
+    );
+    assert_eq!(
+        CoverType::PublisherLogotype.to_lofty(),
+        PictureType::PublisherLogo
+    );
+    assert!(matches!(
+        CoverType::Unknown.to_lofty(),
+        PictureType::Undefined(_)
+    ));
+}
+
+// ── MusicFile picture helpers ───────────────────────────────────────────
+
+fn picture(data: &[u8]) -> MetaPicture {
+    MetaPicture::new("image/png".to_string(), data.to_vec().into(), None, None)
+}
+
+#[test]
+fn test_set_picture_replaces_same_type() {
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_pictures(Either::B(Null)).unwrap();
+
+    t.set_picture(CoverType::Front, &picture(&[1])).unwrap();
+    t.set_picture(CoverType::Back, &picture(&[2])).unwrap();
+    t.set_picture(CoverType::Front, &picture(&[3])).unwrap();
+
+    let front = t.front_cover().expect("front cover should be set");
+    assert_eq!(front.data.as_ref(), &[3u8]);
+    assert_eq!(t.get_pictures_by_type(CoverType::Front).len(), 1);
+    let back = t.get_pictures_by_type(CoverType::Back);
+    assert_eq!(back.len(), 1);
+    assert_eq!(back[0].cover_type, "Cover Art (Back)");
+}
+
+#[test]
+fn test_remove_picture_by_type() {
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_pictures(Either::B(Null)).unwrap();
+    t.set_picture(CoverType::Front, &picture(&[1])).unwrap();
+    t.set_picture(CoverType::Artist, &picture(&[2])).unwrap();
+
+    assert_eq!(t.remove_picture(CoverType::Front).unwrap(), 1);
+    assert_eq!(t.remove_picture(CoverType::Front).unwrap(), 0);
+
+    assert!(t.front_cover().is_none());
+    assert_eq!(t.pictures().map(|pics| pics.len()), Some(1));
+}
+
+#[test]
+fn test_picture_info_without_data() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let front = picture(&[1, 2, 3]);
+    let back = MetaPicture::new(
+        "image/png".to_string(),
+        vec![1, 2, 3].into(),
+        Some("back".to_string()),
+        Some(CoverType::Back),
+    );
+    let other = picture(&[4, 5]);
+    t.set_pictures(Either::A(vec![&front, &back, &other]))
+        .unwrap();
+
+    let infos = t.picture_info();
+
+    assert_eq!(infos.len(), 3);
+    assert!(infos.iter().all(|info| info.data.is_none()));
+    assert_eq!(infos[1].cover_type, "Cover Art (Back)");
+    assert_eq!(infos[1].description.as_deref(), Some("back"));
+    assert_eq!(infos[1].size, 3);
+    assert!(infos.iter().all(|info| info.hash.is_none()));
+
+    let hashes: Vec<Option<String>> = (0..4).map(|index| t.get_picture_hash(index)).collect();
+    assert_eq!(hashes[0].as_ref().map(String::len), Some(64));
+    assert_eq!(hashes[0], hashes[1]);
+    assert_ne!(hashes[0], hashes[2]);
+    assert_eq!(hashes[3], None);
+}
+
+#[test]
+fn test_get_picture_data() {
+    let mut t = music_file_from_buffer("flac.flac");
+    let first = picture(&[1, 2, 3]);
+    let second = picture(&[4, 5]);
+    t.set_pictures(Either::A(vec![&first, &second])).unwrap();
+
+    assert_eq!(t.get_picture_data(1).unwrap().as_ref(), &[4u8, 5]);
+    assert!(t.get_picture_data(2).is_none());
+}
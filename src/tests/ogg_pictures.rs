@@ -0,0 +1,151 @@
+use std::io::Cursor;
+
+use data_encoding::BASE64;
+use lofty::{
+    config::{ParseOptions, WriteOptions},
+    file::AudioFile,
+    ogg::{OggPictureStorage, OpusFile},
+    tag::TagExt,
+};
+use napi::Either;
+
+use crate::{
+    meta_picture::CoverType,
+    music_file::ogg_pictures::validate_block_pictures,
+    tests::{load, music_file_from_buffer, samples_dir, save},
+};
+
+const PNG_HEADER: &[u8] = &[
+    0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0, 0,
+    1, 0, 0, 0, 1, 8, 2, 0, 0, 0, 0, 0, 0, 0,
+];
+
+/// Opus sample with two legacy `COVERART` fields and one broken `METADATA_BLOCK_PICTURE` field
+fn opus_with_legacy_cover_art() -> Vec<u8> {
+    let data = std::fs::read(samples_dir().join("ogg.opus")).unwrap();
+    let mut opus =
+        OpusFile::read_from(&mut Cursor::new(data.clone()), ParseOptions::new()).unwrap();
+    let comments = opus.vorbis_comments_mut();
+    comments.remove_pictures();
+    comments.push("COVERART".to_string(), BASE64.encode(b"legacy image"));
+    comments.push("COVERARTMIME".to_string(), "image/x-custom".to_string());
+    comments.push("COVERART".to_string(), BASE64.encode(PNG_HEADER));
+    comments.push(
+        "METADATA_BLOCK_PICTURE".to_string(),
+        "not base64!".to_string(),
+    );
+
+    let mut output = Cursor::new(data);
+    comments
+        .save_to(&mut output, WriteOptions::default())
+        .unwrap();
+    output.into_inner()
+}
+
+#[test]
+fn test_ogg_picture_fields() {
+    let t = load(opus_with_legacy_cover_art());
+
+    let fields = t.ogg_picture_fields().expect("opus has picture fields");
+    assert_eq!(fields.block_pictures, 0);
+    assert_eq!(fields.legacy_cover_art, 2);
+    assert_eq!(fields.invalid, 1);
+}
+
+#[test]
+fn test_ogg_picture_fields_other_formats() {
+    assert!(music_file_from_buffer("flac.flac")
+        .ogg_picture_fields()
+        .is_none());
+    assert!(music_file_from_buffer("mp3.mp3")
+        .ogg_picture_fields()
+        .is_none());
+}
+
+#[test]
+fn test_read_legacy_cover_art() {
+    let t = load(opus_with_legacy_cover_art());
+
+    let pictures = t.pictures().expect("legacy pictures are read");
+    assert_eq!(pictures.len(), 2);
+    assert_eq!(pictures[0].data.as_ref(), b"legacy image");
+    assert_eq!(pictures[0].mime_type.as_deref(), Some("image/x-custom"));
+    assert_eq!(pictures[1].mime_type.as_deref(), Some("image/png"));
+    assert!(!t.is_dirty());
+}
+
+#[test]
+fn test_convert_legacy_cover_art() {
+    let data = opus_with_legacy_cover_art();
+    let mut t = load(data.clone());
+
+    assert_eq!(t.convert_legacy_cover_art(None).unwrap(), 2);
+    assert!(t.is_dirty());
+    assert_eq!(t.get_pictures_by_type(CoverType::Front).len(), 2);
+
+    let mut reloaded = load(save(&mut t, data));
+    let fields = reloaded.ogg_picture_fields().unwrap();
+    assert_eq!(fields.block_pictures, 2);
+    assert_eq!(fields.legacy_cover_art, 0);
+    assert_eq!(fields.invalid, 0);
+    let front = reloaded.front_cover().unwrap();
+    assert_eq!(front.mime_type.as_deref(), Some("image/x-custom"));
+    assert_eq!(reloaded.convert_legacy_cover_art(None).unwrap(), 0);
+}
+
+#[test]
+fn test_convert_legacy_cover_art_keeping_type() {
+    let mut t = load(opus_with_legacy_cover_art());
+
+    assert_eq!(
+        t.convert_legacy_cover_art(Some(CoverType::Other)).unwrap(),
+        2
+    );
+    assert!(t.is_dirty());
+
+    t.revert();
+    assert!(!t.is_dirty());
+}
+
+#[test]
+fn test_unmapped_comments_survive_save() {
+    let data = std::fs::read(samples_dir().join("ogg.opus")).unwrap();
+    let mut opus =
+        OpusFile::read_from(&mut Cursor::new(data.clone()), ParseOptions::new()).unwrap();
+    let comments = opus.vorbis_comments_mut();
+    comments.push(
+        "CUESHEET".to_string(),
+        "FILE \"ogg.opus\" WAVE\n  TRACK 01 AUDIO\n    INDEX 01 00:00:00\n".to_string(),
+    );
+    comments.push("RIPPER".to_string(), "first".to_string());
+    comments.push("RIPPER".to_string(), "second".to_string());
+    let mut output = Cursor::new(data);
+    comments
+        .save_to(&mut output, WriteOptions::default())
+        .unwrap();
+    let data = output.into_inner();
+
+    let mut t = load(data.clone());
+    t.set_title(Either::A("New title".to_string())).unwrap();
+    let saved = save(&mut t, data);
+
+    let reloaded = load(saved.clone());
+    assert_eq!(reloaded.title().as_deref(), Some("New title"));
+    assert_eq!(reloaded.embedded_cue_sheet().unwrap().tracks.len(), 1);
+    let opus = OpusFile::read_from(&mut Cursor::new(saved), ParseOptions::new()).unwrap();
+    let rippers: Vec<&str> = opus.vorbis_comments().get_all("RIPPER").collect();
+    assert_eq!(rippers, ["first", "second"]);
+}
+
+#[test]
+fn test_validate_block_pictures() {
+    let mut t = load(opus_with_legacy_cover_art());
+
+    let result = t.tag(|tag| Some(validate_block_pictures(tag, &[]).is_ok()));
+    assert_eq!(result, Some(true));
+
+    // Unchanged files have no new pictures to check
+    assert!(t.validate_ogg_pictures().is_ok());
+    t.convert_legacy_cover_art(None).unwrap();
+    assert!(t.validate_ogg_pictures().is_ok());
+}
@@ -0,0 +1,116 @@
+use std::io::Cursor;
+
+use lofty::tag::ItemKey;
+use napi::{bindgen_prelude::Null, Either};
+use ogg_pager::Page;
+
+use crate::{
+    music_file::MusicFile,
+    tests::{load, music_file_from_buffer, samples_dir, save},
+};
+
+fn tag_text(t: &MusicFile, key: ItemKey) -> Option<String> {
+    t.tag(|tag| tag.get_string(key).map(str::to_owned))
+}
+
+#[test]
+fn test_opus_output_gain() {
+    let data = std::fs::read(samples_dir().join("ogg.opus")).unwrap();
+    let mut t = load(data.clone());
+    assert_eq!(t.opus_output_gain(), Some(0.0));
+
+    t.set_opus_output_gain(-3.5).unwrap();
+    assert!(t.is_dirty());
+    assert_eq!(t.opus_output_gain(), Some(-3.5));
+
+    let saved = save(&mut t, data.clone());
+    assert_eq!(saved.len(), data.len());
+    let reloaded = load(saved.clone());
+    assert_eq!(reloaded.opus_output_gain(), Some(-3.5));
+    assert_eq!(
+        reloaded.codec_info().unwrap().opus.unwrap().output_gain,
+        -3.5
+    );
+    assert_eq!(reloaded.duration(), t.duration());
+
+    // The checksum of the rewritten page is valid
+    let page = Page::read(&mut Cursor::new(&saved)).unwrap();
+    let checksum = page.header().checksum();
+    let mut regenerated = page.clone();
+    regenerated.gen_crc();
+    assert_eq!(regenerated.header().checksum(), checksum);
+}
+
+#[test]
+fn test_opus_output_gain_revert() {
+    let mut t = music_file_from_buffer("ogg.opus");
+    t.set_opus_output_gain(1.0).unwrap();
+    t.revert();
+    assert!(!t.is_dirty());
+    assert_eq!(t.opus_output_gain(), Some(0.0));
+}
+
+#[test]
+fn test_opus_output_gain_invalid() {
+    let mut t = music_file_from_buffer("ogg.opus");
+    assert!(t.set_opus_output_gain(128.0).is_err());
+    assert!(t.set_opus_output_gain(f64::NAN).is_err());
+    assert_eq!(t.opus_output_gain(), Some(0.0));
+
+    let mut t = music_file_from_buffer("flac.flac");
+    assert_eq!(t.opus_output_gain(), None);
+    let error = t.set_opus_output_gain(1.0).err().unwrap();
+    assert_eq!(error.reason, "This file is not an Opus file");
+}
+
+#[test]
+fn test_opus_replay_gain_as_r128() {
+    let data = std::fs::read(samples_dir().join("ogg.opus")).unwrap();
+    let mut t = load(data.clone());
+
+    // R128 gains target -23 LUFS, 5 dB below the ReplayGain 2.0 reference
+    t.set_track_replay_gain(Either::A(-2.0)).unwrap();
+    t.set_album_replay_gain(Either::A(5.0)).unwrap();
+    assert_eq!(
+        tag_text(&t, ItemKey::R128TrackGain).as_deref(),
+        Some("-1792")
+    );
+    assert_eq!(tag_text(&t, ItemKey::R128AlbumGain).as_deref(), Some("0"));
+    assert_eq!(tag_text(&t, ItemKey::ReplayGainTrackGain), None);
+
+    let reloaded = load(save(&mut t, data));
+    assert_eq!(reloaded.track_replay_gain(), Some(-2.0));
+    assert_eq!(reloaded.album_replay_gain(), Some(5.0));
+
+    t.set_track_replay_gain(Either::B(Null)).unwrap();
+    assert_eq!(t.track_replay_gain(), None);
+    assert_eq!(tag_text(&t, ItemKey::R128TrackGain), None);
+
+    assert!(t.set_track_replay_gain(Either::A(140.0)).is_err());
+}
+
+#[test]
+fn test_opus_replay_gain_fallback() {
+    let mut t = music_file_from_buffer("ogg.opus");
+    t.set_gain_value(ItemKey::ReplayGainTrackGain, Either::A(-4.0), |value| {
+        format!("{value:+.2} dB")
+    })
+    .unwrap();
+    assert_eq!(t.track_replay_gain(), Some(-4.0));
+
+    // The R128 gain takes precedence, and replaces the ReplayGain tag
+    t.set_track_replay_gain(Either::A(1.0)).unwrap();
+    assert_eq!(t.track_replay_gain(), Some(1.0));
+    assert_eq!(tag_text(&t, ItemKey::ReplayGainTrackGain), None);
+}
+
+#[test]
+fn test_replay_gain_other_formats() {
+    let mut t = music_file_from_buffer("flac.flac");
+    t.set_track_replay_gain(Either::A(-2.0)).unwrap();
+    assert_eq!(
+        tag_text(&t, ItemKey::ReplayGainTrackGain).as_deref(),
+        Some("-2.00 dB")
+    );
+    assert_eq!(tag_text(&t, ItemKey::R128TrackGain), None);
+}
@@ -0,0 +1,180 @@
+use std::io::Cursor;
+
+use lofty::{
+    config::{ParseOptions, WriteOptions},
+    file::AudioFile,
+    flac::FlacFile,
+    tag::TagExt,
+};
+use napi::Either;
+
+use crate::{
+    music_file::{
+        quality::{QualityLabels, QualityOptions},
+        MusicFile,
+    },
+    tests::{music_file_from_buffer, samples_dir},
+};
+
+fn load(data: Vec<u8>) -> MusicFile {
+    MusicFile::load_sync(Either::A(data.into()), None).unwrap()
+}
+
+/// FLAC sample with the Vorbis comments written by MQA encoders
+fn flac_with_mqa_comments() -> Vec<u8> {
+    let data = std::fs::read(samples_dir().join("flac.flac")).unwrap();
+    let mut flac =
+        FlacFile::read_from(&mut Cursor::new(data.clone()), ParseOptions::new()).unwrap();
+    let comments = flac.vorbis_comments_mut().unwrap();
+    comments.push("MQAENCODER".to_string(), "MQAEncode v1.1".to_string());
+    comments.push("ORIGINALSAMPLERATE".to_string(), "96000".to_string());
+
+    let mut output = Cursor::new(data);
+    comments
+        .save_to(&mut output, WriteOptions::default())
+        .unwrap();
+    output.into_inner()
+}
+
+/// One second of 8 kHz mono A-law audio in a WAV file
+fn alaw_wav() -> Vec<u8> {
+    let mut fmt = Vec::new();
+    fmt.extend(6u16.to_le_bytes()); // WAVE_FORMAT_ALAW
+    fmt.extend(1u16.to_le_bytes());
+    fmt.extend(8000u32.to_le_bytes());
+    fmt.extend(8000u32.to_le_bytes());
+    fmt.extend(1u16.to_le_bytes());
+    fmt.extend(8u16.to_le_bytes());
+    fmt.extend(0u16.to_le_bytes());
+
+    let mut body = b"WAVE".to_vec();
+    body.extend(b"fmt ");
+    body.extend((fmt.len() as u32).to_le_bytes());
+    body.extend(fmt);
+    // Non-PCM formats need the sample count
+    body.extend(b"fact");
+    body.extend(4u32.to_le_bytes());
+    body.extend(8000u32.to_le_bytes());
+    body.extend(b"data");
+    body.extend(8000u32.to_le_bytes());
+    body.extend(vec![0xd5; 8000]);
+
+    let mut data = b"RIFF".to_vec();
+    data.extend((body.len() as u32).to_le_bytes());
+    data.extend(body);
+    data
+}
+
+#[test]
+fn test_quality_lossy() {
+    let t = music_file_from_buffer("mp3.mp3");
+    assert_eq!(t.quality(), "HQ");
+
+    let result = t.classify_quality(None);
+    assert_eq!(result.tier, "HQ");
+    assert_eq!(result.label, "HQ");
+    assert!(!result.lossless);
+    assert_eq!(result.reasons, ["MP3 is lossy"]);
+
+    let result = t.classify_quality(Some(QualityOptions {
+        min_hq_bit_rate: Some(5000),
+        labels: Some(QualityLabels {
+            lq: Some("Low".into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }));
+    assert_eq!(result.tier, "LQ");
+    assert_eq!(result.label, "Low");
+    assert!(result.reasons[1].ends_with("is below 5000 kbps"));
+}
+
+#[test]
+fn test_quality_thresholds() {
+    // The sample is 24-bit at 44.1 kHz, the simple classification needs more than 44.1 kHz
+    let t = music_file_from_buffer("flac.flac");
+    assert_eq!(t.quality(), "SQ");
+
+    let result = t.classify_quality(None);
+    assert_eq!(result.tier, "HiRes");
+    assert!(result.lossless);
+    assert_eq!(
+        result.reasons,
+        ["FLAC is lossless", "Bit depth 24 is at least 24 bits"]
+    );
+
+    let result = t.classify_quality(Some(QualityOptions {
+        min_hi_res_bit_depth: Some(32),
+        labels: Some(QualityLabels {
+            sq: Some("Lossless".into()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }));
+    assert_eq!(result.tier, "SQ");
+    assert_eq!(result.label, "Lossless");
+    assert!(result.reasons[1].contains("does not exceed CD quality"));
+
+    let result = t.classify_quality(Some(QualityOptions {
+        min_hi_res_sample_rate: Some(44100),
+        min_hi_res_bit_depth: Some(32),
+        ..Default::default()
+    }));
+    assert_eq!(result.tier, "HiRes");
+}
+
+#[test]
+fn test_quality_float_wav() {
+    let t = music_file_from_buffer("wav.wav");
+    let result = t.classify_quality(None);
+
+    assert_eq!(result.tier, "HiRes");
+    assert!(result.lossless);
+    assert!(result
+        .reasons
+        .contains(&"Floating point samples".to_string()));
+}
+
+#[test]
+fn test_quality_lossy_wav() {
+    let t = load(alaw_wav());
+    let result = t.classify_quality(None);
+
+    assert_eq!(t.quality(), "HQ");
+    assert!(!result.lossless);
+    assert_eq!(result.reasons, ["A-law is lossy"]);
+}
+
+#[test]
+fn test_quality_mqa() {
+    let t = load(flac_with_mqa_comments());
+    let result = t.classify_quality(None);
+
+    assert_eq!(result.tier, "MQA?");
+    assert!(result.lossless);
+    assert!(result
+        .reasons
+        .contains(&"Tagged by MQA encoder \"MQAEncode v1.1\"".to_string()));
+    assert!(result
+        .reasons
+        .contains(&"MQA original sample rate is 96000 Hz".to_string()));
+
+    // The MQA comments survive saving other changes
+    let mut edited = load(flac_with_mqa_comments());
+    edited.set_title(Either::A("Title".to_string())).unwrap();
+    let Either::B(saved) = edited
+        .save_sync(Some(Either::A(flac_with_mqa_comments().into())))
+        .unwrap()
+    else {
+        panic!("expected buffer");
+    };
+    assert_eq!(load(saved.to_vec()).classify_quality(None).tier, "MQA?");
+
+    // The simple classification ignores MQA
+    assert_eq!(t.quality(), "SQ");
+    let result = t.classify_quality(Some(QualityOptions {
+        mqa: Some(false),
+        ..Default::default()
+    }));
+    assert_eq!(result.tier, "HiRes");
+}
@@ -0,0 +1,108 @@
+use std::fs;
+
+use crate::{
+    scan::{glob_match, scan_directory_sync, ScanOptions},
+    tests::samples_dir,
+};
+
+fn samples_root() -> String {
+    samples_dir().to_str().unwrap().to_string()
+}
+
+// ── glob matching ───────────────────────────────────────────────────────
+
+#[test]
+fn test_glob_match() {
+    assert!(glob_match("*.flac", "a.flac"));
+    assert!(!glob_match("*.flac", "dir/a.flac"));
+    assert!(glob_match("**/*.flac", "a.flac"));
+    assert!(glob_match("**/*.flac", "dir/sub/a.flac"));
+    assert!(glob_match("dir/?.mp3", "dir/a.mp3"));
+    assert!(!glob_match("dir/?.mp3", "dir/ab.mp3"));
+    assert!(glob_match("dir/**", "dir/sub/a.mp3"));
+}
+
+// ── directory scanning ──────────────────────────────────────────────────
+
+#[test]
+fn test_scan_detects_audio_by_content() {
+    let entries = scan_directory_sync(samples_root(), None).unwrap();
+    let names: Vec<_> = entries
+        .iter()
+        .map(|entry| entry.path.rsplit(['/', '\\']).next().unwrap())
+        .collect();
+
+    assert!(names.contains(&"flac.flac"));
+    assert!(names.contains(&"ogg.opus"));
+    // garbage with an audio extension is not reported
+    assert!(!names.contains(&"not-mp3.mp3"));
+
+    let flac = entries
+        .iter()
+        .find(|entry| entry.path.ends_with("flac.flac"))
+        .unwrap();
+    assert_eq!(flac.file_type, "Flac");
+    assert!(flac.duration > 0.0);
+    assert!(flac.size > 0.0);
+    assert!(flac.title.is_some());
+}
+
+#[test]
+fn test_scan_filters() {
+    let entries = scan_directory_sync(
+        samples_root(),
+        Some(ScanOptions {
+            file_types: Some(vec!["mpeg".to_string()]),
+            exclude: Some(vec!["*-temp.*".to_string()]),
+            read_tags: Some(false),
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+
+    assert!(!entries.is_empty());
+    assert!(entries.iter().all(|entry| entry.file_type == "Mpeg"));
+    assert!(entries.iter().all(|entry| !entry.path.contains("mp3-temp")));
+    assert!(entries.iter().all(|entry| entry.title.is_none()));
+
+    let entries = scan_directory_sync(
+        samples_root(),
+        Some(ScanOptions {
+            include: Some(vec!["*.opus".to_string()]),
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].file_type, "Opus");
+}
+
+#[test]
+fn test_scan_max_depth() {
+    let root = tempfile::tempdir().unwrap();
+    let nested = root.path().join("a").join("b");
+    fs::create_dir_all(&nested).unwrap();
+    fs::copy(samples_dir().join("flac.flac"), root.path().join("top")).unwrap();
+    fs::copy(samples_dir().join("flac.flac"), nested.join("deep.flac")).unwrap();
+
+    let root_path = root.path().to_str().unwrap().to_string();
+    let all = scan_directory_sync(root_path.clone(), None).unwrap();
+    // the extension-less file is still detected by content
+    assert_eq!(all.len(), 2);
+
+    let shallow = scan_directory_sync(
+        root_path,
+        Some(ScanOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        }),
+    )
+    .unwrap();
+    assert_eq!(shallow.len(), 1);
+    assert!(shallow[0].path.ends_with("top"));
+}
+
+#[test]
+fn test_scan_rejects_missing_root() {
+    assert!(scan_directory_sync("non-existent-directory".to_string(), None).is_err());
+}
@@ -0,0 +1,161 @@
+use std::{fs, path::Path};
+
+use napi::{bindgen_prelude::Null, Either};
+
+use crate::{
+    meta_picture::{CoverType, MetaPicture},
+    music_file::{sidecar::SidecarArtOptions, MusicFile},
+    tests::{music_file_from_buffer, samples_dir},
+};
+
+const PNG_HEADER: &[u8] = &[
+    0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0, 0,
+    1, 0, 0, 0, 1, 8, 2, 0, 0, 0, 0, 0, 0, 0,
+];
+const GIF_HEADER: &[u8] = &[b'G', b'I', b'F', b'8', b'9', b'a', 1, 0, 1, 0, 0, 0, 0];
+const JPEG_HEADER: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0, 2];
+
+/// Copy the FLAC sample into a temporary directory and load it without pictures
+fn load_without_pictures(dir: &Path) -> MusicFile {
+    let path = dir.join("track.flac");
+    fs::copy(samples_dir().join("flac.flac"), &path).unwrap();
+
+    let mut t = MusicFile::load_sync(Either::B(path.to_str().unwrap().to_string()), None).unwrap();
+    t.set_pictures(Either::B(Null)).unwrap();
+    t
+}
+
+#[test]
+fn test_import_sidecar_art() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("Folder.jpg"), JPEG_HEADER).unwrap();
+    fs::write(dir.path().join("cover.png"), PNG_HEADER).unwrap();
+    fs::write(dir.path().join("back.gif"), GIF_HEADER).unwrap();
+    fs::write(dir.path().join("artist.jpg"), b"not an image").unwrap();
+    fs::write(dir.path().join("notes.txt"), b"cover").unwrap();
+    let mut t = load_without_pictures(dir.path());
+
+    let imported = t.import_sidecar_art(None).unwrap();
+
+    assert_eq!(imported.len(), 2);
+    assert!(imported[0].ends_with("cover.png"));
+    assert!(imported[1].ends_with("back.gif"));
+    let front = t.front_cover().unwrap();
+    assert_eq!(front.data.as_ref(), PNG_HEADER);
+    assert_eq!(front.mime_type.as_deref(), Some("image/png"));
+    assert_eq!(t.get_pictures_by_type(CoverType::Back).len(), 1);
+    assert!(t.get_pictures_by_type(CoverType::Artist).is_empty());
+}
+
+#[test]
+fn test_import_sidecar_art_keeps_existing() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("cover.png"), PNG_HEADER).unwrap();
+    let mut t = load_without_pictures(dir.path());
+    let existing = MetaPicture::new(
+        "image/jpeg".to_string(),
+        JPEG_HEADER.to_vec().into(),
+        None,
+        None,
+    );
+    t.set_picture(CoverType::Front, &existing).unwrap();
+
+    assert!(t.import_sidecar_art(None).unwrap().is_empty());
+    assert_eq!(t.front_cover().unwrap().data.as_ref(), JPEG_HEADER);
+
+    let options = SidecarArtOptions {
+        replace: Some(true),
+    };
+    assert_eq!(t.import_sidecar_art(Some(options)).unwrap().len(), 1);
+    assert_eq!(t.front_cover().unwrap().data.as_ref(), PNG_HEADER);
+    assert_eq!(t.pictures().map(|pics| pics.len()), Some(1));
+}
+
+#[test]
+fn test_import_sidecar_art_from_buffer_fails() {
+    let mut t = music_file_from_buffer("flac.flac");
+    assert!(t.import_sidecar_art(None).is_err());
+}
+
+#[test]
+fn test_export_pictures() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut t = music_file_from_buffer("flac.flac");
+    let front = MetaPicture::new(
+        "image/png".to_string(),
+        PNG_HEADER.to_vec().into(),
+        None,
+        None,
+    );
+    let back = MetaPicture::new(
+        "image/gif".to_string(),
+        GIF_HEADER.to_vec().into(),
+        None,
+        Some(CoverType::Back),
+    );
+    let other_back = MetaPicture::new(
+        "image/jpeg".to_string(),
+        JPEG_HEADER.to_vec().into(),
+        None,
+        Some(CoverType::Back),
+    );
+    t.set_pictures(Either::A(vec![&front, &back, &other_back]))
+        .unwrap();
+
+    let out = dir.path().join("art");
+    let written = t
+        .export_pictures(out.to_str().unwrap().to_string(), None)
+        .unwrap();
+
+    assert_eq!(written.len(), 3);
+    assert_eq!(fs::read(out.join("cover.png")).unwrap(), PNG_HEADER);
+    assert_eq!(fs::read(out.join("back.gif")).unwrap(), GIF_HEADER);
+    assert_eq!(fs::read(out.join("back.jpg")).unwrap(), JPEG_HEADER);
+
+    let written = t
+        .export_pictures(
+            out.to_str().unwrap().to_string(),
+            Some("{index}-{type}".to_string()),
+        )
+        .unwrap();
+    assert!(written[1].ends_with("2-back.gif"));
+}
+
+#[test]
+fn test_export_pictures_same_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut t = music_file_from_buffer("flac.flac");
+    let first = MetaPicture::new(
+        "image/png".to_string(),
+        PNG_HEADER.to_vec().into(),
+        None,
+        None,
+    );
+    let second = MetaPicture::new(
+        "image/png".to_string(),
+        PNG_HEADER.to_vec().into(),
+        None,
+        None,
+    );
+    t.set_pictures(Either::A(vec![&first, &second])).unwrap();
+
+    let written = t
+        .export_pictures(dir.path().to_str().unwrap().to_string(), None)
+        .unwrap();
+
+    assert!(written[0].ends_with("cover.png"));
+    assert!(written[1].ends_with("cover-2.png"));
+}
+
+#[test]
+fn test_export_pictures_rejects_path_template() {
+    let dir = tempfile::tempdir().unwrap();
+    let t = music_file_from_buffer("mp3.mp3");
+
+    assert!(t
+        .export_pictures(
+            dir.path().to_str().unwrap().to_string(),
+            Some("../{type}".to_string()),
+        )
+        .is_err());
+}
@@ -0,0 +1,78 @@
+use napi::Either;
+
+use crate::{
+    meta_picture::MetaPicture,
+    music_file::snapshot::SnapshotOptions,
+    tests::{music_file_from_buffer, music_file_from_path},
+};
+
+// ── snapshot ────────────────────────────────────────────────────────────
+
+#[test]
+fn test_snapshot_matches_getters() {
+    let t = music_file_from_path("flac.flac");
+    let snapshot = t.snapshot(None);
+
+    assert_eq!(snapshot.tag_type, t.tag_type());
+    assert_eq!(snapshot.title, t.title());
+    assert_eq!(snapshot.artist, t.artist());
+    assert_eq!(snapshot.track_number, t.track_number());
+    assert_eq!(snapshot.duration, t.duration());
+    assert_eq!(snapshot.sample_rate, t.sample_rate());
+    assert_eq!(snapshot.quality, t.quality());
+}
+
+#[test]
+fn test_snapshot_matches_getters_opus() {
+    let mut t = music_file_from_buffer("ogg.opus");
+    t.set_track_replay_gain(Either::A(-3.5)).unwrap();
+    t.set_comment(Either::A("Comment".to_string())).unwrap();
+    t.set_rating(Either::A(4.0)).unwrap();
+    let snapshot = t.snapshot(None);
+
+    assert_eq!(snapshot.tag_type, t.tag_type());
+    assert_eq!(snapshot.track_replay_gain, Some(-3.5));
+    assert_eq!(snapshot.track_replay_gain, t.track_replay_gain());
+    assert_eq!(snapshot.comment, t.comment());
+    assert_eq!(snapshot.rating, t.rating());
+    assert_eq!(snapshot.lyrics, t.lyrics());
+}
+
+#[test]
+fn test_snapshot_picture_data_is_opt_in() {
+    let mut t = music_file_from_buffer("mp3.mp3");
+    let picture = MetaPicture::new(
+        "image/png".to_string(),
+        vec![1u8, 2, 3, 4].into(),
+        Some("Cover".to_string()),
+        None,
+    );
+    t.set_pictures(Either::A(vec![&picture])).unwrap();
+
+    let snapshot = t.to_json();
+    assert_eq!(snapshot.pictures.len(), 1);
+    assert_eq!(snapshot.pictures[0].size, 4);
+    assert_eq!(snapshot.pictures[0].description.as_deref(), Some("Cover"));
+    assert!(snapshot.pictures[0].data.is_none());
+    assert!(snapshot.pictures[0].hash.is_none());
+
+    let snapshot = t.snapshot(Some(SnapshotOptions {
+        include_picture_data: Some(true),
+        include_picture_hashes: Some(true),
+    }));
+    assert_eq!(
+        snapshot.pictures[0].data.as_ref().map(|data| data.to_vec()),
+        Some(vec![1u8, 2, 3, 4])
+    );
+    assert_eq!(snapshot.pictures[0].hash, Some(picture.hash()));
+}
+
+#[test]
+fn test_snapshot_without_tag() {
+    let snapshot = music_file_from_path("mp3-no-tags.mp3").snapshot(None);
+
+    assert!(snapshot.tag_type.is_none());
+    assert!(snapshot.title.is_none());
+    assert!(snapshot.pictures.is_empty());
+    assert!(snapshot.duration > 0.0);
+}
@@ -0,0 +1,137 @@
+use napi::{bindgen_prelude::Null, Either};
+
+use crate::{
+    music_file::sound_check::{format_sound_check, parse_sound_check},
+    tests::{empty_mp4, load, music_file_from_buffer, save},
+};
+
+#[test]
+fn test_sound_check_text() {
+    let text = format_sound_check(-2.0, 0.5).unwrap();
+    assert_eq!(
+        text,
+        " 00000631 00000631 00000F7A 00000F7A 00000000 00000000 00004000 00004000 00000000 00000000"
+    );
+
+    let sound_check = parse_sound_check(text).unwrap();
+    assert!((sound_check.gain + 2.0).abs() < 0.01);
+    assert!((sound_check.peak - 0.5).abs() < 0.0001);
+    assert_eq!(sound_check.values[0], 1585);
+
+    // Attenuation is clamped
+    let sound_check = parse_sound_check(format_sound_check(-30.0, 1.0).unwrap()).unwrap();
+    assert_eq!(sound_check.values[0], 65534);
+
+    assert!(parse_sound_check("00000631 00000631".to_string()).is_none());
+    assert!(parse_sound_check(" 0 0 0 0 0 0 0 0 0 0".to_string()).is_none());
+    assert!(format_sound_check(f64::NAN, 1.0).is_err());
+    assert!(format_sound_check(0.0, -1.0).is_err());
+}
+
+#[test]
+fn test_sound_check_id3v2() {
+    let data = std::fs::read(crate::tests::samples_dir().join("mp3.mp3")).unwrap();
+    let mut t = load(data.clone());
+    assert!(t.sound_check().is_none());
+
+    t.set_sound_check(Either::A(-3.0), Some(0.25)).unwrap();
+    assert!(t.is_dirty());
+    let mut t = load(save(&mut t, data.clone()));
+    let sound_check = t.sound_check().unwrap();
+    assert!((sound_check.gain + 3.0).abs() < 0.01);
+    assert!((sound_check.peak - 0.25).abs() < 0.0001);
+
+    // The comment is kept apart from the Sound Check
+    assert_eq!(t.comment().as_deref(), Some("test comment"));
+    t.set_comment(Either::A("Comment".to_string())).unwrap();
+    let mut t = load(save(&mut t, data));
+    assert_eq!(t.comment().as_deref(), Some("Comment"));
+    assert!(t.sound_check().is_some());
+
+    t.set_sound_check(Either::B(Null), None).unwrap();
+    assert!(t.sound_check().is_none());
+    assert_eq!(t.comment().as_deref(), Some("Comment"));
+}
+
+#[test]
+fn test_sound_check_mp4() {
+    let data = empty_mp4();
+    let mut t = load(data.clone());
+    assert!(t.sound_check().is_none());
+
+    t.set_sound_check(Either::A(4.0), Some(1.0)).unwrap();
+    assert!(t.is_dirty());
+    t.revert();
+    assert!(!t.is_dirty());
+    assert!(t.sound_check().is_none());
+
+    t.set_title(Either::A("Title".to_string())).unwrap();
+    t.set_sound_check(Either::A(4.0), Some(1.0)).unwrap();
+    let saved = save(&mut t, data);
+    let mut reloaded = load(saved.clone());
+    assert!((reloaded.sound_check().unwrap().gain - 4.0).abs() < 0.01);
+    assert_eq!(reloaded.title().as_deref(), Some("Title"));
+
+    reloaded.set_sound_check(Either::B(Null), None).unwrap();
+    assert!(load(save(&mut reloaded, saved)).sound_check().is_none());
+}
+
+#[test]
+fn test_mp4_itunes_atoms_kept() {
+    let data = empty_mp4();
+    let mut t = load(data.clone());
+    t.set_sound_check(Either::A(-3.0), Some(1.0)).unwrap();
+    t.set_itunes_comment("iTunSMPB", Some("smpb".to_string()))
+        .unwrap();
+    let data = save(&mut t, data);
+
+    // Saving an unrelated field keeps both atoms
+    let mut t = load(data.clone());
+    t.set_title(Either::A("Title".to_string())).unwrap();
+    let data = save(&mut t, data);
+    let mut t = load(data.clone());
+    assert!((t.sound_check().unwrap().gain + 3.0).abs() < 0.01);
+    assert_eq!(t.itunes_comment("iTunSMPB").as_deref(), Some("smpb"));
+
+    // Changing one atom keeps the other
+    t.set_sound_check(Either::A(2.0), None).unwrap();
+    let t = load(save(&mut t, data));
+    assert!((t.sound_check().unwrap().gain - 2.0).abs() < 0.01);
+    assert_eq!(t.itunes_comment("iTunSMPB").as_deref(), Some("smpb"));
+}
+
+#[test]
+fn test_sound_check_unsupported() {
+    let mut t = music_file_from_buffer("flac.flac");
+    assert!(t.sound_check().is_none());
+    let error = t.set_sound_check(Either::A(0.0), None).err().unwrap();
+    assert_eq!(
+        error.reason,
+        "iTunNORM is only supported in MP4 files and ID3v2 tags"
+    );
+}
+
+#[test]
+fn test_sound_check_replay_gain() {
+    let mut t = load(empty_mp4());
+    assert!(!t.sound_check_from_replay_gain().unwrap());
+    assert!(!t.replay_gain_from_sound_check().unwrap());
+
+    t.set_track_replay_gain(Either::A(-6.0)).unwrap();
+    t.set_track_replay_peak(Either::A(0.8)).unwrap();
+    assert!(t.sound_check_from_replay_gain().unwrap());
+    let sound_check = t.sound_check().unwrap();
+    assert!((sound_check.gain + 6.0).abs() < 0.01);
+    assert!((sound_check.peak - 0.8).abs() < 0.0001);
+
+    t.set_track_replay_gain(Either::B(Null)).unwrap();
+    t.set_track_replay_peak(Either::B(Null)).unwrap();
+    assert!(t.replay_gain_from_sound_check().unwrap());
+    assert!((t.track_replay_gain().unwrap() + 6.0).abs() < 0.01);
+    assert!((t.track_replay_peak().unwrap() - 0.8).abs() < 0.0001);
+
+    // Gain and peak are a single undo step
+    assert!(t.undo());
+    assert_eq!(t.track_replay_gain(), None);
+    assert_eq!(t.track_replay_peak(), None);
+}
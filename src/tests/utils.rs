@@ -1,3 +1,5 @@
+use lofty::file::FileType;
+
 use crate::utils::*;
 
 #[test]
@@ -76,3 +78,53 @@ fn test_parse_and_format_round_trip() {
         );
     }
 }
+
+#[test]
+fn test_file_type_extension_and_mime() {
+    assert_eq!(file_type_extension(&FileType::Mpeg), Some("mp3"));
+    assert_eq!(file_type_extension(&FileType::Mp4), Some("m4a"));
+    assert_eq!(file_type_extension(&FileType::Vorbis), Some("ogg"));
+    assert_eq!(file_type_extension(&FileType::WavPack), Some("wv"));
+    assert!(file_type_extensions(&FileType::Opus).contains(&"ogg"));
+    assert!(!file_type_extensions(&FileType::Vorbis).contains(&"opus"));
+    assert_eq!(file_type_mime(&FileType::Flac), Some("audio/flac"));
+    assert_eq!(file_type_mime(&FileType::Opus), Some("audio/ogg"));
+    assert_eq!(file_type_mime(&FileType::Wav), Some("audio/wav"));
+
+    // Every named file type has an extension and a MIME type
+    for file_type in [
+        FileType::Aac,
+        FileType::Aiff,
+        FileType::Ape,
+        FileType::Flac,
+        FileType::Mpeg,
+        FileType::Mp4,
+        FileType::Mpc,
+        FileType::Opus,
+        FileType::Vorbis,
+        FileType::Speex,
+        FileType::Wav,
+        FileType::WavPack,
+    ] {
+        assert!(file_type_name(&file_type).is_some());
+        assert!(file_type_extension(&file_type).is_some());
+        assert!(file_type_mime(&file_type).is_some());
+    }
+}
+
+#[test]
+fn test_r128_gain() {
+    assert_eq!(gain_to_q78(-3.5), Some(-896));
+    assert_eq!(gain_to_q78(127.99), Some(32765));
+    assert_eq!(gain_to_q78(128.0), None);
+    assert_eq!(gain_to_q78(f64::NAN), None);
+
+    // R128 gains target -23 LUFS, ReplayGain 2.0 targets -18 LUFS
+    assert_eq!(parse_r128_gain("-1792"), Some(-2.0));
+    assert_eq!(parse_r128_gain(" 0 "), Some(5.0));
+    assert_eq!(parse_r128_gain("1.5"), None);
+    assert_eq!(parse_r128_gain("40000"), None);
+    assert_eq!(format_r128_gain(-2.0).as_deref(), Some("-1792"));
+    assert_eq!(format_r128_gain(5.0).as_deref(), Some("0"));
+    assert_eq!(format_r128_gain(-124.0), None);
+}
@@ -1,3 +1,7 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use lofty::file::FileType;
+
 // Helper function to parse ReplayGain values
 pub fn parse_replaygain_value(value: &str) -> Option<f64> {
     // ReplayGain values are typically in format: "+1.23 dB" or "-1.23 dB"
@@ -20,3 +24,104 @@ pub fn format_replaygain_gain(value: f64) -> String {
 pub fn format_replaygain_peak(value: f64) -> String {
     format!("{:.6}", value)
 }
+
+/// ReplayGain 2.0 targets -18 LUFS, the R128 gains of Opus files target -23 LUFS
+pub const R128_REPLAYGAIN_OFFSET: f64 = 5.0;
+
+// Helper function to convert a gain in dB to Q7.8 fixed point, as used by Opus headers and R128 tags
+pub fn gain_to_q78(gain: f64) -> Option<i16> {
+    let value = (gain * 256.0).round();
+    (gain.is_finite() && (f64::from(i16::MIN)..=f64::from(i16::MAX)).contains(&value))
+        .then_some(value as i16)
+}
+
+// Helper function to parse an R128 gain tag as a ReplayGain gain in dB
+pub fn parse_r128_gain(value: &str) -> Option<f64> {
+    let value = value.trim().parse::<i16>().ok()?;
+    Some(f64::from(value) / 256.0 + R128_REPLAYGAIN_OFFSET)
+}
+
+// Helper function to format a ReplayGain gain in dB as an R128 gain tag
+pub fn format_r128_gain(value: f64) -> Option<String> {
+    gain_to_q78(value - R128_REPLAYGAIN_OFFSET).map(|value| value.to_string())
+}
+
+// Helper function to get the display name of a file type
+pub fn file_type_name(file_type: &FileType) -> Option<&'static str> {
+    match file_type {
+        FileType::Aac => Some("Aac"),
+        FileType::Aiff => Some("Aiff"),
+        FileType::Ape => Some("Ape"),
+        FileType::Flac => Some("Flac"),
+        FileType::Mpeg => Some("Mpeg"),
+        FileType::Mp4 => Some("Mp4"),
+        FileType::Mpc => Some("Mpc"),
+        FileType::Opus => Some("Opus"),
+        FileType::Vorbis => Some("Vorbis"),
+        FileType::Speex => Some("Speex"),
+        FileType::Wav => Some("Wav"),
+        FileType::WavPack => Some("WavPack"),
+        _ => None,
+    }
+}
+
+// Helper function to get the extensions accepted for a file type, without the leading dot.
+// The first one is the canonical extension.
+pub fn file_type_extensions(file_type: &FileType) -> &'static [&'static str] {
+    match file_type {
+        FileType::Aac => &["aac", "adts"],
+        FileType::Aiff => &["aiff", "aif", "aifc"],
+        FileType::Ape => &["ape"],
+        FileType::Flac => &["flac", "fla"],
+        FileType::Mpeg => &["mp3", "mp2", "mp1", "mpga"],
+        FileType::Mp4 => &["m4a", "m4b", "m4p", "m4r", "m4v", "mp4"],
+        FileType::Mpc => &["mpc", "mp+", "mpp"],
+        FileType::Opus => &["opus", "ogg", "oga"],
+        FileType::Vorbis => &["ogg", "oga"],
+        FileType::Speex => &["spx", "ogg", "oga"],
+        FileType::Wav => &["wav", "wave"],
+        FileType::WavPack => &["wv"],
+        _ => &[],
+    }
+}
+
+// Helper function to get the canonical extension of a file type, without the leading dot
+pub fn file_type_extension(file_type: &FileType) -> Option<&'static str> {
+    file_type_extensions(file_type).first().copied()
+}
+
+// Helper function to get the MIME type of a file type
+pub fn file_type_mime(file_type: &FileType) -> Option<&'static str> {
+    match file_type {
+        FileType::Aac => Some("audio/aac"),
+        FileType::Aiff => Some("audio/aiff"),
+        FileType::Ape => Some("audio/ape"),
+        FileType::Flac => Some("audio/flac"),
+        FileType::Mpeg => Some("audio/mpeg"),
+        FileType::Mp4 => Some("audio/mp4"),
+        FileType::Mpc => Some("audio/musepack"),
+        FileType::Opus | FileType::Vorbis | FileType::Speex => Some("audio/ogg"),
+        FileType::Wav => Some("audio/wav"),
+        FileType::WavPack => Some("audio/wavpack"),
+        _ => None,
+    }
+}
+
+// Helper function to skip an ID3v2 tag in front of an audio stream, returning the stream start
+pub fn skip_id3v2(reader: &mut (impl Read + Seek)) -> std::io::Result<u64> {
+    reader.rewind()?;
+    let mut header = [0; 10];
+    reader.read_exact(&mut header)?;
+
+    let mut start = 0;
+    if header.starts_with(b"ID3") {
+        let size = header[6..10]
+            .iter()
+            .fold(0u64, |size, byte| (size << 7) | u64::from(byte & 0x7F));
+        let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+        start = 10 + size + footer;
+    }
+
+    reader.seek(SeekFrom::Start(start))?;
+    Ok(start)
+}
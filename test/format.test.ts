@@ -3,7 +3,15 @@ import { join } from 'node:path'
 
 import { describe, it, expect, beforeEach } from 'vitest'
 
-import { MusicFile } from '../index.js'
+import {
+  MusicFile,
+  analyzeAlbumLoudness,
+  analyzeAlbumLoudnessSync,
+  detectFileType,
+  formatSoundCheck,
+  parseCueSheet,
+  parseSoundCheck,
+} from '../index.js'
 
 import { base } from './const.ts'
 
@@ -38,6 +46,125 @@ describe('Cross-format Metadata', { concurrent: false }, () => {
         expect(album === null || typeof album === 'string').toBe(true)
       })
 
+      it('should report Ogg picture fields only for Ogg files', () => {
+        const fields = musicFile.oggPictureFields
+        expect(fields !== null).toBe(sample.file.endsWith('.opus'))
+        expect(musicFile.convertLegacyCoverArt()).toBe(0)
+      })
+
+      it('should store ReplayGain as R128 gains in Opus files', async () => {
+        const opus = sample.file.endsWith('.opus')
+        expect(musicFile.opusOutputGain !== null).toBe(opus)
+        if (!opus) {
+          expect(() => musicFile.setOpusOutputGain(1)).toThrow()
+          return
+        }
+        musicFile.trackReplayGain = -2
+        musicFile.setOpusOutputGain(-1.5)
+
+        const reloaded = MusicFile.loadSync((await musicFile.save(buf)) as Uint8Array)
+        expect(reloaded.trackReplayGain).toBe(-2)
+        expect(reloaded.opusOutputGain).toBe(-1.5)
+      })
+
+      it('should report the file type detected from the content', () => {
+        const detected = detectFileType(buf)
+        expect(detected?.fileType).toBe(musicFile.fileType)
+        expect(sample.file.endsWith(`.${musicFile.extension}`)).toBe(true)
+        expect(musicFile.mimeType).toMatch(/^audio\//)
+      })
+
+      it('should check the file name extension', () => {
+        expect(musicFile.checkExtension(sample.file).matches).toBe(true)
+        const check = musicFile.checkExtension('track.bin')
+        expect(check.matches).toBe(false)
+        expect(check.expected).toBe(musicFile.extension)
+        expect(() => musicFile.fixExtension()).toThrow()
+      })
+
+      it('should locate the audio stream and metadata blocks', () => {
+        const layout = musicFile.audioLayout
+        expect(layout).not.toBeNull()
+        expect(layout!.fileSize).toBe(buf.length)
+        expect(layout!.audioOffset + layout!.audioSize).toBeLessThanOrEqual(buf.length)
+        expect(layout!.metadataSize).toBe(layout!.blocks.reduce((sum, block) => sum + block.size, 0))
+      })
+
+      it('should read codec info', () => {
+        const info = musicFile.codecInfo
+        expect(info).not.toBeNull()
+        expect(typeof info!.codec).toBe('string')
+        expect(info!.mpeg !== undefined).toBe(sample.file.endsWith('.mp3'))
+      })
+
+      it('should measure the loudness of decodable audio', async () => {
+        expect(() => musicFile.analyzeLoudnessSync()).toThrow()
+        if (sample.file.endsWith('.opus')) {
+          await expect(musicFile.analyzeLoudness(null, buf)).rejects.toThrow()
+          return
+        }
+        const result = await musicFile.analyzeLoudness({ write: true, truePeak: false }, buf)
+        expect(typeof result.peak).toBe('number')
+        expect(result.truePeak).toBeUndefined()
+        expect(musicFile.trackReplayGain).toBe(result.gain ?? null)
+
+        const { tracks, album } = await analyzeAlbumLoudness([musicFile], null, [buf])
+        expect(analyzeAlbumLoudnessSync([musicFile], null, [buf]).album.peak).toBe(album.peak)
+        expect(tracks).toHaveLength(1)
+        expect(album.peak).toBe(tracks[0]!.peak)
+      })
+
+      it('should report FLAC metadata blocks only for FLAC files', () => {
+        const flac = musicFile.flacMetadata
+        expect(flac !== null).toBe(sample.file.endsWith('.flac'))
+        if (flac) {
+          expect(flac.streamInfo.sampleRate).toBe(musicFile.sampleRate)
+        } else {
+          expect(() => musicFile.setFlacPadding(1024)).toThrow()
+        }
+      })
+
+      it('should read and write gapless playback info', () => {
+        const gapless = musicFile.gapless
+        if (musicFile.fileType === 'Flac' || musicFile.fileType === 'Opus') {
+          expect(gapless?.source ?? null).toBe(musicFile.fileType === 'Opus' ? 'Opus' : null)
+          expect(() => musicFile.setGapless(0, 0, 0)).toThrow()
+          return
+        }
+        musicFile.setGapless(2112, 1000, 44100)
+        expect(musicFile.gapless).toEqual({
+          source: 'iTunSMPB',
+          encoderDelay: 2112,
+          encoderPadding: 1000,
+          originalSamples: 44100,
+        })
+        expect(musicFile.removeGapless()).toBe(true)
+      })
+
+      it('should write the iTunes Sound Check except in Vorbis comments', () => {
+        expect(parseSoundCheck(formatSoundCheck(-2, 0.5))?.gain).toBeCloseTo(-2, 2)
+        if (musicFile.fileType === 'Flac' || musicFile.fileType === 'Opus') {
+          expect(() => musicFile.setSoundCheck(-2)).toThrow()
+          return
+        }
+        musicFile.setSoundCheck(-2, 0.5)
+        expect(musicFile.soundCheck?.gain).toBeCloseTo(-2, 2)
+        expect(musicFile.soundCheck?.peak).toBeCloseTo(0.5, 4)
+        expect(musicFile.comment).not.toContain('0000')
+        expect(musicFile.replayGainFromSoundCheck()).toBe(true)
+        expect(musicFile.trackReplayGain).toBeCloseTo(-2, 2)
+      })
+
+      it('should split the audio into cue sheet tracks', () => {
+        const cue = parseCueSheet(
+          `FILE "${sample.file}" WAVE\n  TRACK 01 AUDIO\n    INDEX 01 00:00:00\n  TRACK 02 AUDIO\n    INDEX 01 00:00:50\n`,
+        )
+        const tracks = musicFile.cueTracks(cue)
+        expect(tracks.map((track) => track.number)).toEqual([1, 2])
+        expect(tracks[0]!.end).toBeCloseTo(tracks[1]!.start)
+        expect(tracks[1]!.end).toBe(musicFile.duration)
+      })
+
       it('should read year (number or null)', () => {
         const year = musicFile.year
         expect(year === null || typeof year === 'number').toBe(true)
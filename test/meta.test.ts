@@ -3,7 +3,7 @@ import { join } from 'node:path'
 
 import { describe, it, expect, beforeEach } from 'vitest'
 
-import { MetaPicture, MusicFile } from '../index.js'
+import { MetaPicture, MusicFile, PictureType } from '../index.js'
 
 import { base } from './const.ts'
 
@@ -443,6 +443,171 @@ for (const sample of samples) {
         musicFile.pictures = null
         expect(musicFile.pictures).toBeNull()
       })
+
+      it('should set and remove pictures by type', () => {
+        musicFile.pictures = null
+        musicFile.setPicture(PictureType.Front, new MetaPicture('image/png', new Uint8Array([1])))
+        musicFile.setPicture(PictureType.Back, new MetaPicture('image/png', new Uint8Array([2])))
+        musicFile.setPicture(PictureType.Front, new MetaPicture('image/png', new Uint8Array([3])))
+
+        expect(musicFile.frontCover!.data).toEqual(new Uint8Array([3]))
+        expect(musicFile.getPicturesByType(PictureType.Back).length).toBe(1)
+        expect(musicFile.removePicture(PictureType.Front)).toBe(1)
+        expect(musicFile.frontCover).toBeNull()
+        expect(musicFile.pictures!.length).toBe(1)
+      })
+
+      it('should describe pictures without data', () => {
+        musicFile.pictures = [new MetaPicture('image/png', new Uint8Array([1, 2, 3]))]
+
+        const [info] = musicFile.pictureInfo
+        expect(info!.size).toBe(3)
+        expect(info!.hash).toBeUndefined()
+        expect(musicFile.getPictureHash(0)).toHaveLength(64)
+        expect(info!.data).toBeUndefined()
+        expect(musicFile.getPictureData(0)).toEqual(new Uint8Array([1, 2, 3]))
+        expect(musicFile.getPictureData(1)).toBeNull()
+      })
+
+      it('should remove duplicate pictures', () => {
+        musicFile.pictures = [
+          new MetaPicture('image/png', new Uint8Array([1, 2, 3]), undefined, PictureType.Other),
+          new MetaPicture('image/png', new Uint8Array([1, 2, 3])),
+        ]
+
+        expect(musicFile.dedupePictures()).toBe(1)
+        expect(musicFile.pictures!.length).toBe(1)
+        expect(musicFile.frontCover!.hash).toBe(musicFile.getPictureHash(0))
+      })
+
+      it('should report optimized pictures', async () => {
+        musicFile.pictures = [new MetaPicture('image/png', new Uint8Array([1, 2, 3]))]
+
+        const report = await musicFile.optimizePictures({ maxDimension: 500 })
+        expect(report.optimized).toBe(0)
+        expect(report.bytesBefore).toBe(3)
+        expect(report.bytesSaved).toBe(0)
+      })
+    })
+
+    describe('update', () => {
+      it('should apply all fields and report changed keys', () => {
+        const changed = musicFile.update({ title: 'Bulk Title', trackNumber: 5, rating: 3 })
+
+        expect(changed).toEqual(expect.arrayContaining(['title', 'trackNumber', 'rating']))
+        expect(musicFile.title).toBe('Bulk Title')
+        expect(musicFile.trackNumber).toBe(5)
+        expect(musicFile.rating).toBe(3)
+      })
+
+      it('should not apply anything when a field is invalid', () => {
+        musicFile.title = 'Original Title'
+
+        expect(() => musicFile.update({ title: 'Bulk Title', trackNumber: -1 })).toThrow()
+        expect(musicFile.title).toBe('Original Title')
+      })
+    })
+
+    describe('changes', () => {
+      it('should be clean after loading', () => {
+        expect(musicFile.isDirty).toBe(false)
+        expect(musicFile.changes()).toEqual([])
+      })
+
+      it('should report changed fields', () => {
+        const oldTitle = musicFile.title
+        musicFile.title = 'Changed Title'
+
+        expect(musicFile.isDirty).toBe(true)
+        expect(musicFile.changes()).toContainEqual(
+          expect.objectContaining({ field: 'title', oldValue: oldTitle, newValue: 'Changed Title' }),
+        )
+      })
+
+      it('should revert to the loaded tags', () => {
+        const oldTitle = musicFile.title
+        musicFile.title = 'Changed Title'
+        musicFile.revert()
+
+        expect(musicFile.isDirty).toBe(false)
+        expect(musicFile.title).toBe(oldTitle)
+      })
+    })
+
+    describe('history', () => {
+      it('should undo and redo setter calls', () => {
+        const oldTitle = musicFile.title
+        musicFile.title = 'Undo Title'
+
+        expect(musicFile.undo()).toBe(true)
+        expect(musicFile.title).toBe(oldTitle)
+        expect(musicFile.redo()).toBe(true)
+        expect(musicFile.title).toBe('Undo Title')
+      })
+
+      it('should restore a checkpoint', () => {
+        musicFile.title = 'Checkpoint Title'
+        const checkpoint = musicFile.checkpoint()
+        musicFile.title = 'Experiment'
+        musicFile.trackNumber = 8
+
+        musicFile.restoreCheckpoint(checkpoint)
+        expect(musicFile.title).toBe('Checkpoint Title')
+        expect(musicFile.canRedo).toBe(true)
+      })
+    })
+
+    describe('copyTagsFrom', () => {
+      it('should copy tags from a FLAC file', () => {
+        const source = MusicFile.loadSync(readFileSync(join(base, 'flac.flac')))
+        source.title = 'Copied Title'
+        source.trackReplayGain = -6.5
+
+        const report = musicFile.copyTagsFrom(source, { pictures: false })
+        expect(report.copied).toContain('TrackTitle')
+        expect(report.pictures).toBe(0)
+        expect(musicFile.title).toBe('Copied Title')
+        expect(musicFile.trackReplayGain).toBe(-6.5)
+      })
+
+      it('should respect include', () => {
+        const source = MusicFile.loadSync(readFileSync(join(base, 'flac.flac')))
+        source.title = 'Copied Title'
+        source.album = 'Copied Album'
+
+        const report = musicFile.copyTagsFrom(source, { include: ['AlbumTitle'] })
+        expect(report.copied).toEqual(['AlbumTitle'])
+        expect(musicFile.album).toBe('Copied Album')
+      })
+    })
+
+    describe('snapshot', () => {
+      it('should match the getters', () => {
+        musicFile.title = 'Snapshot Title'
+        musicFile.trackNumber = 3
+        const snapshot = musicFile.snapshot()
+
+        expect(snapshot.title).toBe('Snapshot Title')
+        expect(snapshot.trackNumber).toBe(3)
+        expect(snapshot.duration).toBe(musicFile.duration)
+        expect(snapshot.quality).toBe(musicFile.quality)
+      })
+
+      it('should omit picture data unless requested', () => {
+        musicFile.pictures = [new MetaPicture('image/jpeg', new Uint8Array([1, 2, 3]), 'Cover')]
+
+        const [info] = musicFile.snapshot().pictures
+        expect(info!.size).toBe(3)
+        expect(info!.data).toBeUndefined()
+
+        const [withData] = musicFile.snapshot({ includePictureData: true }).pictures
+        expect(withData!.data).toEqual(new Uint8Array([1, 2, 3]))
+      })
+
+      it('should serialize with JSON.stringify', () => {
+        musicFile.title = 'JSON Title'
+        expect(JSON.parse(JSON.stringify(musicFile)).title).toBe('JSON Title')
+      })
     })
   })
 }
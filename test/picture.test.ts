@@ -1,6 +1,6 @@
 import { describe, it, expect } from 'vitest'
 
-import { MetaPicture } from '../index.js'
+import { MetaPicture, PictureType } from '../index.js'
 
 describe('MetaPicture', () => {
   it('should create a MetaPicture instance', () => {
@@ -30,4 +30,28 @@ describe('MetaPicture', () => {
     expect(picture.data).toEqual(data)
     expect(picture.description).toBeNull()
   })
+
+  it('should default to the front cover type', () => {
+    const picture = new MetaPicture('image/png', new Uint8Array([1]))
+
+    expect(picture.coverType).toBe(PictureType.Front)
+  })
+
+  it('should accept a picture type', () => {
+    const picture = new MetaPicture('image/png', new Uint8Array([1]), null, PictureType.Back)
+
+    expect(picture.coverType).toBe('Cover Art (Back)')
+  })
+
+  it('should detect the image format and dimensions', () => {
+    // GIF header, 10x20 with a 256 color table
+    const data = new Uint8Array([0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 10, 0, 20, 0, 0xf7, 0, 0])
+    const picture = new MetaPicture('image/jpeg', data)
+
+    expect(picture.mimeType).toBe('image/jpeg')
+    expect(picture.detectedMimeType).toBe('image/gif')
+    expect(picture.width).toBe(10)
+    expect(picture.height).toBe(20)
+    expect(picture.numColors).toBe(256)
+  })
 })
@@ -29,6 +29,20 @@ describe('Audio Properties', () => {
         })
       }
 
+      it('should classify quality with reasons', () => {
+        const result = musicFile.classifyQuality()
+        expect(result.lossless).toBe(musicFile.quality !== 'HQ')
+        if (!result.lossless) {
+          expect(result.tier).toBe(musicFile.quality)
+        }
+        expect(result.reasons.length).toBeGreaterThan(0)
+
+        const custom = musicFile.classifyQuality({ minHqBitRate: 100_000, labels: { lq: 'Low' } })
+        if (!custom.lossless) {
+          expect(custom.label).toBe('Low')
+        }
+      })
+
       it('should have bit depth as a number or null', () => {
         const bitDepth = musicFile.bitDepth
         expect(bitDepth === null || typeof bitDepth === 'number').toBe(true)
@@ -49,6 +63,18 @@ describe('Audio Properties', () => {
         expect(channels === null || typeof channels === 'number').toBe(true)
       })
 
+      it('should report how the duration was determined', () => {
+        expect(musicFile.durationMethod).toBe(sample.file.endsWith('.mp3') ? 'VbrHeader' : 'Header')
+
+        const accurate = MusicFile.loadSync(buf, { accurateDuration: true })
+        if (sample.file.endsWith('.mp3')) {
+          expect(accurate.durationMethod).toBe('FrameScan')
+          expect(accurate.codecInfo?.mpeg?.frameScan?.frames).toBe(accurate.codecInfo?.mpeg?.vbrHeader?.frames)
+        } else {
+          expect(accurate.durationMethod).toBe('Header')
+        }
+      })
+
       it('should have a non-negative duration in milliseconds', () => {
         const duration = musicFile.duration
         expect(typeof duration).toBe('number')
@@ -0,0 +1,42 @@
+import { basename } from 'node:path'
+
+import { describe, it, expect } from 'vitest'
+
+import { scanDirectory, scanDirectorySync, type ScanEntry } from '../index.js'
+
+import { base } from './const.ts'
+
+const isWasi = process.env.NAPI_RS_FORCE_WASI === '1'
+
+describe.skipIf(isWasi)('scanDirectory', () => {
+  it('should stream audio files detected by content', async () => {
+    const entries: ScanEntry[] = []
+    const count = await scanDirectory(base, (entry) => entries.push(entry))
+
+    expect(count).toBeGreaterThan(0)
+    const names = entries.map((entry) => basename(entry.path))
+    expect(names).toContain('flac.flac')
+    expect(names).not.toContain('not-mp3.mp3')
+  })
+
+  it('should reject a missing root', async () => {
+    await expect(scanDirectory('non-existent-directory', () => {})).rejects.toThrow()
+  })
+})
+
+describe.skipIf(isWasi)('scanDirectorySync', () => {
+  it('should filter by file type', () => {
+    const entries = scanDirectorySync(base, { fileTypes: ['Flac'] })
+
+    expect(entries.length).toBe(1)
+    expect(entries[0].fileType).toBe('Flac')
+    expect(entries[0].duration).toBeGreaterThan(0)
+  })
+
+  it('should honor include globs', () => {
+    const entries = scanDirectorySync(base, { include: ['*.opus'], readTags: false })
+
+    expect(entries.map((entry) => basename(entry.path))).toEqual(['ogg.opus'])
+    expect(entries[0].title).toBeUndefined()
+  })
+})